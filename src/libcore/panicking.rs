@@ -58,6 +58,7 @@ fn panic_bounds_check(file_line: &(&'static str, u32),
 }
 
 #[cold] #[inline(never)]
+#[rustc_panic_entry_point]
 pub fn panic_fmt(fmt: fmt::Arguments, file_line: &(&'static str, u32)) -> ! {
     #[allow(improper_ctypes)]
     extern {
@@ -131,6 +131,7 @@ pub unsafe fn replace<T>(dest: *mut T, mut src: T) -> T {
 /// ```
 #[inline(always)]
 #[stable(feature = "rust1", since = "1.0.0")]
+#[rustc_mem_fn = "read"]
 pub unsafe fn read<T>(src: *const T) -> T {
     let mut tmp: T = mem::uninitialized();
     copy_nonoverlapping(src, &mut tmp, 1);
@@ -167,6 +168,7 @@ pub unsafe fn read<T>(src: *const T) -> T {
 /// ```
 #[inline]
 #[stable(feature = "rust1", since = "1.0.0")]
+#[rustc_mem_fn = "write"]
 pub unsafe fn write<T>(dst: *mut T, src: T) {
     intrinsics::move_val_init(&mut *dst, src)
 }
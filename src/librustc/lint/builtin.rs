@@ -14,7 +14,7 @@
 //! compiler code, rather than using their own custom pass. Those
 //! lints are all available in `rustc_lint::builtin`.
 
-use lint::{LintPass, LateLintPass, LintArray};
+use lint::{Lint, LintPass, LateLintPass, LintArray};
 
 declare_lint! {
     pub CONST_ERR,
@@ -217,6 +217,62 @@ declare_lint! {
     "detects use of deprecated items"
 }
 
+declare_lint! {
+    pub LARGE_STACK_FRAME,
+    Warn,
+    "functions whose estimated stack frame exceeds `#![stack_frame_limit]`"
+}
+
+declare_lint! {
+    pub LARGE_MOVES,
+    Allow,
+    "moves and copies of values whose size exceeds `#![move_size_limit]`"
+}
+
+declare_lint! {
+    pub ARITHMETIC_OVERFLOW,
+    Deny,
+    "arithmetic operations which overflow whenever they are executed"
+}
+
+declare_lint! {
+    pub INDEX_OUT_OF_BOUNDS,
+    Deny,
+    "array indexing which is out of bounds whenever it is executed"
+}
+
+declare_lint! {
+    pub UNCONDITIONAL_PANIC,
+    Allow,
+    "functions which panic whenever they are called"
+}
+
+declare_lint! {
+    pub DEAD_ASSIGNMENTS,
+    Warn,
+    "assignments to fields or through references which are never read"
+}
+
+declare_lint! {
+    pub SIMPLIFIABLE_CODE,
+    Allow,
+    "code which the MIR optimizations found could be simplified"
+}
+
+declare_lint! {
+    pub OPTIMIZED_AWAY,
+    Allow,
+    "functions which the MIR optimizations reduced to returning a constant or nothing"
+}
+
+/// The lints which are only checked once the lint pass has run, e.g. during
+/// translation. The lint pass records their level at each function, for
+/// `lint::emit_node_lint`.
+pub fn node_lints() -> [&'static Lint; 6] {
+    [LARGE_STACK_FRAME, LARGE_MOVES, ARITHMETIC_OVERFLOW, INDEX_OUT_OF_BOUNDS, SIMPLIFIABLE_CODE,
+     OPTIMIZED_AWAY]
+}
+
 /// Does nothing as a lint pass, but registers some `Lint`s
 /// which are used by other parts of the compiler.
 #[derive(Copy, Clone)]
@@ -257,7 +313,15 @@ impl LintPass for HardwiredLints {
             PATTERNS_IN_FNS_WITHOUT_BODY,
             EXTRA_REQUIREMENT_IN_IMPL,
             LEGACY_DIRECTORY_OWNERSHIP,
-            DEPRECATED
+            DEPRECATED,
+            LARGE_STACK_FRAME,
+            LARGE_MOVES,
+            ARITHMETIC_OVERFLOW,
+            INDEX_OUT_OF_BOUNDS,
+            UNCONDITIONAL_PANIC,
+            DEAD_ASSIGNMENTS,
+            SIMPLIFIABLE_CODE,
+            OPTIMIZED_AWAY
         )
     }
 }
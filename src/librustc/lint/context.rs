@@ -433,6 +433,24 @@ pub fn raw_emit_lint<S: Into<MultiSpan>>(sess: &Session,
     raw_struct_lint(sess, lints, lint, lvlsrc, span, msg).emit();
 }
 
+/// Emit one of the `builtin::node_lints()` for the function `id`, at the
+/// level the lint pass found there. Returns whether it was reported, i.e.
+/// isn't allowed there.
+pub fn emit_node_lint<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                lint: &'static Lint,
+                                id: ast::NodeId,
+                                span: Span,
+                                msg: &str) -> bool {
+    let lints = tcx.sess.lint_store.borrow();
+    let lint_id = LintId::of(lint);
+    let lvlsrc = match tcx.node_lint_levels.borrow().get(&(id, lint_id)) {
+        Some(&lvlsrc) => lvlsrc,
+        None => lints.get_level_source(lint_id),
+    };
+    raw_emit_lint(tcx.sess, &lints, lint, lvlsrc, Some(span), msg);
+    lvlsrc.0 != Allow
+}
+
 pub fn raw_struct_lint<'a, S>(sess: &'a Session,
                               lints: &LintStore,
                               lint: &'static Lint,
@@ -836,6 +854,14 @@ impl<'a, 'tcx> hir_visit::Visitor<'tcx> for LateContext<'a, 'tcx> {
 
     fn visit_fn(&mut self, fk: hir_visit::FnKind<'tcx>, decl: &'tcx hir::FnDecl,
                 body_id: hir::ExprId, span: Span, id: ast::NodeId) {
+        {
+            let mut node_levels = self.tcx.node_lint_levels.borrow_mut();
+            for &lint in builtin::node_lints().iter() {
+                let lint_id = LintId::of(lint);
+                node_levels.insert((id, lint_id), self.lints.get_level_source(lint_id));
+            }
+        }
+
         let body = self.tcx.map.expr(body_id);
         run_lints!(self, check_fn, late_passes, fk, decl, body, span, id);
         hir_visit::walk_fn(self, fk, decl, body_id, span, id);
@@ -30,6 +30,7 @@ use hir::svh::Svh;
 use middle::lang_items;
 use ty::{self, Ty, TyCtxt};
 use mir::Mir;
+use mir::summary::InlineSummary;
 use session::Session;
 use session::search_paths::PathKind;
 use util::nodemap::{NodeSet, DefIdMap};
@@ -354,6 +355,7 @@ pub trait CrateStore<'tcx> {
 
     fn get_item_mir<'a>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>, def: DefId) -> Mir<'tcx>;
     fn is_item_mir_available(&self, def: DefId) -> bool;
+    fn item_mir_summary(&self, def: DefId) -> Option<InlineSummary>;
 
     // This is basically a 1-based range of ints, which is a little
     // silly - I may fix that.
@@ -528,6 +530,9 @@ impl<'tcx> CrateStore<'tcx> for DummyCrateStore {
     fn is_item_mir_available(&self, def: DefId) -> bool {
         bug!("is_item_mir_available")
     }
+    fn item_mir_summary(&self, def: DefId) -> Option<InlineSummary> {
+        bug!("item_mir_summary")
+    }
 
     // This is basically a 1-based range of ints, which is a little
     // silly - I may fix that.
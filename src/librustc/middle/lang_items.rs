@@ -314,6 +314,11 @@ language_item_table! {
 
     StrEqFnLangItem,                 "str_eq",                  str_eq_fn;
 
+    // The traits and type which the desugaring of `?` goes through.
+    CarrierTraitLangItem,            "carrier",                 carrier_trait;
+    FromTraitLangItem,               "from",                    from_trait;
+    ResultTypeLangItem,              "result",                  result_type;
+
     // A number of panic-related lang items. The `panic` item corresponds to
     // divide-by-zero and various panic cases with `match`. The
     // `panic_bounds_check` item is for indexing arrays.
@@ -333,6 +338,7 @@ language_item_table! {
     StrDupUniqFnLangItem,            "strdup_uniq",             strdup_uniq_fn;
 
     StartFnLangItem,                 "start",                   start_fn;
+    ProfileMirRegisterFnLangItem,    "profile_mir_register",    profile_mir_register_fn;
 
     EhPersonalityLangItem,           "eh_personality",          eh_personality;
     EhUnwindResumeLangItem,          "eh_unwind_resume",        eh_unwind_resume;
@@ -25,6 +25,10 @@ pub fn update_limits(sess: &Session, krate: &ast::Crate) {
                  "recursion limit");
     update_limit(sess, krate, &sess.type_length_limit, "type_length_limit",
                  "type length limit");
+    update_limit(sess, krate, &sess.stack_frame_limit, "stack_frame_limit",
+                 "stack frame limit");
+    update_limit(sess, krate, &sess.move_size_limit, "move_size_limit",
+                 "move size limit");
 }
 
 fn update_limit(sess: &Session, krate: &ast::Crate, limit: &Cell<usize>,
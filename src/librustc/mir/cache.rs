@@ -11,13 +11,14 @@
 use std::cell::{Ref, RefCell};
 use rustc_data_structures::indexed_vec::IndexVec;
 
-use mir::{Mir, BasicBlock};
+use mir::{Mir, BasicBlock, Local, Lvalue, Operand, ProjectionElem, Rvalue, StatementKind};
 
 use rustc_serialize as serialize;
 
 #[derive(Clone, Debug)]
 pub struct Cache {
-    predecessors: RefCell<Option<IndexVec<BasicBlock, Vec<BasicBlock>>>>
+    predecessors: RefCell<Option<IndexVec<BasicBlock, Vec<BasicBlock>>>>,
+    address_taken: RefCell<Option<IndexVec<Local, bool>>>
 }
 
 
@@ -37,13 +38,15 @@ impl serialize::Decodable for Cache {
 impl Cache {
     pub fn new() -> Self {
         Cache {
-            predecessors: RefCell::new(None)
+            predecessors: RefCell::new(None),
+            address_taken: RefCell::new(None)
         }
     }
 
     pub fn invalidate(&self) {
         // FIXME: consider being more fine-grained
         *self.predecessors.borrow_mut() = None;
+        *self.address_taken.borrow_mut() = None;
     }
 
     pub fn predecessors(&self, mir: &Mir) -> Ref<IndexVec<BasicBlock, Vec<BasicBlock>>> {
@@ -53,6 +56,14 @@ impl Cache {
 
         Ref::map(self.predecessors.borrow(), |p| p.as_ref().unwrap())
     }
+
+    pub fn address_taken(&self, mir: &Mir) -> Ref<IndexVec<Local, bool>> {
+        if self.address_taken.borrow().is_none() {
+            *self.address_taken.borrow_mut() = Some(calculate_address_taken(mir));
+        }
+
+        Ref::map(self.address_taken.borrow(), |a| a.as_ref().unwrap())
+    }
 }
 
 fn calculate_predecessors(mir: &Mir) -> IndexVec<BasicBlock, Vec<BasicBlock>> {
@@ -67,3 +78,47 @@ fn calculate_predecessors(mir: &Mir) -> IndexVec<BasicBlock, Vec<BasicBlock>> {
 
     result
 }
+
+fn calculate_address_taken(mir: &Mir) -> IndexVec<Local, bool> {
+    let mut result = IndexVec::from_elem(false, &mir.local_decls);
+    {
+        let mut take = |lvalue: &Lvalue| {
+            if let Some(local) = direct_root(lvalue) {
+                result[local] = true;
+            }
+        };
+        for data in mir.basic_blocks() {
+            for statement in &data.statements {
+                match statement.kind {
+                    StatementKind::Assign(_, Rvalue::Ref(_, _, ref lvalue)) => take(lvalue),
+                    // Memory constraints pass the address of their operand.
+                    StatementKind::Assign(_, Rvalue::InlineAsm { ref outputs, ref inputs, .. }) => {
+                        for output in outputs {
+                            take(output);
+                        }
+                        for input in inputs {
+                            if let Operand::Consume(ref lvalue) = *input {
+                                take(lvalue);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// The local `lvalue` is part of, unless it's behind a pointer.
+fn direct_root(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(local) => Some(local),
+        Lvalue::Static(_) => None,
+        Lvalue::Projection(ref proj) => match proj.elem {
+            ProjectionElem::Deref => None,
+            _ => direct_root(&proj.base)
+        }
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Liveness of the locals of a MIR body, a backward dataflow analysis
+//! shared by the passes and checks which need to know whether a value may
+//! still be read. What counts as reading or writing a local is up to the
+//! user, through `LocalEffects`, e.g. whether a write through a reference
+//! counts as writing the local it points to.
+
+use mir::{BasicBlock, Location, Local, Mir, Statement, Terminator};
+use mir::traversal;
+use rustc_data_structures::indexed_set::IdxSetBuf;
+use rustc_data_structures::indexed_vec::IndexVec;
+
+/// The locals a statement or terminator writes as a whole, which are dead
+/// before it, and those it reads.
+#[derive(Default)]
+pub struct DefsUses {
+    pub defs: Vec<Local>,
+    pub uses: Vec<Local>,
+}
+
+impl DefsUses {
+    /// Turns the set of locals live after into the set live before.
+    pub fn apply(&self, live: &mut IdxSetBuf<Local>) {
+        for local in &self.defs {
+            live.remove(local);
+        }
+        for local in &self.uses {
+            live.add(local);
+        }
+    }
+}
+
+/// The locals read and written by each statement and terminator.
+pub trait LocalEffects<'tcx> {
+    fn statement_effects(&self, statement: &Statement<'tcx>, location: Location) -> DefsUses;
+    fn terminator_effects(&self, terminator: &Terminator<'tcx>, location: Location) -> DefsUses;
+}
+
+pub struct Liveness {
+    /// The locals live on exit from each block.
+    live_out: IndexVec<BasicBlock, IdxSetBuf<Local>>,
+}
+
+impl Liveness {
+    pub fn compute<'tcx, E: LocalEffects<'tcx>>(mir: &Mir<'tcx>, effects: &E) -> Liveness {
+        let locals = mir.local_decls.len();
+        let mut live_out = IndexVec::from_elem_n(IdxSetBuf::new_empty(locals),
+                                                 mir.basic_blocks().len());
+        let mut live_in = live_out.clone();
+        let postorder: Vec<_> = traversal::postorder(mir).map(|(bb, _)| bb).collect();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &bb in &postorder {
+                let mut live = IdxSetBuf::new_empty(locals);
+                for &succ in mir[bb].terminator().successors().iter() {
+                    live.union(&live_in[succ]);
+                }
+                live_out[bb].clone_from(&live);
+                apply_block(mir, bb, effects, &mut live, |_| {});
+                if live.words() != live_in[bb].words() {
+                    live_in[bb].clone_from(&live);
+                    changed = true;
+                }
+            }
+        }
+        Liveness { live_out: live_out }
+    }
+
+    /// The locals live on entry to each statement of `block`, then to its
+    /// terminator, then on exit from it: the locals live after the
+    /// statement at index `i` are at index `i + 1`.
+    pub fn block<'tcx, E: LocalEffects<'tcx>>(&self,
+                                              mir: &Mir<'tcx>,
+                                              block: BasicBlock,
+                                              effects: &E)
+                                              -> Vec<IdxSetBuf<Local>> {
+        let mut live = self.live_out[block].clone();
+        let mut sets = vec![live.clone()];
+        apply_block(mir, block, effects, &mut live, |live| sets.push(live.clone()));
+        sets.reverse();
+        sets
+    }
+}
+
+/// Turns the set of locals live on exit from `block` into the set live on
+/// entry, calling `each` with the set live on entry to each statement and
+/// the terminator, from last to first.
+fn apply_block<'tcx, E, F>(mir: &Mir<'tcx>,
+                           block: BasicBlock,
+                           effects: &E,
+                           live: &mut IdxSetBuf<Local>,
+                           mut each: F)
+    where E: LocalEffects<'tcx>, F: FnMut(&IdxSetBuf<Local>)
+{
+    let data = &mir[block];
+    let location = Location { block: block, statement_index: data.statements.len() };
+    effects.terminator_effects(data.terminator(), location).apply(live);
+    each(live);
+    for (statement_index, statement) in data.statements.iter().enumerate().rev() {
+        let location = Location { block: block, statement_index: statement_index };
+        effects.statement_effects(statement, location).apply(live);
+        each(live);
+    }
+}
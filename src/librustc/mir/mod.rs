@@ -10,7 +10,7 @@
 
 use graphviz::IntoCow;
 use middle::const_val::ConstVal;
-use rustc_const_math::{ConstUsize, ConstInt, ConstMathErr};
+use rustc_const_math::{ConstUsize, ConstInt, ConstMathErr, Op};
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
 use rustc_data_structures::control_flow_graph::dominators::{Dominators, dominators};
 use rustc_data_structures::control_flow_graph::{GraphPredecessors, GraphSuccessors};
@@ -19,7 +19,9 @@ use hir::def::CtorKind;
 use hir::def_id::DefId;
 use ty::subst::Substs;
 use ty::{self, AdtDef, ClosureSubsts, Region, Ty};
+use util::nodemap::{FxHashMap, FxHashSet};
 use util::ppaux;
+use mir::transform::PassRevisions;
 use rustc_back::slice;
 use hir::InlineAsm;
 use std::ascii;
@@ -33,6 +35,9 @@ use syntax::ast::{self, Name};
 use syntax_pos::Span;
 
 mod cache;
+pub mod liveness;
+pub mod patch;
+pub mod summary;
 pub mod tcx;
 pub mod visit;
 pub mod transform;
@@ -79,6 +84,11 @@ pub struct Mir<'tcx> {
     /// in scope, but a separate set of locals.
     pub promoted: IndexVec<Promoted, Mir<'tcx>>,
 
+    /// Cold parts of this function moved into functions of their own by
+    /// `-Z mir-outline-cold`. Each of them has the fn's type parameters in
+    /// scope, and takes a reference to each local of this function it uses.
+    pub outlined: IndexVec<Outlined, Mir<'tcx>>,
+
     /// Return type of the function.
     pub return_ty: Ty<'tcx>,
 
@@ -110,10 +120,56 @@ pub struct Mir<'tcx> {
     /// A span representing this MIR, for error reporting
     pub span: Span,
 
+    /// Relative weights of the edges out of conditional terminators,
+    /// filled in under `-Z mir-branch-prob`. `SwitchInt` has one weight per
+    /// successor, `Assert` one for success followed by one for the panic.
+    pub branch_weights: FxHashMap<BasicBlock, Vec<u32>>,
+
+    /// The integer `SwitchInt`s which are dense enough for a jump table,
+    /// as found by `CanonicalizeSwitches`. Their cases going to the
+    /// `otherwise` block fill the gaps between the others, so trans must
+    /// keep them instead of leaving them to the default.
+    pub dense_switches: FxHashSet<BasicBlock>,
+
+    /// Where the value of each user variable can be found, for debuginfo.
+    /// Passes which remove the local of a variable must update this, see
+    /// `replace_var_debug_info`.
+    pub var_debug_info: Vec<VarDebugInfo<'tcx>>,
+
+    /// Which passes changed this MIR, for the pass manager to skip the
+    /// cleanup passes which have nothing to do.
+    pub pass_revisions: PassRevisions,
+
     /// A cache for various calculations
     cache: cache::Cache
 }
 
+/// Debuginfo for a user variable.
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub struct VarDebugInfo<'tcx> {
+    pub name: Name,
+
+    /// Source information of the variable's declaration.
+    pub source_info: SourceInfo,
+
+    /// Where the value of the variable can be found.
+    pub value: VarDebugInfoContents<'tcx>,
+}
+
+#[derive(Clone, Debug, RustcEncodable, RustcDecodable)]
+pub enum VarDebugInfoContents<'tcx> {
+    /// The variable is stored in this lvalue, which may not be the local
+    /// declared for it, e.g. after copy propagation. Only field and
+    /// dereference projections are used, as debuggers can follow those.
+    Lvalue(Lvalue<'tcx>),
+
+    /// The variable always has this value.
+    Const(Constant<'tcx>),
+
+    /// The value of the variable can't be recovered.
+    OptimizedOut,
+}
+
 /// where execution begins
 pub const START_BLOCK: BasicBlock = BasicBlock(0);
 
@@ -132,16 +188,55 @@ impl<'tcx> Mir<'tcx> {
             "expected at least {} locals, got {}", arg_count + 1, local_decls.len());
         assert_eq!(local_decls[RETURN_POINTER].ty, return_ty);
 
+        // Each user variable starts out in the local declared for it.
+        let var_debug_info = local_decls.iter_enumerated().skip(arg_count + 1)
+            .filter_map(|(local, decl)| match (decl.name, decl.source_info) {
+                (Some(name), Some(source_info)) => Some(VarDebugInfo {
+                    name: name,
+                    source_info: source_info,
+                    value: VarDebugInfoContents::Lvalue(Lvalue::Local(local)),
+                }),
+                _ => None
+            }).collect();
+
         Mir {
             basic_blocks: basic_blocks,
             visibility_scopes: visibility_scopes,
             promoted: promoted,
+            outlined: IndexVec::new(),
             return_ty: return_ty,
             local_decls: local_decls,
             arg_count: arg_count,
             upvar_decls: upvar_decls,
             spread_arg: None,
             span: span,
+            branch_weights: FxHashMap(),
+            dense_switches: FxHashSet(),
+            var_debug_info: var_debug_info,
+            pass_revisions: PassRevisions::new(),
+            cache: cache::Cache::new()
+        }
+    }
+
+    /// A copy of the whole MIR, promoted constants and outlined parts
+    /// included, for the debugging options comparing it before and after
+    /// a pass. `Mir` isn't `Clone` so that this is never done by accident.
+    pub fn duplicate(&self) -> Mir<'tcx> {
+        Mir {
+            basic_blocks: self.basic_blocks.clone(),
+            visibility_scopes: self.visibility_scopes.clone(),
+            promoted: self.promoted.iter().map(|mir| mir.duplicate()).collect(),
+            outlined: self.outlined.iter().map(|mir| mir.duplicate()).collect(),
+            return_ty: self.return_ty,
+            local_decls: self.local_decls.clone(),
+            arg_count: self.arg_count,
+            upvar_decls: self.upvar_decls.clone(),
+            spread_arg: self.spread_arg,
+            span: self.span,
+            branch_weights: self.branch_weights.clone(),
+            dense_switches: self.dense_switches.clone(),
+            var_debug_info: self.var_debug_info.clone(),
+            pass_revisions: self.pass_revisions.clone(),
             cache: cache::Cache::new()
         }
     }
@@ -167,6 +262,25 @@ impl<'tcx> Mir<'tcx> {
         Ref::map(self.predecessors(), |p| &p[bb])
     }
 
+    /// The locals which a reference or pointer to the whole local or part
+    /// of it is taken of anywhere in the body, or whose address inline
+    /// assembly may get. The others can only be accessed by their name, so
+    /// optimizations may assume that no pointer aliases them.
+    ///
+    /// This is cached until the blocks are changed through
+    /// `basic_blocks_mut`, which any pass taking a new reference has to go
+    /// through. Locals added since are not included.
+    #[inline]
+    pub fn address_taken(&self) -> Ref<IndexVec<Local, bool>> {
+        self.cache.address_taken(self)
+    }
+
+    #[inline]
+    pub fn is_address_taken(&self, local: Local) -> bool {
+        let address_taken = self.address_taken();
+        local.index() < address_taken.len() && address_taken[local]
+    }
+
     #[inline]
     pub fn dominators(&self) -> Dominators<BasicBlock> {
         dominators(self)
@@ -241,6 +355,48 @@ impl<'tcx> Mir<'tcx> {
         debug_assert!(location.statement_index < block.statements.len());
         block.statements[location.statement_index].make_nop()
     }
+
+    /// Removes the nops left behind by `make_statement_nop` from every block, see
+    /// `BasicBlockData::compact_statements`.
+    pub fn compact_statements(&mut self) {
+        for data in self.basic_blocks_mut() {
+            data.compact_statements();
+        }
+    }
+
+    /// Makes the debuginfo of the user variables found in `local` refer to `value` instead. Passes
+    /// which replace or remove a local must call this, or the variables silently disappear from
+    /// debuginfo. Variables found in a projection of `local` are rebased onto `value` when it is
+    /// an lvalue, and optimized out otherwise.
+    pub fn replace_var_debug_info(&mut self, local: Local, value: VarDebugInfoContents<'tcx>) {
+        for var in &mut self.var_debug_info {
+            let replaced = match var.value {
+                VarDebugInfoContents::Lvalue(ref lvalue) => rebase_lvalue(lvalue, local, &value),
+                _ => None
+            };
+            if let Some(replaced) = replaced {
+                var.value = replaced;
+            }
+        }
+    }
+}
+
+/// `lvalue` with its root `local` replaced by `value`, or `None` if it isn't based on `local`.
+fn rebase_lvalue<'tcx>(lvalue: &Lvalue<'tcx>,
+                       local: Local,
+                       value: &VarDebugInfoContents<'tcx>)
+                       -> Option<VarDebugInfoContents<'tcx>> {
+    match *lvalue {
+        Lvalue::Local(l) if l == local => Some(value.clone()),
+        Lvalue::Local(_) | Lvalue::Static(_) => None,
+        Lvalue::Projection(ref proj) => match rebase_lvalue(&proj.base, local, value) {
+            Some(VarDebugInfoContents::Lvalue(base)) => {
+                Some(VarDebugInfoContents::Lvalue(base.elem(proj.elem.clone())))
+            }
+            Some(_) => Some(VarDebugInfoContents::OptimizedOut),
+            None => None
+        }
+    }
 }
 
 impl<'tcx> Index<BasicBlock> for Mir<'tcx> {
@@ -259,6 +415,25 @@ impl<'tcx> IndexMut<BasicBlock> for Mir<'tcx> {
     }
 }
 
+/// A compiler-generated function, whose MIR is built by `rustc_mir::shim`
+/// the first time trans asks for it, and kept in `tcx.mir_shims`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Shim<'tcx> {
+    /// The drop glue of a monomorphic type.
+    DropGlue(Ty<'tcx>),
+
+    /// The `FnOnce::call_once` of a closure whose own body takes its
+    /// environment by reference.
+    FnOnceAdapter(DefId, ClosureSubsts<'tcx>),
+
+    /// A method of the `Fn` traits for a fn pointer or fn item, taking
+    /// `self` by reference if the flag is set.
+    FnPtr(Ty<'tcx>, bool),
+
+    /// The function a trait object method is reified to.
+    Object(DefId, &'tcx Substs<'tcx>),
+}
+
 /// Grouped information about the source code origin of a MIR entity.
 /// Intended to be inspected by diagnostics and debuginfo.
 /// Most passes can work with it as a whole, within a single function.
@@ -373,6 +548,15 @@ pub struct LocalDecl<'tcx> {
     /// This is the primary way to differentiate between user-declared
     /// variables and compiler-generated temporaries.
     pub source_info: Option<SourceInfo>,
+
+    /// Whether const qualification found this temporary to hold a constant
+    /// expression: a value computed only from constants and calls to
+    /// `const fn` with constant arguments, without interior mutability or
+    /// a destructor. Such a value is the same every time it's computed,
+    /// and computing it has no side effects besides possibly panicking.
+    ///
+    /// Passes which make a temporary hold a different value must clear this.
+    pub is_const: bool,
 }
 
 impl<'tcx> LocalDecl<'tcx> {
@@ -384,6 +568,7 @@ impl<'tcx> LocalDecl<'tcx> {
             ty: ty,
             name: None,
             source_info: None,
+            is_const: false,
         }
     }
 
@@ -397,6 +582,7 @@ impl<'tcx> LocalDecl<'tcx> {
             ty: return_ty,
             source_info: None,
             name: None,     // FIXME maybe we do want some name here?
+            is_const: false,
         }
     }
 }
@@ -453,24 +639,13 @@ pub enum TerminatorKind<'tcx> {
         target: BasicBlock,
     },
 
-    /// jump to branch 0 if this lvalue evaluates to true
-    If {
-        cond: Operand<'tcx>,
-        targets: (BasicBlock, BasicBlock),
-    },
-
-    /// lvalue evaluates to some enum; jump depending on the branch
-    Switch {
-        discr: Lvalue<'tcx>,
-        adt_def: &'tcx AdtDef,
-        targets: Vec<BasicBlock>,
-    },
-
-    /// operand evaluates to an integer; jump depending on its value
-    /// to one of the targets, and otherwise fallback to `otherwise`
+    /// operand evaluates to an integer, a boolean or the discriminant of
+    /// an enum, as read by `Rvalue::Discriminant`; jump depending on its
+    /// value to one of the targets, and otherwise fallback to `otherwise`.
+    /// Booleans are tested against `false` only, see `TerminatorKind::if_`.
     SwitchInt {
         /// discriminant value being tested
-        discr: Lvalue<'tcx>,
+        discr: Operand<'tcx>,
 
         /// type of value being tested
         switch_ty: Ty<'tcx>,
@@ -490,6 +665,10 @@ pub enum TerminatorKind<'tcx> {
     /// continue. Emitted by build::scope::diverge_cleanup.
     Resume,
 
+    /// Indicates that the landing pad is finished and that the process
+    /// should abort, because unwinding must not continue past this frame.
+    Abort,
+
     /// Indicates a normal return. The return pointer lvalue should
     /// have been filled in by now. This should occur at most once.
     Return,
@@ -525,11 +704,13 @@ pub enum TerminatorKind<'tcx> {
     },
 
     /// Jump to the target if the condition has the expected value,
-    /// otherwise panic with a message and a cleanup target.
+    /// otherwise panic with a message and a cleanup target. The message
+    /// is boxed, as it holds two operands for bounds checks and would
+    /// otherwise make `Assert` the largest terminator by far.
     Assert {
         cond: Operand<'tcx>,
         expected: bool,
-        msg: AssertMessage<'tcx>,
+        msg: Box<AssertMessage<'tcx>>,
         target: BasicBlock,
         cleanup: Option<BasicBlock>
     }
@@ -546,14 +727,52 @@ impl<'tcx> Terminator<'tcx> {
 }
 
 impl<'tcx> TerminatorKind<'tcx> {
+    /// A two-way branch on the boolean `cond`, to `t` if it is true and to
+    /// `f` if it is false.
+    pub fn if_<'a, 'gcx>(tcx: ty::TyCtxt<'a, 'gcx, 'tcx>,
+                         cond: Operand<'tcx>,
+                         t: BasicBlock,
+                         f: BasicBlock)
+                         -> TerminatorKind<'tcx> {
+        TerminatorKind::SwitchInt {
+            discr: cond,
+            switch_ty: tcx.types.bool,
+            values: vec![ConstVal::Bool(false)],
+            targets: vec![f, t],
+        }
+    }
+
+    /// A switch on `discr`, the discriminant of an enum of type `adt_def`
+    /// as read by `Rvalue::Discriminant`, to the target of each of its
+    /// variants. The switch is exhaustive, so `otherwise` should be an
+    /// `Unreachable` block, which lets trans tell LLVM that no other value
+    /// is possible.
+    pub fn switch_on_variants<'a, 'gcx>(tcx: ty::TyCtxt<'a, 'gcx, 'tcx>,
+                                        discr: Operand<'tcx>,
+                                        adt_def: &'tcx AdtDef,
+                                        mut targets: Vec<BasicBlock>,
+                                        otherwise: BasicBlock)
+                                        -> TerminatorKind<'tcx> {
+        assert_eq!(adt_def.variants.len(), targets.len());
+        let values = adt_def.variants.iter().map(|variant| {
+            ConstVal::Integral(variant.disr_val)
+        }).collect();
+        targets.push(otherwise);
+        TerminatorKind::SwitchInt {
+            discr: discr,
+            switch_ty: adt_def.discr_ty(tcx),
+            values: values,
+            targets: targets,
+        }
+    }
+
     pub fn successors(&self) -> Cow<[BasicBlock]> {
         use self::TerminatorKind::*;
         match *self {
             Goto { target: ref b } => slice::ref_slice(b).into_cow(),
-            If { targets: (b1, b2), .. } => vec![b1, b2].into_cow(),
-            Switch { targets: ref b, .. } => b[..].into_cow(),
             SwitchInt { targets: ref b, .. } => b[..].into_cow(),
             Resume => (&[]).into_cow(),
+            Abort => (&[]).into_cow(),
             Return => (&[]).into_cow(),
             Unreachable => (&[]).into_cow(),
             Call { destination: Some((_, t)), cleanup: Some(c), .. } => vec![t, c].into_cow(),
@@ -580,10 +799,9 @@ impl<'tcx> TerminatorKind<'tcx> {
         use self::TerminatorKind::*;
         match *self {
             Goto { target: ref mut b } => vec![b],
-            If { targets: (ref mut b1, ref mut b2), .. } => vec![b1, b2],
-            Switch { targets: ref mut b, .. } => b.iter_mut().collect(),
             SwitchInt { targets: ref mut b, .. } => b.iter_mut().collect(),
             Resume => Vec::new(),
+            Abort => Vec::new(),
             Return => Vec::new(),
             Unreachable => Vec::new(),
             Call { destination: Some((_, ref mut t)), cleanup: Some(ref mut c), .. } => vec![t, c],
@@ -622,6 +840,17 @@ impl<'tcx> BasicBlockData<'tcx> {
     pub fn terminator_mut(&mut self) -> &mut Terminator<'tcx> {
         self.terminator.as_mut().expect("invalid terminator state")
     }
+
+    /// Removes the nops from the statements of the block and gives back the memory they used.
+    /// This invalidates the statement indices in `Location`s, so it must not be called while
+    /// they are held on to.
+    pub fn compact_statements(&mut self) {
+        self.statements.retain(|statement| match statement.kind {
+            StatementKind::Nop => false,
+            _ => true
+        });
+        self.statements.shrink_to_fit();
+    }
 }
 
 impl<'tcx> Debug for TerminatorKind<'tcx> {
@@ -659,11 +888,10 @@ impl<'tcx> TerminatorKind<'tcx> {
         use self::TerminatorKind::*;
         match *self {
             Goto { .. } => write!(fmt, "goto"),
-            If { cond: ref lv, .. } => write!(fmt, "if({:?})", lv),
-            Switch { discr: ref lv, .. } => write!(fmt, "switch({:?})", lv),
             SwitchInt { discr: ref lv, .. } => write!(fmt, "switchInt({:?})", lv),
             Return => write!(fmt, "return"),
             Resume => write!(fmt, "resume"),
+            Abort => write!(fmt, "abort"),
             Unreachable => write!(fmt, "unreachable"),
             Drop { ref location, .. } => write!(fmt, "drop({:?})", location),
             DropAndReplace { ref location, ref value, .. } =>
@@ -688,7 +916,7 @@ impl<'tcx> TerminatorKind<'tcx> {
                 }
                 write!(fmt, "{:?}, ", cond)?;
 
-                match *msg {
+                match **msg {
                     AssertMessage::BoundsCheck { ref len, ref index } => {
                         write!(fmt, "{:?}, {:?}, {:?}",
                                "index out of bounds: the len is {} but the index is {}",
@@ -697,6 +925,12 @@ impl<'tcx> TerminatorKind<'tcx> {
                     AssertMessage::Math(ref err) => {
                         write!(fmt, "{:?}", err.description())?;
                     }
+                    AssertMessage::NullPointerDeref => {
+                        write!(fmt, "{:?}", "null pointer dereference")?;
+                    }
+                    AssertMessage::MisalignedPointerDeref => {
+                        write!(fmt, "{:?}", "misaligned pointer dereference")?;
+                    }
                 }
 
                 write!(fmt, ")")
@@ -708,15 +942,8 @@ impl<'tcx> TerminatorKind<'tcx> {
     pub fn fmt_successor_labels(&self) -> Vec<Cow<'static, str>> {
         use self::TerminatorKind::*;
         match *self {
-            Return | Resume | Unreachable => vec![],
+            Return | Resume | Abort | Unreachable => vec![],
             Goto { .. } => vec!["".into()],
-            If { .. } => vec!["true".into(), "false".into()],
-            Switch { ref adt_def, .. } => {
-                adt_def.variants
-                       .iter()
-                       .map(|variant| variant.name.to_string().into())
-                       .collect()
-            }
             SwitchInt { ref values, .. } => {
                 values.iter()
                       .map(|const_val| {
@@ -751,7 +978,10 @@ pub enum AssertMessage<'tcx> {
         len: Operand<'tcx>,
         index: Operand<'tcx>
     },
-    Math(ConstMathErr)
+    Math(ConstMathErr),
+    /// The checks of `-Z mir-check-refs`.
+    NullPointerDeref,
+    MisalignedPointerDeref,
 }
 
 ///////////////////////////////////////////////////////////////////////////
@@ -785,6 +1015,14 @@ pub enum StatementKind<'tcx> {
     /// End the current live range for the storage of the local.
     StorageDead(Lvalue<'tcx>),
 
+    /// Increment the given profiling counter of the function, see
+    /// `-Z profile-mir`.
+    IncrementCounter(u32),
+
+    /// Overwrite the memory of the local with a byte pattern, so that reading
+    /// it gives recognizable garbage, see `-Z mir-poison-uninit`.
+    Poison(Lvalue<'tcx>),
+
     /// No-op. Useful for deleting instructions without affecting statement indices.
     Nop,
 }
@@ -799,6 +1037,8 @@ impl<'tcx> Debug for Statement<'tcx> {
             SetDiscriminant{lvalue: ref lv, variant_index: index} => {
                 write!(fmt, "discriminant({:?}) = {:?}", lv, index)
             }
+            IncrementCounter(index) => write!(fmt, "IncrementCounter({})", index),
+            Poison(ref lv) => write!(fmt, "Poison({:?})", lv),
             Nop => write!(fmt, "nop"),
         }
     }
@@ -898,6 +1138,29 @@ impl<'tcx> Lvalue<'tcx> {
             elem: elem,
         }))
     }
+
+    /// The local this lvalue is projected from, or the local itself; `None`
+    /// for a static.
+    pub fn base_local(&self) -> Option<Local> {
+        match *self {
+            Lvalue::Local(local) => Some(local),
+            Lvalue::Static(_) => None,
+            Lvalue::Projection(ref proj) => proj.base.base_local(),
+        }
+    }
+
+    /// Whether this lvalue names memory of the function itself, rather than
+    /// memory behind a pointer, which may be written through other pointers.
+    pub fn is_direct(&self) -> bool {
+        match *self {
+            Lvalue::Local(_) => true,
+            Lvalue::Static(_) => false,
+            Lvalue::Projection(ref proj) => match proj.elem {
+                ProjectionElem::Deref => false,
+                _ => proj.base.is_direct(),
+            },
+        }
+    }
 }
 
 impl<'tcx> Debug for Lvalue<'tcx> {
@@ -986,6 +1249,10 @@ pub enum Rvalue<'tcx> {
     /// length of a [X] or [X;n] value
     Len(Lvalue<'tcx>),
 
+    /// Read the discriminant of an enum, as a value of its `repr` type
+    /// (see `AdtDef::discr_ty`).
+    Discriminant(Lvalue<'tcx>),
+
     Cast(CastKind, Operand<'tcx>, Ty<'tcx>),
 
     BinaryOp(BinOp, Operand<'tcx>, Operand<'tcx>),
@@ -1003,8 +1270,10 @@ pub enum Rvalue<'tcx> {
     /// away after type-checking and before lowering.
     Aggregate(AggregateKind<'tcx>, Vec<Operand<'tcx>>),
 
+    /// Inline assembly. Its description is boxed, as it is much larger
+    /// than the other rvalues and rarely used.
     InlineAsm {
-        asm: InlineAsm,
+        asm: Box<InlineAsm>,
         outputs: Vec<Lvalue<'tcx>>,
         inputs: Vec<Operand<'tcx>>
     }
@@ -1083,6 +1352,20 @@ impl BinOp {
             _ => false
         }
     }
+
+    /// The error a checked operation reports when it overflows.
+    pub fn overflow_err(self) -> Option<ConstMathErr> {
+        use self::BinOp::*;
+        let op = match self {
+            Add => Op::Add,
+            Sub => Op::Sub,
+            Mul => Op::Mul,
+            Shl => Op::Shl,
+            Shr => Op::Shr,
+            _ => return None
+        };
+        Some(ConstMathErr::Overflow(op))
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
@@ -1101,6 +1384,7 @@ impl<'tcx> Debug for Rvalue<'tcx> {
             Use(ref lvalue) => write!(fmt, "{:?}", lvalue),
             Repeat(ref a, ref b) => write!(fmt, "[{:?}; {:?}]", a, b),
             Len(ref a) => write!(fmt, "Len({:?})", a),
+            Discriminant(ref lv) => write!(fmt, "discriminant({:?})", lv),
             Cast(ref kind, ref lv, ref ty) => write!(fmt, "{:?} as {:?} ({:?})", lv, ty, kind),
             BinaryOp(ref op, ref a, ref b) => write!(fmt, "{:?}({:?}, {:?})", op, a, b),
             CheckedBinaryOp(ref op, ref a, ref b) => {
@@ -1211,6 +1495,7 @@ impl<'tcx> Debug for TypedConstVal<'tcx> {
 }
 
 newtype_index!(Promoted, "promoted");
+newtype_index!(Outlined, "outlined");
 
 #[derive(Clone, PartialEq, Eq, Hash, RustcEncodable, RustcDecodable)]
 pub enum Literal<'tcx> {
@@ -1225,6 +1510,10 @@ pub enum Literal<'tcx> {
         // Index into the `promoted` vector of `Mir`.
         index: Promoted
     },
+    Outlined {
+        // Index into the `outlined` vector of `Mir`. The constant is a fn pointer.
+        index: Outlined
+    },
 }
 
 impl<'tcx> Debug for Constant<'tcx> {
@@ -1247,12 +1536,15 @@ impl<'tcx> Debug for Literal<'tcx> {
             Promoted { index } => {
                 write!(fmt, "{:?}", index)
             }
+            Outlined { index } => {
+                write!(fmt, "{:?}", index)
+            }
         }
     }
 }
 
 /// Write a `ConstVal` in a way closer to the original source code than the `Debug` output.
-fn fmt_const_val<W: Write>(fmt: &mut W, const_val: &ConstVal) -> fmt::Result {
+pub fn fmt_const_val<W: Write>(fmt: &mut W, const_val: &ConstVal) -> fmt::Result {
     use middle::const_val::ConstVal::*;
     match *const_val {
         Float(f) => write!(fmt, "{:?}", f),
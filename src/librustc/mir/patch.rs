@@ -8,9 +8,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use rustc::ty::Ty;
-use rustc::mir::*;
+//! Changes to the MIR of a function which are queued while it is walked
+//! and applied at once afterwards, so that the locations handed out while
+//! walking it stay valid.
+//!
+//! A pass walks the MIR, with a `Visitor` or by hand, records what it wants
+//! changed in a `MirPatch` along with the locations it was visiting, and
+//! then calls `apply`. Statements added at
+//! a location are inserted before the statement that was there, in the
+//! order they were added, and statements replaced with `patch_statement`
+//! keep their location.
+
+use mir::*;
+use ty::Ty;
 use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax_pos::Span;
 
 /// This struct represents a patch to MIR, which can add
 /// new statements and basic blocks and patch over block
@@ -19,8 +31,13 @@ pub struct MirPatch<'tcx> {
     patch_map: IndexVec<BasicBlock, Option<TerminatorKind<'tcx>>>,
     new_blocks: Vec<BasicBlockData<'tcx>>,
     new_statements: Vec<(Location, StatementKind<'tcx>)>,
+    statement_patches: Vec<(Location, StatementKind<'tcx>)>,
     new_locals: Vec<LocalDecl<'tcx>>,
-    resume_block: BasicBlock,
+    resume_block: Option<BasicBlock>,
+    /// A block ending with `Resume` after some statements, which is
+    /// redirected to the resume block once one has to be made.
+    resume_stmt_block: Option<BasicBlock>,
+    span: Span,
     next_local: usize,
 }
 
@@ -30,51 +47,53 @@ impl<'tcx> MirPatch<'tcx> {
             patch_map: IndexVec::from_elem(None, mir.basic_blocks()),
             new_blocks: vec![],
             new_statements: vec![],
+            statement_patches: vec![],
             new_locals: vec![],
             next_local: mir.local_decls.len(),
-            resume_block: START_BLOCK
+            resume_block: None,
+            resume_stmt_block: None,
+            span: mir.span,
         };
 
-        // make sure the MIR we create has a resume block. It is
-        // completely legal to convert jumps to the resume block
-        // to jumps to None, but we occasionally have to add
-        // instructions just before that.
-
-        let mut resume_block = None;
-        let mut resume_stmt_block = None;
         for (bb, block) in mir.basic_blocks().iter_enumerated() {
             if let TerminatorKind::Resume = block.terminator().kind {
                 if block.statements.len() > 0 {
-                    resume_stmt_block = Some(bb);
+                    result.resume_stmt_block = Some(bb);
                 } else {
-                    resume_block = Some(bb);
+                    result.resume_block = Some(bb);
                 }
                 break
             }
         }
-        let resume_block = resume_block.unwrap_or_else(|| {
-            result.new_block(BasicBlockData {
-                statements: vec![],
-                terminator: Some(Terminator {
-                    source_info: SourceInfo {
-                        span: mir.span,
-                        scope: ARGUMENT_VISIBILITY_SCOPE
-                    },
-                    kind: TerminatorKind::Resume
-                }),
-                is_cleanup: true
-            })});
-        result.resume_block = resume_block;
-        if let Some(resume_stmt_block) = resume_stmt_block {
-            result.patch_terminator(resume_stmt_block, TerminatorKind::Goto {
-                target: resume_block
-            });
-        }
         result
     }
 
-    pub fn resume_block(&self) -> BasicBlock {
-        self.resume_block
+    /// A block which only resumes unwinding, made on first use if the MIR
+    /// has none. It is completely legal to convert jumps to the resume
+    /// block to jumps to None, but we occasionally have to add
+    /// instructions just before that.
+    pub fn resume_block(&mut self) -> BasicBlock {
+        if let Some(resume_block) = self.resume_block {
+            return resume_block;
+        }
+        let resume_block = self.new_block(BasicBlockData {
+            statements: vec![],
+            terminator: Some(Terminator {
+                source_info: SourceInfo {
+                    span: self.span,
+                    scope: ARGUMENT_VISIBILITY_SCOPE
+                },
+                kind: TerminatorKind::Resume
+            }),
+            is_cleanup: true
+        });
+        self.resume_block = Some(resume_block);
+        if let Some(resume_stmt_block) = self.resume_stmt_block {
+            self.patch_terminator(resume_stmt_block, TerminatorKind::Goto {
+                target: resume_block
+            });
+        }
+        resume_block
     }
 
     pub fn is_patched(&self, bb: BasicBlock) -> bool {
@@ -122,6 +141,25 @@ impl<'tcx> MirPatch<'tcx> {
         self.add_statement(loc, StatementKind::Assign(lv, rv));
     }
 
+    /// Replaces the statement at `loc`, keeping its source info. The
+    /// statements added at `loc` still go before it.
+    pub fn patch_statement(&mut self, loc: Location, stmt: StatementKind<'tcx>) {
+        debug!("MirPatch: patch_statement({:?}, {:?})", loc, stmt);
+        assert!(self.statement_patches.iter().all(|&(patched, _)| patched != loc));
+        self.statement_patches.push((loc, stmt));
+    }
+
+    pub fn nop_statement(&mut self, loc: Location) {
+        self.patch_statement(loc, StatementKind::Nop);
+    }
+
+    /// Whether applying the patch would leave the MIR as it is.
+    pub fn is_empty(&self) -> bool {
+        self.new_blocks.is_empty() && self.new_locals.is_empty() &&
+            self.new_statements.is_empty() && self.statement_patches.is_empty() &&
+            self.patch_map.iter().all(|patch| patch.is_none())
+    }
+
     pub fn apply(self, mir: &mut Mir<'tcx>) {
         debug!("MirPatch: {:?} new temps, starting from index {}: {:?}",
                self.new_locals.len(), mir.local_decls.len(), self.new_locals);
@@ -136,6 +174,11 @@ impl<'tcx> MirPatch<'tcx> {
             }
         }
 
+        for (loc, stmt) in self.statement_patches {
+            debug!("MirPatch: patching statement at {:?}", loc);
+            mir[loc.block].statements[loc.statement_index].kind = stmt;
+        }
+
         let mut new_statements = self.new_statements;
         new_statements.sort_by(|u,v| u.0.cmp(&v.0));
 
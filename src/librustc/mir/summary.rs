@@ -0,0 +1,479 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Compact summaries of MIR bodies, computed once by the crate that
+//! owns the MIR and stored next to it in metadata, so that downstream
+//! crates can decide whether a body is worth looking at without
+//! decoding it first.
+
+use middle::const_val::ConstVal;
+use mir::{BasicBlockData, Constant, Literal, Location, Lvalue, Mir, Operand, ProjectionElem};
+use mir::{Rvalue, StatementKind, TerminatorKind, START_BLOCK};
+use mir::visit::{LvalueContext, Visitor};
+use rustc_data_structures::bitvec::BitVector;
+use traits::Reveal;
+use ty::{self, TyCtxt};
+use ty::subst::{Subst, Substs};
+
+/// Cost of a single statement or simple terminator.
+pub const INSTR_COST: u32 = 5;
+
+/// Cost of a terminator which calls out of the function: calls and
+/// drops (which may run a destructor).
+pub const CALL_PENALTY: u32 = 25;
+
+/// Cost of an assert. Only the check is on the path through the body,
+/// the panic and the cleanup it unwinds through are out of line, and
+/// usually shared by all the asserts of the body.
+pub const ASSERT_COST: u32 = INSTR_COST;
+
+/// What the cost of a cleanup block is divided by, as it only runs while
+/// unwinding.
+pub const CLEANUP_DISCOUNT: u32 = 4;
+
+/// Largest cost of a function `-Z cross-crate-auto-inline` makes
+/// available to other crates without `#[inline]`.
+pub const AUTO_INLINE_COST: u32 = 10 * INSTR_COST;
+
+/// The cost model shared by the optimizations weighing how much code a body
+/// is, split by when the code runs. See `mir_cost`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CostSummary {
+    /// Cost of the blocks which run when nothing panics.
+    pub hot: u32,
+
+    /// Cost of the cleanup blocks which run when a call unwinds, already
+    /// divided by `CLEANUP_DISCOUNT`.
+    pub unwind: u32,
+
+    /// Cost of the blocks which only run once an assert failed, typically
+    /// the cleanup of bounds checks.
+    pub cold: u32,
+
+    /// Sum of the sizes in bytes of the locals whose layout is known.
+    pub locals_size: u64,
+}
+
+/// What needs to be known about a function's MIR to estimate the
+/// cost of inlining it at a callsite.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct InlineSummary {
+    /// Estimated cost of the body, in units of `INSTR_COST`.
+    pub cost: u32,
+
+    /// Number of basic blocks in the body.
+    pub blocks: u32,
+
+    /// Whether the body has no `Return` terminator, i.e. it never
+    /// returns normally.
+    pub diverges: bool,
+
+    /// Whether the body refers to closure upvars.
+    pub has_upvars: bool,
+
+    /// Whether the body contains inline assembly.
+    pub has_asm: bool,
+
+    /// Sum of the sizes in bytes of the locals whose layout is known
+    /// without monomorphization.
+    pub locals_size: u64,
+
+    /// Whether the body has no effect besides computing its return
+    /// value: it only writes to its own locals, never calls, drops,
+    /// panics or loops, and always returns.
+    pub pure: bool,
+
+    /// What calling the function may do, including through its callees.
+    pub effects: EffectSummary,
+
+    /// Whether the MIR of the function is only in metadata because of
+    /// `-Z cross-crate-auto-inline`, rather than because it is generic,
+    /// `#[inline]` or `const`. Only set when encoding metadata.
+    pub auto_inline: bool,
+}
+
+/// The side effects a function may have, for the optimizations removing,
+/// merging or moving calls. Each fact is a "may": `false` is a guarantee,
+/// `true` means nothing is known.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RustcEncodable, RustcDecodable)]
+pub struct EffectSummary {
+    /// Whether it may write memory other than its own locals, e.g. memory
+    /// reachable from its arguments, or a static.
+    pub writes_memory: bool,
+
+    /// Whether it may read or write a static.
+    pub accesses_statics: bool,
+
+    /// Whether it may panic, which unwinds to the caller unless panics
+    /// abort.
+    pub can_unwind: bool,
+
+    /// Whether it may loop forever, recurse, or otherwise not return.
+    pub may_not_return: bool,
+}
+
+impl EffectSummary {
+    /// The summary of a function nothing is known about.
+    pub fn unknown() -> EffectSummary {
+        EffectSummary {
+            writes_memory: true,
+            accesses_statics: true,
+            can_unwind: true,
+            may_not_return: true,
+        }
+    }
+
+    /// The summary of a function which only computes its return value.
+    pub fn none() -> EffectSummary {
+        EffectSummary {
+            writes_memory: false,
+            accesses_statics: false,
+            can_unwind: false,
+            may_not_return: false,
+        }
+    }
+
+    /// The effects of doing what either `self` or `other` does.
+    pub fn union(self, other: EffectSummary) -> EffectSummary {
+        EffectSummary {
+            writes_memory: self.writes_memory || other.writes_memory,
+            accesses_statics: self.accesses_statics || other.accesses_statics,
+            can_unwind: self.can_unwind || other.can_unwind,
+            may_not_return: self.may_not_return || other.may_not_return,
+        }
+    }
+
+    /// Whether a call whose result is unused can be removed. Reading
+    /// statics is fine, as long as nothing is written.
+    pub fn is_removable(&self) -> bool {
+        !self.writes_memory && !self.can_unwind && !self.may_not_return
+    }
+
+    /// The effects of running `mir`, with those of its callees taken from
+    /// `tcx.item_mir_effects`. Recursion isn't detected here, see the
+    /// `InferEffects` pass for that.
+    pub fn of_body<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> EffectSummary {
+        let mut effects = EffectSummary::none();
+        let mut statics = StaticAccesses { found: false };
+        statics.visit_mir(mir);
+        effects.accesses_statics = statics.found;
+
+        for block in mir.basic_blocks() {
+            for statement in &block.statements {
+                match statement.kind {
+                    StatementKind::Assign(_, Rvalue::InlineAsm { .. }) => {
+                        return EffectSummary::unknown();
+                    }
+                    StatementKind::Assign(ref lvalue, _) |
+                    StatementKind::SetDiscriminant { ref lvalue, .. } |
+                    StatementKind::Poison(ref lvalue) => {
+                        effects.writes_memory |= writes_memory(lvalue);
+                    }
+                    // The counters are in memory shared by the whole program.
+                    StatementKind::IncrementCounter(_) => effects.writes_memory = true,
+                    StatementKind::StorageLive(_) |
+                    StatementKind::StorageDead(_) |
+                    StatementKind::Nop => {}
+                }
+            }
+
+            match block.terminator().kind {
+                TerminatorKind::Call { ref func, .. } => {
+                    effects = effects.union(callee_effects(tcx, mir, func));
+                }
+                // The destructor could do anything.
+                TerminatorKind::Drop { .. } |
+                TerminatorKind::DropAndReplace { .. } => return EffectSummary::unknown(),
+                TerminatorKind::Assert { .. } => effects.can_unwind = true,
+                TerminatorKind::Abort => effects.may_not_return = true,
+                TerminatorKind::Goto { .. } |
+                TerminatorKind::SwitchInt { .. } |
+                TerminatorKind::Resume |
+                TerminatorKind::Return |
+                TerminatorKind::Unreachable => {}
+            }
+        }
+
+        if !effects.may_not_return {
+            effects.may_not_return = has_loops(mir) || !mir.basic_blocks().iter().any(|block| {
+                match block.terminator().kind {
+                    TerminatorKind::Return => true,
+                    _ => false
+                }
+            });
+        }
+        effects
+    }
+}
+
+impl InlineSummary {
+    pub fn compute<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> InlineSummary {
+        let mut diverges = true;
+        let mut has_asm = false;
+        let mut pure = true;
+
+        for block in mir.basic_blocks() {
+            for statement in &block.statements {
+                match statement.kind {
+                    StatementKind::Assign(_, Rvalue::InlineAsm { .. }) => has_asm = true,
+                    StatementKind::Assign(ref lvalue, _) |
+                    StatementKind::SetDiscriminant { ref lvalue, .. } |
+                    StatementKind::Poison(ref lvalue) => {
+                        pure &= !writes_memory(lvalue);
+                    }
+                    StatementKind::IncrementCounter(_) => pure = false,
+                    StatementKind::StorageLive(_) |
+                    StatementKind::StorageDead(_) |
+                    StatementKind::Nop => {}
+                }
+            }
+            match block.terminator().kind {
+                TerminatorKind::Return => diverges = false,
+                TerminatorKind::Drop { .. } |
+                TerminatorKind::DropAndReplace { .. } |
+                TerminatorKind::Call { .. } |
+                TerminatorKind::Assert { .. } => pure = false,
+                _ => {}
+            }
+        }
+
+        // A loop may never terminate, which is an effect in itself.
+        if pure && !diverges {
+            pure = !has_loops(mir);
+        }
+
+        // The cold blocks aren't charged at all, and neither are the cleanup
+        // blocks if panics abort.
+        let costs = mir_cost(tcx, mir, tcx.intern_substs(&[]), None);
+        let cost = if tcx.sess.no_landing_pads() {
+            costs.hot
+        } else {
+            costs.hot + costs.unwind
+        };
+
+        InlineSummary {
+            cost: cost,
+            blocks: mir.basic_blocks().len() as u32,
+            diverges: diverges,
+            has_upvars: !mir.upvar_decls.is_empty(),
+            has_asm: has_asm,
+            locals_size: costs.locals_size,
+            pure: pure && !diverges && !has_asm,
+            effects: EffectSummary::of_body(tcx, mir),
+            auto_inline: false,
+        }
+    }
+
+    /// Whether another crate could translate a copy of `mir`, summarized
+    /// by `self`, as if it were `#[inline]`. It has to be small, and must
+    /// not refer to statics, functions or constants, whose symbols the
+    /// crate defining it may not export.
+    pub fn can_auto_inline(&self, mir: &Mir) -> bool {
+        if self.cost > AUTO_INLINE_COST || self.has_asm || self.effects.accesses_statics {
+            return false;
+        }
+        let calls = mir.basic_blocks().iter().any(|block| match block.terminator().kind {
+            TerminatorKind::Call { .. } |
+            TerminatorKind::Drop { .. } |
+            TerminatorKind::DropAndReplace { .. } => true,
+            _ => false
+        });
+        if calls {
+            return false;
+        }
+        let mut references = ItemReferences { found: false };
+        references.visit_mir(mir);
+        for promoted in &mir.promoted {
+            references.visit_mir(promoted);
+        }
+        !references.found
+    }
+}
+
+/// The costs of `mir`, for the optimizations deciding whether a body is
+/// small enough to copy, or a part of it large enough to move out, and for
+/// the statistics about the code they leave. The locals are sized with
+/// their types substituted by `substs` and in `param_env`, if any; with
+/// empty `substs`, those whose type depends on generic parameters aren't
+/// counted.
+pub fn mir_cost<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          mir: &Mir<'tcx>,
+                          substs: &'tcx Substs<'tcx>,
+                          param_env: Option<ty::ParameterEnvironment<'tcx>>)
+                          -> CostSummary {
+    let mut costs = CostSummary {
+        hot: 0,
+        unwind: 0,
+        cold: 0,
+        locals_size: 0,
+    };
+    let hot = hot_blocks(mir);
+    for (bb, block) in mir.basic_blocks().iter_enumerated() {
+        let cost = block_cost(block);
+        if !hot.contains(bb.index()) {
+            costs.cold += cost;
+        } else if block.is_cleanup {
+            costs.unwind += cost / CLEANUP_DISCOUNT;
+        } else {
+            costs.hot += cost;
+        }
+    }
+
+    costs.locals_size = tcx.infer_ctxt(None, param_env, Reveal::All).enter(|infcx| {
+        mir.local_decls.iter().filter_map(|decl| {
+            let ty = if substs.is_empty() { decl.ty } else { decl.ty.subst(tcx, substs) };
+            ty.layout(&infcx).ok().map(|layout| layout.size(&tcx.data_layout).bytes())
+        }).sum()
+    });
+    costs
+}
+
+/// The cost of the statements and terminator of `block`.
+fn block_cost(block: &BasicBlockData) -> u32 {
+    let statements = block.statements.iter().map(|statement| match statement.kind {
+        StatementKind::StorageLive(_) |
+        StatementKind::StorageDead(_) |
+        StatementKind::IncrementCounter(_) |
+        StatementKind::Poison(_) |
+        StatementKind::Nop => 0,
+        // Translated along with the `SwitchInt` reading it, which is already
+        // accounted for.
+        StatementKind::Assign(_, Rvalue::Discriminant(_)) => 0,
+        StatementKind::Assign(..) |
+        StatementKind::SetDiscriminant { .. } => INSTR_COST,
+    }).sum::<u32>();
+
+    statements + match block.terminator().kind {
+        TerminatorKind::Resume |
+        TerminatorKind::Abort |
+        TerminatorKind::Unreachable => 0,
+        TerminatorKind::Drop { .. } |
+        TerminatorKind::DropAndReplace { .. } |
+        TerminatorKind::Call { .. } => CALL_PENALTY,
+        TerminatorKind::Assert { .. } => ASSERT_COST,
+        TerminatorKind::Return |
+        TerminatorKind::Goto { .. } |
+        TerminatorKind::SwitchInt { .. } => INSTR_COST,
+    }
+}
+
+/// The effects of calling `func` from `mir`.
+fn callee_effects<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>, func: &Operand<'tcx>)
+                            -> EffectSummary {
+    let constant = match *func {
+        Operand::Constant(ref constant) => constant,
+        _ => return EffectSummary::unknown()
+    };
+    if let Literal::Outlined { index } = constant.literal {
+        return EffectSummary::of_body(tcx, &mir.outlined[index]);
+    }
+    match constant.ty.sty {
+        // The MIR of a trait method, if any, is only its default body,
+        // which impls may override.
+        ty::TyFnDef(callee, _, _) if tcx.trait_of_item(callee).is_none() => {
+            let effects = tcx.item_mir_effects(callee);
+            if tcx.has_attr(callee, "rustc_pure") {
+                // The attribute vouches for the memory the function
+                // touches, not for whether it panics or returns.
+                EffectSummary {
+                    writes_memory: false,
+                    accesses_statics: false,
+                    ..effects
+                }
+            } else {
+                effects
+            }
+        }
+        _ => EffectSummary::unknown()
+    }
+}
+
+/// The blocks reachable from the start of `mir` without unwinding out of an
+/// assert.
+fn hot_blocks(mir: &Mir) -> BitVector {
+    let mut hot = BitVector::new(mir.basic_blocks().len());
+    let mut stack = vec![START_BLOCK];
+    while let Some(bb) = stack.pop() {
+        if !hot.insert(bb.index()) {
+            continue;
+        }
+        let terminator = mir[bb].terminator();
+        match terminator.kind {
+            TerminatorKind::Assert { target, .. } => stack.push(target),
+            _ => stack.extend(terminator.successors().iter().cloned()),
+        }
+    }
+    hot
+}
+
+/// Whether any block reachable from the start of `mir` jumps back to one
+/// dominating it.
+fn has_loops(mir: &Mir) -> bool {
+    let dominators = mir.dominators();
+    mir.basic_blocks().iter_enumerated().any(|(bb, block)| {
+        dominators.is_reachable(bb) && block.terminator().successors().iter().any(|&succ| {
+            dominators.is_dominated_by(bb, succ)
+        })
+    })
+}
+
+/// Finds whether a body reads or writes a static.
+struct StaticAccesses {
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for StaticAccesses {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Static(_) = *lvalue {
+            self.found = true;
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// Finds whether a body refers to an item through a constant.
+struct ItemReferences {
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for ItemReferences {
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, location: Location) {
+        self.found |= match constant.literal {
+            Literal::Value { ref value } => match *value {
+                ConstVal::Float(_) |
+                ConstVal::Integral(_) |
+                ConstVal::Str(_) |
+                ConstVal::ByteStr(_) |
+                ConstVal::Bool(_) |
+                ConstVal::Char(_) => false,
+                _ => true
+            },
+            Literal::Promoted { .. } => false,
+            Literal::Item { .. } |
+            Literal::Outlined { .. } => true,
+        };
+        self.super_constant(constant, location);
+    }
+}
+
+/// Whether assigning to `lvalue` writes to memory other than a local.
+fn writes_memory(lvalue: &Lvalue) -> bool {
+    match *lvalue {
+        Lvalue::Local(_) => false,
+        Lvalue::Static(_) => true,
+        Lvalue::Projection(ref proj) => match proj.elem {
+            ProjectionElem::Deref => true,
+            _ => writes_memory(&proj.base),
+        },
+    }
+}
@@ -153,6 +153,14 @@ impl<'tcx> Rvalue<'tcx> {
                 ))
             }
             &Rvalue::Len(..) => Some(tcx.types.usize),
+            &Rvalue::Discriminant(ref lv) => {
+                let ty = lv.ty(mir, tcx).to_ty(tcx);
+                if let ty::TyAdt(adt_def, _) = ty.sty {
+                    Some(adt_def.discr_ty(tcx))
+                } else {
+                    bug!("Rvalue::Discriminant on non-enum {:?}", ty)
+                }
+            }
             &Rvalue::Cast(.., ty) => Some(ty),
             &Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
                 let lhs_ty = lhs.ty(mir, tcx);
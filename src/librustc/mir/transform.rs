@@ -11,13 +11,19 @@
 use dep_graph::DepNode;
 use hir;
 use hir::map::DefPathData;
+use lint;
 use mir::{Mir, Promoted};
+use session::Session;
 use ty::TyCtxt;
-use syntax::ast::NodeId;
+use syntax::ast::{NodeId, CRATE_NODE_ID};
+use syntax_pos::{Span, NO_EXPANSION};
 use util::common::time;
+use util::nodemap::FxHashMap;
+use rustc_serialize as serialize;
 
 use std::borrow::Cow;
 use std::fmt;
+use std::mem;
 
 /// Where a specific Mir comes from.
 #[derive(Debug, Copy, Clone)]
@@ -32,7 +38,11 @@ pub enum MirSource {
     Static(NodeId, hir::Mutability),
 
     /// Promoted rvalues within a function.
-    Promoted(NodeId, Promoted)
+    Promoted(NodeId, Promoted),
+
+    /// Compiler-generated functions, see `mir::Shim`. They have no node of
+    /// their own, so `item_id` gives the crate's.
+    Shim
 }
 
 impl<'a, 'tcx> MirSource {
@@ -65,11 +75,53 @@ impl<'a, 'tcx> MirSource {
             MirSource::Fn(id) |
             MirSource::Const(id) |
             MirSource::Static(id, _) |
-            MirSource::Promoted(id, _) => id
+            MirSource::Promoted(id, _) => id,
+            MirSource::Shim => CRATE_NODE_ID
         }
     }
 }
 
+/// Something a pass found out about the source code of a function, e.g.
+/// that a branch is never taken, which the user may want to simplify.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The function the pass was looking at.
+    pub item: NodeId,
+    pub span: Span,
+    pub msg: String,
+}
+
+/// Records `msg` about the code at `span` in `src`, to be reported with the
+/// `simplifiable_code` lint by `report_suggestions`. Code coming from a
+/// macro expansion or a shim is left alone, as the user can't change it.
+pub fn suggest<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, span: Span, msg: String) {
+    if span.expn_id != NO_EXPANSION {
+        return;
+    }
+    if let MirSource::Shim = src {
+        return;
+    }
+    tcx.mir_suggestions.borrow_mut().push(Suggestion {
+        item: src.item_id(),
+        span: span,
+        msg: msg,
+    });
+}
+
+/// Reports the suggestions recorded so far, in source order. A pass running
+/// several times reports the same thing only once.
+pub fn report_suggestions<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+    let mut suggestions = ::std::mem::replace(&mut *tcx.mir_suggestions.borrow_mut(), vec![]);
+    suggestions.sort_by(|a, b| {
+        (a.span.lo, a.span.hi, &a.msg).cmp(&(b.span.lo, b.span.hi, &b.msg))
+    });
+    suggestions.dedup();
+    for suggestion in suggestions {
+        lint::emit_node_lint(tcx, lint::builtin::SIMPLIFIABLE_CODE,
+                             suggestion.item, suggestion.span, &suggestion.msg);
+    }
+}
+
 /// Various information about pass.
 pub trait Pass {
     // fn should_run(Session) to check if pass should run?
@@ -82,6 +134,16 @@ pub trait Pass {
         }
     }
     fn disambiguator<'a>(&'a self) -> Option<Box<fmt::Display+'a>> { None }
+
+    /// Whether this pass only cleans up after the others, so that running
+    /// it on MIR which no pass changed since it last ran does nothing. The
+    /// pass manager skips it for such MIR, along with the hooks around it.
+    fn is_cleanup(&self) -> bool { false }
+
+    /// Whether this pass changes functions relying on how the rest of the
+    /// crate uses them, e.g. on the arguments they are called with, so that
+    /// a function alone may behave differently afterwards.
+    fn is_interprocedural(&self) -> bool { false }
 }
 
 /// A pass which inspects the whole Mir map.
@@ -105,8 +167,10 @@ pub trait MirPassHook<'tcx>: Pass {
 
 /// A pass which inspects Mir of functions in isolation.
 pub trait MirPass<'tcx>: Pass {
+    /// Runs the pass on `mir`, returning whether it changed it. Returning
+    /// `true` for MIR left as it was only costs the cleanup passes a run.
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    src: MirSource, mir: &mut Mir<'tcx>);
+                    src: MirSource, mir: &mut Mir<'tcx>) -> bool;
 }
 
 impl<'tcx, T: MirPass<'tcx>> MirMapPass<'tcx> for T {
@@ -126,34 +190,103 @@ impl<'tcx, T: MirPass<'tcx>> MirMapPass<'tcx> for T {
 
             let id = tcx.map.as_local_node_id(def_id).unwrap();
             let src = MirSource::from_node(tcx, id);
-
-            for hook in &mut *hooks {
-                hook.on_mir_pass(tcx, src, mir, self, false);
-            }
-            MirPass::run_pass(self, tcx, src, mir);
-            for hook in &mut *hooks {
-                hook.on_mir_pass(tcx, src, mir, self, true);
-            }
+            run_hooked(self, tcx, src, mir, hooks);
 
             for (i, mir) in mir.promoted.iter_enumerated_mut() {
-                let src = MirSource::Promoted(id, i);
-                for hook in &mut *hooks {
-                    hook.on_mir_pass(tcx, src, mir, self, false);
-                }
-                MirPass::run_pass(self, tcx, src, mir);
-                for hook in &mut *hooks {
-                    hook.on_mir_pass(tcx, src, mir, self, true);
-                }
+                run_hooked(self, tcx, MirSource::Promoted(id, i), mir, hooks);
             }
         }
     }
 }
 
+/// Runs `pass` on `mir` between the hooks, unless it is a cleanup pass
+/// with nothing to do.
+fn run_hooked<'a, 'tcx, T: MirPass<'tcx>>(pass: &mut T,
+                                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                          src: MirSource,
+                                          mir: &mut Mir<'tcx>,
+                                          hooks: &mut [Box<for<'s> MirPassHook<'s>>]) {
+    let cleanup = if pass.is_cleanup() { Some(pass.name()) } else { None };
+    if let Some(ref name) = cleanup {
+        if mir.pass_revisions.is_clean(name) {
+            debug!("skipping {} on unchanged {:?}", name, src);
+            return;
+        }
+    }
+
+    for hook in &mut *hooks {
+        hook.on_mir_pass(tcx, src, mir, pass, false);
+    }
+    if MirPass::run_pass(pass, tcx, src, mir) {
+        mir.pass_revisions.changes += 1;
+    }
+    if let Some(name) = cleanup {
+        let changes = mir.pass_revisions.changes;
+        mir.pass_revisions.cleaned.insert(name, changes);
+    }
+    for hook in &mut *hooks {
+        hook.on_mir_pass(tcx, src, mir, pass, true);
+    }
+}
+
+/// How far each cleanup pass is behind the changes made to a MIR body, for
+/// the pass manager to skip those with nothing to do. It is not encoded in
+/// metadata, and starts afresh in the crates using the MIR.
+#[derive(Clone, Debug)]
+pub struct PassRevisions {
+    /// How many times a pass changed the MIR.
+    changes: u32,
+    /// The value of `changes` when each cleanup pass last ran, by name.
+    cleaned: FxHashMap<Cow<'static, str>, u32>,
+}
+
+impl PassRevisions {
+    pub fn new() -> PassRevisions {
+        PassRevisions {
+            changes: 0,
+            cleaned: FxHashMap(),
+        }
+    }
+
+    /// Whether no pass changed the MIR since the cleanup pass `name` ran.
+    fn is_clean(&self, name: &str) -> bool {
+        self.cleaned.get(name) == Some(&self.changes)
+    }
+}
+
+impl serialize::Encodable for PassRevisions {
+    fn encode<S: serialize::Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        serialize::Encodable::encode(&(), s)
+    }
+}
+
+impl serialize::Decodable for PassRevisions {
+    fn decode<D: serialize::Decoder>(d: &mut D) -> Result<Self, D::Error> {
+        serialize::Decodable::decode(d).map(|_v: ()| Self::new())
+    }
+}
+
+/// The points of the pipeline plugins can run their passes at.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PassPoint {
+    /// First, on the MIR as it was built.
+    Built,
+    /// After the MIR was cleaned up and validated, before constants are
+    /// evaluated.
+    Validated,
+    /// After the optimizations, right before translation.
+    Optimized,
+}
+
+/// Makes a new instance of a pass hook registered by a plugin, for each
+/// pass manager. Instances which need to share state have to do so
+/// themselves, e.g. through an `Rc`.
+pub type MirPassHookFactory = Box<Fn() -> Box<for<'tcx> MirPassHook<'tcx>>>;
+
 /// A manager for MIR passes.
 pub struct Passes {
     passes: Vec<Box<for<'tcx> MirMapPass<'tcx>>>,
     pass_hooks: Vec<Box<for<'tcx> MirPassHook<'tcx>>>,
-    plugin_passes: Vec<Box<for<'tcx> MirMapPass<'tcx>>>
 }
 
 impl<'a, 'tcx> Passes {
@@ -161,14 +294,13 @@ impl<'a, 'tcx> Passes {
         let passes = Passes {
             passes: Vec::new(),
             pass_hooks: Vec::new(),
-            plugin_passes: Vec::new()
         };
         passes
     }
 
     pub fn run_passes(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>) {
-        let Passes { ref mut passes, ref mut plugin_passes, ref mut pass_hooks } = *self;
-        for pass in plugin_passes.iter_mut().chain(passes.iter_mut()) {
+        let Passes { ref mut passes, ref mut pass_hooks } = *self;
+        for pass in passes {
             time(tcx.sess.time_passes(), &*pass.name(),
                  || pass.run_pass(tcx, pass_hooks));
         }
@@ -183,11 +315,23 @@ impl<'a, 'tcx> Passes {
     pub fn push_hook(&mut self, hook: Box<for<'b> MirPassHook<'b>>) {
         self.pass_hooks.push(hook);
     }
-}
 
-/// Copies the plugin passes.
-impl ::std::iter::Extend<Box<for<'a> MirMapPass<'a>>> for Passes {
-    fn extend<I: IntoIterator<Item=Box<for <'a> MirMapPass<'a>>>>(&mut self, it: I) {
-        self.plugin_passes.extend(it);
+    /// Pushes the passes plugins registered to run at `point`, in the
+    /// order they were registered in. They are taken out of the session,
+    /// as they only run once.
+    pub fn push_plugin_passes(&mut self, sess: &Session, point: PassPoint) {
+        let plugin_passes = mem::replace(&mut *sess.plugin_mir_passes.borrow_mut(), vec![]);
+        let (at_point, rest): (Vec<_>, Vec<_>) = plugin_passes.into_iter().partition(|entry| {
+            entry.0 == point
+        });
+        *sess.plugin_mir_passes.borrow_mut() = rest;
+        self.passes.extend(at_point.into_iter().map(|(_, pass)| pass));
+    }
+
+    /// Pushes a new instance of each of the pass hooks plugins registered.
+    pub fn push_plugin_hooks(&mut self, sess: &Session) {
+        for new_hook in sess.plugin_mir_pass_hooks.borrow().iter() {
+            self.pass_hooks.push(new_hook());
+        }
     }
 }
@@ -328,6 +328,10 @@ macro_rules! make_mir_visitor {
                     StatementKind::StorageDead(ref $($mutability)* lvalue) => {
                         self.visit_lvalue(lvalue, LvalueContext::StorageDead, location);
                     }
+                    StatementKind::Poison(ref $($mutability)* lvalue) => {
+                        self.visit_lvalue(lvalue, LvalueContext::Store, location);
+                    }
+                    StatementKind::IncrementCounter(_) |
                     StatementKind::Nop => {}
                 }
             }
@@ -363,28 +367,11 @@ macro_rules! make_mir_visitor {
                         self.visit_branch(block, target);
                     }
 
-                    TerminatorKind::If { ref $($mutability)* cond,
-                                         ref $($mutability)* targets } => {
-                        self.visit_operand(cond, source_location);
-                        for &target in targets.as_slice() {
-                            self.visit_branch(block, target);
-                        }
-                    }
-
-                    TerminatorKind::Switch { ref $($mutability)* discr,
-                                             adt_def: _,
-                                             ref targets } => {
-                        self.visit_lvalue(discr, LvalueContext::Inspect, source_location);
-                        for &target in targets {
-                            self.visit_branch(block, target);
-                        }
-                    }
-
                     TerminatorKind::SwitchInt { ref $($mutability)* discr,
                                                 ref $($mutability)* switch_ty,
                                                 ref $($mutability)* values,
                                                 ref targets } => {
-                        self.visit_lvalue(discr, LvalueContext::Inspect, source_location);
+                        self.visit_operand(discr, source_location);
                         self.visit_ty(switch_ty);
                         for value in values {
                             self.visit_const_val(value, source_location);
@@ -395,6 +382,7 @@ macro_rules! make_mir_visitor {
                     }
 
                     TerminatorKind::Resume |
+                    TerminatorKind::Abort |
                     TerminatorKind::Return |
                     TerminatorKind::Unreachable => {
                     }
@@ -456,7 +444,9 @@ macro_rules! make_mir_visitor {
                         self.visit_operand(len, location);
                         self.visit_operand(index, location);
                     }
-                    AssertMessage::Math(_) => {}
+                    AssertMessage::Math(_) |
+                    AssertMessage::NullPointerDeref |
+                    AssertMessage::MisalignedPointerDeref => {}
                 }
             }
 
@@ -481,7 +471,8 @@ macro_rules! make_mir_visitor {
                         }, location);
                     }
 
-                    Rvalue::Len(ref $($mutability)* path) => {
+                    Rvalue::Len(ref $($mutability)* path) |
+                    Rvalue::Discriminant(ref $($mutability)* path) => {
                         self.visit_lvalue(path, LvalueContext::Inspect, location);
                     }
 
@@ -625,6 +616,7 @@ macro_rules! make_mir_visitor {
                     ref $($mutability)* ty,
                     name: _,
                     ref $($mutability)* source_info,
+                    is_const: _,
                 } = *local_decl;
 
                 self.visit_ty(ty);
@@ -683,6 +675,7 @@ macro_rules! make_mir_visitor {
                         self.visit_const_val(value, location);
                     }
                     Literal::Promoted { index: _ } => {}
+                    Literal::Outlined { index: _ } => {}
                 }
             }
 
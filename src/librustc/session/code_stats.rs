@@ -8,10 +8,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use hir::def_id::DefId;
 use ty::AdtKind;
 use ty::layout::{Align, Size};
 
-use rustc_data_structures::fx::{FxHashSet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 
 use std::cmp::{self, Ordering};
 
@@ -66,10 +67,13 @@ pub struct TypeSizeInfo {
 #[derive(PartialEq, Eq, Debug)]
 pub struct CodeStats {
     type_sizes: FxHashSet<TypeSizeInfo>,
+    /// The stack frame sizes trans estimated for the functions, the
+    /// largest of their instances for generic ones.
+    frame_sizes: FxHashMap<DefId, u64>,
 }
 
 impl CodeStats {
-    pub fn new() -> Self { CodeStats { type_sizes: FxHashSet() } }
+    pub fn new() -> Self { CodeStats { type_sizes: FxHashSet(), frame_sizes: FxHashMap() } }
 
     pub fn record_type_size<S: ToString>(&mut self,
                                          kind: DataTypeKind,
@@ -89,6 +93,16 @@ impl CodeStats {
         self.type_sizes.insert(info);
     }
 
+    pub fn record_frame_size(&mut self, def_id: DefId, frame_size: u64) {
+        let size = self.frame_sizes.entry(def_id).or_insert(0);
+        *size = cmp::max(*size, frame_size);
+    }
+
+    /// The estimated stack frame size of `def_id`, if it was translated.
+    pub fn frame_size(&self, def_id: DefId) -> Option<u64> {
+        self.frame_sizes.get(&def_id).cloned()
+    }
+
     pub fn print_type_sizes(&self) {
         let mut sorted: Vec<_> = self.type_sizes.iter().collect();
 
@@ -76,13 +76,15 @@ pub enum OutputType {
     Object,
     Exe,
     DepInfo,
+    Mir,
 }
 
 impl OutputType {
     fn is_compatible_with_codegen_units_and_single_output_file(&self) -> bool {
         match *self {
             OutputType::Exe |
-            OutputType::DepInfo => true,
+            OutputType::DepInfo |
+            OutputType::Mir => true,
             OutputType::Bitcode |
             OutputType::Assembly |
             OutputType::LlvmAssembly |
@@ -98,6 +100,7 @@ impl OutputType {
             OutputType::Object => "obj",
             OutputType::Exe => "link",
             OutputType::DepInfo => "dep-info",
+            OutputType::Mir => "mir",
         }
     }
 
@@ -108,6 +111,7 @@ impl OutputType {
             OutputType::LlvmAssembly => "ll",
             OutputType::Object => "o",
             OutputType::DepInfo => "d",
+            OutputType::Mir => "mir",
             OutputType::Exe => "",
         }
     }
@@ -861,6 +865,9 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
     save_analysis_api: bool = (false, parse_bool, [UNTRACKED],
         "write syntax and type analysis information for opaque libraries (in JSON format), \
          in addition to normal output"),
+    save_analysis_mir: bool = (false, parse_bool, [UNTRACKED],
+        "write facts about the optimized MIR (in JSON format) to the save-analysis directory, \
+         in addition to normal output"),
     print_move_fragments: bool = (false, parse_bool, [UNTRACKED],
         "print out move-fragment data for every fn"),
     flowgraph_print_loans: bool = (false, parse_bool, [UNTRACKED],
@@ -900,6 +907,8 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "adds unstable command line options to rustc interface"),
     force_overflow_checks: Option<bool> = (None, parse_opt_bool, [TRACKED],
           "force overflow checks on or off"),
+    force_debug_assertions_mir: Option<bool> = (None, parse_opt_bool, [TRACKED],
+          "force overflow and bounds checks in MIR on or off (off makes indexing unsound)"),
     trace_macros: bool = (false, parse_bool, [UNTRACKED],
           "for every macro invocation, print its name and arguments"),
     debug_macros: bool = (false, parse_bool, [TRACKED],
@@ -917,17 +926,73 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
     print_trans_items: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "print the result of the translation item collection pass"),
     mir_opt_level: Option<usize> = (None, parse_opt_uint, [TRACKED],
-          "set the MIR optimization level (0-3)"),
+          "set the MIR optimization level (0-3), 1 by default or 2 with -C opt-level \
+           above 0"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "dump MIR state at various points in translation"),
     dump_mir_dir: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "the directory the MIR is dumped into"),
+    dump_mir_json: bool = (false, parse_bool, [UNTRACKED],
+          "dump MIR selected by -Z dump-mir as JSON rather than as text"),
+    mir_opt_report: Option<String> = (None, parse_opt_string, [UNTRACKED],
+          "write a JSON report of what the MIR optimizations did to the given file"),
+    dump_mir_html: bool = (false, parse_bool, [UNTRACKED],
+          "dump MIR selected by -Z dump-mir as one HTML page per item, with a tab per pass"),
+    dump_mir_verbose: bool = (false, parse_bool, [UNTRACKED],
+          "in the MIR dumped as text, give the types of the lvalues and constants, and the \
+           predecessors of the blocks"),
+    mir_branch_prob: bool = (false, parse_bool, [TRACKED],
+          "attach branch weights derived from MIR to conditional branches"),
+    profile_mir: bool = (false, parse_bool, [TRACKED],
+          "count how often each MIR basic block is executed"),
+    mir_coverage: bool = (false, parse_bool, [TRACKED],
+          "instrument MIR for source-based code coverage"),
+    mir_outline_cold: bool = (false, parse_bool, [TRACKED],
+          "move large blocks of MIR which can only end in a panic into separate functions"),
+    no_mir_fold_floats: bool = (false, parse_bool, [TRACKED],
+          "don't fold float arithmetic on constants in MIR"),
+    mir_check_refs: bool = (false, parse_bool, [TRACKED],
+          "check that the raw pointers dereferenced are non-null and aligned, panicking if not"),
+    mir_poison_uninit: bool = (false, parse_bool, [TRACKED],
+          "fill the memory of locals with a byte pattern while they hold no value, if debug \
+           assertions are on"),
+    const_eval_limit: Option<usize> = (None, parse_opt_uint, [TRACKED],
+          "the number of MIR statements evaluating a constant may execute"),
+    mir_effects_round_limit: Option<usize> = (None, parse_opt_uint, [TRACKED],
+          "how many times the effects of a group of mutually recursive functions are inferred \
+           again before they are given up on (default: 100)"),
+    cross_crate_auto_inline: bool = (false, parse_bool, [TRACKED],
+          "make the MIR of small functions without `#[inline]` available to other crates, \
+           for them to translate their own copies"),
+    mir_interpret: bool = (false, parse_bool, [UNTRACKED],
+          "run `main` in the MIR interpreter, reporting undefined behavior"),
+    mir_interpret_fn: Option<String> = (None, parse_opt_string, [UNTRACKED],
+          "the function `-Z mir-interpret` runs instead of `main`"),
     perf_stats: bool = (false, parse_bool, [UNTRACKED],
           "print some performance-related statistics"),
     hir_stats: bool = (false, parse_bool, [UNTRACKED],
           "print some statistics about AST and HIR"),
     mir_stats: bool = (false, parse_bool, [UNTRACKED],
           "print some statistics about MIR"),
+    mir_memory_stats: bool = (false, parse_bool, [UNTRACKED],
+          "print how much memory the MIR holds after each optimisation pass"),
+    borrowck_mir: bool = (false, parse_bool, [UNTRACKED],
+          "run the MIR borrow checker alongside the AST one, reporting where they disagree"),
+    mir_definite_init: bool = (false, parse_bool, [UNTRACKED],
+          "report uses of possibly uninitialized variables from MIR rather than the AST"),
+    definite_init_crosscheck: bool = (false, parse_bool, [UNTRACKED],
+          "check for uses of uninitialized variables on both the AST and MIR, reporting where \
+           they disagree"),
+    verify_mir_drops: bool = (false, parse_bool, [UNTRACKED],
+          "check after each MIR optimisation that no value is used after a move or dropped twice"),
+    verify_cleanup_edges: bool = (false, parse_bool, [UNTRACKED],
+          "check after each MIR optimisation that cleanup blocks are only reached by unwinding"),
+    mir_opt_verify_equivalence: bool = (false, parse_bool, [UNTRACKED],
+          "interpret each function before and after every MIR optimisation on a few inputs, \
+           ICEing if the results differ"),
+    mir_fuzz: Option<usize> = (None, parse_opt_uint, [UNTRACKED],
+          "run the MIR optimisations on this many random bodies, checking that they still \
+           compute the same thing"),
 }
 
 pub fn default_lib_output() -> CrateType {
@@ -1167,7 +1232,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
                "NAME"),
         opt::multi_s("", "emit", "Comma separated list of types of output for \
                               the compiler to emit",
-                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info]"),
+                 "[asm|llvm-bc|llvm-ir|obj|link|dep-info|mir]"),
         opt::multi_s("", "print", "Comma separated list of compiler information to \
                                print on stdout", &print_opts.join("|")),
         opt::flagmulti_s("g",  "",  "Equivalent to -C debuginfo=2"),
@@ -1320,8 +1385,6 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
 
     let debugging_opts = build_debugging_options(matches, error_format);
 
-    let mir_opt_level = debugging_opts.mir_opt_level.unwrap_or(1);
-
     let mut output_types = BTreeMap::new();
     if !debugging_opts.parse_only {
         for list in matches.opt_strs("emit") {
@@ -1334,6 +1397,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
                     "obj" => OutputType::Object,
                     "link" => OutputType::Exe,
                     "dep-info" => OutputType::DepInfo,
+                    "mir" => OutputType::Mir,
                     part => {
                         early_error(error_format, &format!("unknown emission type: `{}`",
                                                     part))
@@ -1424,6 +1488,13 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
             }
         }
     };
+    // Level 1 only does the cheap cleanups, level 2 the optimizations worth
+    // the compile time when LLVM optimizes too.
+    let mir_opt_level = debugging_opts.mir_opt_level.unwrap_or(match opt_level {
+        OptLevel::No => 1,
+        OptLevel::Less | OptLevel::Default | OptLevel::Aggressive |
+        OptLevel::Size | OptLevel::SizeMin => 2,
+    });
     let debug_assertions = cg.debug_assertions.unwrap_or(opt_level == OptLevel::No);
     let debuginfo = if matches.opt_present("g") {
         if cg.debuginfo.is_some() {
@@ -1894,6 +1965,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_opt_level_implies_mir_opt_level() {
+        let mir_opt_level = |args: &[&str]| {
+            let args: Vec<_> = args.iter().map(|arg| arg.to_string()).collect();
+            let matches = getopts(&args, &optgroups()).unwrap();
+            let (sessopts, _) = build_session_options_and_crate_config(&matches);
+            sessopts.mir_opt_level
+        };
+        assert_eq!(mir_opt_level(&[]), 1);
+        assert_eq!(mir_opt_level(&["-Copt-level=0"]), 1);
+        assert_eq!(mir_opt_level(&["-Copt-level=1"]), 2);
+        assert_eq!(mir_opt_level(&["-O"]), 2);
+        assert_eq!(mir_opt_level(&["-Copt-level=3"]), 2);
+        assert_eq!(mir_opt_level(&["-O", "-Zmir-opt-level=0"]), 0);
+        assert_eq!(mir_opt_level(&["-Zmir-opt-level=3"]), 3);
+    }
+
     #[test]
     fn test_output_types_tracking_hash_different_paths() {
         let mut v1 = super::basic_options();
@@ -2394,6 +2482,8 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.save_analysis_api = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.save_analysis_mir = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.print_move_fragments = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.flowgraph_print_loans = true;
@@ -2430,6 +2520,14 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.dump_mir_dir = Some(String::from("abc"));
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.dump_mir_json = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.dump_mir_html = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.dump_mir_verbose = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.mir_opt_report = Some(String::from("abc"));
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
         // Make sure changing a [TRACKED] option changes the hash
         opts = reference.clone();
@@ -2464,6 +2562,10 @@ mod tests {
         opts.debugging_opts.force_overflow_checks = Some(true);
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
 
+        opts = reference.clone();
+        opts.debugging_opts.force_debug_assertions_mir = Some(false);
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
         opts = reference.clone();
         opts.debugging_opts.enable_nonzeroing_move_hints = true;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
@@ -2475,5 +2577,45 @@ mod tests {
         opts = reference.clone();
         opts.debugging_opts.mir_opt_level = Some(1);
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.mir_branch_prob = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.profile_mir = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.mir_coverage = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.mir_outline_cold = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.no_mir_fold_floats = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.mir_check_refs = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.mir_poison_uninit = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.const_eval_limit = Some(1000);
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.mir_effects_round_limit = Some(10);
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.cross_crate_auto_inline = true;
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 }
@@ -85,6 +85,11 @@ pub struct Session {
     pub one_time_diagnostics: RefCell<FxHashSet<(lint::LintId, Span, String)>>,
     pub plugin_llvm_passes: RefCell<Vec<String>>,
     pub mir_passes: RefCell<mir_pass::Passes>,
+    /// The MIR passes registered by plugins, until the pass managers take
+    /// them at the point they run at.
+    pub plugin_mir_passes:
+        RefCell<Vec<(mir_pass::PassPoint, Box<for<'a> mir_pass::MirMapPass<'a>>)>>,
+    pub plugin_mir_pass_hooks: RefCell<Vec<mir_pass::MirPassHookFactory>>,
     pub plugin_attributes: RefCell<Vec<(String, AttributeType)>>,
     pub crate_types: RefCell<Vec<config::CrateType>>,
     pub dependency_formats: RefCell<dependency_format::Dependencies>,
@@ -103,6 +108,14 @@ pub struct Session {
     /// The maximum length of types during monomorphization.
     pub type_length_limit: Cell<usize>,
 
+    /// The estimated stack frame size in bytes above which the
+    /// `large_stack_frame` lint fires.
+    pub stack_frame_limit: Cell<usize>,
+
+    /// The size in bytes of moved or copied values above which the
+    /// `large_moves` lint fires.
+    pub move_size_limit: Cell<usize>,
+
     /// The metadata::creader module may inject an allocator/panic_runtime
     /// dependency if it didn't already find one, and this tracks what was
     /// injected.
@@ -122,6 +135,10 @@ pub struct Session {
     /// Data about code being compiled, gathered during compilation.
     pub code_stats: RefCell<CodeStats>,
 
+    /// The number of tabs written to each page of `-Z dump-mir-html`, so
+    /// that the first dump of an item in the session creates its page.
+    pub mir_html_dumps: RefCell<FxHashMap<PathBuf, usize>>,
+
     next_node_id: Cell<ast::NodeId>,
 }
 
@@ -368,6 +385,18 @@ impl Session {
     pub fn panic_strategy(&self) -> PanicStrategy {
         self.opts.cg.panic.unwrap_or(self.target.target.options.panic_strategy)
     }
+    /// Whether arithmetic overflow is checked, per `-Z force-overflow-checks`,
+    /// `-Z force-debug-assertions-mir` or else `-C debug-assertions`.
+    pub fn overflow_checks(&self) -> bool {
+        self.opts.debugging_opts.force_overflow_checks
+            .or(self.opts.debugging_opts.force_debug_assertions_mir)
+            .unwrap_or(self.opts.debug_assertions)
+    }
+    /// Whether indexing is bounds checked, which only
+    /// `-Z force-debug-assertions-mir=off` turns off.
+    pub fn bounds_checks(&self) -> bool {
+        self.opts.debugging_opts.force_debug_assertions_mir.unwrap_or(true)
+    }
     pub fn no_landing_pads(&self) -> bool {
         self.opts.debugging_opts.no_landing_pads || self.panic_strategy() == PanicStrategy::Abort
     }
@@ -617,6 +646,8 @@ pub fn build_session_(sopts: config::Options,
         one_time_diagnostics: RefCell::new(FxHashSet()),
         plugin_llvm_passes: RefCell::new(Vec::new()),
         mir_passes: RefCell::new(mir_pass::Passes::new()),
+        plugin_mir_passes: RefCell::new(Vec::new()),
+        plugin_mir_pass_hooks: RefCell::new(Vec::new()),
         plugin_attributes: RefCell::new(Vec::new()),
         crate_types: RefCell::new(Vec::new()),
         dependency_formats: RefCell::new(FxHashMap()),
@@ -624,6 +655,8 @@ pub fn build_session_(sopts: config::Options,
         features: RefCell::new(feature_gate::Features::new()),
         recursion_limit: Cell::new(64),
         type_length_limit: Cell::new(1048576),
+        stack_frame_limit: Cell::new(65536),
+        move_size_limit: Cell::new(4096),
         next_node_id: Cell::new(NodeId::new(1)),
         injected_allocator: Cell::new(None),
         injected_panic_runtime: Cell::new(None),
@@ -637,6 +670,7 @@ pub fn build_session_(sopts: config::Options,
             symbol_hash_time: Cell::new(Duration::from_secs(0)),
         },
         code_stats: RefCell::new(CodeStats::new()),
+        mir_html_dumps: RefCell::new(FxHashMap()),
     };
 
     init_llvm(&sess);
@@ -12,6 +12,7 @@
 
 use dep_graph::{DepGraph, DepTrackingMap};
 use session::Session;
+use lint;
 use middle;
 use hir::TraitMap;
 use hir::def::Def;
@@ -22,7 +23,9 @@ use middle::free_region::FreeRegionMap;
 use middle::region::RegionMaps;
 use middle::resolve_lifetime;
 use middle::stability;
-use mir::Mir;
+use mir::{self, Mir};
+use mir::summary::EffectSummary;
+use mir::transform::Suggestion;
 use ty::subst::{Kind, Substs};
 use traits;
 use ty::{self, TraitRef, Ty, TypeAndMut};
@@ -51,6 +54,7 @@ use std::cmp::Ordering;
 use syntax::ast::{self, Name, NodeId};
 use syntax::attr;
 use syntax::symbol::{Symbol, keywords};
+use syntax_pos::Span;
 
 use hir;
 
@@ -451,6 +455,17 @@ pub struct GlobalCtxt<'tcx> {
     /// (in the `RefCell` sense) to prevent accidental mutation.
     pub mir_map: RefCell<DepTrackingMap<maps::Mir<'tcx>>>,
 
+    /// The MIR of the shims built so far, see `mir::Shim`.
+    pub mir_shims: RefCell<FxHashMap<mir::Shim<'tcx>, &'tcx RefCell<Mir<'tcx>>>>,
+
+    /// The effects of the local functions, as inferred by the `InferEffects`
+    /// MIR pass over the whole crate. See `TyCtxt::item_mir_effects`.
+    pub mir_effects: RefCell<DefIdMap<EffectSummary>>,
+
+    /// What the MIR passes found could be simplified in the source code.
+    /// See `mir::transform::suggest`.
+    pub mir_suggestions: RefCell<Vec<Suggestion>>,
+
     // Records the free variables refrenced by every closure
     // expression. Do not track deps for this, just recompute it from
     // scratch every time.
@@ -500,6 +515,14 @@ pub struct GlobalCtxt<'tcx> {
     /// This is used for warning unused imports.
     pub used_trait_imports: RefCell<NodeSet>,
 
+    /// The levels of the lints checked after the lint pass, at each function,
+    /// as the lint pass found them. See `lint::emit_node_lint`.
+    pub node_lint_levels: RefCell<FxHashMap<(NodeId, lint::LintId), lint::LevelSource>>,
+
+    /// The spans of the asserts a MIR lint already reported as always
+    /// failing, which trans doesn't warn about again.
+    pub reported_panics: RefCell<FxHashSet<Span>>,
+
     /// The set of external nominal types whose implementations have been read.
     /// This is used for lazy resolution of methods.
     pub populated_external_types: RefCell<DefIdSet>,
@@ -802,6 +825,9 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
             fulfilled_predicates: RefCell::new(fulfilled_predicates),
             map: map,
             mir_map: RefCell::new(DepTrackingMap::new(dep_graph.clone())),
+            mir_shims: RefCell::new(FxHashMap()),
+            mir_effects: RefCell::new(DefIdMap()),
+            mir_suggestions: RefCell::new(Vec::new()),
             freevars: RefCell::new(freevars),
             maybe_unused_trait_imports: maybe_unused_trait_imports,
             item_types: RefCell::new(DepTrackingMap::new(dep_graph.clone())),
@@ -816,6 +842,8 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
             used_unsafe: RefCell::new(NodeSet()),
             used_mut_nodes: RefCell::new(NodeSet()),
             used_trait_imports: RefCell::new(NodeSet()),
+            node_lint_levels: RefCell::new(FxHashMap()),
+            reported_panics: RefCell::new(FxHashSet()),
             populated_external_types: RefCell::new(DefIdSet()),
             populated_external_primitive_impls: RefCell::new(DefIdSet()),
             extern_const_statics: RefCell::new(DefIdMap()),
@@ -24,9 +24,11 @@ use hir::def_id::{CrateNum, DefId, CRATE_DEF_INDEX, LOCAL_CRATE};
 use middle::lang_items::{FnTraitLangItem, FnMutTraitLangItem, FnOnceTraitLangItem};
 use middle::region::{CodeExtent, ROOT_CODE_EXTENT};
 use mir::Mir;
+use mir::summary::{EffectSummary, InlineSummary};
 use traits;
 use ty;
 use ty::subst::{Subst, Substs};
+use ty::util::IntTypeExt;
 use ty::walk::TypeWalker;
 use util::common::MemoizationMap;
 use util::nodemap::{NodeSet, NodeMap, FxHashMap, FxHashSet};
@@ -107,6 +109,9 @@ pub struct CrateAnalysis<'tcx> {
     pub export_map: ExportMap,
     pub access_levels: middle::privacy::AccessLevels,
     pub reachable: NodeSet,
+    /// Private functions which no translated MIR refers to, and which
+    /// thus don't need to be translated.
+    pub unreferenced_fns: NodeSet,
     pub name: String,
     pub glob_map: Option<hir::GlobMap>,
     pub hir_ty_to_ty: NodeMap<Ty<'tcx>>,
@@ -1540,6 +1545,15 @@ impl<'a, 'gcx, 'tcx> AdtDef {
         }
     }
 
+    /// The type of the discriminant of this enum, as read by
+    /// `Rvalue::Discriminant`: the integer type of its `#[repr]`
+    /// attribute, or `isize`.
+    pub fn discr_ty(&self, tcx: TyCtxt<'a, 'gcx, 'tcx>) -> Ty<'tcx> {
+        let tcx = tcx.global_tcx();
+        let hints = tcx.lookup_repr_hints(self.did);
+        tcx.enum_repr_type(hints.get(0)).to_ty(tcx)
+    }
+
     pub fn destructor(&self) -> Option<DefId> {
         self.destructor.get()
     }
@@ -2364,6 +2378,29 @@ impl<'a, 'gcx, 'tcx> TyCtxt<'a, 'gcx, 'tcx> {
         }).borrow()
     }
 
+    /// Given the did of an item, returns a summary of its MIR, if it has
+    /// any. For items from other crates, this doesn't decode the MIR.
+    pub fn item_mir_summary(self, did: DefId) -> Option<InlineSummary> {
+        if did.is_local() {
+            let tcx = self.global_tcx();
+            tcx.mir_map.borrow().get(&did).map(|mir| InlineSummary::compute(tcx, &mir.borrow()))
+        } else {
+            self.sess.cstore.item_mir_summary(did)
+        }
+    }
+
+    /// Given the did of a function, returns what calling it may do. Local
+    /// functions are only known once `InferEffects` has run, and functions
+    /// without MIR may do anything.
+    pub fn item_mir_effects(self, did: DefId) -> EffectSummary {
+        let effects = if did.is_local() {
+            self.mir_effects.borrow().get(&did).cloned()
+        } else {
+            self.sess.cstore.item_mir_summary(did).map(|summary| summary.effects)
+        };
+        effects.unwrap_or_else(EffectSummary::unknown)
+    }
+
     /// If `type_needs_drop` returns true, then `ty` is definitely
     /// non-copy and *might* have a destructor attached; if it returns
     /// false, then `ty` definitely has no destructor (i.e. no drop glue).
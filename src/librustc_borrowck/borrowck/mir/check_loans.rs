@@ -0,0 +1,242 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checks that no access to an lvalue conflicts with a loan in scope,
+//! using the `Borrows` dataflow and the regions `region_infer` computes.
+//! This is the MIR counterpart of `borrowck::check_loans`, and reports
+//! errors in the same words, suffixed with `(Mir)`.
+//!
+//! Assignments are "shallow": they only conflict with loans of the lvalue
+//! assigned to, of its prefixes, or of its fields, but not with loans of
+//! data reached through a reference stored in it. Moves, drops and
+//! borrows are "deep" and conflict with any overlapping loan.
+
+use syntax::ast;
+use syntax_pos::Span;
+
+use rustc::mir::*;
+use rustc::mir::traversal;
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::indexed_set::IdxSetBuf;
+use rustc_data_structures::indexed_vec::Idx;
+
+use super::dataflow::{BorrowData, BorrowIndex, BorrowSet, Borrows, DataflowAnalysis};
+use super::describe_lvalue;
+
+/// Runs the MIR borrow checker over `mir`, the body of `id`, and returns the
+/// number of errors reported.
+pub fn check_loans<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                             mir: &Mir<'tcx>,
+                             id: ast::NodeId) -> usize {
+    let borrow_set = BorrowSet::new(tcx, mir);
+    let flow_borrows = DataflowAnalysis::new(tcx, mir, &borrow_set, Borrows::new()).run();
+
+    let mut checker = LoanChecker {
+        tcx: tcx,
+        mir: mir,
+        param_env: ty::ParameterEnvironment::for_item(tcx, id),
+        borrow_set: &borrow_set,
+        in_scope: IdxSetBuf::new_empty(0),
+        errors: 0,
+    };
+    // Unreachable blocks are skipped, as no loan is in scope there.
+    for (bb, data) in traversal::reverse_postorder(mir) {
+        checker.in_scope = flow_borrows.sets().on_entry_set_for(bb.index()).to_owned();
+        for statement_index in 0..data.statements.len() + 1 {
+            let location = Location { block: bb, statement_index: statement_index };
+            borrow_set.kill_out_of_scope(&mut checker.in_scope, location);
+            match data.statements.get(statement_index) {
+                Some(stmt) => checker.visit_statement(bb, stmt, location),
+                None => checker.visit_terminator(bb, data.terminator(), location)
+            }
+            if let Some(index) = borrow_set.borrow_at(location) {
+                checker.in_scope.add(&index);
+            }
+        }
+    }
+    checker.errors
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Depth {
+    Shallow,
+    Deep,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Access {
+    Read,
+    SharedBorrow,
+    MutBorrow,
+    Move,
+    Assign,
+    Drop,
+    StorageDead,
+}
+
+impl Access {
+    fn depth(self) -> Depth {
+        match self {
+            Access::Assign | Access::StorageDead => Depth::Shallow,
+            _ => Depth::Deep
+        }
+    }
+
+    fn writes(self) -> bool {
+        match self {
+            Access::Read | Access::SharedBorrow => false,
+            _ => true
+        }
+    }
+}
+
+struct LoanChecker<'b, 'a: 'b, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'b Mir<'tcx>,
+    param_env: ty::ParameterEnvironment<'tcx>,
+    borrow_set: &'b BorrowSet<'tcx>,
+    /// The loans which may be in scope at the current location.
+    in_scope: IdxSetBuf<BorrowIndex>,
+    errors: usize,
+}
+
+impl<'b, 'a, 'tcx> LoanChecker<'b, 'a, 'tcx> {
+    fn span_of(&self, location: Location) -> Span {
+        let data = &self.mir[location.block];
+        match data.statements.get(location.statement_index) {
+            Some(stmt) => stmt.source_info.span,
+            None => data.terminator().source_info.span
+        }
+    }
+
+    fn check_access(&mut self, lvalue: &Lvalue<'tcx>, access: Access, location: Location) {
+        let borrow_set = self.borrow_set;
+        let conflict = borrow_set.borrows.iter_enumerated().find(|&(index, borrow)| {
+            self.in_scope.contains(&index) &&
+                (access.writes() || borrow.kind != BorrowKind::Shared) &&
+                self.overlaps(&borrow.lvalue, lvalue, access.depth())
+        }).map(|(_, borrow)| borrow);
+        if let Some(borrow) = conflict {
+            self.report(lvalue, access, borrow, location);
+        }
+    }
+
+    /// Whether an access of depth `depth` to `accessed` may touch the data
+    /// borrowed as `borrowed`.
+    fn overlaps(&self, borrowed: &Lvalue<'tcx>, accessed: &Lvalue<'tcx>, depth: Depth) -> bool {
+        let (borrowed_root, borrowed_elems) = components(borrowed);
+        let (accessed_root, accessed_elems) = components(accessed);
+        if borrowed_root != accessed_root {
+            return false;
+        }
+        for (b, a) in borrowed_elems.iter().zip(&accessed_elems) {
+            if let (&ProjectionElem::Field(f1, _), &ProjectionElem::Field(f2, _)) =
+                (&b.elem, &a.elem)
+            {
+                if f1 != f2 {
+                    // Distinct fields are disjoint, except in unions.
+                    let base_ty = b.base.ty(self.mir, self.tcx).to_ty(self.tcx);
+                    return match base_ty.sty {
+                        ty::TyAdt(adt, _) => adt.is_union(),
+                        _ => false
+                    };
+                }
+            }
+        }
+        if borrowed_elems.len() > accessed_elems.len() && depth == Depth::Shallow {
+            // A shallow access doesn't reach data behind a pointer.
+            let beyond = &borrowed_elems[accessed_elems.len()..];
+            return !beyond.iter().any(|proj| match proj.elem {
+                ProjectionElem::Deref => true,
+                _ => false
+            });
+        }
+        true
+    }
+
+    fn report(&mut self,
+              lvalue: &Lvalue<'tcx>,
+              access: Access,
+              borrow: &BorrowData<'tcx>,
+              location: Location) {
+        let desc = describe_lvalue(self.tcx, self.mir, lvalue);
+        let msg = match access {
+            Access::Read => format!("cannot use `{}` because it was mutably borrowed", desc),
+            Access::SharedBorrow => {
+                format!("cannot borrow `{}` as immutable because it is also borrowed \
+                         as mutable", desc)
+            }
+            Access::MutBorrow if borrow.kind == BorrowKind::Shared => {
+                format!("cannot borrow `{}` as mutable because it is also borrowed \
+                         as immutable", desc)
+            }
+            Access::MutBorrow => {
+                format!("cannot borrow `{}` as mutable more than once at a time", desc)
+            }
+            Access::Move => format!("cannot move out of `{}` because it is borrowed", desc),
+            Access::Assign => format!("cannot assign to `{}` because it is borrowed", desc),
+            Access::Drop |
+            Access::StorageDead => format!("`{}` does not live long enough", desc),
+        };
+        let mut err = self.tcx.sess.struct_span_err(self.span_of(location),
+                                                    &format!("{} (Mir)", msg));
+        err.span_label(self.span_of(borrow.location),
+                       &format!("borrow of `{}` occurs here",
+                                describe_lvalue(self.tcx, self.mir, &borrow.lvalue)));
+        err.emit();
+        self.errors += 1;
+    }
+}
+
+/// Splits `lvalue` into its root and its projections, innermost first.
+fn components<'c, 'tcx>(lvalue: &'c Lvalue<'tcx>)
+                        -> (&'c Lvalue<'tcx>, Vec<&'c LvalueProjection<'tcx>>) {
+    match *lvalue {
+        Lvalue::Projection(ref proj) => {
+            let (root, mut elems) = components(&proj.base);
+            elems.push(proj);
+            (root, elems)
+        }
+        _ => (lvalue, vec![])
+    }
+}
+
+impl<'b, 'a, 'tcx> Visitor<'tcx> for LoanChecker<'b, 'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        let access = match context {
+            LvalueContext::Consume => {
+                let ty = lvalue.ty(self.mir, self.tcx).to_ty(self.tcx);
+                let span = self.span_of(location);
+                if ty.moves_by_default(self.tcx, &self.param_env, span) {
+                    Some(Access::Move)
+                } else {
+                    Some(Access::Read)
+                }
+            }
+            LvalueContext::Inspect => Some(Access::Read),
+            LvalueContext::Borrow { kind: BorrowKind::Shared, .. } => Some(Access::SharedBorrow),
+            LvalueContext::Borrow { .. } => Some(Access::MutBorrow),
+            LvalueContext::Store |
+            LvalueContext::Call => Some(Access::Assign),
+            LvalueContext::Drop => Some(Access::Drop),
+            LvalueContext::StorageDead => Some(Access::StorageDead),
+            LvalueContext::StorageLive |
+            LvalueContext::Projection(_) => None
+        };
+        if let Some(access) = access {
+            self.check_access(lvalue, access, location);
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc::ty::TyCtxt;
+use rustc::mir::{self, Mir, Location, Lvalue, BorrowKind, Rvalue, StatementKind};
+use rustc::util::nodemap::FxHashMap;
+use rustc_data_structures::bitslice::BitwiseOperator;
+use rustc_data_structures::indexed_set::IdxSet;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+
+use super::super::region_infer::BorrowRegions;
+use super::{BitDenotation, BlockSets, DataflowOperator};
+
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Index into `BorrowSet::borrows`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BorrowIndex(usize);
+
+impl Idx for BorrowIndex {
+    fn new(idx: usize) -> Self {
+        BorrowIndex(idx)
+    }
+    fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl fmt::Debug for BorrowIndex {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "bw{}", self.index())
+    }
+}
+
+/// A loan, i.e. a `&` or `&mut` rvalue, and where it is taken.
+#[derive(Debug)]
+pub struct BorrowData<'tcx> {
+    pub location: Location,
+    pub kind: BorrowKind,
+    pub lvalue: Lvalue<'tcx>,
+    /// The lvalue the reference is first stored into.
+    pub dest: Lvalue<'tcx>,
+}
+
+/// All the loans of a function, along with the regions inferred for them.
+pub struct BorrowSet<'tcx> {
+    pub borrows: IndexVec<BorrowIndex, BorrowData<'tcx>>,
+    location_map: FxHashMap<Location, BorrowIndex>,
+    regions: BorrowRegions,
+}
+
+impl<'tcx> BorrowSet<'tcx> {
+    pub fn new<'a>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> Self {
+        let mut borrows = IndexVec::new();
+        let mut location_map = FxHashMap();
+        for (block, data) in mir.basic_blocks().iter_enumerated() {
+            for (statement_index, stmt) in data.statements.iter().enumerate() {
+                if let StatementKind::Assign(ref dest, Rvalue::Ref(_, kind, ref lvalue)) =
+                    stmt.kind
+                {
+                    let location = Location { block: block, statement_index: statement_index };
+                    let index = borrows.push(BorrowData {
+                        location: location,
+                        kind: kind,
+                        lvalue: lvalue.clone(),
+                        dest: dest.clone(),
+                    });
+                    location_map.insert(location, index);
+                }
+            }
+        }
+        let regions = BorrowRegions::infer(tcx, mir, &borrows);
+        BorrowSet {
+            borrows: borrows,
+            location_map: location_map,
+            regions: regions,
+        }
+    }
+
+    /// The loan taken by the statement at `location`, if any.
+    pub fn borrow_at(&self, location: Location) -> Option<BorrowIndex> {
+        self.location_map.get(&location).cloned()
+    }
+
+    /// Removes from `in_scope` the loans whose region doesn't include
+    /// `location`, i.e. which can no longer be used once it's reached.
+    pub fn kill_out_of_scope(&self, in_scope: &mut IdxSet<BorrowIndex>, location: Location) {
+        for index in self.borrows.indices() {
+            if !self.regions.contains(index, location) {
+                in_scope.remove(&index);
+            }
+        }
+    }
+}
+
+/// `Borrows` tracks the loans which may be in scope upon reaching a
+/// particular point in the control flow for a function.
+///
+/// A loan comes into scope just after the statement taking it, and goes
+/// out of scope at the first point its region doesn't include; see
+/// `region_infer` for how regions are computed.
+pub struct Borrows<'tcx> {
+    _data: PhantomData<BorrowSet<'tcx>>,
+}
+
+impl<'tcx> Borrows<'tcx> {
+    pub fn new() -> Self {
+        Borrows { _data: PhantomData }
+    }
+}
+
+impl<'tcx> BitDenotation for Borrows<'tcx> {
+    type Idx = BorrowIndex;
+    type Ctxt = BorrowSet<'tcx>;
+    fn name() -> &'static str { "borrows" }
+    fn bits_per_block(&self, ctxt: &Self::Ctxt) -> usize {
+        ctxt.borrows.len()
+    }
+    fn start_block_effect(&self, _ctxt: &Self::Ctxt, _sets: &mut BlockSets<BorrowIndex>) {
+        // no loans are in scope on entry to the function.
+    }
+    fn statement_effect(&self,
+                        ctxt: &Self::Ctxt,
+                        sets: &mut BlockSets<BorrowIndex>,
+                        bb: mir::BasicBlock,
+                        idx: usize) {
+        let location = Location { block: bb, statement_index: idx };
+        for index in ctxt.borrows.indices() {
+            if !ctxt.regions.contains(index, location) {
+                sets.kill(&index);
+            }
+        }
+        if let Some(index) = ctxt.borrow_at(location) {
+            sets.gen(&index);
+        }
+    }
+    fn terminator_effect(&self,
+                         ctxt: &Self::Ctxt,
+                         sets: &mut BlockSets<BorrowIndex>,
+                         bb: mir::BasicBlock,
+                         idx: usize) {
+        let location = Location { block: bb, statement_index: idx };
+        for index in ctxt.borrows.indices() {
+            if !ctxt.regions.contains(index, location) {
+                sets.kill(&index);
+            }
+        }
+    }
+    fn propagate_call_return(&self,
+                             _ctxt: &Self::Ctxt,
+                             _in_out: &mut IdxSet<BorrowIndex>,
+                             _call_bb: mir::BasicBlock,
+                             _dest_bb: mir::BasicBlock,
+                             _dest_lval: &mir::Lvalue) {
+        // calls don't take loans themselves.
+    }
+}
+
+impl<'tcx> BitwiseOperator for Borrows<'tcx> {
+    #[inline]
+    fn join(&self, pred1: usize, pred2: usize) -> usize {
+        pred1 | pred2 // loans from either pred may be in scope
+    }
+}
+
+impl<'tcx> DataflowOperator for Borrows<'tcx> {
+    #[inline]
+    fn bottom_value() -> bool {
+        false // no loans in scope by default
+    }
+}
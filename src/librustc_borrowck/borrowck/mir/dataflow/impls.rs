@@ -20,6 +20,7 @@ use super::super::MoveDataParamEnv;
 use super::super::DropFlagState;
 use super::super::drop_flag_effects_for_function_entry;
 use super::super::drop_flag_effects_for_location;
+use super::super::assignment_effects_for_location;
 use super::super::on_lookup_result_bits;
 
 use super::{BitDenotation, BlockSets, DataflowOperator};
@@ -172,6 +173,46 @@ impl<'a, 'tcx: 'a> DefinitelyInitializedLvals<'a, 'tcx> {
     }
 }
 
+/// `MaybeUnassignedLvals` tracks all l-values that might not have been
+/// assigned since they were declared upon reaching a particular point in
+/// the control flow for a function. Unlike `MaybeUninitializedLvals`,
+/// moves out of an l-value don't make it unassigned, so this is what the
+/// definite-initialization check uses to tell uses of variables which were
+/// never initialized apart from uses of moved values.
+///
+/// For example, in code like the following, we have corresponding
+/// dataflow information shown in the right-hand comments.
+///
+/// ```rust
+/// struct S;
+/// fn foo(pred: bool) {                       // maybe-unassigned:
+///                                            // {a, b, c, d}
+///     let a = S; let b = S; let c; let d;    // {      c, d}
+///
+///     if pred {
+///         drop(a);                           // {      c, d}
+///         b = S;                             // {      c, d}
+///
+///     } else {
+///         drop(b);                           // {      c, d}
+///         d = S;                             // {      c   }
+///
+///     }                                      // {      c, d}
+///
+///     c = S;                                 // {         d}
+/// }
+/// ```
+pub struct MaybeUnassignedLvals<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'a Mir<'tcx>,
+}
+
+impl<'a, 'tcx: 'a> MaybeUnassignedLvals<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &'a Mir<'tcx>) -> Self {
+        MaybeUnassignedLvals { tcx: tcx, mir: mir }
+    }
+}
+
 /// `MovingOutStatements` tracks the statements that perform moves out
 /// of particular l-values. More precisely, it tracks whether the
 /// *effect* of such moves (namely, the uninitialization of the
@@ -224,6 +265,17 @@ impl<'a, 'tcx> DefinitelyInitializedLvals<'a, 'tcx> {
     }
 }
 
+impl<'a, 'tcx> MaybeUnassignedLvals<'a, 'tcx> {
+    fn update_bits(sets: &mut BlockSets<MovePathIndex>, path: MovePathIndex,
+                   state: DropFlagState)
+    {
+        match state {
+            DropFlagState::Absent => sets.gen(&path),
+            DropFlagState::Present => sets.kill(&path),
+        }
+    }
+}
+
 impl<'a, 'tcx> BitDenotation for MaybeInitializedLvals<'a, 'tcx> {
     type Idx = MovePathIndex;
     type Ctxt = MoveDataParamEnv<'tcx>;
@@ -403,6 +455,67 @@ impl<'a, 'tcx> BitDenotation for DefinitelyInitializedLvals<'a, 'tcx> {
     }
 }
 
+impl<'a, 'tcx> BitDenotation for MaybeUnassignedLvals<'a, 'tcx> {
+    type Idx = MovePathIndex;
+    type Ctxt = MoveDataParamEnv<'tcx>;
+    fn name() -> &'static str { "maybe_unassigned" }
+    fn bits_per_block(&self, ctxt: &Self::Ctxt) -> usize {
+        ctxt.move_data.move_paths.len()
+    }
+
+    // sets on_entry bits for Arg lvalues
+    fn start_block_effect(&self, ctxt: &Self::Ctxt, sets: &mut BlockSets<MovePathIndex>) {
+        // set all bits to 1 (unassigned) before gathering counterevidence
+        for e in sets.on_entry.words_mut() { *e = !0; }
+
+        drop_flag_effects_for_function_entry(
+            self.tcx, self.mir, ctxt,
+            |path, s| {
+                assert!(s == DropFlagState::Present);
+                sets.on_entry.remove(&path);
+            });
+    }
+
+    fn statement_effect(&self,
+                        ctxt: &Self::Ctxt,
+                        sets: &mut BlockSets<MovePathIndex>,
+                        bb: mir::BasicBlock,
+                        idx: usize)
+    {
+        assignment_effects_for_location(
+            self.tcx, self.mir, ctxt,
+            Location { block: bb, statement_index: idx },
+            |path, s| Self::update_bits(sets, path, s)
+        )
+    }
+
+    fn terminator_effect(&self,
+                         ctxt: &Self::Ctxt,
+                         sets: &mut BlockSets<MovePathIndex>,
+                         bb: mir::BasicBlock,
+                         statements_len: usize)
+    {
+        assignment_effects_for_location(
+            self.tcx, self.mir, ctxt,
+            Location { block: bb, statement_index: statements_len },
+            |path, s| Self::update_bits(sets, path, s)
+        )
+    }
+
+    fn propagate_call_return(&self,
+                             ctxt: &Self::Ctxt,
+                             in_out: &mut IdxSet<MovePathIndex>,
+                             _call_bb: mir::BasicBlock,
+                             _dest_bb: mir::BasicBlock,
+                             dest_lval: &mir::Lvalue) {
+        // when a call returns successfully, that means we need to set
+        // the bits for that dest_lval to 0 (assigned).
+        on_lookup_result_bits(self.tcx, self.mir, &ctxt.move_data,
+                              ctxt.move_data.rev_lookup.find(dest_lval),
+                              |mpi| { in_out.remove(&mpi); });
+    }
+}
+
 impl<'a, 'tcx> BitDenotation for MovingOutStatements<'a, 'tcx> {
     type Idx = MoveOutIndex;
     type Ctxt = MoveDataParamEnv<'tcx>;
@@ -437,9 +550,7 @@ impl<'a, 'tcx> BitDenotation for MovingOutStatements<'a, 'tcx> {
         }
         let bits_per_block = self.bits_per_block(ctxt);
         match stmt.kind {
-            mir::StatementKind::SetDiscriminant { .. } => {
-                span_bug!(stmt.source_info.span, "SetDiscriminant should not exist in borrowck");
-            }
+            mir::StatementKind::SetDiscriminant { ref lvalue, .. } |
             mir::StatementKind::Assign(ref lvalue, _) => {
                 // assigning into this `lvalue` kills all
                 // MoveOuts from it, and *also* all MoveOuts
@@ -455,6 +566,8 @@ impl<'a, 'tcx> BitDenotation for MovingOutStatements<'a, 'tcx> {
             }
             mir::StatementKind::StorageLive(_) |
             mir::StatementKind::StorageDead(_) |
+            mir::StatementKind::IncrementCounter(_) |
+            mir::StatementKind::Poison(_) |
             mir::StatementKind::Nop => {}
         }
     }
@@ -532,6 +645,13 @@ impl<'a, 'tcx> BitwiseOperator for DefinitelyInitializedLvals<'a, 'tcx> {
     }
 }
 
+impl<'a, 'tcx> BitwiseOperator for MaybeUnassignedLvals<'a, 'tcx> {
+    #[inline]
+    fn join(&self, pred1: usize, pred2: usize) -> usize {
+        pred1 | pred2 // "maybe" means we union effects of both preds
+    }
+}
+
 // The way that dataflow fixed point iteration works, you want to
 // start at bottom and work your way to a fixed point. Control-flow
 // merges will apply the `join` operator to each block entry's current
@@ -569,3 +689,10 @@ impl<'a, 'tcx> DataflowOperator for DefinitelyInitializedLvals<'a, 'tcx> {
         true // bottom = initialized (start_block_effect counters this at outset)
     }
 }
+
+impl<'a, 'tcx> DataflowOperator for MaybeUnassignedLvals<'a, 'tcx> {
+    #[inline]
+    fn bottom_value() -> bool {
+        false // bottom = assigned (start_block_effect counters this at outset)
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The definite-initialization check on MIR: reports uses of variables
+//! which may not have been assigned since they were declared (E0381),
+//! using the `MaybeUnassignedLvals` dataflow. Uses of moved values are
+//! still reported by the AST borrow checker.
+//!
+//! An lvalue is used when it is read, borrowed or matched on, and its
+//! base is used when it is dereferenced or indexed into, even to be
+//! assigned to. Drops are not uses: dropping an unassigned variable is a
+//! no-op once drops are elaborated.
+
+use syntax::ast;
+use syntax_pos::Span;
+
+use rustc::mir::*;
+use rustc::mir::traversal;
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::indexed_set::IdxSetBuf;
+use rustc_data_structures::indexed_vec::Idx;
+
+use super::dataflow::MaybeUnassignedLvals;
+use super::gather_moves::{LookupResult, MoveData, MovePathIndex};
+use super::{assignment_effects_for_location, describe_lvalue, DropFlagState, MoveDataParamEnv};
+
+/// Checks that every variable used in `mir`, the body of `id`, is
+/// definitely assigned, and returns the number of errors reported. With
+/// `crosscheck`, the errors are marked `(Mir)` to tell them apart from
+/// those of the AST check.
+pub fn check_definite_init<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                     mir: &Mir<'tcx>,
+                                     id: ast::NodeId,
+                                     crosscheck: bool) -> usize {
+    let param_env = ty::ParameterEnvironment::for_item(tcx, id);
+    let move_data = MoveData::gather_moves(mir, tcx, &param_env);
+    let env = MoveDataParamEnv {
+        move_data: move_data,
+        param_env: param_env
+    };
+    let flow_unassigned = super::do_dataflow(tcx, mir, id, &[], &env,
+                                             MaybeUnassignedLvals::new(tcx, mir));
+
+    let mut checker = InitChecker {
+        tcx: tcx,
+        mir: mir,
+        move_data: &env.move_data,
+        unassigned: IdxSetBuf::new_empty(0),
+        crosscheck: crosscheck,
+        reported_at: None,
+        errors: 0,
+    };
+    // Unreachable blocks are skipped, as nothing can be used there.
+    for (bb, data) in traversal::reverse_postorder(mir) {
+        checker.unassigned = flow_unassigned.sets().on_entry_set_for(bb.index()).to_owned();
+        for statement_index in 0..data.statements.len() + 1 {
+            let location = Location { block: bb, statement_index: statement_index };
+            match data.statements.get(statement_index) {
+                Some(stmt) => checker.visit_statement(bb, stmt, location),
+                None => checker.visit_terminator(bb, data.terminator(), location)
+            }
+            assignment_effects_for_location(tcx, mir, &env, location, |path, df| {
+                match df {
+                    DropFlagState::Present => { checker.unassigned.remove(&path); }
+                    DropFlagState::Absent => { checker.unassigned.add(&path); }
+                }
+            });
+        }
+    }
+    checker.errors
+}
+
+struct InitChecker<'b, 'a: 'b, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'b Mir<'tcx>,
+    move_data: &'b MoveData<'tcx>,
+    /// The move paths which may be unassigned at the current location.
+    unassigned: IdxSetBuf<MovePathIndex>,
+    crosscheck: bool,
+    /// The last location an error was reported at; a statement using the
+    /// same variable in several ways is only reported once.
+    reported_at: Option<Location>,
+    errors: usize,
+}
+
+impl<'b, 'a, 'tcx> InitChecker<'b, 'a, 'tcx> {
+    fn span_of(&self, location: Location) -> Span {
+        let data = &self.mir[location.block];
+        match data.statements.get(location.statement_index) {
+            Some(stmt) => stmt.source_info.span,
+            None => data.terminator().source_info.span
+        }
+    }
+
+    fn check(&mut self, lvalue: &Lvalue<'tcx>, location: Location) {
+        if self.reported_at == Some(location) {
+            return;
+        }
+        let path = match self.move_data.rev_lookup.find(lvalue) {
+            LookupResult::Exact(path) |
+            LookupResult::Parent(Some(path)) => path,
+            LookupResult::Parent(None) => return
+        };
+        if !self.unassigned.contains(&path) {
+            return;
+        }
+
+        let desc = describe_lvalue(self.tcx, self.mir, lvalue);
+        let span = self.span_of(location);
+        let suffix = if self.crosscheck { " (Mir)" } else { "" };
+        struct_span_err!(self.tcx.sess, span, E0381,
+                         "use of possibly uninitialized variable: `{}`{}", desc, suffix)
+            .span_label(span, &format!("use of possibly uninitialized `{}`", desc))
+            .emit();
+        self.reported_at = Some(location);
+        self.errors += 1;
+    }
+}
+
+impl<'b, 'a, 'tcx> Visitor<'tcx> for InitChecker<'b, 'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        match context {
+            LvalueContext::Consume |
+            LvalueContext::Inspect |
+            LvalueContext::Borrow { .. } => self.check(lvalue, location),
+            _ => {}
+        }
+        if let Lvalue::Projection(ref proj) = *lvalue {
+            match proj.elem {
+                ProjectionElem::Deref |
+                ProjectionElem::Index(_) => self.check(&proj.base, location),
+                _ => {}
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
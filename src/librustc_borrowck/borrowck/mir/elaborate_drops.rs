@@ -14,10 +14,10 @@ use super::dataflow::{DataflowResults};
 use super::{drop_flag_effects_for_location, on_all_children_bits};
 use super::on_lookup_result_bits;
 use super::{DropFlagState, MoveDataParamEnv};
-use super::patch::MirPatch;
 use rustc::ty::{self, Ty, TyCtxt};
 use rustc::ty::subst::{Kind, Subst, Substs};
 use rustc::mir::*;
+use rustc::mir::patch::MirPatch;
 use rustc::mir::transform::{Pass, MirPass, MirSource};
 use rustc::middle::const_val::ConstVal;
 use rustc::middle::lang_items;
@@ -34,12 +34,12 @@ pub struct ElaborateDrops;
 
 impl<'tcx> MirPass<'tcx> for ElaborateDrops {
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    src: MirSource, mir: &mut Mir<'tcx>)
+                    src: MirSource, mir: &mut Mir<'tcx>) -> bool
     {
         debug!("elaborate_drops({:?} @ {:?})", src, mir.span);
         match src {
             MirSource::Fn(..) => {},
-            _ => return
+            _ => return false
         }
         let id = src.item_id();
         let param_env = ty::ParameterEnvironment::for_item(tcx, id);
@@ -67,7 +67,9 @@ impl<'tcx> MirPass<'tcx> for ElaborateDrops {
                 patch: MirPatch::new(mir),
             }.elaborate()
         };
+        let changed = !elaborate_patch.is_empty();
         elaborate_patch.apply(mir);
+        changed
     }
 }
 
@@ -683,12 +685,31 @@ impl<'b, 'tcx> ElaborateDropsCtxt<'b, 'tcx> {
                 // discriminant after it is free-ed, because that
                 // way lies only trouble.
 
-                let switch_block = self.new_block(
-                    c, c.is_cleanup, TerminatorKind::Switch {
-                        discr: c.lvalue.clone(),
-                        adt_def: adt,
-                        targets: variant_drops
-                    });
+                let discr = Lvalue::Local(self.patch.new_temp(adt.discr_ty(self.tcx)));
+                let unreachable = self.patch.new_block(BasicBlockData {
+                    statements: vec![],
+                    terminator: Some(Terminator {
+                        source_info: c.source_info,
+                        kind: TerminatorKind::Unreachable
+                    }),
+                    is_cleanup: c.is_cleanup
+                });
+                let switch_block = self.patch.new_block(BasicBlockData {
+                    statements: vec![Statement {
+                        source_info: c.source_info,
+                        kind: StatementKind::Assign(discr.clone(),
+                                                    Rvalue::Discriminant(c.lvalue.clone()))
+                    }],
+                    terminator: Some(Terminator {
+                        source_info: c.source_info,
+                        kind: TerminatorKind::switch_on_variants(self.tcx,
+                                                                 Operand::Consume(discr),
+                                                                 adt,
+                                                                 variant_drops,
+                                                                 unreachable)
+                    }),
+                    is_cleanup: c.is_cleanup
+                });
 
                 self.drop_flag_test_block(c, switch_block)
             }
@@ -810,10 +831,8 @@ impl<'b, 'tcx> ElaborateDropsCtxt<'b, 'tcx> {
             (true, false) => on_set,
             (true, true) => {
                 let flag = self.drop_flag(c.path).unwrap();
-                self.new_block(c, is_cleanup, TerminatorKind::If {
-                    cond: Operand::Consume(flag),
-                    targets: (on_set, on_unset)
-                })
+                let term = TerminatorKind::if_(self.tcx, Operand::Consume(flag), on_set, on_unset);
+                self.new_block(c, is_cleanup, term)
             }
         }
     }
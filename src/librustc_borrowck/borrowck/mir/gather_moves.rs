@@ -402,12 +402,15 @@ impl<'a, 'tcx> MoveDataBuilder<'a, 'tcx> {
                 self.create_move_path(lval);
                 self.gather_rvalue(loc, rval);
             }
-            StatementKind::StorageLive(_) |
-            StatementKind::StorageDead(_) => {}
-            StatementKind::SetDiscriminant{ .. } => {
-                span_bug!(stmt.source_info.span,
-                          "SetDiscriminant should not exist during borrowck");
+            // These only appear in optimized MIR, which is gathered by
+            // `-Z verify-mir-drops`.
+            StatementKind::SetDiscriminant { ref lvalue, .. } |
+            StatementKind::Poison(ref lvalue) => {
+                self.create_move_path(lvalue);
             }
+            StatementKind::StorageLive(_) |
+            StatementKind::StorageDead(_) |
+            StatementKind::IncrementCounter(_) |
             StatementKind::Nop => {}
         }
     }
@@ -432,6 +435,7 @@ impl<'a, 'tcx> MoveDataBuilder<'a, 'tcx> {
             }
             Rvalue::Ref(..) |
             Rvalue::Len(..) |
+            Rvalue::Discriminant(..) |
             Rvalue::InlineAsm { .. } => {}
             Rvalue::Box(..) => {
                 // This returns an rvalue with uninitialized contents. We can't
@@ -453,16 +457,15 @@ impl<'a, 'tcx> MoveDataBuilder<'a, 'tcx> {
         match term.kind {
             TerminatorKind::Goto { target: _ } |
             TerminatorKind::Resume |
+            TerminatorKind::Abort |
             TerminatorKind::Unreachable => { }
 
             TerminatorKind::Return => {
                 self.gather_move(loc, &Lvalue::Local(RETURN_POINTER));
             }
 
-            TerminatorKind::If { .. } |
             TerminatorKind::Assert { .. } |
-            TerminatorKind::SwitchInt { .. } |
-            TerminatorKind::Switch { .. } => {
+            TerminatorKind::SwitchInt { .. } => {
                 // branching terminators - these don't move anything
             }
 
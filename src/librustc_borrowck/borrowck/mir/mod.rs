@@ -17,14 +17,19 @@ use rustc::hir;
 use rustc::hir::intravisit::{FnKind};
 
 use rustc::mir::{self, BasicBlock, BasicBlockData, Mir, Statement, Terminator, Location};
+use rustc::mir::tcx::LvalueTy;
 use rustc::session::Session;
 use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::indexed_vec::Idx;
 
 mod abs_domain;
+mod check_loans;
+mod definite_init;
 pub mod elaborate_drops;
 mod dataflow;
 mod gather_moves;
-mod patch;
+mod region_infer;
+pub mod verify_drops;
 // mod graphviz;
 
 use self::dataflow::{BitDenotation};
@@ -115,6 +120,79 @@ pub fn borrowck_mir(bcx: &mut BorrowckCtxt,
     debug!("borrowck_mir done");
 }
 
+/// Runs the MIR borrow checker over the function `id`, for `-Z borrowck-mir`,
+/// and warns when it disagrees with the AST borrow checker, which reported
+/// `ast_errors` errors for the same function.
+pub fn borrowck_mir_crosscheck<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                         id: ast::NodeId,
+                                         sp: Span,
+                                         ast_errors: usize) {
+    let mir = &tcx.item_mir(tcx.map.local_def_id(id));
+    let mir_errors = check_loans::check_loans(tcx, mir, id);
+    if (ast_errors == 0) != (mir_errors == 0) {
+        tcx.sess.span_warn(sp, &format!("AST borrowck reported {} error(s) for this function, \
+                                         but MIR borrowck reported {}",
+                                        ast_errors, mir_errors));
+    }
+}
+
+/// Runs the MIR definite-initialization check over the function `id`.
+/// Under `-Z definite-init-crosscheck`, its errors are marked `(Mir)` and a
+/// warning is emitted when it disagrees with the AST check, which reported
+/// `ast_errors` uses of possibly uninitialized variables in the function.
+pub fn definite_init<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               id: ast::NodeId,
+                               sp: Span,
+                               ast_errors: usize) {
+    let mir = &tcx.item_mir(tcx.map.local_def_id(id));
+    let crosscheck = tcx.sess.opts.debugging_opts.definite_init_crosscheck;
+    let mir_errors = definite_init::check_definite_init(tcx, mir, id, crosscheck);
+    if crosscheck && ast_errors != mir_errors {
+        tcx.sess.span_warn(sp, &format!("AST reported {} use(s) of possibly uninitialized \
+                                         variables in this function, but MIR reported {}",
+                                        ast_errors, mir_errors));
+    }
+}
+
+/// Describes `lvalue` the way the AST borrow checker describes loan paths,
+/// e.g. `*x.f`, for error messages.
+fn describe_lvalue<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                             mir: &Mir<'tcx>,
+                             lvalue: &mir::Lvalue<'tcx>) -> String {
+    match *lvalue {
+        mir::Lvalue::Local(local) => match mir.local_decls[local].name {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", lvalue)
+        },
+        mir::Lvalue::Static(def_id) => tcx.item_path_str(def_id),
+        mir::Lvalue::Projection(ref proj) => {
+            let base = describe_lvalue(tcx, mir, &proj.base);
+            match proj.elem {
+                mir::ProjectionElem::Deref => format!("*{}", base),
+                mir::ProjectionElem::Field(field, _) => {
+                    let name = match proj.base.ty(mir, tcx) {
+                        LvalueTy::Ty { ty } => match ty.sty {
+                            ty::TyAdt(adt, _) if !adt.is_enum() => {
+                                adt.struct_variant().fields[field.index()].name.to_string()
+                            }
+                            _ => field.index().to_string()
+                        },
+                        LvalueTy::Downcast { adt_def, variant_index, .. } => {
+                            adt_def.variants[variant_index].fields[field.index()]
+                                .name.to_string()
+                        }
+                    };
+                    format!("{}.{}", base, name)
+                }
+                mir::ProjectionElem::Index(_) |
+                mir::ProjectionElem::ConstantIndex { .. } |
+                mir::ProjectionElem::Subslice { .. } => format!("{}[..]", base),
+                mir::ProjectionElem::Downcast(..) => base
+            }
+        }
+    }
+}
+
 fn do_dataflow<'a, 'tcx, BD>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                              mir: &Mir<'tcx>,
                              node_id: ast::NodeId,
@@ -377,17 +455,23 @@ fn drop_flag_effects_for_location<'a, 'tcx, F>(
     let block = &mir[loc.block];
     match block.statements.get(loc.statement_index) {
         Some(stmt) => match stmt.kind {
-            mir::StatementKind::SetDiscriminant{ .. } => {
-                span_bug!(stmt.source_info.span, "SetDiscrimant should not exist during borrowck");
-            }
+            // Setting the discriminant of an enum whose fields were assigned
+            // by the deaggregator finishes initializing it.
+            mir::StatementKind::SetDiscriminant { ref lvalue, .. } |
             mir::StatementKind::Assign(ref lvalue, _) => {
                 debug!("drop_flag_effects: assignment {:?}", stmt);
                  on_lookup_result_bits(tcx, mir, move_data,
                                        move_data.rev_lookup.find(lvalue),
                                        |moi| callback(moi, DropFlagState::Present))
             }
+            mir::StatementKind::Poison(ref lvalue) => {
+                on_lookup_result_bits(tcx, mir, move_data,
+                                      move_data.rev_lookup.find(lvalue),
+                                      |moi| callback(moi, DropFlagState::Absent))
+            }
             mir::StatementKind::StorageLive(_) |
             mir::StatementKind::StorageDead(_) |
+            mir::StatementKind::IncrementCounter(_) |
             mir::StatementKind::Nop => {}
         },
         None => {
@@ -405,3 +489,47 @@ fn drop_flag_effects_for_location<'a, 'tcx, F>(
         }
     }
 }
+
+/// Like `drop_flag_effects_for_location`, but for whether lvalues have been
+/// assigned since they were declared: moves out are ignored, and a
+/// `StorageDead` makes a local unassigned again.
+fn assignment_effects_for_location<'a, 'tcx, F>(
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &Mir<'tcx>,
+    ctxt: &MoveDataParamEnv<'tcx>,
+    loc: Location,
+    mut callback: F)
+    where F: FnMut(MovePathIndex, DropFlagState)
+{
+    let move_data = &ctxt.move_data;
+    let block = &mir[loc.block];
+    match block.statements.get(loc.statement_index) {
+        Some(stmt) => match stmt.kind {
+            mir::StatementKind::SetDiscriminant { ref lvalue, .. } |
+            mir::StatementKind::Assign(ref lvalue, _) => {
+                on_lookup_result_bits(tcx, mir, move_data,
+                                      move_data.rev_lookup.find(lvalue),
+                                      |moi| callback(moi, DropFlagState::Present))
+            }
+            mir::StatementKind::StorageDead(ref lvalue) => {
+                on_lookup_result_bits(tcx, mir, move_data,
+                                      move_data.rev_lookup.find(lvalue),
+                                      |moi| callback(moi, DropFlagState::Absent))
+            }
+            mir::StatementKind::StorageLive(_) |
+            mir::StatementKind::IncrementCounter(_) |
+            mir::StatementKind::Poison(_) |
+            mir::StatementKind::Nop => {}
+        },
+        None => match block.terminator().kind {
+            mir::TerminatorKind::DropAndReplace { ref location, .. } => {
+                on_lookup_result_bits(tcx, mir, move_data,
+                                      move_data.rev_lookup.find(location),
+                                      |moi| callback(moi, DropFlagState::Present))
+            }
+            _ => {
+                // call destinations are assigned by `propagate_call_return`.
+            }
+        }
+    }
+}
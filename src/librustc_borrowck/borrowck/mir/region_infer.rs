@@ -0,0 +1,206 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Region inference over the MIR control-flow graph, for the MIR borrow
+//! checker.
+//!
+//! Rather than the lexical scopes the AST borrow checker uses, the region
+//! of a loan is the set of points at which some local holding the
+//! reference, or something derived from it, is live (see `mir::liveness`).
+//! The local the reference is stored into holds it, and so does any local
+//! assigned from an rvalue reading a holder, as long as its type can
+//! contain references. Calls are followed the same way, except that a
+//! reference passed directly as an argument isn't returned when its region
+//! doesn't appear in the return type of the callee.
+//!
+//! References stored through another reference, e.g. pushed into a vector
+//! by a method call, aren't followed, so such loans may end too early.
+
+use rustc::mir::*;
+use rustc::mir::liveness::{DefsUses, Liveness, LocalEffects};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_data_structures::indexed_set::{IdxSet, IdxSetBuf};
+use rustc_data_structures::indexed_vec::IndexVec;
+
+use super::dataflow::{BorrowData, BorrowIndex};
+
+pub struct BorrowRegions {
+    /// For each loan, the locals which may hold it.
+    holders: IndexVec<BorrowIndex, IdxSetBuf<Local>>,
+    /// For each block, the locals live on entry to each of its statements
+    /// and to its terminator.
+    live_in: IndexVec<BasicBlock, Vec<IdxSetBuf<Local>>>,
+}
+
+impl BorrowRegions {
+    pub fn infer<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                           mir: &Mir<'tcx>,
+                           borrows: &IndexVec<BorrowIndex, BorrowData<'tcx>>)
+                           -> Self {
+        BorrowRegions {
+            holders: borrows.iter().map(|borrow| holders(tcx, mir, borrow)).collect(),
+            live_in: live_in(mir),
+        }
+    }
+
+    /// Whether the region of `borrow` includes `location`.
+    pub fn contains(&self, borrow: BorrowIndex, location: Location) -> bool {
+        let live = &self.live_in[location.block][location.statement_index];
+        self.holders[borrow].words().iter().zip(live.words()).any(|(h, l)| h & l != 0)
+    }
+}
+
+/// The locals live on entry to each statement and terminator.
+fn live_in<'tcx>(mir: &Mir<'tcx>) -> IndexVec<BasicBlock, Vec<IdxSetBuf<Local>>> {
+    let liveness = Liveness::compute(mir, &Effects);
+    mir.basic_blocks().indices().map(|block| liveness.block(mir, block, &Effects)).collect()
+}
+
+fn may_hold_reference<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 mir: &Mir<'tcx>,
+                                 lvalue: &Lvalue<'tcx>) -> bool {
+    lvalue.ty(mir, tcx).to_ty(tcx).walk().any(|ty| !ty.regions().is_empty())
+}
+
+/// Whether a call to a function of type `func_ty` may return the reference
+/// passed as its `i`th argument, `arg`. `dest` is the local the reference
+/// was first stored into: only then is `arg` known to be that reference,
+/// rather than something containing it.
+fn may_return<'tcx>(func_ty: Ty<'tcx>, i: usize, arg: &Operand<'tcx>, dest: Local) -> bool {
+    match *arg {
+        Operand::Consume(Lvalue::Local(local)) if local == dest => {}
+        _ => return true
+    }
+    let sig = match func_ty.sty {
+        ty::TyFnDef(.., f) | ty::TyFnPtr(f) => f.sig.skip_binder(),
+        _ => return true
+    };
+    match sig.inputs().get(i).map(|ty| &ty.sty) {
+        Some(&ty::TyRef(region, _)) => {
+            sig.output().walk().any(|ty| ty.regions().contains(&region))
+        }
+        _ => true
+    }
+}
+
+fn holders<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                     mir: &Mir<'tcx>,
+                     borrow: &BorrowData<'tcx>) -> IdxSetBuf<Local> {
+    let mut holders = IdxSetBuf::new_empty(mir.local_decls.len());
+    let dest = match borrow.dest.base_local() {
+        Some(dest) => dest,
+        None => return holders
+    };
+    holders.add(&dest);
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (block, data) in mir.basic_blocks().iter_enumerated() {
+            for (statement_index, stmt) in data.statements.iter().enumerate() {
+                let location = Location { block: block, statement_index: statement_index };
+                if let StatementKind::Assign(ref lhs, ref rvalue) = stmt.kind {
+                    let found = {
+                        let mut reads = ReadsAny { locals: &holders, found: false };
+                        reads.visit_rvalue(rvalue, location);
+                        reads.found
+                    };
+                    if found && may_hold_reference(tcx, mir, lhs) {
+                        if let Some(local) = lhs.base_local() {
+                            changed |= holders.add(&local);
+                        }
+                    }
+                }
+            }
+
+            let location = Location { block: block, statement_index: data.statements.len() };
+            if let TerminatorKind::Call {
+                ref func, ref args, destination: Some((ref lhs, _)), ..
+            } = data.terminator().kind {
+                let func_ty = func.ty(mir, tcx);
+                let returned = args.iter().enumerate().any(|(i, arg)| {
+                    let mut reads = ReadsAny { locals: &holders, found: false };
+                    reads.visit_operand(arg, location);
+                    reads.found && may_return(func_ty, i, arg, dest)
+                });
+                if returned && may_hold_reference(tcx, mir, lhs) {
+                    if let Some(local) = lhs.base_local() {
+                        changed |= holders.add(&local);
+                    }
+                }
+            }
+        }
+    }
+    holders
+}
+
+/// Finds whether any of `locals` is read.
+struct ReadsAny<'b> {
+    locals: &'b IdxSet<Local>,
+    found: bool,
+}
+
+impl<'b, 'tcx> Visitor<'tcx> for ReadsAny<'b> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Local(local) = *lvalue {
+            if self.locals.contains(&local) {
+                self.found = true;
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// The locals a statement or terminator assigns as a whole, and those it
+/// reads in any way.
+struct Effects;
+
+impl<'tcx> LocalEffects<'tcx> for Effects {
+    fn statement_effects(&self, statement: &Statement<'tcx>, location: Location) -> DefsUses {
+        let mut collector = Collector { defs_uses: DefsUses::default() };
+        collector.visit_statement(location.block, statement, location);
+        collector.defs_uses
+    }
+
+    fn terminator_effects(&self, terminator: &Terminator<'tcx>, location: Location) -> DefsUses {
+        let mut collector = Collector { defs_uses: DefsUses::default() };
+        collector.visit_terminator(location.block, terminator, location);
+        if let TerminatorKind::Return = terminator.kind {
+            collector.defs_uses.uses.push(RETURN_POINTER);
+        }
+        collector.defs_uses
+    }
+}
+
+struct Collector {
+    defs_uses: DefsUses,
+}
+
+impl<'tcx> Visitor<'tcx> for Collector {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Local(local) = *lvalue {
+            match context {
+                LvalueContext::Store |
+                LvalueContext::Call => self.defs_uses.defs.push(local),
+                LvalueContext::StorageLive |
+                LvalueContext::StorageDead => {}
+                _ => self.defs_uses.uses.push(local),
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
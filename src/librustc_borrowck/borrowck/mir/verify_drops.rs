@@ -0,0 +1,150 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A debugging aid for MIR optimisations, enabled with `-Z verify-mir-drops`.
+//!
+//! After every pass, the move paths of each function are recomputed and
+//! the same "maybe initialized" analysis drop elaboration uses is run over
+//! them. A use of a move path which is not initialized along any path
+//! reaching it means the pass broke the MIR; once drops are elaborated, so
+//! does a drop of such a path, as it was already moved out of or dropped.
+//!
+//! Only uses of whole move paths are checked: reading a field of a value
+//! which was moved as a whole is not reported. An enum built by the
+//! deaggregator is only initialized once its discriminant is set, so a pass
+//! losing the `SetDiscriminant` is caught.
+
+use super::gather_moves::{MoveData, MovePathIndex, LookupResult};
+use super::dataflow::MaybeInitializedLvals;
+use super::{drop_flag_effects_for_location, DropFlagState, MoveDataParamEnv};
+use rustc::mir::*;
+use rustc::mir::transform::{MirPassHook, MirSource, Pass};
+use rustc::mir::traversal;
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::indexed_set::IdxSetBuf;
+use rustc_data_structures::indexed_vec::Idx;
+use syntax_pos::Span;
+
+pub struct VerifyDrops {
+    /// Whether `ElaborateDrops` has run. Before that, drops of values which
+    /// may be uninitialized are expected.
+    elaborated: bool
+}
+
+impl VerifyDrops {
+    pub fn new() -> VerifyDrops {
+        VerifyDrops { elaborated: false }
+    }
+}
+
+impl Pass for VerifyDrops {}
+
+impl<'tcx> MirPassHook<'tcx> for VerifyDrops {
+    fn on_mir_pass<'a>(&mut self,
+                       tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                       src: MirSource,
+                       mir: &Mir<'tcx>,
+                       pass: &Pass,
+                       is_after: bool)
+    {
+        let id = match src {
+            MirSource::Fn(id) if is_after => id,
+            _ => return
+        };
+        if pass.name() == "ElaborateDrops" {
+            self.elaborated = true;
+        }
+        let param_env = ty::ParameterEnvironment::for_item(tcx, id);
+        let move_data = MoveData::gather_moves(mir, tcx, &param_env);
+        let env = MoveDataParamEnv {
+            move_data: move_data,
+            param_env: param_env
+        };
+        let flow_inits = super::do_dataflow(tcx, mir, id, &[], &env,
+                                            MaybeInitializedLvals::new(tcx, mir));
+
+        let mut checker = UseChecker {
+            move_data: &env.move_data,
+            inits: IdxSetBuf::new_empty(0),
+            span: mir.span,
+            errors: vec![]
+        };
+        // Unreachable blocks are skipped, as nothing is initialized there.
+        for (bb, data) in traversal::reverse_postorder(mir) {
+            checker.inits = flow_inits.sets().on_entry_set_for(bb.index()).to_owned();
+            for statement_index in 0..data.statements.len() + 1 {
+                let loc = Location { block: bb, statement_index: statement_index };
+                match data.statements.get(statement_index) {
+                    Some(stmt) => {
+                        checker.span = stmt.source_info.span;
+                        checker.visit_statement(bb, stmt, loc);
+                    }
+                    None => {
+                        let terminator = data.terminator();
+                        checker.span = terminator.source_info.span;
+                        checker.visit_terminator(bb, terminator, loc);
+                        if let TerminatorKind::Drop { ref location, .. } = terminator.kind {
+                            if self.elaborated {
+                                checker.check(location, "drop of a moved or dropped value");
+                            }
+                        }
+                    }
+                }
+                drop_flag_effects_for_location(tcx, mir, &env, loc, |path, df| {
+                    match df {
+                        DropFlagState::Present => { checker.inits.add(&path); }
+                        DropFlagState::Absent => { checker.inits.remove(&path); }
+                    }
+                });
+            }
+        }
+
+        for (span, msg) in checker.errors {
+            tcx.sess.span_err(span, &format!("MIR verification after `{}` failed: {}",
+                                             pass.name(), msg));
+        }
+    }
+}
+
+struct UseChecker<'a, 'tcx: 'a> {
+    move_data: &'a MoveData<'tcx>,
+    /// The move paths which may be initialized at the current location.
+    inits: IdxSetBuf<MovePathIndex>,
+    span: Span,
+    errors: Vec<(Span, String)>
+}
+
+impl<'a, 'tcx> UseChecker<'a, 'tcx> {
+    fn check(&mut self, lvalue: &Lvalue<'tcx>, what: &str) {
+        if let LookupResult::Exact(path) = self.move_data.rev_lookup.find(lvalue) {
+            if !self.inits.contains(&path) {
+                self.errors.push((self.span, format!("{} `{:?}`", what, lvalue)));
+            }
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for UseChecker<'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        match context {
+            LvalueContext::Consume |
+            LvalueContext::Inspect |
+            LvalueContext::Borrow { .. } => {
+                self.check(lvalue, "use of a moved value");
+            }
+            _ => {}
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
@@ -19,6 +19,7 @@ pub use self::AliasableViolationKind::*;
 pub use self::MovedValueUseKind::*;
 
 pub use self::mir::elaborate_drops::ElaborateDrops;
+pub use self::mir::verify_drops::VerifyDrops;
 
 use self::InteriorKind::*;
 
@@ -38,6 +39,7 @@ use rustc::middle::mem_categorization::Categorization;
 use rustc::middle::region;
 use rustc::ty::{self, TyCtxt};
 
+use std::cell::{Cell, RefCell};
 use std::fmt;
 use std::mem;
 use std::rc::Rc;
@@ -111,7 +113,9 @@ pub fn check_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
             loaned_paths_imm: 0,
             stable_paths: 0,
             guaranteed_paths: 0
-        }
+        },
+        uninitialized_uses: Cell::new(0),
+        deferred_uninitialized_uses: RefCell::new(vec![])
     };
 
     tcx.visit_all_item_likes_in_krate(DepNode::BorrowCheck, &mut bccx.as_deep_visitor());
@@ -175,6 +179,8 @@ fn borrowck_fn<'a, 'tcx>(this: &mut BorrowckCtxt<'a, 'tcx>,
         });
     }
 
+    let errors_before = this.tcx.sess.err_count();
+    let uninitialized_uses_before = this.uninitialized_uses.get();
     let cfg = cfg::CFG::new(this.tcx, body);
     let AnalysisData { all_loans,
                        loans: loan_dfcx,
@@ -197,6 +203,27 @@ fn borrowck_fn<'a, 'tcx>(this: &mut BorrowckCtxt<'a, 'tcx>,
                              decl,
                              body);
 
+    if this.tcx.sess.opts.debugging_opts.borrowck_mir {
+        let ast_errors = this.tcx.sess.err_count() - errors_before;
+        mir::borrowck_mir_crosscheck(this.tcx, id, sp, ast_errors);
+    }
+
+    let mut deferred = mem::replace(&mut *this.deferred_uninitialized_uses.borrow_mut(), vec![]);
+    if this.tcx.sess.opts.debugging_opts.mir_definite_init ||
+       this.tcx.sess.opts.debugging_opts.definite_init_crosscheck {
+        // Move paths can't be gathered from MIR the AST borrow checker
+        // found illegal moves in, so only run on functions it accepted.
+        let uninitialized_uses = this.uninitialized_uses.get() - uninitialized_uses_before;
+        if this.tcx.sess.err_count() - errors_before == uninitialized_uses {
+            mir::definite_init(this.tcx, id, sp, uninitialized_uses);
+            deferred.clear();
+        }
+    }
+    // The MIR check didn't run, so the AST one reports these after all.
+    for (span, verb, lp) in deferred {
+        this.report_uninitialized_use(span, verb, &lp);
+    }
+
     intravisit::walk_fn(this, fk, decl, body_id, sp, id);
 }
 
@@ -260,7 +287,9 @@ pub fn build_borrowck_dataflow_data_for_fn<'a, 'tcx>(
             loaned_paths_imm: 0,
             stable_paths: 0,
             guaranteed_paths: 0
-        }
+        },
+        uninitialized_uses: Cell::new(0),
+        deferred_uninitialized_uses: RefCell::new(vec![])
     };
 
     let body = tcx.map.expr(fn_parts.body);
@@ -295,7 +324,16 @@ pub struct BorrowckCtxt<'a, 'tcx: 'a> {
     free_region_map: FreeRegionMap,
 
     // Statistics:
-    stats: BorrowStats
+    stats: BorrowStats,
+
+    // The number of uses of possibly uninitialized variables reported so
+    // far, for `-Z definite-init-crosscheck`.
+    uninitialized_uses: Cell<usize>,
+
+    // The uses of possibly uninitialized variables found in the current
+    // function under `-Z mir-definite-init`, only reported if the MIR
+    // check can't run on it.
+    deferred_uninitialized_uses: RefCell<Vec<(Span, &'static str, String)>>
 }
 
 #[derive(Clone)]
@@ -625,6 +663,16 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
         db.emit();
     }
 
+    fn report_uninitialized_use(&self, use_span: Span, verb: &str, lp: &str) {
+        struct_span_err!(
+            self.tcx.sess, use_span, E0381,
+            "{} of possibly uninitialized variable: `{}`",
+            verb,
+            lp)
+        .span_label(use_span, &format!("use of possibly uninitialized `{}`", lp))
+        .emit();
+    }
+
     pub fn report_use_of_moved_value(&self,
                                      use_span: Span,
                                      use_kind: MovedValueUseKind,
@@ -639,16 +687,20 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
 
         let (_ol, _moved_lp_msg, mut err) = match the_move.kind {
             move_data::Declared => {
+                // Under `-Z mir-definite-init`, uninitialized variables are
+                // reported by the MIR check instead, if it runs on the
+                // function (see `borrowck_fn`).
+                let lp = self.loan_path_to_string(lp);
+                let opts = &self.tcx.sess.opts.debugging_opts;
+                if opts.mir_definite_init && !opts.definite_init_crosscheck {
+                    self.deferred_uninitialized_uses.borrow_mut().push((use_span, verb, lp));
+                    return;
+                }
+                self.uninitialized_uses.set(self.uninitialized_uses.get() + 1);
+
                 // If this is an uninitialized variable, just emit a simple warning
                 // and return.
-                struct_span_err!(
-                    self.tcx.sess, use_span, E0381,
-                    "{} of possibly uninitialized variable: `{}`",
-                    verb,
-                    self.loan_path_to_string(lp))
-                .span_label(use_span, &format!("use of possibly uninitialized `{}`",
-                    self.loan_path_to_string(lp)))
-                .emit();
+                self.report_uninitialized_use(use_span, verb, &lp);
                 return;
             }
             _ => {
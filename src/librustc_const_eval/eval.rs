@@ -474,6 +474,8 @@ pub enum ErrKind {
     BadType(ConstVal),
     ErroneousReferencedConstant(Box<ConstEvalErr>),
     CharCast(ConstInt),
+    /// Evaluating a `const fn` took more steps than `-Z const-eval-limit` allows.
+    StepLimitReached,
 }
 
 impl From<ConstMathErr> for ErrKind {
@@ -556,6 +558,7 @@ impl ConstEvalErr {
             CharCast(ref got) => {
                 simple!("only `u8` can be cast as `char`, not `{}`", got.description())
             },
+            StepLimitReached => simple!("constant evaluation exceeded limit"),
         }
     }
 }
@@ -1195,7 +1198,7 @@ fn cast_const_float<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
-fn cast_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, val: ConstVal, ty: ty::Ty) -> CastResult {
+pub fn cast_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, val: ConstVal, ty: ty::Ty) -> CastResult {
     match val {
         Integral(i) => cast_const_int(tcx, i, ty),
         Bool(b) => cast_const_int(tcx, Infer(b as u64), ty),
@@ -20,6 +20,7 @@ use rustc::session::config::{self, Input, OutputFilenames, OutputType,
                              OutputTypes};
 use rustc::session::search_paths::PathKind;
 use rustc::lint;
+use rustc::mir::transform::PassPoint;
 use rustc::middle::{self, dependency_format, stability, reachable};
 use rustc::middle::privacy::AccessLevels;
 use rustc::ty::{self, TyCtxt};
@@ -28,6 +29,7 @@ use rustc::util::nodemap::{NodeSet, NodeMap};
 use rustc_borrowck as borrowck;
 use rustc_incremental::{self, IncrementalHashesMap};
 use rustc_resolve::{MakeGlobMap, Resolver};
+use rustc_save_analysis as save;
 use rustc_metadata::creader::CrateLoader;
 use rustc_metadata::cstore::CStore;
 use rustc_trans::back::{link, write};
@@ -202,7 +204,14 @@ pub fn compile_input(sess: &Session,
                 println!("Pre-trans");
                 tcx.print_debug_stats();
             }
-            let trans = phase_4_translate_to_llvm(tcx, analysis, &incremental_hashes_map);
+            let trans = phase_4_translate_to_llvm(tcx, analysis, &incremental_hashes_map, &outputs);
+
+            // The MIR is only final once trans has run.
+            if sess.opts.debugging_opts.save_analysis_mir {
+                time(sess.time_passes(), "save MIR analysis", || {
+                    save::process_mir(tcx, &crate_name, outdir.as_ref().map(|s| &**s))
+                });
+            }
 
             if log_enabled!(::log::INFO) {
                 println!("Post-trans");
@@ -602,7 +611,7 @@ pub fn phase_2_configure_and_expand<F>(sess: &Session,
     });
 
     let Registry { syntax_exts, early_lint_passes, late_lint_passes, lint_groups,
-                   llvm_passes, attributes, mir_passes, .. } = registry;
+                   llvm_passes, attributes, mir_passes, mir_pass_hooks, .. } = registry;
 
     sess.track_errors(|| {
         let mut ls = sess.lint_store.borrow_mut();
@@ -618,7 +627,8 @@ pub fn phase_2_configure_and_expand<F>(sess: &Session,
         }
 
         *sess.plugin_llvm_passes.borrow_mut() = llvm_passes;
-        sess.mir_passes.borrow_mut().extend(mir_passes);
+        sess.plugin_mir_passes.borrow_mut().extend(mir_passes);
+        sess.plugin_mir_pass_hooks.borrow_mut().extend(mir_pass_hooks);
         *sess.plugin_attributes.borrow_mut() = attributes.clone();
     })?;
 
@@ -787,6 +797,7 @@ pub fn phase_2_configure_and_expand<F>(sess: &Session,
             export_map: resolver.export_map,
             access_levels: AccessLevels::default(),
             reachable: NodeSet(),
+            unreferenced_fns: NodeSet(),
             name: crate_name.to_string(),
             glob_map: if resolver.make_glob_map { Some(resolver.glob_map) } else { None },
             hir_ty_to_ty: NodeMap(),
@@ -942,17 +953,26 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
             // NB: if you’re adding an *optimisation* it ought to go to another set of passes
             // in stage 4 below.
             passes.push_hook(box mir::transform::dump_mir::DumpMir);
+            passes.push_plugin_hooks(sess);
+            passes.push_plugin_passes(sess, PassPoint::Built);
             passes.push_pass(box mir::transform::simplify::SimplifyCfg::new("initial"));
             passes.push_pass(
                 box mir::transform::qualify_consts::QualifyAndPromoteConstants::default());
             passes.push_pass(box mir::transform::type_check::TypeckMir);
+            passes.push_pass(box mir::transform::dead_assignments::DeadAssignments);
             passes.push_pass(
                 box mir::transform::simplify_branches::SimplifyBranches::new("initial"));
             passes.push_pass(box mir::transform::simplify::SimplifyCfg::new("qualify-consts"));
+            passes.push_pass(box mir::transform::unconditional_panic::UnconditionalPanic);
+            passes.push_plugin_passes(sess, PassPoint::Validated);
             // And run everything.
             passes.run_passes(tcx);
         });
 
+        time(time_passes,
+             "MIR constant evaluation",
+             || mir::interpret::check_crate(tcx));
+
         time(time_passes,
              "borrow checking",
              || borrowck::check_crate(tcx));
@@ -967,6 +987,12 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
             return Ok(f(tcx, analysis, incremental_hashes_map, Err(sess.err_count())));
         }
 
+        if sess.opts.debugging_opts.mir_interpret {
+            time(time_passes,
+                 "MIR interpretation",
+                 || mir::interpret::run_fn(tcx));
+        }
+
         analysis.reachable =
             time(time_passes,
                  "reachability checking",
@@ -995,8 +1021,9 @@ pub fn phase_3_run_analysis_passes<'tcx, F, R>(sess: &'tcx Session,
 
 /// Run the translation phase to LLVM, after which the AST and analysis can
 pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                                           analysis: ty::CrateAnalysis,
-                                           incremental_hashes_map: &IncrementalHashesMap)
+                                           mut analysis: ty::CrateAnalysis,
+                                           incremental_hashes_map: &IncrementalHashesMap,
+                                           outputs: &OutputFilenames)
                                            -> trans::CrateTranslation {
     let time_passes = tcx.sess.time_passes();
 
@@ -1013,33 +1040,82 @@ pub fn phase_4_translate_to_llvm<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     time(time_passes, "MIR optimisations", || {
         let mut passes = ::rustc::mir::transform::Passes::new();
         passes.push_hook(box mir::transform::dump_mir::DumpMir);
+        let opt_report = mir::transform::opt_report::OptReport::new();
+        if tcx.sess.opts.debugging_opts.mir_opt_report.is_some() {
+            passes.push_hook(box opt_report.clone());
+        }
+        let memory_stats = mir::transform::memory_stats::MemoryStats::new();
+        if tcx.sess.opts.debugging_opts.mir_memory_stats {
+            passes.push_hook(box memory_stats.clone());
+        }
+        if tcx.sess.opts.debugging_opts.verify_mir_drops {
+            passes.push_hook(box borrowck::VerifyDrops::new());
+        }
+        if tcx.sess.opts.debugging_opts.verify_cleanup_edges {
+            passes.push_hook(box mir::transform::verify_cleanup_edges::VerifyCleanupEdges::new());
+        }
+        if tcx.sess.opts.debugging_opts.mir_opt_verify_equivalence {
+            passes.push_hook(box mir::transform::verify_equivalence::VerifyEquivalence::new());
+        }
+        passes.push_plugin_hooks(tcx.sess);
+        passes.push_pass(box mir::transform::check_refs::CheckRefs);
         passes.push_pass(box mir::transform::no_landing_pads::NoLandingPads);
+        passes.push_pass(box mir::transform::elide_asserts::ElideAsserts);
+        passes.push_pass(box mir::transform::fold_overflow_checks::FoldOverflowChecks);
         passes.push_pass(box mir::transform::simplify::SimplifyCfg::new("no-landing-pads"));
 
         // From here on out, regions are gone.
         passes.push_pass(box mir::transform::erase_regions::EraseRegions);
+        passes.push_pass(box mir::transform::eval_consts::EvalConsts);
 
         passes.push_pass(box mir::transform::add_call_guards::AddCallGuards);
         passes.push_pass(box borrowck::ElaborateDrops);
         passes.push_pass(box mir::transform::no_landing_pads::NoLandingPads);
         passes.push_pass(box mir::transform::simplify::SimplifyCfg::new("elaborate-drops"));
+        passes.push_pass(box mir::transform::large_moves::LargeMoves);
 
         // No lifetime analysis based on borrowing can be done from here on out.
-        passes.push_pass(box mir::transform::instcombine::InstCombine::new());
-        passes.push_pass(box mir::transform::deaggregator::Deaggregator);
-        passes.push_pass(box mir::transform::copy_prop::CopyPropagation);
-
-        passes.push_pass(box mir::transform::simplify::SimplifyLocals);
-        passes.push_pass(box mir::transform::add_call_guards::AddCallGuards);
+        mir::transform::pipeline::push_late_passes(&mut passes);
+        passes.push_plugin_passes(tcx.sess, PassPoint::Optimized);
         passes.push_pass(box mir::transform::dump_mir::Marker("PreTrans"));
 
         passes.run_passes(tcx);
+        ::rustc::mir::transform::report_suggestions(tcx);
+
+        if let Some(ref path) = tcx.sess.opts.debugging_opts.mir_opt_report {
+            if let Err(e) = opt_report.write(Path::new(path)) {
+                tcx.sess.err(&format!("could not write the MIR optimization report to `{}`: {}",
+                                      path, e));
+            }
+        }
+        if tcx.sess.opts.debugging_opts.mir_memory_stats {
+            memory_stats.print(tcx);
+        }
     });
 
+    if tcx.sess.opts.debugging_opts.mir_fuzz.is_some() {
+        time(time_passes, "MIR fuzzing", || mir::fuzz::fuzz_passes(tcx));
+    }
+
     if tcx.sess.opts.debugging_opts.mir_stats {
         mir_stats::print_mir_stats(tcx, "POST OPTIMISATION MIR STATS");
     }
 
+    if tcx.sess.opts.output_types.contains_key(&OutputType::Mir) {
+        time(time_passes, "writing MIR", || write_out_mir(tcx, outputs));
+    }
+
+    // The optimized MIR of inlinable items can refer to items the HIR-based
+    // reachability pass didn't see, and those have to be exported too.
+    time(time_passes,
+         "MIR reachability",
+         || mir::reachable::extend_reachable_from_mir(tcx, &mut analysis.reachable));
+
+    analysis.unreferenced_fns =
+        time(time_passes,
+             "finding unreferenced functions",
+             || mir::internalize::find_unreferenced_fns(tcx, &analysis.reachable));
+
     let translation =
         time(time_passes,
              "translation",
@@ -1122,6 +1198,24 @@ fn escape_dep_filename(filename: &str) -> String {
     filename.replace(" ", "\\ ")
 }
 
+fn write_out_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, outputs: &OutputFilenames) {
+    let path = outputs.path(OutputType::Mir);
+
+    // Items are written in definition order rather than in the order of the
+    // MIR map, so that the output is the same from one build to the next.
+    let mut def_ids: Vec<_> = tcx.mir_map.borrow().keys().into_iter()
+                                 .filter(|def_id| def_id.is_local())
+                                 .collect();
+    def_ids.sort();
+
+    let result = fs::File::create(&path).and_then(|mut file| {
+        mir::pretty::write_mir_pretty(tcx, def_ids.into_iter(), &mut file)
+    });
+    if let Err(e) = result {
+        tcx.sess.fatal(&format!("error writing MIR to `{}`: {}", path.display(), e));
+    }
+}
+
 fn write_out_deps(sess: &Session, outputs: &OutputFilenames, crate_name: &str) {
     let mut out_filenames = Vec::new();
     for output_type in sess.opts.output_types.keys() {
@@ -160,6 +160,7 @@ pub fn register_builtins(store: &mut lint::LintStore, sess: Option<&Session>) {
                     UNUSED_IMPORTS,
                     UNUSED_VARIABLES,
                     UNUSED_ASSIGNMENTS,
+                    DEAD_ASSIGNMENTS,
                     DEAD_CODE,
                     UNUSED_MUT,
                     UNREACHABLE_CODE,
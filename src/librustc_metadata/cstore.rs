@@ -71,6 +71,9 @@ pub struct CrateMetadata {
     pub cnum: CrateNum,
     pub codemap_import_info: RefCell<Vec<ImportedFileMap>>,
 
+    /// MIR blocks inflated so far, shared by all the items they contain.
+    pub inflated_mir_blocks: RefCell<FxHashMap<u32, Rc<Bytes>>>,
+
     pub root: schema::CrateRoot,
 
     /// For each public item in this crate, we encode a key.  When the
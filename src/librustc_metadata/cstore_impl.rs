@@ -25,6 +25,7 @@ use rustc::dep_graph::DepNode;
 use rustc::hir::map as hir_map;
 use rustc::hir::map::DefKey;
 use rustc::mir::Mir;
+use rustc::mir::summary::InlineSummary;
 use rustc::util::nodemap::{NodeSet, DefIdMap};
 use rustc_back::PanicStrategy;
 
@@ -517,7 +518,17 @@ impl<'tcx> CrateStore<'tcx> for cstore::CStore {
 
     fn get_item_mir<'a>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>, def: DefId) -> Mir<'tcx> {
         self.dep_graph.read(DepNode::MetaData(def));
-        self.get_crate_data(def.krate).maybe_get_item_mir(tcx, def.index).unwrap_or_else(|| {
+        let cdata = self.get_crate_data(def.krate);
+        cdata.maybe_get_item_mir(tcx, def.index).unwrap_or_else(|| {
+            if !cdata.is_mir_format_supported() {
+                tcx.sess.fatal(&format!("the MIR of `{}` can't be used: crate `{}` was \
+                                         compiled by a compiler with an incompatible MIR \
+                                         format (version {}, expected {}); recompile it",
+                                        tcx.item_path_str(def),
+                                        cdata.name,
+                                        cdata.root.mir_format_version,
+                                        schema::MIR_FORMAT_VERSION));
+            }
             bug!("get_item_mir: missing MIR for {}", tcx.item_path_str(def))
         })
     }
@@ -527,6 +538,11 @@ impl<'tcx> CrateStore<'tcx> for cstore::CStore {
         self.get_crate_data(def.krate).is_item_mir_available(def.index)
     }
 
+    fn item_mir_summary(&self, def: DefId) -> Option<InlineSummary> {
+        self.dep_graph.read(DepNode::MetaData(def));
+        self.get_crate_data(def.krate).maybe_get_item_mir_summary(def.index)
+    }
+
     fn crates(&self) -> Vec<CrateNum>
     {
         let mut result = vec![];
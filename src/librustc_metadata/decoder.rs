@@ -32,11 +32,15 @@ use rustc::ty::subst::Substs;
 use rustc_const_math::ConstInt;
 
 use rustc::mir::Mir;
+use rustc::mir::summary::InlineSummary;
+
+use flate::{self, Bytes};
 
 use std::borrow::Cow;
 use std::cell::Ref;
 use std::io;
 use std::mem;
+use std::rc::Rc;
 use std::str;
 use std::u32;
 
@@ -58,6 +62,10 @@ pub struct DecodeContext<'a, 'tcx: 'a> {
     last_filemap_index: usize,
 
     lazy_state: LazyState,
+
+    // Whether type shorthands may be cached in `tcx.rcache`, which is
+    // keyed on positions in the crate's metadata blob.
+    cache_shorthands: bool,
 }
 
 /// Abstract over the various ways one can create metadata decoders.
@@ -82,6 +90,7 @@ pub trait Metadata<'a, 'tcx>: Copy {
             to_id_range: id_range,
             last_filemap_index: 0,
             lazy_state: LazyState::NoNode,
+            cache_shorthands: true,
         }
     }
 }
@@ -149,6 +158,35 @@ impl<'a, 'tcx> Metadata<'a, 'tcx> for (&'a CrateMetadata, TyCtxt<'a, 'tcx, 'tcx>
     }
 }
 
+/// An inflated MIR block of a crate, decoded within its own position space.
+#[derive(Copy, Clone)]
+pub struct MirBlockData<'a, 'tcx: 'a> {
+    data: &'a [u8],
+    cdata: &'a CrateMetadata,
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> Metadata<'a, 'tcx> for MirBlockData<'a, 'tcx> {
+    fn raw_bytes(self) -> &'a [u8] {
+        self.data
+    }
+    fn cdata(self) -> Option<&'a CrateMetadata> {
+        Some(self.cdata)
+    }
+    fn tcx(self) -> Option<TyCtxt<'a, 'tcx, 'tcx>> {
+        Some(self.tcx)
+    }
+
+    fn decoder(self, pos: usize) -> DecodeContext<'a, 'tcx> {
+        let mut dcx = (self.cdata, self.tcx).decoder(0);
+        dcx.opaque = opaque::Decoder::new(self.data, pos);
+        // Shorthand positions are relative to the block, so they
+        // would clash with those of the metadata blob in `rcache`.
+        dcx.cache_shorthands = false;
+        dcx
+    }
+}
+
 impl<'a, 'tcx: 'a, T: Decodable> Lazy<T> {
     pub fn decode<M: Metadata<'a, 'tcx>>(self, meta: M) -> T {
         let mut dcx = meta.decoder(self.position);
@@ -363,6 +401,9 @@ impl<'a, 'tcx> SpecializedDecoder<Ty<'tcx>> for DecodeContext<'a, 'tcx> {
                 cnum: self.cdata().cnum,
                 pos: pos - SHORTHAND_OFFSET,
             };
+            if !self.cache_shorthands {
+                return self.with_position(key.pos, Ty::decode);
+            }
             if let Some(ty) = tcx.rcache.borrow().get(&key).cloned() {
                 return Ok(ty);
             }
@@ -835,15 +876,66 @@ impl<'a, 'tcx> CrateMetadata {
         })
     }
 
+    /// Whether this crate's MIR was encoded in a format we can decode.
+    /// If not, the crate is treated as if it didn't export any MIR.
+    pub fn is_mir_format_supported(&self) -> bool {
+        self.root.mir_format_version == MIR_FORMAT_VERSION
+    }
+
     pub fn is_item_mir_available(&self, id: DefIndex) -> bool {
-        self.maybe_entry(id).and_then(|item| item.decode(self).mir).is_some()
+        self.is_mir_format_supported() &&
+            self.maybe_entry(id).and_then(|item| item.decode(self).mir).is_some()
+    }
+
+    pub fn maybe_get_item_mir_summary(&self, id: DefIndex) -> Option<InlineSummary> {
+        if !self.is_mir_format_supported() {
+            return None;
+        }
+        self.maybe_entry(id)
+            .and_then(|item| item.decode(self).mir_summary)
+            .map(|summary| summary.decode(self))
     }
 
     pub fn maybe_get_item_mir(&self,
                               tcx: TyCtxt<'a, 'tcx, 'tcx>,
                               id: DefIndex)
                               -> Option<Mir<'tcx>> {
-        self.entry(id).mir.map(|mir| mir.decode((self, tcx)))
+        if !self.is_mir_format_supported() {
+            debug!("maybe_get_item_mir: crate {} has MIR format version {}, expected {}",
+                   self.name, self.root.mir_format_version, MIR_FORMAT_VERSION);
+            return None;
+        }
+        self.entry(id).mir.map(|location| {
+            let block = self.inflated_mir_block(location.block);
+            let meta = MirBlockData {
+                data: &block[..],
+                cdata: self,
+                tcx: tcx,
+            };
+            Mir::decode(&mut meta.decoder(location.offset as usize)).unwrap()
+        })
+    }
+
+    /// Inflates a MIR block, or returns it from the cache if an item
+    /// sharing the same block had its MIR decoded already.
+    fn inflated_mir_block(&self, index: u32) -> Rc<Bytes> {
+        if let Some(block) = self.inflated_mir_blocks.borrow().get(&index) {
+            return block.clone();
+        }
+
+        let compressed: Vec<u8> = self.root
+            .mir_blocks
+            .decode(self)
+            .nth(index as usize)
+            .unwrap_or_else(|| bug!("inflated_mir_block: no MIR block {}", index))
+            .decode(self)
+            .collect();
+        let block = match flate::inflate_bytes(&compressed) {
+            Ok(block) => Rc::new(block),
+            Err(_) => bug!("inflated_mir_block: corrupt MIR block {} in {}", index, self.name),
+        };
+        self.inflated_mir_blocks.borrow_mut().insert(index, block.clone());
+        block
     }
 
     pub fn get_associated_item(&self, id: DefIndex) -> Option<ty::AssociatedItem> {
@@ -18,18 +18,20 @@ use rustc::hir::def;
 use rustc::hir::def_id::{CrateNum, CRATE_DEF_INDEX, DefIndex, DefId};
 use rustc::middle::dependency_format::Linkage;
 use rustc::middle::lang_items;
-use rustc::mir;
+use rustc::mir::summary::InlineSummary;
 use rustc::traits::specialization_graph;
 use rustc::ty::{self, Ty, TyCtxt};
 
 use rustc::session::config::{self, CrateTypeProcMacro};
 use rustc::util::nodemap::{FxHashMap, NodeSet};
 
+use flate;
 use rustc_serialize::{Encodable, Encoder, SpecializedEncoder, opaque};
 use std::hash::Hash;
 use std::intrinsics;
 use std::io::prelude::*;
 use std::io::Cursor;
+use std::mem;
 use std::rc::Rc;
 use std::u32;
 use syntax::ast::{self, CRATE_NODE_ID};
@@ -38,6 +40,7 @@ use syntax::symbol::Symbol;
 use syntax_pos;
 
 use rustc::hir::{self, PatKind};
+use rustc::hir::map as hir_map;
 use rustc::hir::itemlikevisit::ItemLikeVisitor;
 use rustc::hir::intravisit::{Visitor, NestedVisitorMap};
 use rustc::hir::intravisit;
@@ -55,6 +58,31 @@ pub struct EncodeContext<'a, 'tcx: 'a> {
     lazy_state: LazyState,
     type_shorthands: FxHashMap<Ty<'tcx>, usize>,
     predicate_shorthands: FxHashMap<ty::Predicate<'tcx>, usize>,
+
+    /// The MIR block currently being filled, `None` while
+    /// encoding the MIR of an item into it.
+    mir_block: Option<MirBlock<'tcx>>,
+
+    /// Compressed MIR blocks written so far.
+    mir_blocks: Vec<LazySeq<u8>>,
+}
+
+/// Uncompressed MIR of several items, with its own shorthands
+/// as positions are relative to the start of the block.
+struct MirBlock<'tcx> {
+    data: Cursor<Vec<u8>>,
+    type_shorthands: FxHashMap<Ty<'tcx>, usize>,
+    predicate_shorthands: FxHashMap<ty::Predicate<'tcx>, usize>,
+}
+
+impl<'tcx> MirBlock<'tcx> {
+    fn new() -> MirBlock<'tcx> {
+        MirBlock {
+            data: Cursor::new(vec![]),
+            type_shorthands: Default::default(),
+            predicate_shorthands: Default::default(),
+        }
+    }
 }
 
 macro_rules! encoder_methods {
@@ -293,6 +321,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_summary: None,
         }
     }
 
@@ -330,7 +359,8 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             predicates: None,
 
             ast: None,
-            mir: None
+            mir: None,
+            mir_summary: None,
         }
     }
 }
@@ -410,6 +440,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_summary: None,
         }
     }
 
@@ -444,6 +475,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_summary: None,
         }
     }
 
@@ -532,6 +564,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
                 None
             },
             mir: self.encode_mir(def_id),
+            mir_summary: self.encode_mir_summary(def_id),
         }
     }
 
@@ -577,7 +610,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             let types = generics.parent_types as usize + generics.types.len();
             let needs_inline = types > 0 || attr::requests_inline(&ast_item.attrs);
             let is_const_fn = sig.constness == hir::Constness::Const;
-            (is_const_fn, needs_inline || is_const_fn)
+            (is_const_fn, needs_inline || is_const_fn || self.auto_inline(def_id))
         } else {
             (false, false)
         };
@@ -606,6 +639,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
                 None
             },
             mir: if mir { self.encode_mir(def_id) } else { None },
+            mir_summary: if mir { self.encode_mir_summary(def_id) } else { None },
         }
     }
 
@@ -619,8 +653,112 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
         }))
     }
 
-    fn encode_mir(&mut self, def_id: DefId) -> Option<Lazy<mir::Mir<'tcx>>> {
-        self.tcx.mir_map.borrow().get(&def_id).map(|mir| self.lazy(&*mir.borrow()))
+    fn encode_mir(&mut self, def_id: DefId) -> Option<MirLocation> {
+        let tcx = self.tcx;
+        let mir = match tcx.mir_map.borrow().get(&def_id) {
+            Some(&mir) => mir,
+            None => return None,
+        };
+
+        let mut block = self.mir_block.take().expect("encode_mir: already encoding MIR");
+        let location = MirLocation {
+            block: self.mir_blocks.len() as u32,
+            offset: block.data.position() as u32,
+        };
+
+        {
+            let mut ecx = EncodeContext {
+                opaque: opaque::Encoder::new(&mut block.data),
+                tcx: tcx,
+                reexports: self.reexports,
+                link_meta: self.link_meta,
+                cstore: self.cstore,
+                exported_symbols: self.exported_symbols,
+                lazy_state: LazyState::NoNode,
+                type_shorthands: mem::replace(&mut block.type_shorthands, Default::default()),
+                predicate_shorthands: mem::replace(&mut block.predicate_shorthands,
+                                                   Default::default()),
+                mir_block: None,
+                mir_blocks: vec![],
+            };
+            mir.borrow().encode(&mut ecx).unwrap();
+            block.type_shorthands = ecx.type_shorthands;
+            block.predicate_shorthands = ecx.predicate_shorthands;
+        }
+
+        if block.data.position() as usize >= MIR_BLOCK_SIZE {
+            self.flush_mir_block(block);
+        } else {
+            self.mir_block = Some(block);
+        }
+
+        Some(location)
+    }
+
+    fn encode_mir_summary(&mut self, def_id: DefId) -> Option<Lazy<InlineSummary>> {
+        let tcx = self.tcx;
+        tcx.item_mir_summary(def_id).map(|mut summary| {
+            summary.auto_inline = self.auto_inline(def_id);
+            self.lazy(&summary)
+        })
+    }
+
+    /// Whether `-Z cross-crate-auto-inline` makes the MIR of the function
+    /// `def_id` available to other crates although it is neither generic,
+    /// `#[inline]` nor `const`, as it is small enough to be worth a copy.
+    fn auto_inline(&self, def_id: DefId) -> bool {
+        let tcx = self.tcx;
+        if !tcx.sess.opts.debugging_opts.cross_crate_auto_inline {
+            return false;
+        }
+        let id = match tcx.map.as_local_node_id(def_id) {
+            Some(id) => id,
+            None => return false
+        };
+        let is_fn = match tcx.map.find(id) {
+            Some(hir_map::NodeItem(&hir::Item {
+                node: hir::ItemFn(_, _, hir::Constness::NotConst, ..), ..
+            })) |
+            Some(hir_map::NodeImplItem(&hir::ImplItem {
+                node: hir::ImplItemKind::Method(hir::MethodSig {
+                    constness: hir::Constness::NotConst, ..
+                }, _), ..
+            })) => true,
+            _ => false
+        };
+        let generics = tcx.item_generics(def_id);
+        if !is_fn || generics.parent_types > 0 || !generics.types.is_empty() {
+            return false;
+        }
+        // `#[inline(never)]` keeps the function out too, and so do the
+        // attributes applying to the function as a whole, e.g. an explicit
+        // linkage, which the copies wouldn't have.
+        let attrs = tcx.get_attrs(def_id);
+        if attr::find_inline_attr(None, &attrs) != attr::InlineAttr::None ||
+           attr::has_function_granular_attr(&attrs) {
+            return false;
+        }
+        match (tcx.item_mir_summary(def_id), tcx.mir_map.borrow().get(&def_id)) {
+            (Some(summary), Some(mir)) => summary.can_auto_inline(&mir.borrow()),
+            _ => false
+        }
+    }
+
+    /// Compresses and writes out a MIR block, starting a new one.
+    fn flush_mir_block(&mut self, block: MirBlock<'tcx>) {
+        let compressed = flate::deflate_bytes(block.data.get_ref());
+        let seq = self.lazy_seq_ref(compressed.iter());
+        self.mir_blocks.push(seq);
+        self.mir_block = Some(MirBlock::new());
+    }
+
+    fn encode_mir_blocks(&mut self) -> LazySeq<LazySeq<u8>> {
+        let block = self.mir_block.take().unwrap();
+        if block.data.position() > 0 {
+            self.flush_mir_block(block);
+        }
+        let blocks = mem::replace(&mut self.mir_blocks, vec![]);
+        self.lazy_seq(blocks)
     }
 
     // Encodes the inherent implementations of a structure, enumeration, or trait.
@@ -745,6 +883,16 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             hir::ItemUse(..) => bug!("cannot encode info for item {:?}", item),
         };
 
+        let mir = match item.node {
+            hir::ItemConst(..) => true,
+            hir::ItemFn(_, _, constness, _, ref generics, _) => {
+                let tps_len = generics.ty_params.len();
+                let needs_inline = tps_len > 0 || attr::requests_inline(&item.attrs);
+                needs_inline || constness == hir::Constness::Const || self.auto_inline(def_id)
+            }
+            _ => false,
+        };
+
         Entry {
             kind: kind,
             visibility: item.vis.simplify(),
@@ -837,19 +985,8 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
                 }
                 _ => None,
             },
-            mir: match item.node {
-                hir::ItemConst(..) => self.encode_mir(def_id),
-                hir::ItemFn(_, _, constness, _, ref generics, _) => {
-                    let tps_len = generics.ty_params.len();
-                    let needs_inline = tps_len > 0 || attr::requests_inline(&item.attrs);
-                    if needs_inline || constness == hir::Constness::Const {
-                        self.encode_mir(def_id)
-                    } else {
-                        None
-                    }
-                }
-                _ => None,
-            },
+            mir: if mir { self.encode_mir(def_id) } else { None },
+            mir_summary: if mir { self.encode_mir_summary(def_id) } else { None },
         }
     }
 
@@ -875,6 +1012,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             predicates: None,
             ast: None,
             mir: None,
+            mir_summary: None,
         }
     }
 }
@@ -978,6 +1116,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_summary: None,
         }
     }
 }
@@ -1061,6 +1200,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_summary: None,
         }
     }
 
@@ -1090,6 +1230,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
 
             ast: None,
             mir: self.encode_mir(def_id),
+            mir_summary: self.encode_mir_summary(def_id),
         }
     }
 
@@ -1288,6 +1429,11 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
         let items = self.encode_info_for_items();
         let item_bytes = self.position() - i;
 
+        // Encode the last, partially filled, MIR block and the table of all blocks.
+        i = self.position();
+        let mir_blocks = self.encode_mir_blocks();
+        let mir_bytes = self.position() - i;
+
         i = self.position();
         let index = items.write_index(&mut self.opaque.cursor);
         let index_bytes = self.position() - i;
@@ -1320,6 +1466,8 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             codemap: codemap,
             impls: impls,
             exported_symbols: exported_symbols,
+            mir_format_version: MIR_FORMAT_VERSION,
+            mir_blocks: mir_blocks,
             index: index,
         });
 
@@ -1341,6 +1489,7 @@ impl<'a, 'tcx> EncodeContext<'a, 'tcx> {
             println!("            impl bytes: {}", impl_bytes);
             println!("    exp. symbols bytes: {}", exported_symbols_bytes);
             println!("            item bytes: {}", item_bytes);
+            println!("  last MIR block bytes: {}", mir_bytes);
             println!("           index bytes: {}", index_bytes);
             println!("            zero bytes: {}", zero_bytes);
             println!("           total bytes: {}", total_bytes);
@@ -1396,6 +1545,8 @@ pub fn encode_metadata<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
             lazy_state: LazyState::NoNode,
             type_shorthands: Default::default(),
             predicate_shorthands: Default::default(),
+            mir_block: Some(MirBlock::new()),
+            mir_blocks: vec![],
         };
 
         // Encode the rustc version string in a predictable location.
@@ -16,7 +16,7 @@ use rustc::hir::def::{self, CtorKind};
 use rustc::hir::def_id::{DefIndex, DefId};
 use rustc::middle::cstore::{DepKind, LinkagePreference, NativeLibrary};
 use rustc::middle::lang_items;
-use rustc::mir;
+use rustc::mir::summary::InlineSummary;
 use rustc::ty::{self, Ty};
 use rustc_back::PanicStrategy;
 
@@ -37,6 +37,12 @@ pub fn rustc_version() -> String {
 /// the rustc version can't be found to compare with `rustc_version()`.
 pub const METADATA_VERSION: u8 = 4;
 
+/// Version of the encoding of MIR bodies and their summaries.
+/// NB: increment this if you change the MIR data structures or how
+/// they are encoded. Crates whose MIR was encoded with a different
+/// version are treated as not having any MIR available.
+pub const MIR_FORMAT_VERSION: u32 = 15;
+
 /// Metadata header which includes `METADATA_VERSION`.
 /// To get older versions of rustc to ignore this metadata,
 /// there are 4 zero bytes at the start, which are treated
@@ -181,6 +187,8 @@ pub struct CrateRoot {
     pub codemap: LazySeq<syntax_pos::FileMap>,
     pub impls: LazySeq<TraitImpls>,
     pub exported_symbols: LazySeq<DefIndex>,
+    pub mir_format_version: u32,
+    pub mir_blocks: LazySeq<LazySeq<u8>>,
     pub index: LazySeq<index::Index>,
 }
 
@@ -215,7 +223,25 @@ pub struct Entry<'tcx> {
     pub predicates: Option<Lazy<ty::GenericPredicates<'tcx>>>,
 
     pub ast: Option<Lazy<astencode::Ast<'tcx>>>,
-    pub mir: Option<Lazy<mir::Mir<'tcx>>>,
+    pub mir: Option<MirLocation>,
+    pub mir_summary: Option<Lazy<InlineSummary>>,
+}
+
+/// Size, in bytes of uncompressed MIR, past which a MIR block
+/// is closed, compressed and written out to the metadata.
+pub const MIR_BLOCK_SIZE: usize = 64 * 1024;
+
+/// The MIR of an item, found in one of the compressed blocks of
+/// `CrateRoot::mir_blocks`. Several items share a block so that
+/// compression can take advantage of their similarities, while
+/// only the block containing a requested item has to be inflated.
+#[derive(Copy, Clone, RustcEncodable, RustcDecodable)]
+pub struct MirLocation {
+    /// Index of the block in `CrateRoot::mir_blocks`.
+    pub block: u32,
+
+    /// Position of the encoded `Mir` within the inflated block.
+    pub offset: u32,
 }
 
 #[derive(Copy, Clone, RustcEncodable, RustcDecodable)]
@@ -0,0 +1,210 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Region-erased borrow analysis, for optimizations to check that moving or
+//! merging accesses to a local can't be observed through a reference.
+//!
+//! Without regions, a borrow of a local is assumed to be usable until the
+//! local's `StorageDead`, or the end of the function if it has none. Shared
+//! borrows of types with interior mutability count as mutable ones.
+
+use rustc::mir::*;
+use rustc::mir::traversal;
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::indexed_vec::IndexVec;
+use std::cmp;
+
+/// How a local may be borrowed at some point of a function.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BorrowState {
+    /// No reference to the local can exist.
+    NotBorrowed,
+    /// Only references which can't be used to change the local may exist.
+    Shared,
+    /// References which can be used to change the local may exist.
+    Mutable,
+}
+
+pub struct BorrowAnalysis {
+    /// The state of each local on entry to each block.
+    on_entry: IndexVec<BasicBlock, IndexVec<Local, BorrowState>>,
+    /// The borrow taken by each statement, if any.
+    borrows: IndexVec<BasicBlock, Vec<Option<(Local, BorrowState)>>>,
+}
+
+impl BorrowAnalysis {
+    pub fn new<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> BorrowAnalysis {
+        let borrows = mir.basic_blocks().iter().map(|data| {
+            data.statements.iter().map(|statement| statement_borrow(tcx, mir, statement)).collect()
+        }).collect();
+        let num_locals = mir.local_decls.len();
+        let mut analysis = BorrowAnalysis {
+            on_entry: IndexVec::from_elem_n(
+                IndexVec::from_elem_n(BorrowState::NotBorrowed, num_locals),
+                mir.basic_blocks().len()),
+            borrows: borrows,
+        };
+        if !mir.address_taken().iter().any(|&taken| taken) {
+            return analysis;
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (bb, data) in traversal::reverse_postorder(mir) {
+                let mut state = analysis.on_entry[bb].clone();
+                for (index, statement) in data.statements.iter().enumerate() {
+                    analysis.apply_statement(&mut state, bb, index, statement);
+                }
+                for &succ in data.terminator().successors().iter() {
+                    for (entry, &exit) in analysis.on_entry[succ].iter_mut().zip(&state) {
+                        if exit > *entry {
+                            *entry = exit;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        analysis
+    }
+
+    fn apply_statement(&self,
+                       state: &mut IndexVec<Local, BorrowState>,
+                       bb: BasicBlock,
+                       index: usize,
+                       statement: &Statement) {
+        if let Some((local, borrow)) = self.borrows[bb][index] {
+            state[local] = cmp::max(state[local], borrow);
+        }
+        if let StatementKind::StorageDead(Lvalue::Local(local)) = statement.kind {
+            state[local] = BorrowState::NotBorrowed;
+        }
+    }
+
+    /// How `local` may be borrowed just before the statement or terminator
+    /// at `location` is executed.
+    pub fn state_at(&self, mir: &Mir, local: Local, location: Location) -> BorrowState {
+        if !mir.is_address_taken(local) {
+            return BorrowState::NotBorrowed;
+        }
+        let mut state = self.on_entry[location.block].clone();
+        let statements = &mir[location.block].statements[..location.statement_index];
+        for (index, statement) in statements.iter().enumerate() {
+            self.apply_statement(&mut state, location.block, index, statement);
+        }
+        state[local]
+    }
+
+    /// Whether the value of `local` may change after the statement at
+    /// `from` and before the statement or terminator at `to` is executed,
+    /// either directly or through a reference. Both locations must be in
+    /// the same block, `from` coming first.
+    pub fn may_change_between(&self,
+                              mir: &Mir,
+                              local: Local,
+                              from: Location,
+                              to: Location) -> bool {
+        assert!(from.block == to.block && from.statement_index < to.statement_index);
+        let statements = &mir[from.block].statements;
+        let mut borrowed = self.state_at(mir, local, from);
+        for index in from.statement_index..to.statement_index {
+            let statement = &statements[index];
+            if index > from.statement_index {
+                let mut writes = WritesLocal { local: local, found: false };
+                let location = Location { block: from.block, statement_index: index };
+                writes.visit_statement(from.block, statement, location);
+                if writes.found ||
+                   (borrowed == BorrowState::Mutable && writes_through_pointer(statement)) {
+                    return true;
+                }
+            }
+            if let Some((borrowed_local, borrow)) = self.borrows[from.block][index] {
+                if borrowed_local == local {
+                    borrowed = cmp::max(borrowed, borrow);
+                }
+            }
+        }
+        false
+    }
+}
+
+/// The local borrowed by `statement`, and how, if it takes a reference to
+/// a local or to a part of one.
+fn statement_borrow<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                              mir: &Mir<'tcx>,
+                              statement: &Statement<'tcx>)
+                              -> Option<(Local, BorrowState)> {
+    let (kind, lvalue) = match statement.kind {
+        StatementKind::Assign(_, Rvalue::Ref(_, kind, ref lvalue)) => (kind, lvalue),
+        _ => return None
+    };
+    let local = match direct_root(lvalue) {
+        Some(local) => local,
+        None => return None
+    };
+    let ty = lvalue.ty(mir, tcx).to_ty(tcx);
+    let state = match kind {
+        BorrowKind::Shared if !ty.type_contents(tcx).interior_unsafe() => BorrowState::Shared,
+        _ => BorrowState::Mutable
+    };
+    Some((local, state))
+}
+
+/// The local `lvalue` is part of, unless it's behind a pointer.
+fn direct_root(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(local) => Some(local),
+        Lvalue::Static(_) => None,
+        Lvalue::Projection(ref proj) => match proj.elem {
+            ProjectionElem::Deref => None,
+            _ => direct_root(&proj.base)
+        }
+    }
+}
+
+/// Whether `statement` may write to memory through a pointer.
+fn writes_through_pointer(statement: &Statement) -> bool {
+    match statement.kind {
+        StatementKind::Assign(_, Rvalue::InlineAsm { .. }) => true,
+        StatementKind::Assign(ref lvalue, _) |
+        StatementKind::SetDiscriminant { ref lvalue, .. } |
+        StatementKind::Poison(ref lvalue) => direct_root(lvalue).is_none(),
+        StatementKind::StorageLive(_) |
+        StatementKind::StorageDead(_) |
+        StatementKind::IncrementCounter(_) |
+        StatementKind::Nop => false,
+    }
+}
+
+/// Finds whether a statement writes to `local` directly, as a whole or in
+/// part. Taking a mutable reference doesn't count: the writes through it
+/// are what `BorrowState::Mutable` is for.
+struct WritesLocal {
+    local: Local,
+    found: bool,
+}
+
+impl<'tcx> Visitor<'tcx> for WritesLocal {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if *lvalue == Lvalue::Local(self.local) {
+            match context {
+                LvalueContext::Borrow { .. } => {}
+                LvalueContext::StorageDead => self.found = true,
+                _ => self.found |= context.is_mutating_use()
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
@@ -59,7 +59,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 }).collect();
 
                 block.and(Rvalue::InlineAsm {
-                    asm: asm.clone(),
+                    asm: Box::new(asm.clone()),
                     outputs: outputs,
                     inputs: inputs
                 })
@@ -80,19 +80,14 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             }
             ExprKind::Unary { op, arg } => {
                 let arg = unpack!(block = this.as_operand(block, arg));
-                // Check for -MIN on signed integers
+                // Check for -MIN on signed integers, as `0 - arg`, which
+                // overflows in exactly that case.
                 if this.hir.check_overflow() && op == UnOp::Neg && expr.ty.is_signed() {
-                    let bool_ty = this.hir.bool_ty();
-
-                    let minval = this.minval_literal(expr_span, expr.ty);
-                    let is_min = this.temp(bool_ty);
-
-                    this.cfg.push_assign(block, source_info, &is_min,
-                                         Rvalue::BinaryOp(BinOp::Eq, arg.clone(), minval));
-
-                    let err = ConstMathErr::Overflow(Op::Neg);
-                    block = this.assert(block, Operand::Consume(is_min), false,
-                                        AssertMessage::Math(err), expr_span);
+                    let zero = this.zero_literal(expr_span, expr.ty);
+                    let val = unpack!(block = this.checked_binary_op(block, BinOp::Sub, Op::Neg,
+                                                                     expr_span, expr.ty,
+                                                                     zero, arg));
+                    return block.and(Rvalue::Use(Operand::Consume(val)));
                 }
                 block.and(Rvalue::UnaryOp(op, arg))
             }
@@ -257,20 +252,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         let source_info = self.source_info(span);
         let bool_ty = self.hir.bool_ty();
         if self.hir.check_overflow() && op.is_checkable() && ty.is_integral() {
-            let result_tup = self.hir.tcx().intern_tup(&[ty, bool_ty]);
-            let result_value = self.temp(result_tup);
-
-            self.cfg.push_assign(block, source_info,
-                                 &result_value, Rvalue::CheckedBinaryOp(op,
-                                                                        lhs,
-                                                                        rhs));
-            let val_fld = Field::new(0);
-            let of_fld = Field::new(1);
-
-            let val = result_value.clone().field(val_fld, ty);
-            let of = result_value.field(of_fld, bool_ty);
-
-            let err = ConstMathErr::Overflow(match op {
+            let err_op = match op {
                 BinOp::Add => Op::Add,
                 BinOp::Sub => Op::Sub,
                 BinOp::Mul => Op::Mul,
@@ -279,11 +261,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 _ => {
                     bug!("MIR build_binary_op: {:?} is not checkable", op)
                 }
-            });
-
-            block = self.assert(block, Operand::Consume(of), false,
-                                AssertMessage::Math(err), span);
-
+            };
+            let val = unpack!(block = self.checked_binary_op(block, op, err_op, span, ty,
+                                                             lhs, rhs));
             block.and(Rvalue::Use(Operand::Consume(val)))
         } else {
             if ty.is_integral() && (op == BinOp::Div || op == BinOp::Rem) {
@@ -338,6 +318,28 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
         }
     }
 
+    /// Computes `lhs op rhs` with a `CheckedBinaryOp`, followed by an
+    /// assert that it didn't overflow, reported as an overflow of `err_op`.
+    /// Returns the lvalue holding the result.
+    fn checked_binary_op(&mut self, mut block: BasicBlock,
+                         op: BinOp, err_op: Op, span: Span, ty: ty::Ty<'tcx>,
+                         lhs: Operand<'tcx>, rhs: Operand<'tcx>) -> BlockAnd<Lvalue<'tcx>> {
+        let source_info = self.source_info(span);
+        let bool_ty = self.hir.bool_ty();
+        let result_tup = self.hir.tcx().intern_tup(&[ty, bool_ty]);
+        let result_value = self.temp(result_tup);
+
+        self.cfg.push_assign(block, source_info,
+                             &result_value, Rvalue::CheckedBinaryOp(op, lhs, rhs));
+        let val = result_value.clone().field(Field::new(0), ty);
+        let of = result_value.field(Field::new(1), bool_ty);
+
+        let err = ConstMathErr::Overflow(err_op);
+        block = self.assert(block, Operand::Consume(of), false,
+                            AssertMessage::Math(err), span);
+        block.and(val)
+    }
+
     // Helper to get a `-1` value of the appropriate type
     fn neg_1_literal(&mut self, span: Span, ty: ty::Ty<'tcx>) -> Operand<'tcx> {
         let literal = match ty.sty {
@@ -670,9 +670,8 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             let source_info = self.source_info(guard.span);
             let cond = unpack!(block = self.as_operand(block, guard));
             let otherwise = self.cfg.start_new_block();
-            self.cfg.terminate(block, source_info,
-                               TerminatorKind::If { cond: cond,
-                                                    targets: (arm_block, otherwise)});
+            let term = TerminatorKind::if_(self.hir.tcx(), cond, arm_block, otherwise);
+            self.cfg.terminate(block, source_info, term);
             Some(otherwise)
         } else {
             let source_info = self.source_info(candidate.span);
@@ -728,6 +727,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
             ty: var_ty.clone(),
             name: Some(name),
             source_info: Some(source_info),
+            is_const: false,
         });
         let extent = self.extent_of_innermost_scope();
         self.schedule_drop(source_info.span, extent, &Lvalue::Local(var), var_ty);
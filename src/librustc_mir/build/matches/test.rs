@@ -194,18 +194,45 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 }).collect();
                 debug!("num_enum_variants: {}, num tested variants: {}, variants: {:?}",
                        num_enum_variants, variants.iter().count(), variants);
-                self.cfg.terminate(block, source_info, TerminatorKind::Switch {
-                    discr: lvalue.clone(),
-                    adt_def: adt_def,
-                    targets: target_blocks.clone()
+                let discr_ty = adt_def.discr_ty(self.hir.tcx());
+                let discr = self.temp(discr_ty);
+                self.cfg.push_assign(block, source_info, &discr,
+                                     Rvalue::Discriminant(lvalue.clone()));
+
+                // Only the tested variants get a value of their own; the
+                // others all go to the `otherwise` block. If every variant is
+                // tested, the `otherwise` block is unreachable.
+                let mut values = Vec::with_capacity(num_enum_variants);
+                let mut targets = Vec::with_capacity(num_enum_variants + 1);
+                for (variant, &target) in adt_def.variants.iter().zip(&target_blocks) {
+                    if Some(target) != otherwise_block {
+                        values.push(ConstVal::Integral(variant.disr_val));
+                        targets.push(target);
+                    }
+                }
+                let otherwise = match otherwise_block {
+                    Some(otherwise) => otherwise,
+                    None => {
+                        let unreachable = self.cfg.start_new_block();
+                        self.cfg.terminate(unreachable, source_info,
+                                           TerminatorKind::Unreachable);
+                        unreachable
+                    }
+                };
+                targets.push(otherwise);
+                self.cfg.terminate(block, source_info, TerminatorKind::SwitchInt {
+                    discr: Operand::Consume(discr),
+                    switch_ty: discr_ty,
+                    values: values,
+                    targets: targets
                 });
                 target_blocks
             }
 
             TestKind::SwitchInt { switch_ty, ref options, indices: _ } => {
                 let (targets, term) = match switch_ty.sty {
-                    // If we're matching on boolean we can
-                    // use the If TerminatorKind instead
+                    // Booleans are only tested against `false`, with the
+                    // `true` block as the otherwise branch
                     ty::TyBool => {
                         assert!(options.len() > 0 && options.len() <= 2);
 
@@ -220,10 +247,10 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                         };
 
                         (targets,
-                         TerminatorKind::If {
-                             cond: Operand::Consume(lvalue.clone()),
-                             targets: (true_bb, else_bb)
-                         })
+                         TerminatorKind::if_(self.hir.tcx(),
+                                             Operand::Consume(lvalue.clone()),
+                                             true_bb,
+                                             else_bb))
 
                     }
                     _ => {
@@ -238,7 +265,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
 
                         (targets.clone(),
                          TerminatorKind::SwitchInt {
-                             discr: lvalue.clone(),
+                             discr: Operand::Consume(lvalue.clone()),
                              switch_ty: switch_ty,
                              values: options.clone(),
                              targets: targets
@@ -312,10 +339,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
 
                     // check the result
                     let block = self.cfg.start_new_block();
-                    self.cfg.terminate(eq_block, source_info, TerminatorKind::If {
-                        cond: Operand::Consume(eq_result),
-                        targets: (block, fail),
-                    });
+                    let term = TerminatorKind::if_(self.hir.tcx(), Operand::Consume(eq_result),
+                                                   block, fail);
+                    self.cfg.terminate(eq_block, source_info, term);
 
                     vec![block, fail]
                 } else {
@@ -357,10 +383,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 // branch based on result
                 let target_blocks: Vec<_> = vec![self.cfg.start_new_block(),
                                                  self.cfg.start_new_block()];
-                self.cfg.terminate(block, source_info, TerminatorKind::If {
-                    cond: Operand::Consume(result),
-                    targets: (target_blocks[0], target_blocks[1])
-                });
+                let term = TerminatorKind::if_(self.hir.tcx(), Operand::Consume(result),
+                                               target_blocks[0], target_blocks[1]);
+                self.cfg.terminate(block, source_info, term);
 
                 target_blocks
             }
@@ -384,10 +409,9 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
 
         // branch based on result
         let target_block = self.cfg.start_new_block();
-        self.cfg.terminate(block, source_info, TerminatorKind::If {
-            cond: Operand::Consume(result),
-            targets: (target_block, fail_block)
-        });
+        let term = TerminatorKind::if_(self.hir.tcx(), Operand::Consume(result),
+                                       target_block, fail_block);
+        self.cfg.terminate(block, source_info, term);
 
         target_block
     }
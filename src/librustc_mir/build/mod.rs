@@ -296,6 +296,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                 ty: ty,
                 source_info: None,
                 name: name,
+                is_const: false,
             });
         }
 
@@ -651,7 +651,7 @@ impl<'a, 'gcx, 'tcx> Builder<'a, 'gcx, 'tcx> {
                            TerminatorKind::Assert {
                                cond: cond,
                                expected: expected,
-                               msg: msg,
+                               msg: Box::new(msg),
                                target: success_block,
                                cleanup: cleanup
                            });
@@ -0,0 +1,520 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A fuzzer for the MIR optimizations, run with `-Z mir-fuzz=N`.
+//!
+//! It generates `N` random bodies computing a `u32`, `i32` or `bool` from
+//! constants, with arithmetic, comparisons, casts, overflow checks and
+//! branches, and runs the optimizations of the pipeline on each one, one
+//! pass after the other, as `transform::pipeline` lists them. After every pass the body must still be
+//! well-formed, and the interpreter must get the same result from it as
+//! from the body before any optimization, be it a value or a panic.
+//! Anything else is reported as an error naming the pass and the body,
+//! along with the MIR the pass produced.
+//!
+//! The bodies take no arguments, and their control flow only goes forward,
+//! so they always terminate. Divisions are by positive constants and
+//! shifts by constants smaller than 32, which leaves overflow as the only
+//! way for them to panic. Body `i` is generated from seed `i`, so a
+//! failure is reproduced by running the fuzzer again with the same `N`.
+//!
+//! The bodies stand in for the MIR of `main`, which the interpreter runs
+//! them as, so the crate needs a `main` function.
+
+use interpret::{EvalContext, EvalError, Value};
+use pretty;
+use transform;
+use transform::pipeline;
+
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::{Ty, TyCtxt};
+use rustc_const_math::{ConstInt, ConstMathErr, Op};
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax_pos::Span;
+
+use std::{i32, u32};
+
+/// Generates the bodies and runs the optimizations on them, if asked to
+/// with `-Z mir-fuzz`.
+pub fn fuzz_passes<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+    let count = match tcx.sess.opts.debugging_opts.mir_fuzz {
+        Some(count) => count,
+        None => return
+    };
+    let _ignore = tcx.dep_graph.in_ignore();
+
+    let (id, span) = match *tcx.sess.entry_fn.borrow() {
+        Some(entry) => entry,
+        None => {
+            tcx.sess.err("`-Z mir-fuzz` needs a `main` function to run the bodies as");
+            return;
+        }
+    };
+    let def_id = tcx.map.local_def_id(id);
+    for index in 0..count {
+        let mir = Generator::new(tcx, index as u64, span).body();
+        check_passes(tcx, MirSource::Fn(id), def_id, index, mir, span);
+    }
+}
+
+/// The optimizations of the pipeline the driver runs once drops are
+/// elaborated, in the same order, after a first cleanup of the body.
+fn passes() -> Vec<Box<for<'tcx> MirPass<'tcx>>> {
+    let mut passes: Vec<Box<for<'tcx> MirPass<'tcx>>> =
+        vec![box transform::simplify::SimplifyCfg::new("fuzz")];
+    passes.extend(pipeline::optimizations());
+    passes
+}
+
+/// Runs the passes on `mir`, comparing the result of interpreting it after
+/// each one with the result before the first.
+fn check_passes<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          src: MirSource,
+                          def_id: DefId,
+                          index: usize,
+                          mir: Mir<'tcx>,
+                          span: Span) {
+    let (mut mir, expected) = EvalContext::new(tcx, false).call_body(def_id, mir, vec![], span);
+    if let Err(ref err) = expected {
+        if err.is_unsupported() {
+            // The generator produced something the interpreter can't run.
+            span_bug!(span, "MIR fuzzing: body #{} can't be interpreted: {}",
+                      index, err.description(tcx));
+        }
+    }
+
+    for mut pass in passes() {
+        pass.run_pass(tcx, src, &mut mir);
+
+        let error = match well_formed(tcx, &mir) {
+            Err(msg) => Some(msg),
+            Ok(()) => {
+                let (ran, result) = EvalContext::new(tcx, false)
+                    .call_body(def_id, mir, vec![], span);
+                mir = ran;
+                if same_result(tcx, &expected, &result) {
+                    None
+                } else {
+                    Some(format!("it returns {} instead of {}",
+                                 describe(tcx, &result), describe(tcx, &expected)))
+                }
+            }
+        };
+
+        if let Some(msg) = error {
+            let mut dump = vec![];
+            pretty::write_mir_fn(tcx, src, &mir, &mut dump).unwrap();
+            tcx.sess.struct_span_err(span, &format!("MIR fuzzing: body #{} is wrong after `{}`: {}",
+                                                    index, pass.name(), msg))
+                .note(&String::from_utf8_lossy(&dump))
+                .emit();
+            return;
+        }
+    }
+}
+
+fn same_result<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                         a: &Result<Value<'tcx>, EvalError>,
+                         b: &Result<Value<'tcx>, EvalError>)
+                         -> bool {
+    match (a, b) {
+        (&Ok(ref a), &Ok(ref b)) => a == b,
+        (&Err(ref a), &Err(ref b)) => a.description(tcx) == b.description(tcx),
+        _ => false
+    }
+}
+
+fn describe<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, result: &Result<Value<'tcx>, EvalError>)
+                      -> String {
+    match *result {
+        Ok(ref value) => format!("`{:?}`", value),
+        Err(ref err) => format!("an error ({})", err.description(tcx))
+    }
+}
+
+/// Checks that every block has a terminator going to existing blocks, that
+/// only existing locals are used, and that assignments are well-typed.
+fn well_formed<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> Result<(), String> {
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        let terminator = match data.terminator {
+            Some(ref terminator) => terminator,
+            None => return Err(format!("{:?} has no terminator", bb))
+        };
+        for target in terminator.successors().iter() {
+            if target.index() >= mir.basic_blocks().len() {
+                return Err(format!("{:?} jumps to the missing block {:?}", bb, target));
+            }
+        }
+    }
+
+    let mut locals = LocalsInRange { count: mir.local_decls.len(), missing: None };
+    locals.visit_mir(mir);
+    if let Some(local) = locals.missing {
+        return Err(format!("{:?} is used but not declared", local));
+    }
+
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        for statement in &data.statements {
+            if let StatementKind::Assign(ref lvalue, ref rvalue) = statement.kind {
+                let lvalue_ty = lvalue.ty(mir, tcx).to_ty(tcx);
+                if let Some(rvalue_ty) = rvalue.ty(mir, tcx) {
+                    if lvalue_ty != rvalue_ty {
+                        return Err(format!("{:?} assigns a `{}` to `{:?}`, a `{}`",
+                                           bb, rvalue_ty, lvalue, lvalue_ty));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+struct LocalsInRange {
+    count: usize,
+    missing: Option<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalsInRange {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Local(local) = *lvalue {
+            if local.index() >= self.count {
+                self.missing = Some(local);
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// A xorshift generator, good enough to pick MIR at random and the same
+/// on every host.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // The state must not be zero.
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+}
+
+/// The types the bodies compute with.
+#[derive(Copy, Clone, PartialEq)]
+enum Kind {
+    U32,
+    I32,
+    Bool,
+}
+
+const KINDS: [Kind; 3] = [Kind::U32, Kind::I32, Kind::Bool];
+
+struct Generator<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    rng: Rng,
+    span: Span,
+    local_decls: IndexVec<Local, LocalDecl<'tcx>>,
+    /// The locals computed with, i.e. all of them but the return pointer
+    /// and the results of overflow checks.
+    locals: Vec<(Local, Kind)>,
+}
+
+impl<'a, 'tcx> Generator<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, seed: u64, span: Span) -> Generator<'a, 'tcx> {
+        Generator {
+            tcx: tcx,
+            rng: Rng::new(seed),
+            span: span,
+            local_decls: IndexVec::new(),
+            locals: vec![],
+        }
+    }
+
+    fn body(mut self) -> Mir<'tcx> {
+        let return_kind = KINDS[self.rng.below(KINDS.len())];
+        let return_ty = self.ty(return_kind);
+        self.local_decls.push(LocalDecl::new_return_pointer(return_ty));
+        for &kind in &KINDS {
+            for _ in 0..1 + self.rng.below(3) {
+                let ty = self.ty(kind);
+                let local = self.local_decls.push(LocalDecl::new_temp(ty));
+                self.locals.push((local, kind));
+            }
+        }
+
+        let count = 2 + self.rng.below(6);
+        let mut blocks = IndexVec::new();
+        for index in 0..count {
+            let mut statements = vec![];
+            if index == 0 {
+                // Every local starts out with a constant, so nothing is
+                // read before it is written.
+                for (local, kind) in self.locals.clone() {
+                    let value = self.constant(kind);
+                    statements.push(self.assign(Lvalue::Local(local), Rvalue::Use(value)));
+                }
+            }
+            for _ in 0..self.rng.below(5) {
+                let statement = self.assignment();
+                statements.push(statement);
+            }
+
+            let kind = if index + 1 == count {
+                let value = self.operand(return_kind);
+                statements.push(self.assign(Lvalue::Local(RETURN_POINTER), Rvalue::Use(value)));
+                TerminatorKind::Return
+            } else {
+                self.terminator(index + 1, count, &mut statements)
+            };
+            blocks.push(BasicBlockData {
+                statements: statements,
+                terminator: Some(Terminator { source_info: self.source_info(), kind: kind }),
+                is_cleanup: false,
+            });
+        }
+
+        let scopes = IndexVec::from_elem_n(VisibilityScopeData {
+            span: self.span,
+            parent_scope: None,
+        }, 1);
+        Mir::new(blocks, scopes, IndexVec::new(), return_ty, self.local_decls, 0, vec![], self.span)
+    }
+
+    /// The terminator of a block, which only goes to the blocks in
+    /// `first..count`. It may add statements to the block.
+    fn terminator(&mut self, first: usize, count: usize, statements: &mut Vec<Statement<'tcx>>)
+                  -> TerminatorKind<'tcx> {
+        match self.rng.below(4) {
+            0 => TerminatorKind::Goto { target: self.target(first, count) },
+            1 => {
+                let (t, f) = (self.target(first, count), self.target(first, count));
+                let cond = self.operand(Kind::Bool);
+                TerminatorKind::if_(self.tcx, cond, t, f)
+            }
+            2 => {
+                let kind = if self.rng.one_in(2) { Kind::U32 } else { Kind::I32 };
+                let mut values = vec![];
+                for _ in 0..1 + self.rng.below(4) {
+                    if let Operand::Constant(Constant {
+                        literal: Literal::Value { value }, ..
+                    }) = self.constant(kind) {
+                        if !values.contains(&value) {
+                            values.push(value);
+                        }
+                    }
+                }
+                let mut targets = vec![];
+                for _ in 0..values.len() + 1 {
+                    targets.push(self.target(first, count));
+                }
+                TerminatorKind::SwitchInt {
+                    discr: self.operand(kind),
+                    switch_ty: self.ty(kind),
+                    values: values,
+                    targets: targets,
+                }
+            }
+            _ => {
+                // An overflow check, the way MIR construction builds them.
+                let kind = if self.rng.one_in(2) { Kind::U32 } else { Kind::I32 };
+                let (bin_op, op) = match self.rng.below(3) {
+                    0 => (BinOp::Add, Op::Add),
+                    1 => (BinOp::Sub, Op::Sub),
+                    _ => (BinOp::Mul, Op::Mul),
+                };
+                let ty = self.ty(kind);
+                let result_ty = self.tcx.intern_tup(&[ty, self.tcx.types.bool]);
+                let result = self.local_decls.push(LocalDecl::new_temp(result_ty));
+                let result = Lvalue::Local(result);
+                let (lhs, rhs) = (self.operand(kind), self.operand(kind));
+                statements.push(self.assign(result.clone(),
+                                            Rvalue::CheckedBinaryOp(bin_op, lhs, rhs)));
+                let dest = self.local(kind);
+                statements.push(self.assign(dest, Rvalue::Use(Operand::Consume(
+                    result.clone().field(Field::new(0), ty)))));
+                TerminatorKind::Assert {
+                    cond: Operand::Consume(result.field(Field::new(1), self.tcx.types.bool)),
+                    expected: false,
+                    msg: Box::new(AssertMessage::Math(ConstMathErr::Overflow(op))),
+                    target: self.target(first, count),
+                    cleanup: None,
+                }
+            }
+        }
+    }
+
+    /// A block in `first..count`.
+    fn target(&mut self, first: usize, count: usize) -> BasicBlock {
+        BasicBlock::new(first + self.rng.below(count - first))
+    }
+
+    /// An assignment of a random value to a random local.
+    fn assignment(&mut self) -> Statement<'tcx> {
+        let kind = KINDS[self.rng.below(KINDS.len())];
+        let rvalue = match kind {
+            Kind::Bool => self.bool_rvalue(),
+            _ => self.int_rvalue(kind)
+        };
+        let dest = self.local(kind);
+        self.assign(dest, rvalue)
+    }
+
+    fn int_rvalue(&mut self, kind: Kind) -> Rvalue<'tcx> {
+        match self.rng.below(7) {
+            0 => Rvalue::Use(self.operand(kind)),
+            1 => {
+                let op = match self.rng.below(6) {
+                    0 => BinOp::Add,
+                    1 => BinOp::Sub,
+                    2 => BinOp::Mul,
+                    3 => BinOp::BitAnd,
+                    4 => BinOp::BitOr,
+                    _ => BinOp::BitXor,
+                };
+                Rvalue::BinaryOp(op, self.operand(kind), self.operand(kind))
+            }
+            2 => {
+                // Only by positive constants, which can't overflow.
+                let op = if self.rng.one_in(2) { BinOp::Div } else { BinOp::Rem };
+                let divisor = 1 + self.rng.below(100) as u32;
+                let divisor = self.int_constant(kind, divisor);
+                Rvalue::BinaryOp(op, self.operand(kind), divisor)
+            }
+            3 => {
+                let op = if self.rng.one_in(2) { BinOp::Shl } else { BinOp::Shr };
+                let amount = self.rng.below(32) as u32;
+                let amount = self.int_constant(Kind::U32, amount);
+                Rvalue::BinaryOp(op, self.operand(kind), amount)
+            }
+            4 if kind == Kind::I32 => Rvalue::UnaryOp(UnOp::Neg, self.operand(kind)),
+            4 => Rvalue::UnaryOp(UnOp::Not, self.operand(kind)),
+            5 => {
+                let from = KINDS[self.rng.below(KINDS.len())];
+                Rvalue::Cast(CastKind::Misc, self.operand(from), self.ty(kind))
+            }
+            _ => Rvalue::UnaryOp(UnOp::Not, self.operand(kind)),
+        }
+    }
+
+    fn bool_rvalue(&mut self) -> Rvalue<'tcx> {
+        match self.rng.below(4) {
+            0 => Rvalue::Use(self.operand(Kind::Bool)),
+            1 => Rvalue::UnaryOp(UnOp::Not, self.operand(Kind::Bool)),
+            2 => {
+                let op = match self.rng.below(3) {
+                    0 => BinOp::BitAnd,
+                    1 => BinOp::BitOr,
+                    _ => BinOp::BitXor,
+                };
+                Rvalue::BinaryOp(op, self.operand(Kind::Bool), self.operand(Kind::Bool))
+            }
+            _ => {
+                let kind = if self.rng.one_in(2) { Kind::U32 } else { Kind::I32 };
+                let op = match self.rng.below(6) {
+                    0 => BinOp::Eq,
+                    1 => BinOp::Ne,
+                    2 => BinOp::Lt,
+                    3 => BinOp::Le,
+                    4 => BinOp::Gt,
+                    _ => BinOp::Ge,
+                };
+                Rvalue::BinaryOp(op, self.operand(kind), self.operand(kind))
+            }
+        }
+    }
+
+    /// A local of `kind`, or now and then a constant.
+    fn operand(&mut self, kind: Kind) -> Operand<'tcx> {
+        if self.rng.one_in(4) {
+            self.constant(kind)
+        } else {
+            Operand::Consume(self.local(kind))
+        }
+    }
+
+    fn local(&mut self, kind: Kind) -> Lvalue<'tcx> {
+        let candidates: Vec<_> = self.locals.iter().filter(|&&(_, k)| k == kind).collect();
+        let (local, _) = *candidates[self.rng.below(candidates.len())];
+        Lvalue::Local(local)
+    }
+
+    /// A constant of `kind`, favoring the values at the edges of the
+    /// range of its type.
+    fn constant(&mut self, kind: Kind) -> Operand<'tcx> {
+        let bits = match self.rng.below(6) {
+            0 => 0,
+            1 => 1,
+            2 => u32::MAX,
+            3 => i32::MAX as u32,
+            4 => i32::MIN as u32,
+            _ => self.rng.next() as u32,
+        };
+        match kind {
+            Kind::Bool => self.value(ConstVal::Bool(bits & 1 == 1), kind),
+            _ => self.int_constant(kind, bits)
+        }
+    }
+
+    fn int_constant(&mut self, kind: Kind, bits: u32) -> Operand<'tcx> {
+        let value = match kind {
+            Kind::U32 => ConstInt::U32(bits),
+            Kind::I32 => ConstInt::I32(bits as i32),
+            Kind::Bool => bug!("int_constant: not an integer")
+        };
+        self.value(ConstVal::Integral(value), kind)
+    }
+
+    fn value(&mut self, value: ConstVal, kind: Kind) -> Operand<'tcx> {
+        Operand::Constant(Constant {
+            span: self.span,
+            ty: self.ty(kind),
+            literal: Literal::Value { value: value }
+        })
+    }
+
+    fn ty(&self, kind: Kind) -> Ty<'tcx> {
+        match kind {
+            Kind::U32 => self.tcx.types.u32,
+            Kind::I32 => self.tcx.types.i32,
+            Kind::Bool => self.tcx.types.bool,
+        }
+    }
+
+    fn assign(&self, lvalue: Lvalue<'tcx>, rvalue: Rvalue<'tcx>) -> Statement<'tcx> {
+        Statement {
+            source_info: self.source_info(),
+            kind: StatementKind::Assign(lvalue, rvalue),
+        }
+    }
+
+    fn source_info(&self) -> SourceInfo {
+        SourceInfo { span: self.span, scope: ARGUMENT_VISIBILITY_SCOPE }
+    }
+}
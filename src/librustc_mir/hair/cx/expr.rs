@@ -20,6 +20,7 @@ use rustc::middle::const_val::ConstVal;
 use rustc_const_eval as const_eval;
 use rustc::ty::{self, AdtKind, VariantDef, Ty};
 use rustc::ty::cast::CastKind as TyCastKind;
+use transform::simplify_try::{try_call, TryCall};
 use rustc::hir;
 use syntax::ptr::P;
 
@@ -293,10 +294,30 @@ fn make_mirror_unadjusted<'a, 'gcx, 'tcx>(cx: &mut Cx<'a, 'gcx, 'tcx>,
                         base: None
                     }
                 } else {
-                    ExprKind::Call {
-                        ty: cx.tcx.tables().node_id_to_type(fun.id),
-                        fun: fun.to_ref(),
-                        args: args.to_ref(),
+                    let fun_ty = cx.tcx.tables().node_id_to_type(fun.id);
+                    // The calls left by the desugaring of `?` on a `Result` are
+                    // built as the moves or variants they amount to.
+                    let try_call = match fun_ty.sty {
+                        ty::TyFnDef(callee, substs, _) => try_call(cx.tcx, callee, substs),
+                        _ => None
+                    };
+                    match try_call {
+                        Some(TryCall::Identity) => ExprKind::Use { source: args[0].to_ref() },
+                        Some(TryCall::Variant(adt_def, index, substs)) => ExprKind::Adt {
+                            adt_def: adt_def,
+                            substs: substs,
+                            variant_index: index,
+                            fields: vec![FieldExprRef {
+                                name: Field::new(0),
+                                expr: args[0].to_ref()
+                            }],
+                            base: None
+                        },
+                        None => ExprKind::Call {
+                            ty: fun_ty,
+                            fun: fun.to_ref(),
+                            args: args.to_ref(),
+                        }
                     }
                 }
             }
@@ -0,0 +1,253 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An HTML rendering of MIR, written by `-Z dump-mir` when
+//! `-Z dump-mir-html` is also passed.
+//!
+//! Each item gets a single page, with one tab per dump of its MIR or of
+//! its promoted constants. The first dump of an item in a session creates
+//! the page, and the later ones are appended to it. Locals and blocks
+//! link to their declarations, and hovering over a statement shows the
+//! source it was built from.
+
+use dot;
+use rustc::mir::*;
+use rustc::mir::transform::MirSource;
+use rustc::ty::TyCtxt;
+use rustc_data_structures::indexed_vec::Idx;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use syntax_pos::Span;
+
+use pretty::write_mir_sig;
+
+const INDENT: &'static str = "    ";
+
+const STYLE: &'static str = r#"
+body { font-family: sans-serif; }
+nav button { font-family: monospace; margin: 0 2px 2px 0; }
+nav button.current { font-weight: bold; }
+pre { line-height: 1.4; }
+a { color: inherit; }
+span[title] { cursor: help; }
+span[title]:hover { background: #ffc; }
+:target { background: #cef; }
+.comment { color: #888; }
+"#;
+
+// The sections are appended to the page as passes run, so the tabs are only
+// built once the whole page has been loaded.
+const SCRIPT: &'static str = r#"
+document.addEventListener("DOMContentLoaded", function() {
+    var tabs = document.getElementById("tabs");
+    var sections = document.getElementsByTagName("section");
+    function show(index) {
+        for (var i = 0; i < sections.length; i++) {
+            sections[i].style.display = i == index ? "" : "none";
+            tabs.children[i].className = i == index ? "current" : "";
+        }
+    }
+    function showTarget() {
+        var target = document.getElementById(location.hash.slice(1));
+        for (var i = 0; target && i < sections.length; i++) {
+            if (sections[i].contains(target)) {
+                show(i);
+            }
+        }
+    }
+    for (var i = 0; i < sections.length; i++) {
+        var button = document.createElement("button");
+        button.textContent = sections[i].getAttribute("data-title");
+        button.onclick = show.bind(null, i);
+        tabs.appendChild(button);
+    }
+    window.addEventListener("hashchange", showTarget);
+    show(sections.length - 1);
+    showTarget();
+});
+"#;
+
+/// Adds `mir`, the body of `src`, as a tab named `title` to the page at
+/// `path`, creating the page if this is the first tab of the session.
+pub fn write_mir_html_tab<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                    src: MirSource,
+                                    title: &str,
+                                    mir: &Mir<'tcx>,
+                                    path: &Path)
+                                    -> io::Result<()> {
+    let tab = {
+        let mut dumps = tcx.sess.mir_html_dumps.borrow_mut();
+        let tabs = dumps.entry(path.to_path_buf()).or_insert(0);
+        *tabs += 1;
+        *tabs - 1
+    };
+    let mut file = if tab == 0 {
+        let mut file = fs::File::create(path)?;
+        let item = tcx.node_path_str(src.item_id());
+        writeln!(file, "<!DOCTYPE html>")?;
+        writeln!(file, "<html><head><meta charset=\"utf-8\">")?;
+        writeln!(file, "<title>MIR for {}</title>", dot::escape_html(&item))?;
+        writeln!(file, "<style>{}</style>", STYLE)?;
+        writeln!(file, "<script>{}</script>", SCRIPT)?;
+        writeln!(file, "</head><body>")?;
+        writeln!(file, "<h1>MIR for <code>{}</code></h1>", dot::escape_html(&item))?;
+        writeln!(file, "<nav id=\"tabs\"></nav>")?;
+        file
+    } else {
+        fs::OpenOptions::new().append(true).open(path)?
+    };
+    let mut html = Vec::new();
+    write_mir_fn_html(tcx, src, mir, tab, &mut html)?;
+    writeln!(file, "<section data-title=\"{}\">", dot::escape_html(title))?;
+    file.write_all(&html)?;
+    writeln!(file, "</section>")
+}
+
+fn write_mir_fn_html<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               src: MirSource,
+                               mir: &Mir<'tcx>,
+                               tab: usize,
+                               w: &mut Write)
+                               -> io::Result<()> {
+    let html = Html { tcx: tcx, mir: mir, tab: tab };
+
+    let mut sig = Vec::new();
+    write_mir_sig(tcx, src, mir, &mut sig)?;
+    writeln!(w, "<pre>{} {{", html.linkify(&String::from_utf8_lossy(&sig)))?;
+
+    for (local, decl) in mir.local_decls.iter_enumerated() {
+        let mut_str = if decl.mutability == Mutability::Mut { "mut " } else { "" };
+        let comment = match (decl.name, decl.source_info) {
+            (Some(name), Some(source_info)) => {
+                format!("\"{}\" in scope {} at {}",
+                        name, source_info.scope.index(), html.location(source_info.span))
+            }
+            _ if local == RETURN_POINTER => "return pointer".to_string(),
+            _ => format!("{:?}", mir.local_kind(local)).to_lowercase(),
+        };
+        writeln!(w, "{}<span id=\"{}\">let {}{:?}: {};</span> {}",
+                 INDENT, html.local_id(local), mut_str, local,
+                 dot::escape_html(&decl.ty.to_string()), html.comment(&comment))?;
+    }
+
+    for (block, data) in mir.basic_blocks().iter_enumerated() {
+        let cleanup = if data.is_cleanup { " (cleanup)" } else { "" };
+        writeln!(w, "")?;
+        writeln!(w, "{}<span id=\"{}\">{:?}</span>{}: {{",
+                 INDENT, html.block_id(block), block, cleanup)?;
+        for statement in &data.statements {
+            html.write_line(w, &format!("{:?}", statement), statement.source_info)?;
+        }
+
+        let terminator = data.terminator();
+        let mut head = String::new();
+        terminator.kind.fmt_head(&mut head).unwrap();
+        let mut text = html.linkify(&head);
+        let successors = terminator.successors();
+        let labels = terminator.kind.fmt_successor_labels();
+        if !successors.is_empty() {
+            let targets: Vec<_> = successors.iter().zip(&labels).map(|(&target, label)| {
+                let link = html.block_link(target);
+                if successors.len() == 1 { link } else { format!("{}: {}", label, link) }
+            }).collect();
+            if targets.len() == 1 {
+                text.push_str(&format!(" -> {}", targets[0]));
+            } else {
+                text.push_str(&format!(" -> [{}]", targets.join(", ")));
+            }
+        }
+        html.write_html_line(w, &text, terminator.source_info)?;
+        writeln!(w, "{}}}", INDENT)?;
+    }
+    writeln!(w, "}}</pre>")
+}
+
+struct Html<'b, 'a: 'b, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'b Mir<'tcx>,
+    tab: usize,
+}
+
+impl<'b, 'a, 'tcx> Html<'b, 'a, 'tcx> {
+    fn local_id(&self, local: Local) -> String {
+        format!("t{}-{:?}", self.tab, local)
+    }
+
+    fn block_id(&self, block: BasicBlock) -> String {
+        format!("t{}-{:?}", self.tab, block)
+    }
+
+    fn block_link(&self, block: BasicBlock) -> String {
+        format!("<a href=\"#{}\">{:?}</a>", self.block_id(block), block)
+    }
+
+    fn location(&self, span: Span) -> String {
+        self.tcx.sess.codemap().span_to_string(span)
+    }
+
+    fn comment(&self, text: &str) -> String {
+        format!("<span class=\"comment\">// {}</span>", dot::escape_html(text))
+    }
+
+    fn write_line(&self, w: &mut Write, text: &str, source_info: SourceInfo) -> io::Result<()> {
+        self.write_html_line(w, &self.linkify(text), source_info)
+    }
+
+    /// Writes a statement or terminator, with the source of `source_info`
+    /// shown when hovering over it.
+    fn write_html_line(&self,
+                       w: &mut Write,
+                       html: &str,
+                       source_info: SourceInfo)
+                       -> io::Result<()> {
+        let snippet = self.tcx.sess.codemap().span_to_snippet(source_info.span)
+                                             .unwrap_or(String::new());
+        let comment = format!("scope {} at {}",
+                              source_info.scope.index(), self.location(source_info.span));
+        writeln!(w, "{0}{0}<span title=\"{1}\">{2};</span> {3}",
+                 INDENT, dot::escape_html(&snippet), html, self.comment(&comment))
+    }
+
+    /// Escapes `text`, the debug output of some MIR, and turns the locals
+    /// in it into links to their declarations.
+    fn linkify(&self, text: &str) -> String {
+        let text = dot::escape_html(text);
+        let bytes = text.as_bytes();
+        let is_ident = |b: u8| b == b'_' || b == b':' || (b as char).is_alphanumeric();
+        let mut html = String::with_capacity(text.len());
+        let mut start = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] != b'_' || (i > 0 && is_ident(bytes[i - 1])) {
+                i += 1;
+                continue;
+            }
+            let mut end = i + 1;
+            while end < bytes.len() && (bytes[end] as char).is_digit(10) {
+                end += 1;
+            }
+            if end == i + 1 || (end < bytes.len() && is_ident(bytes[end])) {
+                i = end;
+                continue;
+            }
+            let index: usize = text[i + 1..end].parse().unwrap();
+            if index < self.mir.local_decls.len() {
+                let local = Local::new(index);
+                html.push_str(&text[start..i]);
+                html.push_str(&format!("<a href=\"#{}\">{:?}</a>", self.local_id(local), local));
+                start = end;
+            }
+            i = end;
+        }
+        html.push_str(&text[start..]);
+        html
+    }
+}
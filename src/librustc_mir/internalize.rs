@@ -0,0 +1,154 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Finds private functions which no optimized MIR refers to anymore.
+//!
+//! Every non-generic function is a root for trans, so a private function
+//! whose every use was optimized away (e.g. inlined into its callers)
+//! would still be translated, only for the linker to maybe drop it
+//! again. Using the final MIR call graph we can tell trans to skip it.
+//! A function is only considered when nothing outside of MIR can refer
+//! to it: it must not be reachable from other crates, a lang item, the
+//! entry point, a registrar, a trait method (which vtables and drop
+//! glue refer to) or carry an extern indicator.
+
+use rustc::dep_graph::DepNode;
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::hir::map as hir_map;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::Location;
+use rustc::mir::visit::Visitor;
+use rustc::ty::TyCtxt;
+use rustc::util::nodemap::{DefIdSet, NodeSet};
+use syntax::abi::Abi;
+use syntax::attr;
+
+/// Returns the local functions which trans doesn't need to translate,
+/// because no MIR that is itself translated refers to them.
+pub fn find_unreferenced_fns<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                       reachable: &NodeSet)
+                                       -> NodeSet {
+    let _task = tcx.dep_graph.in_task(DepNode::Reachability);
+
+    let def_ids = tcx.mir_map.borrow().keys();
+    let candidates: DefIdSet = def_ids.iter()
+        .cloned()
+        .filter(|&def_id| is_candidate(tcx, def_id, reachable))
+        .collect();
+
+    // Anything which isn't a candidate is translated regardless, so
+    // its MIR keeps the candidates it refers to alive.
+    let mut worklist: Vec<DefId> = def_ids.iter()
+        .cloned()
+        .filter(|def_id| def_id.is_local() && !candidates.contains(def_id))
+        .collect();
+    let mut live = DefIdSet();
+
+    while let Some(def_id) = worklist.pop() {
+        let mir = match tcx.mir_map.borrow().get(&def_id) {
+            Some(&mir) => mir,
+            None => continue,
+        };
+        let mir = mir.borrow();
+
+        let mut collector = ReferenceCollector {
+            candidates: &candidates,
+            live: &mut live,
+            worklist: &mut worklist,
+        };
+        collector.visit_mir(&mir);
+        for promoted in &mir.promoted {
+            collector.visit_mir(promoted);
+        }
+    }
+
+    candidates.iter()
+        .filter(|def_id| !live.contains(def_id))
+        .map(|&def_id| {
+            debug!("find_unreferenced_fns: {} is never referenced",
+                   tcx.item_path_str(def_id));
+            tcx.map.as_local_node_id(def_id).unwrap()
+        })
+        .collect()
+}
+
+/// Whether only MIR could refer to the given item, and trans would
+/// otherwise translate it unconditionally.
+fn is_candidate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          def_id: DefId,
+                          reachable: &NodeSet)
+                          -> bool {
+    let id = match tcx.map.as_local_node_id(def_id) {
+        Some(id) => id,
+        None => return false,
+    };
+
+    if reachable.contains(&id) ||
+       tcx.lang_items.items().iter().any(|item| *item == Some(def_id)) ||
+       tcx.sess.entry_fn.borrow().map(|(entry, _)| entry) == Some(id) ||
+       tcx.sess.plugin_registrar_fn.get() == Some(id) ||
+       tcx.sess.derive_registrar_fn.get() == Some(id) {
+        return false;
+    }
+
+    let generics = tcx.item_generics(def_id);
+    if generics.parent_types > 0 || !generics.types.is_empty() {
+        // Only translated when instantiated anyway.
+        return false;
+    }
+
+    let attrs = match tcx.map.get(id) {
+        hir_map::NodeItem(&hir::Item { node: hir::ItemFn(_, _, _, abi, ..), ref attrs, .. }) => {
+            if abi != Abi::Rust {
+                return false;
+            }
+            attrs
+        }
+        hir_map::NodeImplItem(&hir::ImplItem {
+            node: hir::ImplItemKind::Method(..), ref attrs, ..
+        }) => {
+            let impl_def_id = tcx.map.get_parent_did(id);
+            if tcx.impl_trait_ref(impl_def_id).is_some() {
+                return false;
+            }
+            attrs
+        }
+        _ => return false,
+    };
+
+    !attr::contains_extern_indicator(tcx.sess.diagnostic(), attrs)
+}
+
+struct ReferenceCollector<'b> {
+    candidates: &'b DefIdSet,
+    live: &'b mut DefIdSet,
+    worklist: &'b mut Vec<DefId>,
+}
+
+impl<'b> ReferenceCollector<'b> {
+    fn mark_live(&mut self, def_id: DefId) {
+        if self.candidates.contains(&def_id) && self.live.insert(def_id) {
+            self.worklist.push(def_id);
+        }
+    }
+}
+
+impl<'b, 'tcx> Visitor<'tcx> for ReferenceCollector<'b> {
+    fn visit_def_id(&mut self, def_id: &DefId, _: Location) {
+        self.mark_live(*def_id);
+    }
+
+    fn visit_const_val(&mut self, const_val: &ConstVal, _: Location) {
+        if let ConstVal::Function(def_id) = *const_val {
+            self.mark_live(def_id);
+        }
+    }
+}
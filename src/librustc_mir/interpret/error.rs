@@ -0,0 +1,116 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc::hir::def_id::DefId;
+use rustc::ty::TyCtxt;
+use rustc_const_eval::{ConstEvalErr, ErrKind};
+use rustc_const_math::ConstMathErr;
+use syntax_pos::Span;
+
+pub type EvalResult<T> = Result<T, EvalErrorKind>;
+
+#[derive(Clone, Debug)]
+pub struct EvalError {
+    pub span: Span,
+    pub kind: EvalErrorKind,
+    /// The calls the error happened inside of, innermost first, as the
+    /// called function and the span of the call.
+    pub backtrace: Vec<(DefId, Span)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum EvalErrorKind {
+    /// Overflow, division by zero and such.
+    Math(ConstMathErr),
+    /// A cast rejected by `rustc_const_eval::cast_const`.
+    Cast(ErrKind),
+    IndexOutOfBounds { len: u64, index: u64 },
+    /// Using the value of uninitialized memory.
+    ReadUndef,
+    /// Accessing the storage of a local after it was freed.
+    DanglingPointer,
+    /// Writing to the memory of a constant or an immutable static.
+    ModifiedConstant,
+    /// Accessing the fields of an enum variant other than the current one.
+    InvalidVariant,
+    /// Reaching an `Unreachable` terminator.
+    Unreachable,
+    StepLimitReached,
+    /// Calling a function which never returns, e.g. to panic.
+    Diverged(DefId),
+    NonConstFn(DefId),
+    NoMirFor(DefId),
+    Unsupported(&'static str),
+    /// The evaluation of a constant used by this one failed.
+    ReferencedConstant(Box<EvalError>),
+}
+
+impl From<ConstMathErr> for EvalErrorKind {
+    fn from(err: ConstMathErr) -> EvalErrorKind {
+        EvalErrorKind::Math(err)
+    }
+}
+
+impl EvalError {
+    pub fn new(span: Span, kind: EvalErrorKind) -> EvalError {
+        EvalError {
+            span: span,
+            kind: kind,
+            backtrace: vec![],
+        }
+    }
+
+    /// Whether the evaluation failed because the interpreter doesn't
+    /// support some operation, rather than because of the program.
+    pub fn is_unsupported(&self) -> bool {
+        match self.kind {
+            EvalErrorKind::NonConstFn(_) |
+            EvalErrorKind::NoMirFor(_) |
+            EvalErrorKind::Unsupported(_) |
+            EvalErrorKind::Cast(ErrKind::UnimplementedConstVal(_)) => true,
+            EvalErrorKind::ReferencedConstant(ref err) => err.is_unsupported(),
+            _ => false
+        }
+    }
+
+    pub fn description(&self, tcx: TyCtxt) -> String {
+        match self.kind {
+            EvalErrorKind::Math(ref err) => err.description().to_string(),
+            EvalErrorKind::Cast(ref kind) => {
+                let err = ConstEvalErr { span: self.span, kind: kind.clone() };
+                err.description().into_oneline().into_owned()
+            }
+            EvalErrorKind::IndexOutOfBounds { len, index } => {
+                format!("index out of bounds: the len is {} but the index is {}", len, index)
+            }
+            EvalErrorKind::ReadUndef => "use of uninitialized memory".to_string(),
+            EvalErrorKind::DanglingPointer => "use of a dangling pointer".to_string(),
+            EvalErrorKind::ModifiedConstant => "modification of constant memory".to_string(),
+            EvalErrorKind::InvalidVariant => "access to an inactive enum variant".to_string(),
+            EvalErrorKind::Unreachable => "entered unreachable code".to_string(),
+            EvalErrorKind::StepLimitReached => "constant evaluation exceeded limit".to_string(),
+            EvalErrorKind::Diverged(def_id) => {
+                format!("call to diverging fn `{}`", tcx.item_path_str(def_id))
+            }
+            EvalErrorKind::NonConstFn(def_id) => {
+                format!("call to non-const fn `{}`", tcx.item_path_str(def_id))
+            }
+            EvalErrorKind::NoMirFor(def_id) => {
+                format!("no MIR available for `{}`", tcx.item_path_str(def_id))
+            }
+            EvalErrorKind::Unsupported(what) => {
+                format!("unimplemented constant expression: {}", what)
+            }
+            EvalErrorKind::ReferencedConstant(_) => {
+                "could not evaluate referenced constant".to_string()
+            }
+        }
+    }
+}
@@ -0,0 +1,825 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc::hir;
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::hir::map as hir_map;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::traits::{self, Reveal, SelectionContext};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::subst::{Subst, Substs};
+use rustc::util::nodemap::{DefIdMap, FxHashMap};
+use rustc_const_eval::cast_const;
+use rustc_const_math::{ConstInt, ConstUsize};
+use rustc_data_structures::control_flow_graph::dominators::Dominators;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax::abi::Abi;
+use syntax_pos::{Span, DUMMY_SP};
+
+use std::cell::Ref;
+use std::ops::Deref;
+use std::rc::Rc;
+use std::usize;
+
+use transform::qualify_consts::is_const_fn;
+
+use super::error::{EvalError, EvalErrorKind, EvalResult};
+use super::memory::{AllocId, Memory};
+use super::value::{self, PathElem, Pointer, Value};
+
+/// The number of statements and terminators one evaluation may execute,
+/// unless overridden with `-Z const-eval-limit`.
+pub const STEP_LIMIT: usize = 1_000_000;
+
+/// The longest array a repeat expression may build.
+const MAX_ARRAY_LEN: u64 = 1 << 16;
+
+pub struct EvalContext<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    pub memory: Memory<'tcx>,
+    stack: Vec<Frame<'tcx>>,
+    step_limit: usize,
+    steps_remaining: usize,
+    /// The span of the last jump back to a loop header, where running
+    /// out of steps gets reported.
+    back_edge: Option<Span>,
+    /// Whether only `const fn`s may be called, as in constants.
+    const_only: bool,
+    statics: DefIdMap<AllocId>,
+    consts: FxHashMap<(DefId, &'tcx Substs<'tcx>), Value<'tcx>>,
+    /// The MIR run for the functions given to `call_body`, instead of
+    /// their MIR in the MIR map.
+    bodies: DefIdMap<Rc<Mir<'tcx>>>,
+}
+
+/// The MIR a frame runs.
+enum FrameMir<'tcx> {
+    Map(Ref<'tcx, Mir<'tcx>>),
+    Given(Rc<Mir<'tcx>>),
+}
+
+impl<'tcx> Deref for FrameMir<'tcx> {
+    type Target = Mir<'tcx>;
+
+    fn deref(&self) -> &Mir<'tcx> {
+        match *self {
+            FrameMir::Map(ref mir) => mir,
+            FrameMir::Given(ref mir) => mir,
+        }
+    }
+}
+
+struct Frame<'tcx> {
+    def_id: DefId,
+    mir: FrameMir<'tcx>,
+    promoted: Option<Promoted>,
+    substs: &'tcx Substs<'tcx>,
+    locals: IndexVec<Local, AllocId>,
+    block: BasicBlock,
+    statement: usize,
+    return_ptr: Pointer,
+    /// The block the caller continues at, unless the frame was pushed
+    /// by `EvalContext::run`.
+    return_block: Option<BasicBlock>,
+    /// Whether this is the body of a constant or static, whose locals
+    /// outlive it, as the value may borrow them.
+    is_const: bool,
+    /// Computed on the first backwards jump, to tell loops apart.
+    dominators: Option<Dominators<BasicBlock>>,
+}
+
+impl<'tcx> Frame<'tcx> {
+    fn mir(&self) -> &Mir<'tcx> {
+        match self.promoted {
+            Some(promoted) => &self.mir.promoted[promoted],
+            None => &self.mir
+        }
+    }
+
+    /// The span of the statement or terminator being executed.
+    fn span(&self) -> Span {
+        let data = &self.mir()[self.block];
+        match data.statements.get(self.statement) {
+            Some(statement) => statement.source_info.span,
+            None => data.terminator().source_info.span
+        }
+    }
+}
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, const_only: bool) -> Self {
+        EvalContext {
+            tcx: tcx,
+            memory: Memory::new(),
+            stack: vec![],
+            step_limit: match tcx.sess.opts.debugging_opts.const_eval_limit {
+                Some(limit) => limit,
+                // Whole programs may legitimately run for a long time.
+                None if const_only => STEP_LIMIT,
+                None => usize::MAX
+            },
+            steps_remaining: 0,
+            back_edge: None,
+            const_only: const_only,
+            statics: DefIdMap(),
+            consts: FxHashMap(),
+            bodies: DefIdMap(),
+        }
+    }
+
+    /// Limits every evaluation to `limit` statements and terminators.
+    pub fn set_step_limit(&mut self, limit: usize) {
+        self.step_limit = limit;
+    }
+
+    /// Evaluates the constant `def_id`, resolving it to the impl which
+    /// defines it if it is an associated constant.
+    pub fn eval_const(&mut self, def_id: DefId, substs: &'tcx Substs<'tcx>, span: Span)
+                      -> Result<Value<'tcx>, EvalError> {
+        let (def_id, substs) = self.resolve_const(def_id, substs);
+        if let Some(value) = self.consts.get(&(def_id, substs)) {
+            return Ok(value.clone());
+        }
+
+        let ret = self.memory.allocate(Value::Undef, true);
+        self.run(def_id, None, substs, vec![], Pointer::new(ret), true, span)?;
+        let value = self.memory.read(&Pointer::new(ret)).map_err(|kind| {
+            EvalError::new(span, kind)
+        })?;
+        self.memory.freeze(ret);
+        self.consts.insert((def_id, substs), value.clone());
+        Ok(value)
+    }
+
+    /// Runs the function `def_id`, which must take no arguments, to
+    /// completion, returning its return value.
+    pub fn call_fn(&mut self, def_id: DefId, span: Span) -> Result<Value<'tcx>, EvalError> {
+        let ret = self.memory.allocate(Value::Undef, true);
+        let substs = self.tcx.intern_substs(&[]);
+        self.run(def_id, None, substs, vec![], Pointer::new(ret), false, span)?;
+        self.memory.read(&Pointer::new(ret)).map_err(|kind| EvalError::new(span, kind))
+    }
+
+    /// Runs `mir` as the body of the function `def_id` to completion, in
+    /// place of its MIR in the MIR map, for the calls it makes to `def_id`
+    /// too. The MIR is given back along with the return value.
+    pub fn call_body(&mut self,
+                     def_id: DefId,
+                     mir: Mir<'tcx>,
+                     args: Vec<Value<'tcx>>,
+                     span: Span)
+                     -> (Mir<'tcx>, Result<Value<'tcx>, EvalError>) {
+        self.bodies.insert(def_id, Rc::new(mir));
+        let ret = self.memory.allocate(Value::Undef, true);
+        let substs = self.tcx.intern_substs(&[]);
+        let result = self.run(def_id, None, substs, args, Pointer::new(ret), false, span)
+            .and_then(|()| {
+                self.memory.read(&Pointer::new(ret)).map_err(|kind| EvalError::new(span, kind))
+            });
+
+        // The frames running it are gone, whether it returned or not.
+        let mir = self.bodies.remove(&def_id).unwrap();
+        let mir = Rc::try_unwrap(mir).ok().expect("call_body: MIR still in use");
+        (mir, result)
+    }
+
+    /// Evaluates the initializer of the static `def_id`, if that wasn't
+    /// done yet, returning the allocation holding its value.
+    pub fn eval_static(&mut self, def_id: DefId, span: Span) -> Result<AllocId, EvalError> {
+        if let Some(&alloc) = self.statics.get(&def_id) {
+            return Ok(alloc);
+        }
+
+        // Record the allocation first, so the initializer can take the
+        // address of the static itself.
+        let alloc = self.memory.allocate(Value::Undef, true);
+        self.statics.insert(def_id, alloc);
+        let substs = self.tcx.intern_substs(&[]);
+        self.run(def_id, None, substs, vec![], Pointer::new(alloc), true, span)?;
+        if !self.is_mutable_static(def_id) {
+            self.memory.freeze(alloc);
+        }
+        Ok(alloc)
+    }
+
+    /// Runs the MIR of `def_id` (or of its promoted constant `promoted`)
+    /// until it returns, writing the return value to `return_ptr`.
+    fn run(&mut self,
+           def_id: DefId,
+           promoted: Option<Promoted>,
+           substs: &'tcx Substs<'tcx>,
+           args: Vec<Value<'tcx>>,
+           return_ptr: Pointer,
+           is_const: bool,
+           span: Span)
+           -> Result<(), EvalError> {
+        let depth = self.stack.len();
+        if depth == 0 {
+            // Every top-level evaluation gets its own budget.
+            self.steps_remaining = self.step_limit;
+            self.back_edge = None;
+        }
+        if let Err(kind) = self.push_frame(def_id, promoted, substs, args,
+                                           return_ptr, None, is_const) {
+            return Err(EvalError::new(span, kind));
+        }
+
+        while self.stack.len() > depth {
+            if let Err(kind) = self.step() {
+                let span = match (&kind, self.back_edge) {
+                    (&EvalErrorKind::StepLimitReached, Some(span)) => span,
+                    _ => self.span()
+                };
+                let mut err = EvalError::new(span, kind);
+                err.backtrace = self.stack[depth..].windows(2).rev()
+                    .map(|frames| (frames[1].def_id, frames[0].span()))
+                    .collect();
+                self.stack.truncate(depth);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    fn push_frame(&mut self,
+                  def_id: DefId,
+                  promoted: Option<Promoted>,
+                  substs: &'tcx Substs<'tcx>,
+                  args: Vec<Value<'tcx>>,
+                  return_ptr: Pointer,
+                  return_block: Option<BasicBlock>,
+                  is_const: bool)
+                  -> EvalResult<()> {
+        let mir = self.load_mir(def_id)?;
+        let (num_locals, arg_count) = {
+            let mir = match promoted {
+                Some(promoted) => &mir.promoted[promoted],
+                None => &*mir
+            };
+            (mir.local_decls.len(), mir.arg_count)
+        };
+        if args.len() != arg_count {
+            return Err(EvalErrorKind::Unsupported("call with a mismatched argument count"));
+        }
+
+        let mut locals = IndexVec::with_capacity(num_locals);
+        locals.push(self.memory.allocate(Value::Undef, true));
+        for arg in args {
+            locals.push(self.memory.allocate(arg, true));
+        }
+        while locals.len() < num_locals {
+            locals.push(self.memory.allocate(Value::Undef, true));
+        }
+
+        self.stack.push(Frame {
+            def_id: def_id,
+            mir: mir,
+            promoted: promoted,
+            substs: substs,
+            locals: locals,
+            block: START_BLOCK,
+            statement: 0,
+            return_ptr: return_ptr,
+            return_block: return_block,
+            is_const: is_const,
+            dominators: None,
+        });
+        Ok(())
+    }
+
+    fn pop_frame(&mut self) -> EvalResult<()> {
+        let frame = self.stack.pop().unwrap();
+        let value = self.memory.read(&Pointer::new(frame.locals[RETURN_POINTER]))?;
+        self.memory.write(&frame.return_ptr, value)?;
+        for &alloc in &frame.locals {
+            if frame.is_const {
+                self.memory.freeze(alloc);
+            } else {
+                self.memory.deallocate(alloc);
+            }
+        }
+        if let Some(block) = frame.return_block {
+            let caller = self.stack.last_mut().unwrap();
+            caller.block = block;
+            caller.statement = 0;
+        }
+        Ok(())
+    }
+
+    /// Executes the next statement or terminator of the current frame.
+    fn step(&mut self) -> EvalResult<()> {
+        if self.steps_remaining == 0 {
+            return Err(EvalErrorKind::StepLimitReached);
+        }
+        self.steps_remaining -= 1;
+
+        let statement = {
+            let frame = self.frame();
+            frame.mir()[frame.block].statements.get(frame.statement).cloned()
+        };
+        if let Some(statement) = statement {
+            self.statement(&statement)?;
+            self.stack.last_mut().unwrap().statement += 1;
+            return Ok(());
+        }
+
+        let terminator = {
+            let frame = self.frame();
+            frame.mir()[frame.block].terminator().clone()
+        };
+        self.terminator(&terminator)
+    }
+
+    fn statement(&mut self, statement: &Statement<'tcx>) -> EvalResult<()> {
+        match statement.kind {
+            StatementKind::Assign(ref lvalue, ref rvalue) => {
+                let value = self.eval_rvalue(rvalue)?;
+                let (ptr, _) = self.eval_lvalue(lvalue)?;
+                self.memory.write(&ptr, value)
+            }
+            StatementKind::SetDiscriminant { ref lvalue, variant_index } => {
+                let (ptr, _) = self.eval_lvalue(lvalue)?;
+                self.memory.set_discriminant(&ptr, variant_index)
+            }
+            StatementKind::StorageLive(ref lvalue) |
+            StatementKind::StorageDead(ref lvalue) |
+            StatementKind::Poison(ref lvalue) => {
+                let (ptr, _) = self.eval_lvalue(lvalue)?;
+                self.memory.write(&ptr, Value::Undef)
+            }
+            StatementKind::IncrementCounter(_) |
+            StatementKind::Nop => Ok(())
+        }
+    }
+
+    fn terminator(&mut self, terminator: &Terminator<'tcx>) -> EvalResult<()> {
+        match terminator.kind {
+            TerminatorKind::Goto { target } => self.goto(target),
+            TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. } => {
+                let value = self.eval_operand(discr)?;
+                value.check_defined()?;
+                let value = value.to_const_val();
+                let index = values.iter().position(|v| Some(v) == value.as_ref())
+                    .unwrap_or(values.len());
+                self.goto(targets[index])
+            }
+            TerminatorKind::Return => self.pop_frame(),
+            TerminatorKind::Resume |
+            TerminatorKind::Abort => Err(EvalErrorKind::Unsupported("unwinding")),
+            TerminatorKind::Unreachable => Err(EvalErrorKind::Unreachable),
+            TerminatorKind::Drop { ref location, target, .. } => {
+                // Constants can't have destructors to run, but running
+                // them elsewhere would need drop glue.
+                if !self.const_only {
+                    let ty = self.lvalue_ty(location);
+                    let param_env = self.tcx.empty_parameter_environment();
+                    if self.tcx.type_needs_drop_given_env(ty, &param_env) {
+                        return Err(EvalErrorKind::Unsupported("drop glue"));
+                    }
+                }
+                self.goto(target)
+            }
+            TerminatorKind::DropAndReplace { ref location, ref value, target, .. } => {
+                let value = self.eval_operand(value)?;
+                let (ptr, _) = self.eval_lvalue(location)?;
+                self.memory.write(&ptr, value)?;
+                self.goto(target)
+            }
+            TerminatorKind::Call { ref func, ref args, ref destination, .. } => {
+                let (def_id, substs) = match self.eval_operand(func)? {
+                    Value::Fn(def_id, substs) => (def_id, substs),
+                    Value::Undef => return Err(EvalErrorKind::ReadUndef),
+                    _ => return Err(EvalErrorKind::Unsupported("call of a non-function"))
+                };
+                let mut arg_values = Vec::with_capacity(args.len());
+                for arg in args {
+                    arg_values.push(self.eval_operand(arg)?);
+                }
+                let destination = match *destination {
+                    Some((ref lvalue, target)) => Some((self.eval_lvalue(lvalue)?.0, target)),
+                    None => None
+                };
+                self.call(def_id, substs, arg_values, destination)
+            }
+            TerminatorKind::Assert { ref cond, expected, ref msg, target, .. } => {
+                if self.eval_operand(cond)?.to_bool()? == expected {
+                    return self.goto(target);
+                }
+                Err(match **msg {
+                    AssertMessage::BoundsCheck { ref len, ref index } => {
+                        EvalErrorKind::IndexOutOfBounds {
+                            len: self.eval_operand(len)?.to_u64()?,
+                            index: self.eval_operand(index)?.to_u64()?
+                        }
+                    }
+                    AssertMessage::Math(ref err) => EvalErrorKind::Math(err.clone()),
+                    AssertMessage::NullPointerDeref => EvalErrorKind::DanglingPointer,
+                    AssertMessage::MisalignedPointerDeref => {
+                        EvalErrorKind::Unsupported("misaligned pointer dereference")
+                    }
+                })
+            }
+        }
+    }
+
+    fn call(&mut self,
+            def_id: DefId,
+            substs: &'tcx Substs<'tcx>,
+            args: Vec<Value<'tcx>>,
+            destination: Option<(Pointer, BasicBlock)>)
+            -> EvalResult<()> {
+        if self.const_only && !is_const_fn(self.tcx, def_id) {
+            return Err(EvalErrorKind::NonConstFn(def_id));
+        }
+        let (def_id, substs) = match self.tcx.trait_of_item(def_id) {
+            Some(trait_id) => self.resolve_method(trait_id, def_id, substs)?,
+            None => (def_id, substs)
+        };
+        if let ty::TyFnDef(_, _, fn_ty) = self.tcx.item_type(def_id).sty {
+            if fn_ty.abi == Abi::RustIntrinsic || fn_ty.abi == Abi::PlatformIntrinsic {
+                return Err(EvalErrorKind::Unsupported("intrinsic call"));
+            }
+        }
+        match destination {
+            Some((return_ptr, target)) => {
+                self.push_frame(def_id, None, substs, args, return_ptr, Some(target), false)
+            }
+            None => Err(EvalErrorKind::Diverged(def_id))
+        }
+    }
+
+    fn goto(&mut self, target: BasicBlock) -> EvalResult<()> {
+        if self.is_back_edge(target) {
+            self.back_edge = Some(self.span());
+        }
+        let frame = self.stack.last_mut().unwrap();
+        frame.block = target;
+        frame.statement = 0;
+        Ok(())
+    }
+
+    /// Whether jumping to `target` starts another iteration of a loop.
+    fn is_back_edge(&mut self, target: BasicBlock) -> bool {
+        let frame = self.stack.last_mut().unwrap();
+        // Loop headers are built before their bodies.
+        if target.index() > frame.block.index() {
+            return false;
+        }
+        if frame.dominators.is_none() {
+            let dominators = frame.mir().dominators();
+            frame.dominators = Some(dominators);
+        }
+        frame.dominators.as_ref().unwrap().is_dominated_by(frame.block, target)
+    }
+
+    fn eval_rvalue(&mut self, rvalue: &Rvalue<'tcx>) -> EvalResult<Value<'tcx>> {
+        match *rvalue {
+            Rvalue::Use(ref operand) => self.eval_operand(operand),
+            Rvalue::Repeat(ref operand, ref count) => {
+                let count = count.value.as_u64(self.tcx.sess.target.uint_type);
+                if count > MAX_ARRAY_LEN {
+                    return Err(EvalErrorKind::Unsupported("large array"));
+                }
+                let value = self.eval_operand(operand)?;
+                Ok(Value::Aggregate(0, vec![value; count as usize]))
+            }
+            Rvalue::Ref(_, _, ref lvalue) => {
+                let (ptr, len) = self.eval_lvalue(lvalue)?;
+                Ok(Value::Ptr(ptr, len))
+            }
+            Rvalue::Len(ref lvalue) => {
+                let (ptr, len) = self.eval_lvalue(lvalue)?;
+                let len = match len {
+                    Some(len) => len,
+                    None => self.memory.len(&ptr)?
+                };
+                self.usize_value(len)
+            }
+            Rvalue::Discriminant(ref lvalue) => {
+                let adt_def = match self.lvalue_ty(lvalue).sty {
+                    ty::TyAdt(adt_def, _) if adt_def.is_enum() => adt_def,
+                    _ => bug!("EvalContext: discriminant of non-enum {:?}", lvalue)
+                };
+                let (ptr, _) = self.eval_lvalue(lvalue)?;
+                match self.memory.read(&ptr)? {
+                    Value::Aggregate(variant, _) => {
+                        Ok(Value::Int(adt_def.variants[variant].disr_val))
+                    }
+                    Value::Undef => Err(EvalErrorKind::ReadUndef),
+                    _ => Err(EvalErrorKind::Unsupported("discriminant of a scalar"))
+                }
+            }
+            Rvalue::Cast(kind, ref operand, cast_ty) => {
+                let value = self.eval_operand(operand)?;
+                let cast_ty = self.monomorphize(cast_ty);
+                match kind {
+                    CastKind::Misc => {
+                        let src_ty = self.operand_ty(operand);
+                        self.cast(value, src_ty, cast_ty)
+                    }
+                    CastKind::ReifyFnPointer |
+                    CastKind::UnsafeFnPointer => Ok(value),
+                    CastKind::Unsize => {
+                        let pointee = cast_ty.builtin_deref(true, ty::NoPreference)
+                            .map(|mt| mt.ty);
+                        match (value, pointee.map(|ty| &ty.sty)) {
+                            (Value::Ptr(ptr, None), Some(&ty::TySlice(_))) => {
+                                let len = self.memory.len(&ptr)?;
+                                Ok(Value::Ptr(ptr, Some(len)))
+                            }
+                            (Value::Undef, _) => Err(EvalErrorKind::ReadUndef),
+                            _ => Err(EvalErrorKind::Unsupported("unsizing cast"))
+                        }
+                    }
+                }
+            }
+            Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+                let lhs = self.eval_operand(lhs)?;
+                let rhs = self.eval_operand(rhs)?;
+                value::wrapping_binary_op(op, lhs, rhs)
+            }
+            Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => {
+                let lhs = self.eval_operand(lhs)?;
+                let rhs = self.eval_operand(rhs)?;
+                let (value, overflowed) = value::checked_binary_op(op, lhs, rhs)?;
+                Ok(Value::Aggregate(0, vec![value, Value::Bool(overflowed)]))
+            }
+            Rvalue::UnaryOp(op, ref operand) => {
+                let value = self.eval_operand(operand)?;
+                value::unary_op(op, value)
+            }
+            Rvalue::Aggregate(ref kind, ref operands) => {
+                let mut values = Vec::with_capacity(operands.len());
+                for operand in operands {
+                    values.push(self.eval_operand(operand)?);
+                }
+                match *kind {
+                    AggregateKind::Adt(_, _, _, Some(_)) => {
+                        Err(EvalErrorKind::Unsupported("union expression"))
+                    }
+                    AggregateKind::Adt(_, variant, _, None) => {
+                        Ok(Value::Aggregate(variant, values))
+                    }
+                    AggregateKind::Array |
+                    AggregateKind::Tuple |
+                    AggregateKind::Closure(..) => Ok(Value::Aggregate(0, values))
+                }
+            }
+            Rvalue::Box(_) => Err(EvalErrorKind::Unsupported("box expression")),
+            Rvalue::InlineAsm { .. } => Err(EvalErrorKind::Unsupported("inline assembly")),
+        }
+    }
+
+    fn cast(&mut self, value: Value<'tcx>, src_ty: Ty<'tcx>, cast_ty: Ty<'tcx>)
+            -> EvalResult<Value<'tcx>> {
+        let value = match (value, &src_ty.sty) {
+            (Value::Undef, _) => return Err(EvalErrorKind::ReadUndef),
+            (Value::Aggregate(variant, _), &ty::TyAdt(adt_def, _)) if adt_def.is_enum() => {
+                Value::Int(adt_def.variants[variant].disr_val)
+            }
+            (value @ Value::Ptr(..), _) => {
+                return match cast_ty.sty {
+                    ty::TyRef(..) | ty::TyRawPtr(_) => Ok(value),
+                    _ => Err(EvalErrorKind::Unsupported("casting a pointer to an integer"))
+                };
+            }
+            (value, _) => value
+        };
+        let const_val = match value.to_const_val() {
+            Some(const_val) => const_val,
+            None => return Err(EvalErrorKind::Unsupported("cast of a non-scalar"))
+        };
+        let const_val = cast_const(self.tcx, const_val, cast_ty).map_err(EvalErrorKind::Cast)?;
+        self.const_val_to_value(&const_val)
+    }
+
+    fn eval_operand(&mut self, operand: &Operand<'tcx>) -> EvalResult<Value<'tcx>> {
+        let constant = match *operand {
+            Operand::Consume(ref lvalue) => {
+                let (ptr, _) = self.eval_lvalue(lvalue)?;
+                return self.memory.read(&ptr);
+            }
+            Operand::Constant(ref constant) => constant
+        };
+
+        match constant.literal {
+            Literal::Value { ref value } => self.const_val_to_value(value),
+            Literal::Item { def_id, substs } => {
+                let substs = self.monomorphize_substs(substs);
+                if let ty::TyFnDef(..) = self.monomorphize(constant.ty).sty {
+                    return Ok(Value::Fn(def_id, substs));
+                }
+                let span = self.span();
+                self.eval_const(def_id, substs, span).map_err(|err| {
+                    EvalErrorKind::ReferencedConstant(Box::new(err))
+                })
+            }
+            Literal::Promoted { index } => {
+                let (def_id, substs) = {
+                    let frame = self.frame();
+                    (frame.def_id, frame.substs)
+                };
+                let ret = self.memory.allocate(Value::Undef, true);
+                let span = self.span();
+                self.run(def_id, Some(index), substs, vec![], Pointer::new(ret), true, span)
+                    .map_err(|err| err.kind)?;
+                self.memory.freeze(ret);
+                self.memory.read(&Pointer::new(ret))
+            }
+            Literal::Outlined { .. } => Err(EvalErrorKind::Unsupported("outlined cold code")),
+        }
+    }
+
+    /// Finds the value `lvalue` refers to, along with its length if it
+    /// was reached through a fat pointer.
+    fn eval_lvalue(&mut self, lvalue: &Lvalue<'tcx>) -> EvalResult<(Pointer, Option<u64>)> {
+        let proj = match *lvalue {
+            Lvalue::Local(local) => return Ok((Pointer::new(self.frame().locals[local]), None)),
+            Lvalue::Static(def_id) => {
+                let span = self.span();
+                let alloc = self.eval_static(def_id, span).map_err(|err| {
+                    EvalErrorKind::ReferencedConstant(Box::new(err))
+                })?;
+                return Ok((Pointer::new(alloc), None));
+            }
+            Lvalue::Projection(ref proj) => proj
+        };
+
+        let (base, len) = self.eval_lvalue(&proj.base)?;
+        let elem = match proj.elem {
+            ProjectionElem::Deref => {
+                return match self.memory.read(&base)? {
+                    Value::Ptr(ptr, len) => Ok((ptr, len)),
+                    Value::Undef => Err(EvalErrorKind::ReadUndef),
+                    _ => Err(EvalErrorKind::Unsupported("dereference of a non-pointer"))
+                };
+            }
+            ProjectionElem::Field(field, _) => PathElem::Field(field.index()),
+            ProjectionElem::Index(ref index) => {
+                PathElem::Index(self.eval_operand(index)?.to_u64()?)
+            }
+            ProjectionElem::ConstantIndex { offset, from_end, .. } => {
+                if from_end {
+                    let len = match len {
+                        Some(len) => len,
+                        None => self.memory.len(&base)?
+                    };
+                    PathElem::Index(len - offset as u64)
+                } else {
+                    PathElem::Index(offset as u64)
+                }
+            }
+            ProjectionElem::Subslice { .. } => {
+                return Err(EvalErrorKind::Unsupported("subslice pattern"));
+            }
+            ProjectionElem::Downcast(_, variant) => PathElem::Downcast(variant),
+        };
+        Ok((base.project(elem), None))
+    }
+
+    fn const_val_to_value(&mut self, value: &ConstVal) -> EvalResult<Value<'tcx>> {
+        if let Some(value) = Value::from_const_val(value) {
+            return Ok(value);
+        }
+        let (bytes, len) = match *value {
+            ConstVal::Str(ref s) => (s.as_bytes(), Some(s.len() as u64)),
+            ConstVal::ByteStr(ref b) => (&b[..], None),
+            ConstVal::Function(def_id) => {
+                return Ok(Value::Fn(def_id, self.tcx.intern_substs(&[])));
+            }
+            _ => return Err(EvalErrorKind::Unsupported("constant value"))
+        };
+        let bytes = bytes.iter().map(|&b| Value::Int(ConstInt::U8(b))).collect();
+        let alloc = self.memory.allocate(Value::Aggregate(0, bytes), false);
+        Ok(Value::Ptr(Pointer::new(alloc), len))
+    }
+
+    fn usize_value(&self, n: u64) -> EvalResult<Value<'tcx>> {
+        let n = ConstUsize::new(n, self.tcx.sess.target.uint_type)?;
+        Ok(Value::Int(ConstInt::Usize(n)))
+    }
+
+    /// Finds the impl defining the associated constant `def_id`, if it is one.
+    fn resolve_const(&self, def_id: DefId, substs: &'tcx Substs<'tcx>)
+                     -> (DefId, &'tcx Substs<'tcx>) {
+        let trait_id = match self.tcx.trait_of_item(def_id) {
+            Some(trait_id) => trait_id,
+            None => return (def_id, substs)
+        };
+        let tcx = self.tcx;
+        let trait_ref = ty::Binder(ty::TraitRef::new(trait_id, tcx.erase_regions(&substs)));
+        if let Some(traits::VtableImpl(vtable_impl)) = self.select(trait_ref) {
+            let name = tcx.item_name(def_id);
+            let ac = tcx.associated_items(vtable_impl.impl_def_id)
+                .find(|item| item.kind == ty::AssociatedKind::Const && item.name == name);
+            if let Some(ac) = ac {
+                return (ac.def_id, vtable_impl.substs);
+            }
+        }
+        (def_id, substs)
+    }
+
+    /// Finds the impl method a call to the trait method `def_id` calls.
+    fn resolve_method(&self, trait_id: DefId, def_id: DefId, substs: &'tcx Substs<'tcx>)
+                      -> EvalResult<(DefId, &'tcx Substs<'tcx>)> {
+        let tcx = self.tcx;
+        let trait_ref = ty::TraitRef::from_method(tcx, trait_id, substs);
+        let trait_ref = tcx.normalize_associated_type(&ty::Binder(trait_ref));
+        match self.select(trait_ref) {
+            Some(traits::VtableImpl(vtable_impl)) => {
+                let name = tcx.item_name(def_id);
+                Ok(traits::find_method(tcx, name, substs, &vtable_impl))
+            }
+            Some(traits::VtableClosure(_)) |
+            Some(traits::VtableFnPointer(_)) => Err(EvalErrorKind::Unsupported("closure call")),
+            Some(traits::VtableObject(_)) => Err(EvalErrorKind::Unsupported("virtual call")),
+            _ => Err(EvalErrorKind::Unsupported("trait method call"))
+        }
+    }
+
+    /// Selects the impl (or other source) of `trait_ref`, which must be
+    /// fully monomorphic.
+    fn select(&self, trait_ref: ty::PolyTraitRef<'tcx>) -> Option<traits::Vtable<'tcx, ()>> {
+        self.tcx.infer_ctxt(None, None, Reveal::All).enter(|infcx| {
+            let mut selcx = SelectionContext::new(&infcx);
+            let obligation = traits::Obligation::new(traits::ObligationCause::dummy(),
+                                                     trait_ref.to_poly_trait_predicate());
+            let selection = match selcx.select(&obligation) {
+                Ok(Some(selection)) => selection,
+                _ => return None
+            };
+            let mut fulfill_cx = traits::FulfillmentContext::new();
+            let vtable = selection.map(|predicate| {
+                fulfill_cx.register_predicate_obligation(&infcx, predicate);
+            });
+            Some(infcx.drain_fulfillment_cx_or_panic(DUMMY_SP, &mut fulfill_cx, &vtable))
+        })
+    }
+
+    fn load_mir(&self, def_id: DefId) -> EvalResult<FrameMir<'tcx>> {
+        if let Some(mir) = self.bodies.get(&def_id) {
+            return Ok(FrameMir::Given(mir.clone()));
+        }
+        let available = if def_id.is_local() {
+            self.tcx.mir_map.borrow().contains_key(&def_id)
+        } else {
+            self.tcx.sess.cstore.is_item_mir_available(def_id)
+        };
+        if !available {
+            return Err(EvalErrorKind::NoMirFor(def_id));
+        }
+        Ok(FrameMir::Map(self.tcx.item_mir(def_id)))
+    }
+
+    fn is_mutable_static(&self, def_id: DefId) -> bool {
+        match self.tcx.map.as_local_node_id(def_id) {
+            Some(id) => match self.tcx.map.get(id) {
+                hir_map::NodeItem(&hir::Item { node: hir::ItemStatic(_, m, _), .. }) => {
+                    m == hir::MutMutable
+                }
+                _ => false
+            },
+            None => match self.tcx.sess.cstore.describe_def(def_id) {
+                Some(Def::Static(_, mutable)) => mutable,
+                _ => false
+            }
+        }
+    }
+
+    fn frame(&self) -> &Frame<'tcx> {
+        self.stack.last().unwrap()
+    }
+
+    fn span(&self) -> Span {
+        self.stack.last().map_or(DUMMY_SP, |frame| frame.span())
+    }
+
+    fn monomorphize(&self, ty: Ty<'tcx>) -> Ty<'tcx> {
+        let ty = ty.subst(self.tcx, self.frame().substs);
+        self.tcx.normalize_associated_type(&ty)
+    }
+
+    fn monomorphize_substs(&self, substs: &'tcx Substs<'tcx>) -> &'tcx Substs<'tcx> {
+        let substs = substs.subst(self.tcx, self.frame().substs);
+        self.tcx.erase_regions(&substs)
+    }
+
+    fn lvalue_ty(&self, lvalue: &Lvalue<'tcx>) -> Ty<'tcx> {
+        let ty = lvalue.ty(self.frame().mir(), self.tcx).to_ty(self.tcx);
+        self.monomorphize(ty)
+    }
+
+    fn operand_ty(&self, operand: &Operand<'tcx>) -> Ty<'tcx> {
+        let ty = operand.ty(self.frame().mir(), self.tcx);
+        self.monomorphize(ty)
+    }
+}
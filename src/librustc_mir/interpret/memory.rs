@@ -0,0 +1,192 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+
+use super::error::EvalErrorKind;
+use super::value::{PathElem, Pointer, Value};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AllocId(u32);
+
+impl Idx for AllocId {
+    fn new(idx: usize) -> Self {
+        assert!(idx < (u32::max_value() as usize));
+        AllocId(idx as u32)
+    }
+
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The storage of a local, a static, or a constant which was borrowed.
+#[derive(Clone, Debug)]
+pub struct Allocation<'tcx> {
+    pub value: Value<'tcx>,
+    pub mutable: bool,
+    /// Whether the allocation was not freed yet.
+    pub live: bool,
+}
+
+pub struct Memory<'tcx> {
+    allocs: IndexVec<AllocId, Allocation<'tcx>>,
+}
+
+impl<'tcx> Memory<'tcx> {
+    pub fn new() -> Self {
+        Memory { allocs: IndexVec::new() }
+    }
+
+    pub fn allocate(&mut self, value: Value<'tcx>, mutable: bool) -> AllocId {
+        self.allocs.push(Allocation {
+            value: value,
+            mutable: mutable,
+            live: true
+        })
+    }
+
+    pub fn deallocate(&mut self, id: AllocId) {
+        let alloc = &mut self.allocs[id];
+        alloc.value = Value::Undef;
+        alloc.live = false;
+    }
+
+    pub fn freeze(&mut self, id: AllocId) {
+        self.allocs[id].mutable = false;
+    }
+
+    pub fn read(&self, ptr: &Pointer) -> Result<Value<'tcx>, EvalErrorKind> {
+        let alloc = &self.allocs[ptr.alloc];
+        if !alloc.live {
+            return Err(EvalErrorKind::DanglingPointer);
+        }
+
+        let mut value = &alloc.value;
+        for elem in &ptr.path {
+            value = match (value, *elem) {
+                (&Value::Undef, _) => return Ok(Value::Undef),
+                (&Value::Aggregate(_, ref fields), PathElem::Field(i)) => {
+                    match fields.get(i) {
+                        Some(field) => field,
+                        None => return Ok(Value::Undef)
+                    }
+                }
+                (&Value::Aggregate(_, ref elems), PathElem::Index(i)) => {
+                    match elems.get(i as usize) {
+                        Some(elem) => elem,
+                        None => {
+                            return Err(EvalErrorKind::IndexOutOfBounds {
+                                len: elems.len() as u64,
+                                index: i
+                            });
+                        }
+                    }
+                }
+                (&Value::Aggregate(variant, _), PathElem::Downcast(v)) => {
+                    if variant != v {
+                        return Err(EvalErrorKind::InvalidVariant);
+                    }
+                    value
+                }
+                _ => return Err(EvalErrorKind::Unsupported("projection of a scalar"))
+            };
+        }
+        Ok(value.clone())
+    }
+
+    pub fn write(&mut self, ptr: &Pointer, new: Value<'tcx>) -> Result<(), EvalErrorKind> {
+        *self.get_mut(ptr)? = new;
+        Ok(())
+    }
+
+    /// Switches the enum `ptr` points to over to the variant `variant`.
+    pub fn set_discriminant(&mut self, ptr: &Pointer, variant: usize)
+                            -> Result<(), EvalErrorKind> {
+        let value = self.get_mut(ptr)?;
+        if let Value::Aggregate(ref mut v, _) = *value {
+            *v = variant;
+            return Ok(());
+        }
+        *value = Value::Aggregate(variant, vec![]);
+        Ok(())
+    }
+
+    /// The length of the array `ptr` points to.
+    pub fn len(&self, ptr: &Pointer) -> Result<u64, EvalErrorKind> {
+        match self.read(ptr)? {
+            Value::Aggregate(_, ref elems) => Ok(elems.len() as u64),
+            Value::Undef => Err(EvalErrorKind::ReadUndef),
+            _ => Err(EvalErrorKind::Unsupported("length of a scalar"))
+        }
+    }
+
+    /// Finds the value `ptr` points to for writing, filling in the
+    /// aggregates along the way if they are uninitialized.
+    fn get_mut(&mut self, ptr: &Pointer) -> Result<&mut Value<'tcx>, EvalErrorKind> {
+        let alloc = &mut self.allocs[ptr.alloc];
+        if !alloc.live {
+            return Err(EvalErrorKind::DanglingPointer);
+        }
+        if !alloc.mutable {
+            return Err(EvalErrorKind::ModifiedConstant);
+        }
+
+        let mut value = &mut alloc.value;
+        for elem in &ptr.path {
+            let tmp = value;
+            value = match *elem {
+                PathElem::Field(i) => {
+                    if let Value::Undef = *tmp {
+                        *tmp = Value::Aggregate(0, vec![]);
+                    }
+                    match *tmp {
+                        Value::Aggregate(_, ref mut fields) => {
+                            while fields.len() <= i {
+                                fields.push(Value::Undef);
+                            }
+                            &mut fields[i]
+                        }
+                        _ => return Err(EvalErrorKind::Unsupported("projection of a scalar"))
+                    }
+                }
+                PathElem::Index(i) => match *tmp {
+                    Value::Aggregate(_, ref mut elems) => {
+                        let len = elems.len() as u64;
+                        match elems.get_mut(i as usize) {
+                            Some(elem) => elem,
+                            None => {
+                                return Err(EvalErrorKind::IndexOutOfBounds {
+                                    len: len,
+                                    index: i
+                                });
+                            }
+                        }
+                    }
+                    Value::Undef => return Err(EvalErrorKind::ReadUndef),
+                    _ => return Err(EvalErrorKind::Unsupported("projection of a scalar"))
+                },
+                PathElem::Downcast(v) => {
+                    // Writing to the fields of another variant switches
+                    // the enum over to it, as building an enum does.
+                    let switch = match *tmp {
+                        Value::Aggregate(variant, _) => variant != v,
+                        _ => true
+                    };
+                    if switch {
+                        *tmp = Value::Aggregate(v, vec![]);
+                    }
+                    tmp
+                }
+            };
+        }
+        Ok(value)
+    }
+}
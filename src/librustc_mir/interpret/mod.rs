@@ -0,0 +1,210 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An interpreter for MIR, used to evaluate constants, and with
+//! `-Z mir-interpret` to run a program inside the compiler, where reads of
+//! uninitialized memory, dangling pointers or invalid casts are reported
+//! instead of being undefined behavior.
+//!
+//! Unlike the HIR constant evaluator, it runs the same code trans does,
+//! so a constant can call `const fn`s, use `let` bindings and refer to
+//! other constants and statics, and any overflow or out-of-bounds index
+//! is caught by the `Assert`s MIR construction inserted.
+//!
+//! The values of the constants functions refer to are the interpreter's,
+//! see the `EvalConsts` pass. Array lengths, repeat counts and enum
+//! discriminants are needed by typeck before MIR exists, so the HIR
+//! evaluator still computes them, and `check_crate` reports an error if
+//! the interpreter disagrees.
+//!
+//! Memory is a set of allocations holding values, which are trees of
+//! fields rather than bytes; a pointer is an allocation along with the
+//! path of fields, indices and variants leading to its target. This
+//! keeps the interpreter independent of type layouts, at the price of
+//! not supporting transmutes, unions or pointer arithmetic.
+
+use rustc::hir::map as hir_map;
+use rustc::lint::builtin::CONST_ERR;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::transform::MirSource;
+use rustc::ty::TyCtxt;
+use rustc_const_eval::{eval_const_expr_partial, ExprTypeChecked};
+use syntax::ast::NodeId;
+
+mod error;
+mod eval_context;
+mod memory;
+mod value;
+
+pub use self::error::{EvalError, EvalErrorKind, EvalResult};
+pub use self::eval_context::{EvalContext, STEP_LIMIT};
+pub use self::memory::{AllocId, Memory};
+pub use self::value::{Pointer, Value};
+
+/// Evaluates every constant and static of the crate, reporting the
+/// ones whose evaluation is sure to fail through the `const_err` lint.
+pub fn check_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+    if tcx.sess.err_count() > 0 {
+        return;
+    }
+    let _ignore = tcx.dep_graph.in_ignore();
+
+    let def_ids = tcx.mir_map.borrow().keys();
+    let mut ecx = EvalContext::new(tcx, true);
+    for def_id in def_ids {
+        let id = match tcx.map.as_local_node_id(def_id) {
+            Some(id) => id,
+            None => continue
+        };
+        let span = tcx.map.span(id);
+        let result = match MirSource::from_node(tcx, id) {
+            MirSource::Const(_) => {
+                if is_generic_const(tcx, id) {
+                    continue;
+                }
+                let substs = tcx.intern_substs(&[]);
+                ecx.eval_const(def_id, substs, span).map(|value| {
+                    check_typeck_value(tcx, id, &value);
+                })
+            }
+            MirSource::Static(..) => ecx.eval_static(def_id, span).map(|_| ()),
+            MirSource::Fn(_) | MirSource::Promoted(..) | MirSource::Shim => continue
+        };
+
+        let err = match result {
+            Ok(()) => continue,
+            Err(err) => err
+        };
+        if err.is_unsupported() {
+            debug!("interpret::check_crate: {} not evaluated: {}",
+                   tcx.item_path_str(def_id), err.description(tcx));
+            continue;
+        }
+        match err.kind {
+            EvalErrorKind::Math(_) |
+            EvalErrorKind::IndexOutOfBounds { .. } |
+            EvalErrorKind::Cast(_) => {
+                tcx.sess.add_lint(CONST_ERR, id, err.span,
+                                  format!("constant evaluation error: {}. This will \
+                                           become a HARD ERROR in the future",
+                                          err.description(tcx)));
+            }
+            EvalErrorKind::StepLimitReached => {
+                let limit = tcx.sess.opts.debugging_opts.const_eval_limit.unwrap_or(STEP_LIMIT);
+                tcx.sess.struct_span_err(err.span, "constant evaluation exceeded limit")
+                    .span_label(err.span, &format!("evaluation took more than {} steps", limit))
+                    .note("the limit can be raised with `-Z const-eval-limit`")
+                    .emit();
+            }
+            // The referenced constant reports its own error, and the
+            // other errors aren't reported by the HIR evaluator either.
+            _ => {
+                debug!("interpret::check_crate: {} failed: {}",
+                       tcx.item_path_str(def_id), err.description(tcx));
+            }
+        }
+    }
+}
+
+/// Runs the entry function, or the one named with `-Z mir-interpret-fn`,
+/// reporting the first error it runs into along with the calls leading
+/// up to it. Nothing is reported if it returns.
+pub fn run_fn<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+    let _ignore = tcx.dep_graph.in_ignore();
+
+    let def_id = match tcx.sess.opts.debugging_opts.mir_interpret_fn {
+        Some(ref name) => {
+            let def_ids = tcx.mir_map.borrow().keys();
+            let def_id = def_ids.into_iter().find(|&def_id| {
+                def_id.is_local() && tcx.item_path_str(def_id) == *name
+            });
+            match def_id {
+                Some(def_id) => def_id,
+                None => {
+                    tcx.sess.err(&format!("no function named `{}` to interpret", name));
+                    return;
+                }
+            }
+        }
+        None => match *tcx.sess.entry_fn.borrow() {
+            Some((id, _)) => tcx.map.local_def_id(id),
+            None => {
+                tcx.sess.err("no `main` function to interpret");
+                return;
+            }
+        }
+    };
+
+    let span = tcx.def_span(def_id);
+    let generics = tcx.item_generics(def_id);
+    if generics.parent_types > 0 || !generics.types.is_empty() ||
+       tcx.item_mir(def_id).arg_count != 0 {
+        tcx.sess.span_err(span, "only functions without arguments or type parameters \
+                                 can be interpreted");
+        return;
+    }
+
+    let mut ecx = EvalContext::new(tcx, false);
+    let err = match ecx.call_fn(def_id, span) {
+        Ok(_) => return,
+        Err(err) => err
+    };
+    let msg = format!("MIR interpretation failed: {}", err.description(tcx));
+    let mut diag = if err.is_unsupported() {
+        tcx.sess.struct_span_warn(err.span, &msg)
+    } else {
+        tcx.sess.struct_span_err(err.span, &msg)
+    };
+    for &(callee, call_span) in &err.backtrace {
+        diag.span_note(call_span, &format!("inside call to `{}`", tcx.item_path_str(callee)));
+    }
+    diag.emit();
+}
+
+/// Checks the value the interpreter gave to an array length, repeat count
+/// or enum discriminant `id` against the one typeck used. Typeck needs
+/// them before MIR is built, so they are evaluated by the HIR evaluator
+/// first. If the two evaluators disagree, the crate would be compiled with
+/// both values, so that is reported as an error.
+fn check_typeck_value<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, id: NodeId, value: &Value<'tcx>) {
+    let expr = match tcx.map.get(id) {
+        hir_map::NodeExpr(expr) => expr,
+        _ => return
+    };
+    let value = match value.to_const_val() {
+        Some(value) => value,
+        None => return
+    };
+    if let Ok(typeck_value) = eval_const_expr_partial(tcx, expr, ExprTypeChecked, None) {
+        if typeck_value != value {
+            let describe = |value: &ConstVal| match *value {
+                ConstVal::Integral(ref value) => format!("`{}`", value),
+                ref value => format!("`{:?}`", value)
+            };
+            tcx.sess.struct_span_err(expr.span, "inconsistent values for this constant")
+                .note(&format!("type checking used {}, but its MIR evaluates to {}",
+                               describe(&typeck_value), describe(&value)))
+                .emit();
+        }
+    }
+}
+
+/// Whether the value of the associated constant `id` depends on type
+/// parameters, which are only known where it is used.
+fn is_generic_const(tcx: TyCtxt, id: NodeId) -> bool {
+    match tcx.map.get(id) {
+        hir_map::NodeTraitItem(_) => true,
+        hir_map::NodeImplItem(_) => {
+            let generics = tcx.item_generics(tcx.map.local_def_id(id));
+            generics.parent_types > 0 || !generics.types.is_empty()
+        }
+        _ => false
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::{BinOp, UnOp};
+use rustc::ty::subst::Substs;
+use rustc_const_math::{ConstInt, ConstFloat, ConstIsize, ConstUsize};
+use rustc_const_math::ConstMathErr;
+use rustc_const_math::ConstMathErr::Overflow;
+
+use std::cmp::Ordering;
+
+use super::error::EvalErrorKind;
+use super::memory::AllocId;
+
+/// A value of the interpreter. Compound values are trees of fields,
+/// rather than bytes, so the layout of types never matters.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'tcx> {
+    /// Uninitialized memory, or memory of a dead local.
+    Undef,
+    Bool(bool),
+    Char(char),
+    Int(ConstInt),
+    Float(ConstFloat),
+    /// The fields of tuples, structs, closures and enum variants, along
+    /// with the variant index, or the elements of arrays.
+    Aggregate(usize, Vec<Value<'tcx>>),
+    /// A pointer, along with the length of the slice or `str` it points
+    /// to, if it is a fat pointer.
+    Ptr(Pointer, Option<u64>),
+    Fn(DefId, &'tcx Substs<'tcx>),
+}
+
+/// A pointer to a value nested somewhere in an allocation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pointer {
+    pub alloc: AllocId,
+    pub path: Vec<PathElem>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PathElem {
+    Field(usize),
+    Index(u64),
+    /// The fields of the given variant of an enum.
+    Downcast(usize),
+}
+
+impl Pointer {
+    pub fn new(alloc: AllocId) -> Pointer {
+        Pointer { alloc: alloc, path: vec![] }
+    }
+
+    pub fn project(mut self, elem: PathElem) -> Pointer {
+        self.path.push(elem);
+        self
+    }
+}
+
+impl<'tcx> Value<'tcx> {
+    /// Converts a scalar constant; strings need memory, so they're
+    /// handled by the `EvalContext`.
+    pub fn from_const_val(value: &ConstVal) -> Option<Value<'tcx>> {
+        match *value {
+            ConstVal::Integral(i) => Some(Value::Int(i)),
+            ConstVal::Float(f) => Some(Value::Float(f)),
+            ConstVal::Bool(b) => Some(Value::Bool(b)),
+            ConstVal::Char(c) => Some(Value::Char(c)),
+            _ => None
+        }
+    }
+
+    pub fn to_const_val(&self) -> Option<ConstVal> {
+        match *self {
+            Value::Int(i) => Some(ConstVal::Integral(i)),
+            Value::Float(f) => Some(ConstVal::Float(f)),
+            Value::Bool(b) => Some(ConstVal::Bool(b)),
+            Value::Char(c) => Some(ConstVal::Char(c)),
+            Value::Fn(def_id, _) => Some(ConstVal::Function(def_id)),
+            _ => None
+        }
+    }
+
+    pub fn to_bool(&self) -> Result<bool, EvalErrorKind> {
+        match *self {
+            Value::Bool(b) => Ok(b),
+            Value::Undef => Err(EvalErrorKind::ReadUndef),
+            _ => Err(EvalErrorKind::Unsupported("non-boolean condition"))
+        }
+    }
+
+    pub fn to_u64(&self) -> Result<u64, EvalErrorKind> {
+        match *self {
+            Value::Int(i) => i.to_u64().ok_or(EvalErrorKind::Math(ConstMathErr::NotInRange)),
+            Value::Undef => Err(EvalErrorKind::ReadUndef),
+            _ => Err(EvalErrorKind::Unsupported("non-integer index or length"))
+        }
+    }
+
+    /// Checks that the value doesn't contain uninitialized memory, at
+    /// least where it is used as a scalar.
+    pub fn check_defined(&self) -> Result<(), EvalErrorKind> {
+        match *self {
+            Value::Undef => Err(EvalErrorKind::ReadUndef),
+            _ => Ok(())
+        }
+    }
+}
+
+pub fn binary_op<'tcx>(op: BinOp, lhs: Value<'tcx>, rhs: Value<'tcx>)
+                       -> Result<Value<'tcx>, EvalErrorKind> {
+    lhs.check_defined()?;
+    rhs.check_defined()?;
+
+    if let Some(ordering) = compare(op, &lhs, &rhs)? {
+        return Ok(Value::Bool(match op {
+            BinOp::Eq => ordering == Some(Ordering::Equal),
+            BinOp::Ne => ordering != Some(Ordering::Equal),
+            BinOp::Lt => ordering == Some(Ordering::Less),
+            BinOp::Le => ordering == Some(Ordering::Less) || ordering == Some(Ordering::Equal),
+            BinOp::Gt => ordering == Some(Ordering::Greater),
+            BinOp::Ge => {
+                ordering == Some(Ordering::Greater) || ordering == Some(Ordering::Equal)
+            }
+            _ => bug!("binary_op: {:?} is not a comparison", op)
+        }));
+    }
+
+    Ok(match (lhs, rhs) {
+        (Value::Int(a), Value::Int(b)) => Value::Int(match op {
+            BinOp::Add => (a + b)?,
+            BinOp::Sub => (a - b)?,
+            BinOp::Mul => (a * b)?,
+            BinOp::Div => (a / b)?,
+            BinOp::Rem => (a % b)?,
+            BinOp::BitAnd => (a & b)?,
+            BinOp::BitOr => (a | b)?,
+            BinOp::BitXor => (a ^ b)?,
+            BinOp::Shl => (a << b)?,
+            BinOp::Shr => (a >> b)?,
+            _ => return Err(EvalErrorKind::Unsupported("binary operation on integers"))
+        }),
+        (Value::Bool(a), Value::Bool(b)) => Value::Bool(match op {
+            BinOp::BitAnd => a & b,
+            BinOp::BitOr => a | b,
+            BinOp::BitXor => a ^ b,
+            _ => return Err(EvalErrorKind::Unsupported("binary operation on booleans"))
+        }),
+        (Value::Float(a), Value::Float(b)) => Value::Float(match op {
+            BinOp::Add => (a + b)?,
+            BinOp::Sub => (a - b)?,
+            BinOp::Mul => (a * b)?,
+            BinOp::Div => (a / b)?,
+            BinOp::Rem => (a % b)?,
+            _ => return Err(EvalErrorKind::Unsupported("binary operation on floats"))
+        }),
+        _ => return Err(EvalErrorKind::Unsupported("binary operation"))
+    })
+}
+
+/// Like `binary_op`, but wrapping around on overflow, like the
+/// arithmetic of code built without overflow checks does.
+pub fn wrapping_binary_op<'tcx>(op: BinOp, lhs: Value<'tcx>, rhs: Value<'tcx>)
+                                -> Result<Value<'tcx>, EvalErrorKind> {
+    match (op, &lhs, &rhs) {
+        (BinOp::Add, &Value::Int(a), &Value::Int(b)) => {
+            Ok(Value::Int(truncate(a, a.to_u64_unchecked().wrapping_add(b.to_u64_unchecked()))))
+        }
+        (BinOp::Sub, &Value::Int(a), &Value::Int(b)) => {
+            Ok(Value::Int(truncate(a, a.to_u64_unchecked().wrapping_sub(b.to_u64_unchecked()))))
+        }
+        (BinOp::Mul, &Value::Int(a), &Value::Int(b)) => {
+            Ok(Value::Int(truncate(a, a.to_u64_unchecked().wrapping_mul(b.to_u64_unchecked()))))
+        }
+        (BinOp::Shl, &Value::Int(a), &Value::Int(b)) |
+        (BinOp::Shr, &Value::Int(a), &Value::Int(b)) => {
+            // Only the low bits of the shift amount count, as in trans.
+            let amount = b.to_u64_unchecked() & (bits(a) - 1);
+            binary_op(op, lhs.clone(), Value::Int(ConstInt::U32(amount as u32)))
+        }
+        _ => binary_op(op, lhs.clone(), rhs.clone())
+    }
+}
+
+/// Like `binary_op`, but also returning whether the operation overflowed,
+/// as `CheckedBinaryOp` does.
+pub fn checked_binary_op<'tcx>(op: BinOp, lhs: Value<'tcx>, rhs: Value<'tcx>)
+                               -> Result<(Value<'tcx>, bool), EvalErrorKind> {
+    match binary_op(op, lhs.clone(), rhs.clone()) {
+        Ok(value) => Ok((value, false)),
+        Err(EvalErrorKind::Math(Overflow(_))) => {
+            Ok((wrapping_binary_op(op, lhs, rhs)?, true))
+        }
+        Err(err) => Err(err)
+    }
+}
+
+pub fn unary_op<'tcx>(op: UnOp, value: Value<'tcx>) -> Result<Value<'tcx>, EvalErrorKind> {
+    Ok(match (op, value) {
+        (_, Value::Undef) => return Err(EvalErrorKind::ReadUndef),
+        (UnOp::Not, Value::Bool(b)) => Value::Bool(!b),
+        (UnOp::Not, Value::Int(i)) => Value::Int((!i)?),
+        // Negating the minimum value wraps around to itself.
+        (UnOp::Neg, Value::Int(i)) => match -i {
+            Ok(i) => Value::Int(i),
+            Err(Overflow(_)) => Value::Int(i),
+            Err(err) => return Err(err.into())
+        },
+        (UnOp::Neg, Value::Float(f)) => Value::Float(-f),
+        _ => return Err(EvalErrorKind::Unsupported("unary operation"))
+    })
+}
+
+/// Orders the operands of a comparison; `None` inside means they are
+/// unordered, as NaNs are.
+fn compare<'tcx>(op: BinOp, lhs: &Value<'tcx>, rhs: &Value<'tcx>)
+                 -> Result<Option<Option<Ordering>>, EvalErrorKind> {
+    match op {
+        BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {}
+        _ => return Ok(None)
+    }
+    Ok(Some(match (lhs, rhs) {
+        (&Value::Int(a), &Value::Int(b)) => Some(a.try_cmp(b)?),
+        (&Value::Bool(a), &Value::Bool(b)) => Some(a.cmp(&b)),
+        (&Value::Char(a), &Value::Char(b)) => Some(a.cmp(&b)),
+        (&Value::Float(a), &Value::Float(b)) => float_value(a).partial_cmp(&float_value(b)),
+        (&Value::Ptr(..), &Value::Ptr(..)) |
+        (&Value::Fn(..), &Value::Fn(..)) => match op {
+            BinOp::Eq | BinOp::Ne => {
+                if lhs == rhs { Some(Ordering::Equal) } else { None }
+            }
+            _ => return Err(EvalErrorKind::Unsupported("ordering of pointers"))
+        },
+        _ => return Err(EvalErrorKind::Unsupported("comparison"))
+    }))
+}
+
+fn float_value(f: ConstFloat) -> f64 {
+    match f {
+        ConstFloat::F32(f) => f as f64,
+        ConstFloat::F64(f) | ConstFloat::FInfer { f64: f, .. } => f,
+    }
+}
+
+fn bits(i: ConstInt) -> u64 {
+    match i {
+        ConstInt::I8(_) | ConstInt::U8(_) => 8,
+        ConstInt::I16(_) | ConstInt::U16(_) |
+        ConstInt::Isize(ConstIsize::Is16(_)) | ConstInt::Usize(ConstUsize::Us16(_)) => 16,
+        ConstInt::I32(_) | ConstInt::U32(_) |
+        ConstInt::Isize(ConstIsize::Is32(_)) | ConstInt::Usize(ConstUsize::Us32(_)) => 32,
+        _ => 64,
+    }
+}
+
+/// Makes an integer of the same type as `like` from the low bits of `v`.
+fn truncate(like: ConstInt, v: u64) -> ConstInt {
+    match like {
+        ConstInt::I8(_) => ConstInt::I8(v as i8),
+        ConstInt::I16(_) => ConstInt::I16(v as i16),
+        ConstInt::I32(_) => ConstInt::I32(v as i32),
+        ConstInt::I64(_) => ConstInt::I64(v as i64),
+        ConstInt::Isize(ConstIsize::Is16(_)) => ConstInt::Isize(ConstIsize::Is16(v as i16)),
+        ConstInt::Isize(ConstIsize::Is32(_)) => ConstInt::Isize(ConstIsize::Is32(v as i32)),
+        ConstInt::Isize(ConstIsize::Is64(_)) => ConstInt::Isize(ConstIsize::Is64(v as i64)),
+        ConstInt::U8(_) => ConstInt::U8(v as u8),
+        ConstInt::U16(_) => ConstInt::U16(v as u16),
+        ConstInt::U32(_) => ConstInt::U32(v as u32),
+        ConstInt::U64(_) => ConstInt::U64(v),
+        ConstInt::Usize(ConstUsize::Us16(_)) => ConstInt::Usize(ConstUsize::Us16(v as u16)),
+        ConstInt::Usize(ConstUsize::Us32(_)) => ConstInt::Usize(ConstUsize::Us32(v as u32)),
+        ConstInt::Usize(ConstUsize::Us64(_)) => ConstInt::Usize(ConstUsize::Us64(v)),
+        ConstInt::Infer(_) => ConstInt::Infer(v),
+        ConstInt::InferSigned(_) => ConstInt::InferSigned(v as i64),
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A JSON encoding of MIR for external tools, written by `-Z dump-mir`
+//! when `-Z dump-mir-json` is also passed.
+//!
+//! Every node is an object with a `kind` naming its variant and one key
+//! per field. Locals, blocks, scopes and promoted constants are referred
+//! to by index. Types, constants and paths are strings in the syntax of
+//! the pretty-printer, spans are objects giving the file and the 1-based
+//! line and column of both ends. New keys may be added without bumping
+//! `FORMAT_VERSION`.
+
+use rustc::mir::*;
+use rustc::mir::transform::MirSource;
+use rustc::ty::TyCtxt;
+use rustc_data_structures::indexed_vec::Idx;
+use serialize::json::{Json, Object};
+use std::fmt::Display;
+use syntax_pos::Span;
+
+/// The version of the format, bumped whenever a key is removed or its
+/// meaning changes.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// Encodes `mir`, the body of `src`, as dumped before or after the pass
+/// `pass_name` according to `disambiguator`.
+pub fn mir_to_json<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                             src: MirSource,
+                             pass_name: &str,
+                             disambiguator: &Display,
+                             mir: &Mir<'tcx>) -> Json {
+    let node_id = src.item_id();
+    let encoder = Encoder { tcx: tcx };
+    let promoted = match src {
+        MirSource::Promoted(_, index) => Json::U64(index.index() as u64),
+        _ => Json::Null
+    };
+    let locals = mir.local_decls.iter_enumerated().map(|(local, decl)| {
+        object(vec![
+            ("index", Json::U64(local.index() as u64)),
+            ("kind", string(format!("{:?}", mir.local_kind(local)))),
+            ("name", decl.name.map_or(Json::Null, |name| string(name.as_str()))),
+            ("ty", string(decl.ty)),
+            ("mutable", Json::Boolean(decl.mutability == Mutability::Mut)),
+            ("is_const", Json::Boolean(decl.is_const)),
+            ("source_info", decl.source_info.map_or(Json::Null,
+                                                    |info| encoder.source_info(info))),
+        ])
+    }).collect();
+    let scopes = mir.visibility_scopes.iter_enumerated().map(|(scope, data)| {
+        object(vec![
+            ("index", Json::U64(scope.index() as u64)),
+            ("parent", data.parent_scope.map_or(Json::Null,
+                                                |parent| Json::U64(parent.index() as u64))),
+            ("span", encoder.span(data.span)),
+        ])
+    }).collect();
+    let blocks = mir.basic_blocks().iter_enumerated().map(|(bb, data)| {
+        object(vec![
+            ("index", Json::U64(bb.index() as u64)),
+            ("is_cleanup", Json::Boolean(data.is_cleanup)),
+            ("statements", Json::Array(data.statements.iter().map(|statement| {
+                encoder.statement(statement)
+            }).collect())),
+            ("terminator", encoder.terminator(data.terminator())),
+        ])
+    }).collect();
+
+    object(vec![
+        ("format_version", Json::U64(FORMAT_VERSION)),
+        ("item", string(tcx.item_path_str(tcx.map.local_def_id(node_id)))),
+        ("node_id", Json::U64(node_id.as_u32() as u64)),
+        ("promoted", promoted),
+        ("pass", string(pass_name)),
+        ("disambiguator", string(disambiguator)),
+        ("span", encoder.span(mir.span)),
+        ("return_ty", string(mir.return_ty)),
+        ("arg_count", Json::U64(mir.arg_count as u64)),
+        ("locals", Json::Array(locals)),
+        ("scopes", Json::Array(scopes)),
+        ("blocks", Json::Array(blocks)),
+    ])
+}
+
+/// Encodes `span` as the file and the 1-based line and column of both ends.
+pub fn span_to_json(tcx: TyCtxt, span: Span) -> Json {
+    let codemap = tcx.sess.codemap();
+    let lo = codemap.lookup_char_pos(span.lo);
+    let hi = codemap.lookup_char_pos(span.hi);
+    object(vec![
+        ("file", string(&lo.file.name)),
+        ("lo_line", Json::U64(lo.line as u64)),
+        ("lo_col", Json::U64(lo.col.0 as u64 + 1)),
+        ("hi_line", Json::U64(hi.line as u64)),
+        ("hi_col", Json::U64(hi.col.0 as u64 + 1)),
+    ])
+}
+
+pub fn object(fields: Vec<(&str, Json)>) -> Json {
+    let mut object = Object::new();
+    for (key, value) in fields {
+        object.insert(key.to_string(), value);
+    }
+    Json::Object(object)
+}
+
+pub fn string<T: ToString>(value: T) -> Json {
+    Json::String(value.to_string())
+}
+
+fn block(bb: BasicBlock) -> Json {
+    Json::U64(bb.index() as u64)
+}
+
+fn opt_block(bb: Option<BasicBlock>) -> Json {
+    bb.map_or(Json::Null, block)
+}
+
+struct Encoder<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> Encoder<'a, 'tcx> {
+    fn span(&self, span: Span) -> Json {
+        span_to_json(self.tcx, span)
+    }
+
+    fn source_info(&self, source_info: SourceInfo) -> Json {
+        object(vec![
+            ("span", self.span(source_info.span)),
+            ("scope", Json::U64(source_info.scope.index() as u64)),
+        ])
+    }
+
+    fn statement(&self, statement: &Statement<'tcx>) -> Json {
+        let mut fields = match statement.kind {
+            StatementKind::Assign(ref lvalue, ref rvalue) => vec![
+                ("kind", string("Assign")),
+                ("lvalue", self.lvalue(lvalue)),
+                ("rvalue", self.rvalue(rvalue)),
+            ],
+            StatementKind::SetDiscriminant { ref lvalue, variant_index } => vec![
+                ("kind", string("SetDiscriminant")),
+                ("lvalue", self.lvalue(lvalue)),
+                ("variant_index", Json::U64(variant_index as u64)),
+            ],
+            StatementKind::StorageLive(ref lvalue) => vec![
+                ("kind", string("StorageLive")),
+                ("lvalue", self.lvalue(lvalue)),
+            ],
+            StatementKind::StorageDead(ref lvalue) => vec![
+                ("kind", string("StorageDead")),
+                ("lvalue", self.lvalue(lvalue)),
+            ],
+            StatementKind::IncrementCounter(counter) => vec![
+                ("kind", string("IncrementCounter")),
+                ("counter", Json::U64(counter as u64)),
+            ],
+            StatementKind::Poison(ref lvalue) => vec![
+                ("kind", string("Poison")),
+                ("lvalue", self.lvalue(lvalue)),
+            ],
+            StatementKind::Nop => vec![("kind", string("Nop"))],
+        };
+        fields.push(("source_info", self.source_info(statement.source_info)));
+        object(fields)
+    }
+
+    fn terminator(&self, terminator: &Terminator<'tcx>) -> Json {
+        let mut fields = match terminator.kind {
+            TerminatorKind::Goto { target } => vec![
+                ("kind", string("Goto")),
+                ("target", block(target)),
+            ],
+            TerminatorKind::SwitchInt { ref discr, switch_ty, ref values, ref targets } => vec![
+                ("kind", string("SwitchInt")),
+                ("discr", self.operand(discr)),
+                ("switch_ty", string(switch_ty)),
+                ("values", Json::Array(values.iter().map(|value| {
+                    let mut buf = String::new();
+                    fmt_const_val(&mut buf, value).unwrap();
+                    string(buf)
+                }).collect())),
+                ("targets", Json::Array(targets.iter().cloned().map(block).collect())),
+            ],
+            TerminatorKind::Resume => vec![("kind", string("Resume"))],
+            TerminatorKind::Abort => vec![("kind", string("Abort"))],
+            TerminatorKind::Return => vec![("kind", string("Return"))],
+            TerminatorKind::Unreachable => vec![("kind", string("Unreachable"))],
+            TerminatorKind::Drop { ref location, target, unwind } => vec![
+                ("kind", string("Drop")),
+                ("location", self.lvalue(location)),
+                ("target", block(target)),
+                ("unwind", opt_block(unwind)),
+            ],
+            TerminatorKind::DropAndReplace { ref location, ref value, target, unwind } => vec![
+                ("kind", string("DropAndReplace")),
+                ("location", self.lvalue(location)),
+                ("value", self.operand(value)),
+                ("target", block(target)),
+                ("unwind", opt_block(unwind)),
+            ],
+            TerminatorKind::Call { ref func, ref args, ref destination, cleanup } => vec![
+                ("kind", string("Call")),
+                ("func", self.operand(func)),
+                ("args", self.operands(args)),
+                ("destination", match *destination {
+                    Some((ref lvalue, target)) => object(vec![
+                        ("lvalue", self.lvalue(lvalue)),
+                        ("target", block(target)),
+                    ]),
+                    None => Json::Null
+                }),
+                ("cleanup", opt_block(cleanup)),
+            ],
+            TerminatorKind::Assert { ref cond, expected, ref msg, target, cleanup } => vec![
+                ("kind", string("Assert")),
+                ("cond", self.operand(cond)),
+                ("expected", Json::Boolean(expected)),
+                ("msg", match **msg {
+                    AssertMessage::BoundsCheck { ref len, ref index } => object(vec![
+                        ("kind", string("BoundsCheck")),
+                        ("len", self.operand(len)),
+                        ("index", self.operand(index)),
+                    ]),
+                    AssertMessage::Math(ref err) => object(vec![
+                        ("kind", string("Math")),
+                        ("description", string(err.description())),
+                    ]),
+                    AssertMessage::NullPointerDeref => object(vec![
+                        ("kind", string("NullPointerDeref")),
+                    ]),
+                    AssertMessage::MisalignedPointerDeref => object(vec![
+                        ("kind", string("MisalignedPointerDeref")),
+                    ]),
+                }),
+                ("target", block(target)),
+                ("cleanup", opt_block(cleanup)),
+            ],
+        };
+        fields.push(("source_info", self.source_info(terminator.source_info)));
+        object(fields)
+    }
+
+    fn lvalue(&self, lvalue: &Lvalue<'tcx>) -> Json {
+        match *lvalue {
+            Lvalue::Local(local) => object(vec![
+                ("kind", string("Local")),
+                ("local", Json::U64(local.index() as u64)),
+            ]),
+            Lvalue::Static(def_id) => object(vec![
+                ("kind", string("Static")),
+                ("path", string(self.tcx.item_path_str(def_id))),
+            ]),
+            Lvalue::Projection(ref proj) => object(vec![
+                ("kind", string("Projection")),
+                ("base", self.lvalue(&proj.base)),
+                ("elem", self.projection_elem(&proj.elem)),
+            ]),
+        }
+    }
+
+    fn projection_elem(&self, elem: &LvalueElem<'tcx>) -> Json {
+        match *elem {
+            ProjectionElem::Deref => object(vec![("kind", string("Deref"))]),
+            ProjectionElem::Field(field, ty) => object(vec![
+                ("kind", string("Field")),
+                ("field", Json::U64(field.index() as u64)),
+                ("ty", string(ty)),
+            ]),
+            ProjectionElem::Index(ref index) => object(vec![
+                ("kind", string("Index")),
+                ("index", self.operand(index)),
+            ]),
+            ProjectionElem::ConstantIndex { offset, min_length, from_end } => object(vec![
+                ("kind", string("ConstantIndex")),
+                ("offset", Json::U64(offset as u64)),
+                ("min_length", Json::U64(min_length as u64)),
+                ("from_end", Json::Boolean(from_end)),
+            ]),
+            ProjectionElem::Subslice { from, to } => object(vec![
+                ("kind", string("Subslice")),
+                ("from", Json::U64(from as u64)),
+                ("to", Json::U64(to as u64)),
+            ]),
+            ProjectionElem::Downcast(adt_def, variant_index) => object(vec![
+                ("kind", string("Downcast")),
+                ("adt", string(self.tcx.item_path_str(adt_def.did))),
+                ("variant_index", Json::U64(variant_index as u64)),
+                ("variant_name", string(adt_def.variants[variant_index].name)),
+            ]),
+        }
+    }
+
+    fn operand(&self, operand: &Operand<'tcx>) -> Json {
+        match *operand {
+            Operand::Consume(ref lvalue) => object(vec![
+                ("kind", string("Consume")),
+                ("lvalue", self.lvalue(lvalue)),
+            ]),
+            Operand::Constant(ref constant) => {
+                let literal = match constant.literal {
+                    Literal::Item { .. } => object(vec![
+                        ("kind", string("Item")),
+                        ("path", string(format!("{:?}", constant.literal))),
+                    ]),
+                    Literal::Value { ref value } => {
+                        let mut buf = String::new();
+                        fmt_const_val(&mut buf, value).unwrap();
+                        object(vec![
+                            ("kind", string("Value")),
+                            ("value", string(buf)),
+                        ])
+                    }
+                    Literal::Promoted { index } => object(vec![
+                        ("kind", string("Promoted")),
+                        ("index", Json::U64(index.index() as u64)),
+                    ]),
+                    Literal::Outlined { index } => object(vec![
+                        ("kind", string("Outlined")),
+                        ("index", Json::U64(index.index() as u64)),
+                    ]),
+                };
+                object(vec![
+                    ("kind", string("Constant")),
+                    ("ty", string(constant.ty)),
+                    ("literal", literal),
+                    ("span", self.span(constant.span)),
+                ])
+            }
+        }
+    }
+
+    fn operands(&self, operands: &[Operand<'tcx>]) -> Json {
+        Json::Array(operands.iter().map(|operand| self.operand(operand)).collect())
+    }
+
+    fn rvalue(&self, rvalue: &Rvalue<'tcx>) -> Json {
+        match *rvalue {
+            Rvalue::Use(ref operand) => object(vec![
+                ("kind", string("Use")),
+                ("operand", self.operand(operand)),
+            ]),
+            Rvalue::Repeat(ref operand, ref count) => object(vec![
+                ("kind", string("Repeat")),
+                ("operand", self.operand(operand)),
+                ("count", Json::U64(count.value.as_u64(self.tcx.sess.target.uint_type))),
+            ]),
+            Rvalue::Ref(region, borrow_kind, ref lvalue) => object(vec![
+                ("kind", string("Ref")),
+                ("region", string(format!("{:?}", region))),
+                ("borrow_kind", string(format!("{:?}", borrow_kind))),
+                ("lvalue", self.lvalue(lvalue)),
+            ]),
+            Rvalue::Len(ref lvalue) => object(vec![
+                ("kind", string("Len")),
+                ("lvalue", self.lvalue(lvalue)),
+            ]),
+            Rvalue::Discriminant(ref lvalue) => object(vec![
+                ("kind", string("Discriminant")),
+                ("lvalue", self.lvalue(lvalue)),
+            ]),
+            Rvalue::Cast(cast_kind, ref operand, ty) => object(vec![
+                ("kind", string("Cast")),
+                ("cast_kind", string(format!("{:?}", cast_kind))),
+                ("operand", self.operand(operand)),
+                ("ty", string(ty)),
+            ]),
+            Rvalue::BinaryOp(op, ref lhs, ref rhs) => object(vec![
+                ("kind", string("BinaryOp")),
+                ("op", string(format!("{:?}", op))),
+                ("lhs", self.operand(lhs)),
+                ("rhs", self.operand(rhs)),
+            ]),
+            Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => object(vec![
+                ("kind", string("CheckedBinaryOp")),
+                ("op", string(format!("{:?}", op))),
+                ("lhs", self.operand(lhs)),
+                ("rhs", self.operand(rhs)),
+            ]),
+            Rvalue::UnaryOp(op, ref operand) => object(vec![
+                ("kind", string("UnaryOp")),
+                ("op", string(format!("{:?}", op))),
+                ("operand", self.operand(operand)),
+            ]),
+            Rvalue::Box(ty) => object(vec![
+                ("kind", string("Box")),
+                ("ty", string(ty)),
+            ]),
+            Rvalue::Aggregate(ref kind, ref operands) => object(vec![
+                ("kind", string("Aggregate")),
+                ("aggregate", self.aggregate_kind(kind)),
+                ("operands", self.operands(operands)),
+            ]),
+            Rvalue::InlineAsm { ref asm, ref outputs, ref inputs } => object(vec![
+                ("kind", string("InlineAsm")),
+                ("asm", string(asm.asm.as_str())),
+                ("outputs", Json::Array(outputs.iter().map(|output| {
+                    self.lvalue(output)
+                }).collect())),
+                ("inputs", self.operands(inputs)),
+            ]),
+        }
+    }
+
+    fn aggregate_kind(&self, kind: &AggregateKind<'tcx>) -> Json {
+        match *kind {
+            AggregateKind::Array => object(vec![("kind", string("Array"))]),
+            AggregateKind::Tuple => object(vec![("kind", string("Tuple"))]),
+            AggregateKind::Adt(adt_def, variant_index, _, active_field) => object(vec![
+                ("kind", string("Adt")),
+                ("adt", string(self.tcx.item_path_str(adt_def.did))),
+                ("variant_index", Json::U64(variant_index as u64)),
+                ("active_field", active_field.map_or(Json::Null,
+                                                     |field| Json::U64(field as u64))),
+            ]),
+            AggregateKind::Closure(def_id, _) => object(vec![
+                ("kind", string("Closure")),
+                ("path", string(self.tcx.item_path_str(def_id))),
+            ]),
+        }
+    }
+}
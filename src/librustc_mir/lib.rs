@@ -41,14 +41,23 @@ extern crate syntax;
 extern crate syntax_pos;
 extern crate rustc_const_math;
 extern crate rustc_const_eval;
+extern crate serialize;
 
 pub mod diagnostics;
 
 pub mod build;
+pub mod borrows;
 pub mod def_use;
+pub mod fuzz;
 pub mod graphviz;
+pub mod internalize;
 mod hair;
+pub mod html;
+pub mod interpret;
+pub mod json;
 pub mod mir_map;
 pub mod pretty;
+pub mod reachable;
+pub mod shim;
 pub mod transform;
 
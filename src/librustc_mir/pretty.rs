@@ -12,7 +12,12 @@ use rustc::hir;
 use rustc::hir::def_id::DefId;
 use rustc::mir::*;
 use rustc::mir::transform::MirSource;
+use rustc::mir::visit::{LvalueContext, Visitor};
 use rustc::ty::TyCtxt;
+use syntax::ast::{self, NodeId};
+use syntax::codemap::Spanned;
+use html;
+use json;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_data_structures::indexed_vec::{Idx};
 use std::fmt::Display;
@@ -38,27 +43,39 @@ const ALIGN: usize = 40;
 /// - `substring1&substring2,...` -- `&`-separated list of substrings
 ///   that can appear in the pass-name or the `item_path_str` for the given
 ///   node-id. If any one of the substrings match, the data is dumped out.
+///
+/// The MIR of a single item can also be dumped by marking it with
+/// `#[rustc_dump_mir]`, or `#[rustc_dump_mir(Pass1, "Pass2", ...)]` to only
+/// dump the passes whose names contain one of the given substrings.
+///
+/// With `-Z dump-mir-json`, the MIR is written in the JSON encoding of the
+/// `json` module instead, into a file with the `.json` extension. With
+/// `-Z dump-mir-html`, it is added as a tab to the page of the item,
+/// `rustc.node<node_id>.html`. With `-Z dump-mir-verbose`, the text gives
+/// the types of the lvalues and constants of each statement, and which
+/// blocks are cleanup blocks and what their predecessors are.
 pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                           pass_name: &str,
                           disambiguator: &Display,
                           src: MirSource,
                           mir: &Mir<'tcx>) {
-    let filters = match tcx.sess.opts.debugging_opts.dump_mir {
-        None => return,
-        Some(ref filters) => filters,
-    };
     let node_id = src.item_id();
-    let node_path = tcx.item_path_str(tcx.map.local_def_id(node_id));
-    let is_matched =
-        filters.split("&")
-               .any(|filter| {
-                   filter == "all" ||
-                       pass_name.contains(filter) ||
-                       node_path.contains(filter)
-               });
-    if !is_matched {
+    let is_matched = match tcx.sess.opts.debugging_opts.dump_mir {
+        Some(ref filters) => {
+            let node_path = tcx.item_path_str(tcx.map.local_def_id(node_id));
+            filters.split("&")
+                   .any(|filter| {
+                       filter == "all" ||
+                           pass_name.contains(filter) ||
+                           node_path.contains(filter)
+                   })
+        }
+        None => false
+    };
+    if !is_matched && !dump_requested_by_attr(tcx, node_id, pass_name) {
         return;
     }
+    let node_path = tcx.item_path_str(tcx.map.local_def_id(node_id));
 
     let promotion_id = match src {
         MirSource::Promoted(_, id) => format!("-{:?}", id),
@@ -70,9 +87,26 @@ pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
         let p = Path::new(file_dir);
         file_path.push(p);
     };
-    let file_name = format!("rustc.node{}{}.{}.{}.mir",
-                            node_id, promotion_id, pass_name, disambiguator);
+
+    if tcx.sess.opts.debugging_opts.dump_mir_html {
+        let title = format!("{}{} {}", pass_name, promotion_id, disambiguator);
+        file_path.push(&format!("rustc.node{}.html", node_id));
+        let _ = html::write_mir_html_tab(tcx, src, &title, mir, &file_path);
+        return;
+    }
+
+    let as_json = tcx.sess.opts.debugging_opts.dump_mir_json;
+    let file_name = format!("rustc.node{}{}.{}.{}.{}",
+                            node_id, promotion_id, pass_name, disambiguator,
+                            if as_json { "json" } else { "mir" });
     file_path.push(&file_name);
+    if as_json {
+        let json = json::mir_to_json(tcx, src, pass_name, disambiguator, mir);
+        let _ = fs::File::create(&file_path).and_then(|mut file| {
+            writeln!(file, "{}", json.pretty())
+        });
+        return;
+    }
     let _ = fs::File::create(&file_path).and_then(|mut file| {
         writeln!(file, "// MIR for `{}`", node_path)?;
         writeln!(file, "// node_id = {}", node_id)?;
@@ -84,6 +118,31 @@ pub fn dump_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     });
 }
 
+/// Whether the item `node_id` is marked with a `#[rustc_dump_mir]` asking
+/// for its MIR after or before `pass_name`.
+fn dump_requested_by_attr(tcx: TyCtxt, node_id: NodeId, pass_name: &str) -> bool {
+    tcx.map.attrs(node_id).iter().any(|attr| {
+        if !attr.check_name("rustc_dump_mir") {
+            return false;
+        }
+        let items = match attr.meta_item_list() {
+            Some(items) => items,
+            None => return true
+        };
+        items.iter().any(|item| {
+            let filter = match (item.word(), item.literal()) {
+                (Some(word), _) => word.name().as_str(),
+                (_, Some(&Spanned { node: ast::LitKind::Str(name, _), .. })) => name.as_str(),
+                _ => {
+                    tcx.sess.span_err(item.span, "expected the name of a MIR pass");
+                    return false;
+                }
+            };
+            pass_name.contains(&*filter)
+        })
+    })
+}
+
 /// Write out a human-readable textual representation for the given MIR.
 pub fn write_mir_pretty<'a, 'b, 'tcx, I>(tcx: TyCtxt<'b, 'tcx, 'tcx>,
                                          iter: I,
@@ -132,15 +191,25 @@ pub fn write_mir_fn<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
 }
 
 /// Write out a human-readable textual representation for the given basic block.
-fn write_basic_block(tcx: TyCtxt,
-                     block: BasicBlock,
-                     mir: &Mir,
-                     w: &mut Write)
-                     -> io::Result<()> {
+fn write_basic_block<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               block: BasicBlock,
+                               mir: &Mir<'tcx>,
+                               w: &mut Write)
+                               -> io::Result<()> {
     let data = &mir[block];
+    let verbose = tcx.sess.opts.debugging_opts.dump_mir_verbose;
 
     // Basic block label at the top.
-    writeln!(w, "{}{:?}: {{", INDENT, block)?;
+    if verbose {
+        let label = format!("{}{:?}: {{", INDENT, block);
+        writeln!(w, "{0:1$} // {2}predecessors: {3:?}",
+                 label,
+                 ALIGN,
+                 if data.is_cleanup { "cleanup, " } else { "" },
+                 mir.predecessors_for(block))?;
+    } else {
+        writeln!(w, "{}{:?}: {{", INDENT, block)?;
+    }
 
     // List of statements in the middle.
     let mut current_location = Location { block: block, statement_index: 0 };
@@ -150,6 +219,11 @@ fn write_basic_block(tcx: TyCtxt,
                  indented_mir,
                  ALIGN,
                  comment(tcx, statement.source_info))?;
+        if verbose {
+            let mut types = Types { tcx: tcx, mir: mir, types: vec![] };
+            types.visit_statement(block, statement, current_location);
+            types.write(w)?;
+        }
 
         current_location.statement_index += 1;
     }
@@ -160,10 +234,54 @@ fn write_basic_block(tcx: TyCtxt,
              indented_terminator,
              ALIGN,
              comment(tcx, data.terminator().source_info))?;
+    if verbose {
+        let mut types = Types { tcx: tcx, mir: mir, types: vec![] };
+        types.visit_terminator(block, data.terminator(), current_location);
+        types.write(w)?;
+    }
 
     writeln!(w, "{}}}", INDENT)
 }
 
+/// The types of the lvalues and constants of a statement or terminator,
+/// for `-Z dump-mir-verbose`.
+struct Types<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'a Mir<'tcx>,
+    types: Vec<String>,
+}
+
+impl<'a, 'tcx> Types<'a, 'tcx> {
+    fn push(&mut self, annotation: String) {
+        if !self.types.contains(&annotation) {
+            self.types.push(annotation);
+        }
+    }
+
+    fn write(&self, w: &mut Write) -> io::Result<()> {
+        if self.types.is_empty() {
+            return Ok(());
+        }
+        writeln!(w, "{0}{0}// {1}", INDENT, self.types.join(", "))
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for Types<'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        let ty = lvalue.ty(self.mir, self.tcx).to_ty(self.tcx);
+        self.push(format!("{:?}: {}", lvalue, ty));
+        self.super_lvalue(lvalue, context, location);
+    }
+
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, location: Location) {
+        self.push(format!("{:?}: {}", constant, constant.ty));
+        self.super_constant(constant, location);
+    }
+}
+
 fn comment(tcx: TyCtxt, SourceInfo { span, scope }: SourceInfo) -> String {
     format!("scope {} at {}", scope.index(), tcx.sess.codemap().span_to_string(span))
 }
@@ -190,6 +308,20 @@ fn write_scope_tree(tcx: TyCtxt,
         assert_eq!(data.parent_scope, Some(parent));
         writeln!(w, "{0:1$}scope {2} {{", "", indent, child.index())?;
 
+        // Where the debuginfo of user variables points.
+        for var in &mir.var_debug_info {
+            if var.source_info.scope != child {
+                continue;
+            }
+            let indent = indent + INDENT.len();
+            let value = match var.value {
+                VarDebugInfoContents::Lvalue(ref lvalue) => format!("{:?}", lvalue),
+                VarDebugInfoContents::Const(ref constant) => format!("{:?}", constant),
+                VarDebugInfoContents::OptimizedOut => "<optimized out>".to_string(),
+            };
+            writeln!(w, "{0:1$}debug {2} => {3};", INDENT, indent, var.name, value)?;
+        }
+
         // User variable types (including the user's name in a comment).
         for local in mir.vars_iter() {
             let var = &mir.local_decls[local];
@@ -270,7 +402,7 @@ fn write_mir_intro<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     Ok(())
 }
 
-fn write_mir_sig(tcx: TyCtxt, src: MirSource, mir: &Mir, w: &mut Write)
+pub fn write_mir_sig(tcx: TyCtxt, src: MirSource, mir: &Mir, w: &mut Write)
                  -> io::Result<()>
 {
     match src {
@@ -278,7 +410,8 @@ fn write_mir_sig(tcx: TyCtxt, src: MirSource, mir: &Mir, w: &mut Write)
         MirSource::Const(_) => write!(w, "const")?,
         MirSource::Static(_, hir::MutImmutable) => write!(w, "static")?,
         MirSource::Static(_, hir::MutMutable) => write!(w, "static mut")?,
-        MirSource::Promoted(_, i) => write!(w, "{:?} in", i)?
+        MirSource::Promoted(_, i) => write!(w, "{:?} in", i)?,
+        MirSource::Shim => write!(w, "shim in")?
     }
 
     write!(w, " {}", tcx.node_path_str(src.item_id()))?;
@@ -0,0 +1,141 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recomputes reachability from the final, optimized MIR.
+//!
+//! `rustc::middle::reachable` works on the HIR, before any MIR exists.
+//! Once MIR optimizations have run, the MIR of generic and `#[inline]`
+//! functions (which is what other crates instantiate) can refer to items
+//! their HIR never mentioned, e.g. the callees of a function that was
+//! inlined into them. Those items have to be exported as well, or the
+//! downstream crates instantiating that MIR would fail to link.
+
+use rustc::dep_graph::DepNode;
+use rustc::hir::def_id::DefId;
+use rustc::hir::map::DefPathData;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::Location;
+use rustc::mir::transform::MirSource;
+use rustc::mir::visit::Visitor;
+use rustc::session::config;
+use rustc::ty::TyCtxt;
+use rustc::util::nodemap::{DefIdSet, NodeSet};
+use syntax::attr;
+
+use transform::qualify_consts::is_const_fn;
+
+/// Adds to `reachable` every local item referenced from the optimized MIR
+/// of an item whose MIR is made available to other crates.
+pub fn extend_reachable_from_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                           reachable: &mut NodeSet) {
+    let _task = tcx.dep_graph.in_task(DepNode::Reachability);
+
+    // Executables only export items with an explicit extern
+    // indicator, which MIR optimizations can't introduce.
+    let any_library = tcx.sess.crate_types.borrow().iter().any(|ty| {
+        *ty == config::CrateTypeRlib || *ty == config::CrateTypeDylib ||
+        *ty == config::CrateTypeProcMacro || *ty == config::CrateTypeMetadata
+    });
+    if !any_library {
+        return;
+    }
+
+    let mut worklist: Vec<DefId> = reachable.iter()
+        .map(|&id| tcx.map.local_def_id(id))
+        .filter(|&def_id| is_mir_exported(tcx, def_id))
+        .collect();
+    let mut scanned = DefIdSet();
+
+    while let Some(def_id) = worklist.pop() {
+        if !scanned.insert(def_id) {
+            continue;
+        }
+
+        let mir = match tcx.mir_map.borrow().get(&def_id) {
+            Some(&mir) => mir,
+            None => continue,
+        };
+
+        let mut collector = ReferenceCollector {
+            tcx: tcx,
+            reachable: reachable,
+            worklist: &mut worklist,
+        };
+        let mir = mir.borrow();
+        collector.visit_mir(&mir);
+        for promoted in &mir.promoted {
+            collector.visit_mir(promoted);
+        }
+    }
+}
+
+/// Whether the MIR of a local item is encoded into the crate metadata,
+/// mirroring the choice made by the metadata encoder.
+fn is_mir_exported<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> bool {
+    if !tcx.mir_map.borrow().contains_key(&def_id) {
+        return false;
+    }
+
+    // Closures always have their MIR encoded.
+    if tcx.def_key(def_id).disambiguated_data.data == DefPathData::ClosureExpr {
+        return true;
+    }
+
+    let id = tcx.map.as_local_node_id(def_id).unwrap();
+    match MirSource::from_node(tcx, id) {
+        MirSource::Const(_) => true,
+        MirSource::Static(..) => false,
+        MirSource::Fn(_) => {
+            let generics = tcx.item_generics(def_id);
+            generics.parent_types > 0 || !generics.types.is_empty() ||
+                attr::requests_inline(&tcx.get_attrs(def_id)) ||
+                is_const_fn(tcx, def_id)
+        }
+        MirSource::Promoted(..) | MirSource::Shim => bug!(),
+    }
+}
+
+struct ReferenceCollector<'a, 'b, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    reachable: &'b mut NodeSet,
+    worklist: &'b mut Vec<DefId>,
+}
+
+impl<'a, 'b, 'tcx> ReferenceCollector<'a, 'b, 'tcx> {
+    fn mark_reachable(&mut self, def_id: DefId) {
+        let node_id = match self.tcx.map.as_local_node_id(def_id) {
+            Some(node_id) => node_id,
+            None => return,
+        };
+
+        if is_mir_exported(self.tcx, def_id) {
+            // Its MIR will be instantiated downstream too, so
+            // whatever it refers to has to be reachable as well.
+            self.worklist.push(def_id);
+        }
+
+        if self.reachable.insert(node_id) {
+            debug!("extend_reachable_from_mir: {} became reachable",
+                   self.tcx.item_path_str(def_id));
+        }
+    }
+}
+
+impl<'a, 'b, 'tcx> Visitor<'tcx> for ReferenceCollector<'a, 'b, 'tcx> {
+    fn visit_def_id(&mut self, def_id: &DefId, _: Location) {
+        self.mark_reachable(*def_id);
+    }
+
+    fn visit_const_val(&mut self, const_val: &ConstVal, _: Location) {
+        if let ConstVal::Function(def_id) = *const_val {
+            self.mark_reachable(def_id);
+        }
+    }
+}
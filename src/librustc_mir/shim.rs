@@ -0,0 +1,572 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! MIR bodies for compiler-generated functions, so trans can translate
+//! them like any other MIR instead of emitting LLVM IR by hand. They are
+//! built when trans first needs them, by `shim_mir`, which runs the
+//! optimizations of the pass pipeline on them and keeps them in
+//! `tcx.mir_shims`.
+//!
+//! The drop glue of a type `T` is `fn(*mut T)`: it runs the `Drop` impl
+//! of `T`, if any, and then drops each of its fields (the active variant's
+//! for enums, every element for arrays) with `Drop` terminators, which in
+//! turn call the glue of the field types. If dropping a field panics, the
+//! remaining ones are still dropped on the way out.
+//!
+//! The other shims adapt a callee to the signature of a trait method:
+//! `FnOnce::call_once` for closures implementing `Fn` or `FnMut`, the `Fn`
+//! traits for fn pointers and fn items, and the methods of trait objects
+//! called through a fn pointer.
+
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource};
+use rustc::ty::{self, AdtDef, ClosureSubsts, Ty, TyCtxt};
+use rustc::ty::subst::{Subst, Substs};
+use rustc_const_math::{ConstInt, ConstUsize};
+use rustc_data_structures::indexed_vec::{IndexVec, Idx};
+use syntax::abi::Abi;
+use syntax_pos::{Span, DUMMY_SP};
+use transform::pipeline;
+
+use std::cell::RefCell;
+use std::iter;
+
+/// The MIR of `shim`, which is built and optimized the first time it is
+/// asked for. Returns `None` for the drop glue still built by trans, i.e.
+/// that of boxes, trait objects and unsized types.
+pub fn shim_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, shim: Shim<'tcx>)
+                          -> Option<&'tcx RefCell<Mir<'tcx>>> {
+    if let Some(&mir) = tcx.mir_shims.borrow().get(&shim) {
+        return Some(mir);
+    }
+
+    let mut mir = match shim {
+        Shim::DropGlue(ty) => match build_drop_shim(tcx, ty) {
+            Some(mir) => mir,
+            None => return None
+        },
+        Shim::FnOnceAdapter(def_id, substs) => build_fn_once_adapter_shim(tcx, def_id, substs),
+        Shim::FnPtr(fn_ty, by_ref) => build_fn_ptr_shim(tcx, fn_ty, by_ref),
+        Shim::Object(def_id, substs) => build_object_shim(tcx, def_id, substs),
+    };
+    // The shims are built for trans, after the pass pipeline ran on the
+    // rest of the crate, so they go through its optimizations here.
+    for mut pass in pipeline::optimizations() {
+        pass.run_pass(tcx, MirSource::Shim, &mut mir);
+    }
+    let mir = tcx.alloc_mir(mir);
+    tcx.mir_shims.borrow_mut().insert(shim, mir);
+    Some(mir)
+}
+
+/// Builds the drop glue of `ty`, which has to be monomorphic.
+fn build_drop_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>)
+                             -> Option<Mir<'tcx>> {
+    debug!("build_drop_shim({:?})", ty);
+    match ty.sty {
+        ty::TyBox(_) | ty::TyDynamic(..) | ty::TySlice(_) | ty::TyStr => return None,
+        _ => {}
+    }
+    if !ty.is_sized(tcx, &tcx.empty_parameter_environment(), DUMMY_SP) {
+        return None;
+    }
+
+    let mut builder = ShimBuilder::new(tcx, tcx.mk_nil(), &[tcx.mk_mut_ptr(ty)]);
+    let return_block = builder.new_block(false, TerminatorKind::Return);
+    let resume_block = builder.resume_block();
+    let value = Lvalue::Local(Local::new(1)).deref();
+    let start = builder.drop_value(value, ty, return_block, resume_block);
+    Some(builder.finish(start, None))
+}
+
+/// Builds the `FnOnce::call_once` of a closure whose own body takes its
+/// environment by reference, which calls that body directly:
+///
+/// ```text
+/// fn call_once(self, args) -> R {
+///     let r = closure_body(&mut self, args); // or `&self`
+///     drop(self);
+///     r
+/// }
+/// ```
+fn build_fn_once_adapter_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                        closure_def_id: DefId,
+                                        substs: ClosureSubsts<'tcx>)
+                                        -> Mir<'tcx> {
+    debug!("build_fn_once_adapter_shim({:?}, {:?})", closure_def_id, substs);
+    let closure_ty = tcx.mk_closure_from_closure_substs(closure_def_id, substs);
+    let ty::ClosureTy { unsafety, abi, sig } = tcx.closure_type(closure_def_id, substs);
+    let sig = tcx.erase_late_bound_regions_and_normalize(&sig);
+    let args_ty = sig.inputs()[0];
+
+    let region = tcx.mk_region(ty::ReErased);
+    let closure_kind = tcx.closure_kind(closure_def_id);
+    let (env_ty, borrow_kind) = match closure_kind {
+        ty::ClosureKind::Fn => (tcx.mk_imm_ref(region, closure_ty), BorrowKind::Shared),
+        ty::ClosureKind::FnMut => (tcx.mk_mut_ref(region, closure_ty), BorrowKind::Mut),
+        ty::ClosureKind::FnOnce => bug!("build_fn_once_adapter_shim: FnOnce closure"),
+    };
+    // The closure body is a function of the environment reference and the
+    // arguments, which trans calls like a fn item.
+    let body_sig = tcx.mk_fn_sig(iter::once(env_ty).chain(sig.inputs().iter().cloned()),
+                                 sig.output(),
+                                 sig.variadic);
+    let body_ty = tcx.mk_fn_def(closure_def_id, substs.substs, tcx.mk_bare_fn(ty::BareFnTy {
+        unsafety: unsafety,
+        abi: abi,
+        sig: ty::Binder(body_sig),
+    }));
+
+    let mut builder = ShimBuilder::new(tcx, sig.output(), &[closure_ty, args_ty]);
+    let env = Lvalue::Local(Local::new(1));
+    let env_ref = builder.new_temp(env_ty);
+    let borrow = builder.assign(env_ref.clone(), Rvalue::Ref(region, borrow_kind, env.clone()));
+
+    // Either way, `self` is dropped afterwards.
+    let return_block = builder.new_block(false, TerminatorKind::Return);
+    let drop_env = builder.new_block(false, TerminatorKind::Drop {
+        location: env.clone(),
+        target: return_block,
+        unwind: None,
+    });
+    let cleanup = builder.resume_block().map(|resume| {
+        builder.new_block(true, TerminatorKind::Drop {
+            location: env,
+            target: resume,
+            unwind: None,
+        })
+    });
+    let call = TerminatorKind::Call {
+        func: Operand::Constant(Constant {
+            span: builder.span,
+            ty: body_ty,
+            literal: Literal::Item { def_id: closure_def_id, substs: substs.substs },
+        }),
+        args: vec![Operand::Consume(env_ref), Operand::Consume(Lvalue::Local(Local::new(2)))],
+        destination: Some((Lvalue::Local(RETURN_POINTER), drop_env)),
+        cleanup: cleanup,
+    };
+    let start = builder.new_block_with_statements(false, vec![borrow], call);
+    builder.finish(start, Some(Local::new(2)))
+}
+
+/// Builds the `Fn`, `FnMut` or `FnOnce` method of a fn pointer or fn item
+/// of type `fn_ty`, which calls it with the arguments untupled:
+///
+/// ```text
+/// fn call(&self, args: (A, B)) -> R {
+///     (*self)(args.0, args.1)
+/// }
+/// ```
+///
+/// `self` is taken by value if `by_ref` is false.
+fn build_fn_ptr_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               fn_ty: Ty<'tcx>,
+                               by_ref: bool)
+                               -> Mir<'tcx> {
+    debug!("build_fn_ptr_shim({:?}, {:?})", fn_ty, by_ref);
+    let sig = tcx.erase_late_bound_regions_and_normalize(fn_ty.fn_sig());
+    let self_ty = if by_ref {
+        tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), fn_ty)
+    } else {
+        fn_ty
+    };
+    let args_ty = tcx.intern_tup(sig.inputs());
+
+    let mut builder = ShimBuilder::new(tcx, sig.output(), &[self_ty, args_ty]);
+    let callee = if by_ref {
+        Lvalue::Local(Local::new(1)).deref()
+    } else {
+        Lvalue::Local(Local::new(1))
+    };
+    let args = sig.inputs().iter().enumerate().map(|(i, &ty)| {
+        Operand::Consume(Lvalue::Local(Local::new(2)).field(Field::new(i), ty))
+    }).collect();
+
+    let return_block = builder.new_block(false, TerminatorKind::Return);
+    let start = builder.new_block(false, TerminatorKind::Call {
+        func: Operand::Consume(callee),
+        args: args,
+        destination: Some((Lvalue::Local(RETURN_POINTER), return_block)),
+        cleanup: None,
+    });
+    builder.finish(start, Some(Local::new(2)))
+}
+
+/// Builds the function a trait object method `def_id` is reified to,
+/// which makes the virtual call with its own arguments.
+fn build_object_shim<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               def_id: DefId,
+                               substs: &'tcx Substs<'tcx>)
+                               -> Mir<'tcx> {
+    debug!("build_object_shim({:?}, {:?})", def_id, substs);
+    let fn_ty = tcx.item_type(def_id).subst(tcx, substs);
+    let sig = tcx.erase_late_bound_regions_and_normalize(fn_ty.fn_sig());
+
+    let mut builder = ShimBuilder::new(tcx, sig.output(), sig.inputs());
+    let args = (1..sig.inputs().len() + 1).map(|i| {
+        Operand::Consume(Lvalue::Local(Local::new(i)))
+    }).collect();
+
+    let func = builder.item(def_id, substs);
+    let return_block = builder.new_block(false, TerminatorKind::Return);
+    let start = builder.new_block(false, TerminatorKind::Call {
+        func: func,
+        args: args,
+        destination: Some((Lvalue::Local(RETURN_POINTER), return_block)),
+        cleanup: None,
+    });
+    let spread_arg = if fn_ty.fn_abi() == Abi::RustCall {
+        Some(Local::new(sig.inputs().len()))
+    } else {
+        None
+    };
+    builder.finish(start, spread_arg)
+}
+
+struct ShimBuilder<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    span: Span,
+    basic_blocks: IndexVec<BasicBlock, BasicBlockData<'tcx>>,
+    local_decls: IndexVec<Local, LocalDecl<'tcx>>,
+    arg_count: usize,
+    return_ty: Ty<'tcx>,
+    /// False under `-C panic=abort`, in which case no cleanup blocks
+    /// are built.
+    unwinding: bool,
+}
+
+impl<'a, 'tcx> ShimBuilder<'a, 'tcx> {
+    fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, return_ty: Ty<'tcx>, arg_tys: &[Ty<'tcx>])
+           -> ShimBuilder<'a, 'tcx> {
+        ShimBuilder {
+            tcx: tcx,
+            span: DUMMY_SP,
+            basic_blocks: IndexVec::new(),
+            local_decls: iter::once(LocalDecl::new_return_pointer(return_ty))
+                .chain(arg_tys.iter().map(|&ty| LocalDecl::new_temp(ty)))
+                .collect(),
+            arg_count: arg_tys.len(),
+            return_ty: return_ty,
+            unwinding: !tcx.sess.no_landing_pads(),
+        }
+    }
+
+    fn finish(mut self, start: BasicBlock, spread_arg: Option<Local>) -> Mir<'tcx> {
+        if start != START_BLOCK {
+            // Blocks are built back to front, so move the first one into place.
+            self.basic_blocks.swap(START_BLOCK, start);
+            for data in self.basic_blocks.iter_mut() {
+                for target in data.terminator_mut().successors_mut() {
+                    if *target == START_BLOCK {
+                        *target = start;
+                    } else if *target == start {
+                        *target = START_BLOCK;
+                    }
+                }
+            }
+        }
+
+        let scope = VisibilityScopeData { span: self.span, parent_scope: None };
+        let mut mir = Mir::new(self.basic_blocks,
+                               IndexVec::from_elem_n(scope, 1),
+                               IndexVec::new(),
+                               self.return_ty,
+                               self.local_decls,
+                               self.arg_count,
+                               vec![],
+                               self.span);
+        mir.spread_arg = spread_arg;
+        mir
+    }
+
+    fn resume_block(&mut self) -> Option<BasicBlock> {
+        if self.unwinding {
+            Some(self.new_block(true, TerminatorKind::Resume))
+        } else {
+            None
+        }
+    }
+
+    fn item(&self, def_id: DefId, substs: &'tcx Substs<'tcx>) -> Operand<'tcx> {
+        Operand::Constant(Constant {
+            span: self.span,
+            ty: self.tcx.item_type(def_id).subst(self.tcx, substs),
+            literal: Literal::Item { def_id: def_id, substs: substs },
+        })
+    }
+
+    fn source_info(&self) -> SourceInfo {
+        SourceInfo { span: self.span, scope: ARGUMENT_VISIBILITY_SCOPE }
+    }
+
+    fn new_block(&mut self, is_cleanup: bool, kind: TerminatorKind<'tcx>) -> BasicBlock {
+        self.new_block_with_statements(is_cleanup, vec![], kind)
+    }
+
+    fn new_block_with_statements(&mut self,
+                                 is_cleanup: bool,
+                                 statements: Vec<Statement<'tcx>>,
+                                 kind: TerminatorKind<'tcx>)
+                                 -> BasicBlock {
+        let source_info = self.source_info();
+        self.basic_blocks.push(BasicBlockData {
+            statements: statements,
+            terminator: Some(Terminator { source_info: source_info, kind: kind }),
+            is_cleanup: is_cleanup,
+        })
+    }
+
+    fn new_temp(&mut self, ty: Ty<'tcx>) -> Lvalue<'tcx> {
+        Lvalue::Local(self.local_decls.push(LocalDecl::new_temp(ty)))
+    }
+
+    fn assign(&self, lvalue: Lvalue<'tcx>, rvalue: Rvalue<'tcx>) -> Statement<'tcx> {
+        Statement {
+            source_info: self.source_info(),
+            kind: StatementKind::Assign(lvalue, rvalue),
+        }
+    }
+
+    fn usize_constant(&self, value: u64) -> Operand<'tcx> {
+        let value = match ConstUsize::new(value, self.tcx.sess.target.uint_type) {
+            Ok(value) => value,
+            Err(_) => bug!("usize literal out of range for target"),
+        };
+        Operand::Constant(Constant {
+            span: self.span,
+            ty: self.tcx.types.usize,
+            literal: Literal::Value { value: ConstVal::Integral(ConstInt::Usize(value)) },
+        })
+    }
+
+    /// Returns a block dropping `lvalue`, continuing to `succ`, or to
+    /// `unwind` when a destructor panics.
+    fn drop_value(&mut self,
+                  lvalue: Lvalue<'tcx>,
+                  ty: Ty<'tcx>,
+                  succ: BasicBlock,
+                  unwind: Option<BasicBlock>)
+                  -> BasicBlock {
+        let is_cleanup = unwind.is_none() && self.unwinding;
+        match ty.sty {
+            ty::TyAdt(adt_def, substs) => {
+                match adt_def.destructor() {
+                    Some(dtor) => {
+                        // The contents are dropped even if the destructor panics.
+                        let contents_unwind = unwind.map(|unwind| {
+                            self.drop_adt_contents(lvalue.clone(), adt_def, substs,
+                                                   unwind, None)
+                        });
+                        let contents = self.drop_adt_contents(lvalue.clone(), adt_def, substs,
+                                                              succ, unwind);
+                        self.call_destructor(lvalue, ty, dtor, substs, contents,
+                                             contents_unwind, is_cleanup)
+                    }
+                    None => self.drop_adt_contents(lvalue, adt_def, substs, succ, unwind),
+                }
+            }
+            ty::TyClosure(def_id, substs) => {
+                let fields = substs.upvar_tys(def_id, self.tcx).enumerate().map(|(i, ty)| {
+                    (lvalue.clone().field(Field::new(i), ty), ty)
+                }).collect();
+                self.drop_fields(fields, succ, unwind)
+            }
+            ty::TyTuple(tys) => {
+                let fields = tys.iter().enumerate().map(|(i, &ty)| {
+                    (lvalue.clone().field(Field::new(i), ty), ty)
+                }).collect();
+                self.drop_fields(fields, succ, unwind)
+            }
+            ty::TyArray(element_ty, len) => {
+                self.drop_array(lvalue, element_ty, len, succ, unwind)
+            }
+            _ => succ,
+        }
+    }
+
+    fn call_destructor(&mut self,
+                       lvalue: Lvalue<'tcx>,
+                       ty: Ty<'tcx>,
+                       dtor: DefId,
+                       substs: &'tcx Substs<'tcx>,
+                       succ: BasicBlock,
+                       unwind: Option<BasicBlock>,
+                       is_cleanup: bool)
+                       -> BasicBlock {
+        // `impl Drop` has to be exactly as generic as the type it is for,
+        // so the substitutions of the type are those of the impl as well.
+        let tcx = self.tcx;
+        let ref_ty = tcx.mk_mut_ref(tcx.mk_region(ty::ReErased), ty);
+        let self_ref = self.new_temp(ref_ty);
+        let unit = self.new_temp(tcx.mk_nil());
+        let borrow = self.assign(self_ref.clone(),
+                                 Rvalue::Ref(tcx.mk_region(ty::ReErased),
+                                             BorrowKind::Mut,
+                                             lvalue));
+        let func = self.item(dtor, substs);
+        self.new_block_with_statements(is_cleanup, vec![borrow], TerminatorKind::Call {
+            func: func,
+            args: vec![Operand::Consume(self_ref)],
+            destination: Some((unit, succ)),
+            cleanup: unwind,
+        })
+    }
+
+    fn drop_adt_contents(&mut self,
+                         lvalue: Lvalue<'tcx>,
+                         adt_def: &'tcx AdtDef,
+                         substs: &'tcx Substs<'tcx>,
+                         succ: BasicBlock,
+                         unwind: Option<BasicBlock>)
+                         -> BasicBlock {
+        let tcx = self.tcx;
+        let variant_fields = |lvalue: Lvalue<'tcx>, variant: &ty::VariantDef| {
+            variant.fields.iter().enumerate().map(|(i, field)| {
+                let ty = tcx.normalize_associated_type(&field.ty(tcx, substs));
+                (lvalue.clone().field(Field::new(i), ty), ty)
+            }).collect::<Vec<_>>()
+        };
+
+        match adt_def.adt_kind() {
+            ty::AdtKind::Union => succ,
+            ty::AdtKind::Struct => {
+                let fields = variant_fields(lvalue, adt_def.struct_variant());
+                self.drop_fields(fields, succ, unwind)
+            }
+            ty::AdtKind::Enum => {
+                if adt_def.variants.is_empty() {
+                    return succ;
+                }
+                let targets = adt_def.variants.iter().enumerate().map(|(i, variant)| {
+                    let variant_lvalue = lvalue.clone().elem(ProjectionElem::Downcast(adt_def, i));
+                    let fields = variant_fields(variant_lvalue, variant);
+                    self.drop_fields(fields, succ, unwind)
+                }).collect();
+                let is_cleanup = unwind.is_none() && self.unwinding;
+                let discr = self.new_temp(adt_def.discr_ty(self.tcx));
+                let read_discr = self.assign(discr.clone(), Rvalue::Discriminant(lvalue));
+                let unreachable = self.new_block(is_cleanup, TerminatorKind::Unreachable);
+                let switch = TerminatorKind::switch_on_variants(self.tcx,
+                                                                Operand::Consume(discr),
+                                                                adt_def,
+                                                                targets,
+                                                                unreachable);
+                self.new_block_with_statements(is_cleanup, vec![read_discr], switch)
+            }
+        }
+    }
+
+    /// Drops `fields` in order. If one of them panics, the ones after it
+    /// are dropped by a separate chain of cleanup blocks.
+    fn drop_fields(&mut self,
+                   fields: Vec<(Lvalue<'tcx>, Ty<'tcx>)>,
+                   mut succ: BasicBlock,
+                   mut unwind: Option<BasicBlock>)
+                   -> BasicBlock {
+        let is_cleanup = unwind.is_none() && self.unwinding;
+        for (lvalue, ty) in fields.into_iter().rev() {
+            if !self.tcx.type_needs_drop_given_env(ty, &self.tcx.empty_parameter_environment()) {
+                continue;
+            }
+            let field_unwind = unwind.map(|unwind| {
+                self.new_block(true, TerminatorKind::Drop {
+                    location: lvalue.clone(),
+                    target: unwind,
+                    unwind: None,
+                })
+            });
+            succ = self.new_block(is_cleanup, TerminatorKind::Drop {
+                location: lvalue,
+                target: succ,
+                unwind: unwind,
+            });
+            unwind = field_unwind;
+        }
+        succ
+    }
+
+    /// Drops the elements of an array in a loop. If one of them panics,
+    /// the rest are dropped by a second loop made of cleanup blocks.
+    fn drop_array(&mut self,
+                  lvalue: Lvalue<'tcx>,
+                  element_ty: Ty<'tcx>,
+                  len: usize,
+                  succ: BasicBlock,
+                  unwind: Option<BasicBlock>)
+                  -> BasicBlock {
+        let tcx = self.tcx;
+        if len == 0 || !tcx.type_needs_drop_given_env(element_ty,
+                                                     &tcx.empty_parameter_environment()) {
+            return succ;
+        }
+
+        let index = self.new_temp(tcx.types.usize);
+        let unwind = unwind.map(|unwind| {
+            let (_, next) = self.drop_array_loop(lvalue.clone(), index.clone(), len, unwind, None);
+            next
+        });
+        let (head, _) = self.drop_array_loop(lvalue, index.clone(), len, succ, unwind);
+
+        let is_cleanup = unwind.is_none() && self.unwinding;
+        let init = self.assign(index, Rvalue::Use(self.usize_constant(0)));
+        self.new_block_with_statements(is_cleanup, vec![init], TerminatorKind::Goto {
+            target: head,
+        })
+    }
+
+    /// Builds
+    ///
+    /// ```text
+    /// head: cond = Lt(index, len); if cond { body } else { succ }
+    /// body: drop(lvalue[index]) -> next, unwinding to `unwind`
+    /// next: index = Add(index, 1); goto head
+    /// ```
+    ///
+    /// and returns `(head, next)`. The cleanup loop is entered at `next`, to
+    /// pick up after the element which panicked.
+    fn drop_array_loop(&mut self,
+                       lvalue: Lvalue<'tcx>,
+                       index: Lvalue<'tcx>,
+                       len: usize,
+                       succ: BasicBlock,
+                       unwind: Option<BasicBlock>)
+                       -> (BasicBlock, BasicBlock) {
+        let tcx = self.tcx;
+        let is_cleanup = unwind.is_none() && self.unwinding;
+        let cond = self.new_temp(tcx.types.bool);
+
+        let compare = self.assign(cond.clone(),
+                                  Rvalue::BinaryOp(BinOp::Lt,
+                                                   Operand::Consume(index.clone()),
+                                                   self.usize_constant(len as u64)));
+        let increment = self.assign(index.clone(),
+                                    Rvalue::BinaryOp(BinOp::Add,
+                                                     Operand::Consume(index.clone()),
+                                                     self.usize_constant(1)));
+
+        // The targets are patched up once all three blocks exist.
+        let head = self.new_block_with_statements(is_cleanup, vec![compare],
+                                                  TerminatorKind::Unreachable);
+        let next = self.new_block_with_statements(is_cleanup, vec![increment],
+                                                  TerminatorKind::Goto { target: head });
+        let body = self.new_block(is_cleanup, TerminatorKind::Drop {
+            location: lvalue.index(Operand::Consume(index)),
+            target: next,
+            unwind: unwind,
+        });
+        self.basic_blocks[head].terminator_mut().kind =
+            TerminatorKind::if_(tcx, Operand::Consume(cond), body, succ);
+
+        (head, next)
+    }
+}
@@ -36,7 +36,8 @@ pub struct AddCallGuards;
  */
 
 impl<'tcx> MirPass<'tcx> for AddCallGuards {
-    fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>, _src: MirSource, mir: &mut Mir<'tcx>) {
+    fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>, _src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
         let pred_count: IndexVec<_, _> =
             mir.predecessors().iter().map(|ps| ps.len()).collect();
 
@@ -75,7 +76,9 @@ impl<'tcx> MirPass<'tcx> for AddCallGuards {
 
         debug!("Broke {} N edges", new_blocks.len());
 
+        let changed = !new_blocks.is_empty();
         mir.basic_blocks_mut().extend(new_blocks);
+        changed
     }
 }
 
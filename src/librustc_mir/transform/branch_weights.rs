@@ -0,0 +1,167 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Attaches static branch weights to conditional terminators, which
+//! trans forwards to LLVM as `!prof` metadata (`-Z mir-branch-prob`).
+//!
+//! Edges leading only to panics, unwinding or unreachable code are cold,
+//! edges selected by the `likely`/`unlikely` intrinsics follow the hint,
+//! and loop back edges are hot. Anything else is left to LLVM.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::FxHashMap;
+use rustc_data_structures::control_flow_graph::dominators::Dominators;
+use rustc_data_structures::indexed_vec::IndexVec;
+use syntax::abi::Abi;
+
+/// Weight of an edge which is practically never taken.
+pub const COLD: u32 = 1;
+
+/// Weight of an edge without any hint.
+pub const NORMAL: u32 = 64;
+
+/// Weight of an edge which is practically always taken.
+pub const HOT: u32 = 2000;
+
+pub struct BranchWeights;
+
+impl Pass for BranchWeights {}
+
+impl<'tcx> MirPass<'tcx> for BranchWeights {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, _: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        if !tcx.sess.opts.debugging_opts.mir_branch_prob {
+            let changed = !mir.branch_weights.is_empty();
+            mir.branch_weights.clear();
+            return changed;
+        }
+
+        let cold = cold_blocks(mir);
+        let hints = expect_hints(tcx, mir);
+        let dominators = mir.dominators();
+
+        let mut branch_weights = FxHashMap();
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            let weights = match data.terminator().kind {
+                TerminatorKind::SwitchInt { ref discr, switch_ty, ref targets, .. } => {
+                    let hint = match *discr {
+                        Operand::Consume(Lvalue::Local(local)) if switch_ty.is_bool() => {
+                            hints.get(&local).cloned()
+                        }
+                        _ => None,
+                    };
+                    // A boolean switch goes to its `false` target first.
+                    match hint {
+                        Some(true) => vec![COLD, HOT],
+                        Some(false) => vec![HOT, COLD],
+                        None => targets.iter().map(|&target| {
+                            edge_weight(&dominators, &cold, bb, target)
+                        }).collect(),
+                    }
+                }
+                TerminatorKind::Assert { .. } => vec![HOT, COLD],
+                _ => continue,
+            };
+
+            // Uniform weights tell LLVM nothing it wouldn't assume anyway.
+            if weights.iter().any(|&w| w != weights[0]) {
+                branch_weights.insert(bb, weights);
+            }
+        }
+
+        debug!("BranchWeights: weighted {} terminators", branch_weights.len());
+        let changed = mir.branch_weights != branch_weights;
+        mir.branch_weights = branch_weights;
+        changed
+    }
+}
+
+fn edge_weight(dominators: &Dominators<BasicBlock>,
+               cold: &IndexVec<BasicBlock, bool>,
+               source: BasicBlock,
+               target: BasicBlock)
+               -> u32 {
+    if cold[target] {
+        COLD
+    } else if dominators.is_reachable(source) &&
+              dominators.is_dominated_by(source, target) {
+        // A back edge, so most likely staying in the loop.
+        HOT
+    } else {
+        NORMAL
+    }
+}
+
+/// Finds the blocks from which execution can't return normally,
+/// i.e. which inevitably end up panicking, unwinding, aborting or unreachable.
+fn cold_blocks(mir: &Mir) -> IndexVec<BasicBlock, bool> {
+    let mut cold: IndexVec<BasicBlock, bool> = mir.basic_blocks().iter().map(|data| {
+        data.is_cleanup || match data.terminator().kind {
+            TerminatorKind::Resume |
+            TerminatorKind::Abort |
+            TerminatorKind::Unreachable |
+            TerminatorKind::Call { destination: None, .. } => true,
+            _ => false,
+        }
+    }).collect();
+
+    // Propagate backwards until nothing changes; loops stay warm,
+    // as only the least fixpoint is computed.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            if cold[bb] {
+                continue;
+            }
+            let terminator = data.terminator();
+            let normal_successors = match terminator.kind {
+                TerminatorKind::Return => continue,
+                TerminatorKind::Call { destination: Some((_, target)), .. } |
+                TerminatorKind::Drop { target, .. } |
+                TerminatorKind::DropAndReplace { target, .. } |
+                TerminatorKind::Assert { target, .. } => vec![target],
+                _ => terminator.successors().into_owned(),
+            };
+            if normal_successors.iter().all(|&succ| cold[succ]) {
+                cold[bb] = true;
+                changed = true;
+            }
+        }
+    }
+
+    cold
+}
+
+/// Collects the locals holding the result of a `likely` or `unlikely`
+/// intrinsic call, along with the expected value of that result.
+fn expect_hints<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> FxHashMap<Local, bool> {
+    let mut hints = FxHashMap();
+    for data in mir.basic_blocks() {
+        if let TerminatorKind::Call {
+            func: Operand::Constant(ref func),
+            destination: Some((Lvalue::Local(local), _)),
+            ..
+        } = data.terminator().kind {
+            if let ty::TyFnDef(def_id, _, &ty::BareFnTy { abi: Abi::RustIntrinsic, .. }) =
+                   func.ty.sty {
+                let name = tcx.item_name(def_id);
+                if name == "likely" {
+                    hints.insert(local, true);
+                } else if name == "unlikely" {
+                    hints.insert(local, false);
+                }
+            }
+        }
+    }
+    hints
+}
@@ -0,0 +1,230 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Puts integer `SwitchInt`s in a canonical shape right before trans, so
+//! that how they are lowered doesn't depend on the order in which match
+//! building or the optimizations happened to leave their values.
+//!
+//! The values of every switch are sorted, and split into clusters dense
+//! enough for a jump table. A switch with a single cluster gets cases
+//! going to its `otherwise` block for the gaps between its values, and is
+//! recorded in `Mir::dense_switches` so trans keeps those cases. A very
+//! sparse switch, made of several clusters with at least one big enough
+//! for a jump table, becomes a binary search on the value, comparing it
+//! with the first value of the clusters, down to a switch for each one.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{Ty, TyCtxt};
+use rustc_const_math::ConstInt;
+
+/// The fewest cases for which a jump table is worth it.
+const MIN_JUMP_TABLE_CASES: usize = 4;
+
+/// The lowest percentage of the values in the range of a cluster which
+/// must have a case of their own.
+const MIN_DENSITY_PERCENT: u64 = 40;
+
+/// The most cases a cluster may have once its gaps are filled.
+const MAX_JUMP_TABLE_CASES: u64 = 1024;
+
+pub struct CanonicalizeSwitches;
+
+impl Pass for CanonicalizeSwitches {}
+
+impl<'tcx> MirPass<'tcx> for CanonicalizeSwitches {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, _: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        mir.dense_switches.clear();
+
+        for bb in mir.basic_blocks().indices() {
+            let (discr, switch_ty, mut cases, otherwise) = match mir[bb].terminator().kind {
+                TerminatorKind::SwitchInt { ref discr, switch_ty, ref values, ref targets } => {
+                    match integer_cases(values, targets) {
+                        Some(cases) => (discr.clone(), switch_ty, cases, *targets.last().unwrap()),
+                        None => continue
+                    }
+                }
+                _ => continue
+            };
+            cases.sort_by_key(|&(value, _)| value);
+
+            let clusters = clusters(&cases);
+            let source_info = mir[bb].terminator().source_info;
+            let is_cleanup = mir[bb].is_cleanup;
+            let (statements, kind) = {
+                let mut builder = SwitchBuilder {
+                    tcx: tcx,
+                    mir: &mut *mir,
+                    discr: discr,
+                    switch_ty: switch_ty,
+                    otherwise: otherwise,
+                    source_info: source_info,
+                    is_cleanup: is_cleanup,
+                };
+                if clusters.len() > 1 &&
+                        clusters.iter().any(|cluster| cluster.len() >= MIN_JUMP_TABLE_CASES) {
+                    debug!("CanonicalizeSwitches: splitting the switch of {:?} in {} clusters",
+                           bb, clusters.len());
+                    builder.search(&clusters)
+                } else {
+                    (vec![], builder.switch(bb, &cases))
+                }
+            };
+
+            let data = &mut mir[bb];
+            data.statements.extend(statements);
+            data.terminator_mut().kind = kind;
+        }
+        true
+    }
+}
+
+/// The value and target of each case of a switch on integers, unless some
+/// values aren't integers or can't be compared with the others.
+fn integer_cases(values: &[ConstVal], targets: &[BasicBlock])
+                 -> Option<Vec<(ConstInt, BasicBlock)>> {
+    let mut cases = vec![];
+    for (value, &target) in values.iter().zip(targets) {
+        match *value {
+            ConstVal::Integral(value) => cases.push((value, target)),
+            _ => return None
+        }
+    }
+    if cases.is_empty() || cases.iter().any(|&(value, _)| value.try_cmp(cases[0].0).is_err()) {
+        return None;
+    }
+    Some(cases)
+}
+
+/// The number of values from `min` to `max`, if it fits in a `u64`.
+fn range_len(min: ConstInt, max: ConstInt) -> Option<u64> {
+    (max - min).ok().and_then(|diff| diff.to_u64()).and_then(|diff| diff.checked_add(1))
+}
+
+/// Whether a jump table from `min` to `max`, with `cases` cases of its own,
+/// would be dense enough.
+fn dense(min: ConstInt, max: ConstInt, cases: usize) -> bool {
+    match range_len(min, max) {
+        Some(len) => {
+            len <= MAX_JUMP_TABLE_CASES && (cases as u64) * 100 >= len * MIN_DENSITY_PERCENT
+        }
+        None => false
+    }
+}
+
+/// Splits the sorted `cases` in runs dense enough for a jump table.
+fn clusters(cases: &[(ConstInt, BasicBlock)]) -> Vec<&[(ConstInt, BasicBlock)]> {
+    let mut clusters = vec![];
+    let mut start = 0;
+    for end in 1..cases.len() {
+        if !dense(cases[start].0, cases[end].0, end - start + 1) {
+            clusters.push(&cases[start..end]);
+            start = end;
+        }
+    }
+    clusters.push(&cases[start..]);
+    clusters
+}
+
+struct SwitchBuilder<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'a mut Mir<'tcx>,
+    discr: Operand<'tcx>,
+    switch_ty: Ty<'tcx>,
+    otherwise: BasicBlock,
+    source_info: SourceInfo,
+    is_cleanup: bool,
+}
+
+impl<'a, 'tcx> SwitchBuilder<'a, 'tcx> {
+    fn constant(&self, value: ConstInt) -> Operand<'tcx> {
+        Operand::Constant(Constant {
+            span: self.source_info.span,
+            ty: self.switch_ty,
+            literal: Literal::Value { value: ConstVal::Integral(value) }
+        })
+    }
+
+    /// The switch on the sorted `cases` which ends `bb`, with its gaps
+    /// filled if it's dense enough for a jump table.
+    fn switch(&mut self, bb: BasicBlock, cases: &[(ConstInt, BasicBlock)])
+              -> TerminatorKind<'tcx> {
+        let (min, max) = (cases[0].0, cases[cases.len() - 1].0);
+        let mut filled = vec![];
+        if cases.len() >= MIN_JUMP_TABLE_CASES && dense(min, max, cases.len()) {
+            let mut next = min;
+            for &(value, target) in cases {
+                while next < value {
+                    filled.push((next, self.otherwise));
+                    next = next.wrap_incr();
+                }
+                filled.push((value, target));
+                next = value.wrap_incr();
+            }
+            self.mir.dense_switches.insert(bb);
+        } else {
+            filled.extend_from_slice(cases);
+        }
+
+        let mut targets: Vec<_> = filled.iter().map(|&(_, target)| target).collect();
+        targets.push(self.otherwise);
+        TerminatorKind::SwitchInt {
+            discr: self.discr.clone(),
+            switch_ty: self.switch_ty,
+            values: filled.into_iter().map(|(value, _)| ConstVal::Integral(value)).collect(),
+            targets: targets,
+        }
+    }
+
+    /// The statements and terminator of a binary search on the discriminant
+    /// among `clusters`, ending in a switch on each.
+    fn search(&mut self, clusters: &[&[(ConstInt, BasicBlock)]])
+              -> (Vec<Statement<'tcx>>, TerminatorKind<'tcx>) {
+        let mid = clusters.len() / 2;
+        let lower = self.search_block(&clusters[..mid]);
+        let upper = self.search_block(&clusters[mid..]);
+
+        let bool_ty = self.tcx.types.bool;
+        let below = self.mir.local_decls.push(LocalDecl::new_temp(bool_ty));
+        let first_upper = self.constant(clusters[mid][0].0);
+        let compare = Statement {
+            source_info: self.source_info,
+            kind: StatementKind::Assign(Lvalue::Local(below),
+                                        Rvalue::BinaryOp(BinOp::Lt,
+                                                         self.discr.clone(),
+                                                         first_upper)),
+        };
+        let cond = Operand::Consume(Lvalue::Local(below));
+        (vec![compare], TerminatorKind::if_(self.tcx, cond, lower, upper))
+    }
+
+    /// A new block searching among `clusters`, or switching on the only one.
+    fn search_block(&mut self, clusters: &[&[(ConstInt, BasicBlock)]]) -> BasicBlock {
+        let bb = self.mir.basic_blocks_mut().push(BasicBlockData {
+            statements: vec![],
+            terminator: None,
+            is_cleanup: self.is_cleanup,
+        });
+        let (statements, kind) = if clusters.len() == 1 {
+            (vec![], self.switch(bb, clusters[0]))
+        } else {
+            self.search(clusters)
+        };
+        let data = &mut self.mir[bb];
+        data.statements = statements;
+        data.terminator = Some(Terminator {
+            source_info: self.source_info,
+            kind: kind,
+        });
+        bb
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checks the `#[rustc_mir_assert(...)]` attributes of the functions against
+//! their MIR at the end of the pipeline, so that tests can say what the
+//! optimizations should have done without comparing whole dumps, which
+//! break whenever locals or blocks are numbered differently:
+//!
+//!     #[rustc_mir_assert(blocks = "1", max_statements = "4", no_calls, no_asserts)]
+//!
+//! - `blocks = "N"`: the function has exactly `N` basic blocks;
+//! - `max_statements = "M"`: it has at most `M` statements;
+//! - `no_calls`: no `Call` terminator is left;
+//! - `no_asserts`: no `Assert` terminator is left.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use syntax::ast::NestedMetaItem;
+
+pub struct CheckMirAsserts;
+
+impl Pass for CheckMirAsserts {}
+
+impl<'tcx> MirPass<'tcx> for CheckMirAsserts {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+
+        for attr in tcx.map.attrs(id) {
+            if !attr.check_name("rustc_mir_assert") {
+                continue;
+            }
+            let items = match attr.meta_item_list() {
+                Some(items) => items,
+                None => {
+                    tcx.sess.span_err(attr.span, "malformed `#[rustc_mir_assert]` attribute");
+                    continue;
+                }
+            };
+            for item in items {
+                check(tcx, mir, item);
+            }
+        }
+        false
+    }
+}
+
+/// Checks `mir` against one of the properties in the attribute.
+fn check<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>, item: &NestedMetaItem) {
+    let error = |msg: String| tcx.sess.span_err(item.span, &msg);
+    let terminators = || mir.basic_blocks().iter().map(|data| &data.terminator().kind);
+
+    let name = match item.name() {
+        Some(name) => name,
+        None => return error("malformed `#[rustc_mir_assert]` property".to_string())
+    };
+    match &*name.as_str() {
+        "blocks" => {
+            if let Some(expected) = number(tcx, item) {
+                let blocks = mir.basic_blocks().len();
+                if blocks != expected {
+                    error(format!("MIR has {} blocks, expected {}", blocks, expected));
+                }
+            }
+        }
+        "max_statements" => {
+            if let Some(max) = number(tcx, item) {
+                let statements: usize = mir.basic_blocks().iter().map(|data| {
+                    data.statements.len()
+                }).sum();
+                if statements > max {
+                    error(format!("MIR has {} statements, expected at most {}", statements, max));
+                }
+            }
+        }
+        "no_calls" if item.is_word() => {
+            let calls = terminators().filter(|kind| match **kind {
+                TerminatorKind::Call { .. } => true,
+                _ => false
+            }).count();
+            if calls > 0 {
+                error(format!("MIR has {} calls, expected none", calls));
+            }
+        }
+        "no_asserts" if item.is_word() => {
+            let asserts = terminators().filter(|kind| match **kind {
+                TerminatorKind::Assert { .. } => true,
+                _ => false
+            }).count();
+            if asserts > 0 {
+                error(format!("MIR has {} assertions, expected none", asserts));
+            }
+        }
+        _ => error(format!("unknown `#[rustc_mir_assert]` property `{}`", name))
+    }
+}
+
+/// The value of a `name = "N"` property.
+fn number<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, item: &NestedMetaItem) -> Option<usize> {
+    let value = item.value_str().and_then(|value| value.as_str().parse().ok());
+    if value.is_none() {
+        tcx.sess.span_err(item.span, "expected a number, as in `blocks = \"3\"`");
+    }
+    value
+}
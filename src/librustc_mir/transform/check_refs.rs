@@ -0,0 +1,228 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Checks, for `-Z mir-check-refs`, that the raw pointers dereferenced
+//! by a function are not null and aligned for their pointee, panicking
+//! otherwise.
+//!
+//! The checks are ordinary `Assert` terminators placed right before the
+//! statement or terminator dereferencing the pointer:
+//!
+//!     addr = ptr as usize;
+//!     null = Eq(addr, const 0usize);
+//!     assert(!null, "null pointer dereference") -> bb1;
+//! bb1:
+//!     low = BitAnd(addr, const align - 1);
+//!     misaligned = Ne(low, const 0usize);
+//!     assert(!misaligned, "misaligned pointer dereference") -> bb2;
+//!
+//! so that the optimizations can fold the checks of pointers which are
+//! known to be fine. Pointers to unsized types, and to types whose layout
+//! depends on type parameters, are not checked, as the cast to `usize`
+//! and the alignment need a sized type known before monomorphization.
+//!
+//! The checks unwind to the cleanup of the terminator of their block, if
+//! it has one, and skip dropping the values in scope otherwise.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::traits::Reveal;
+use rustc::ty::{self, TyCtxt};
+use rustc_const_math::{ConstInt, ConstUsize};
+use syntax_pos::Span;
+
+pub struct CheckRefs;
+
+impl Pass for CheckRefs {}
+
+impl<'tcx> MirPass<'tcx> for CheckRefs {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) => {}
+            _ => return false
+        }
+        if !tcx.sess.opts.debugging_opts.mir_check_refs {
+            return false;
+        }
+
+        let derefs = {
+            let mir = &*mir;
+            let mut derefs = RawDerefs { tcx: tcx, mir: mir, derefs: vec![] };
+            derefs.visit_mir(mir);
+            derefs.derefs
+        };
+
+        // The pointers to check, along with the alignment of their pointee.
+        let checks: Vec<_> = tcx.infer_ctxt(None, None, Reveal::All).enter(|infcx| {
+            derefs.into_iter().filter_map(|(location, pointer)| {
+                let pointee = match pointer.ty(mir, tcx).to_ty(tcx).sty {
+                    ty::TyRawPtr(ref mt) => mt.ty,
+                    _ => return None
+                };
+                match pointee.layout(&infcx) {
+                    Ok(layout) if !layout.is_unsized() => {
+                        let align = layout.align(&tcx.data_layout).abi();
+                        Some((location, pointer, align))
+                    }
+                    _ => None
+                }
+            }).collect()
+        });
+
+        // Splitting a block keeps the locations before the split valid, so
+        // the checks are inserted starting from the last one. Those of the
+        // same location end up in the order they were found in, which is
+        // the order the dereferences happen in.
+        debug!("CheckRefs: checking {} dereferences in {:?}", checks.len(), src);
+        let changed = !checks.is_empty();
+        for (location, pointer, align) in checks.into_iter().rev() {
+            insert_check(tcx, mir, location, pointer, align);
+        }
+        changed
+    }
+}
+
+/// Collects the raw pointers dereferenced at each location, innermost
+/// dereference first.
+struct RawDerefs<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'a Mir<'tcx>,
+    derefs: Vec<(Location, Lvalue<'tcx>)>,
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for RawDerefs<'a, 'tcx> {
+    fn visit_projection(&mut self,
+                        lvalue: &LvalueProjection<'tcx>,
+                        context: LvalueContext<'tcx>,
+                        location: Location) {
+        self.super_projection(lvalue, context, location);
+        if let ProjectionElem::Deref = lvalue.elem {
+            if let ty::TyRawPtr(_) = lvalue.base.ty(self.mir, self.tcx).to_ty(self.tcx).sty {
+                self.derefs.push((location, lvalue.base.clone()));
+            }
+        }
+    }
+}
+
+/// Splits the block of `location` right before it, and checks that
+/// `pointer` isn't null, and is aligned to `align` if that is above 1,
+/// in between.
+fn insert_check<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          mir: &mut Mir<'tcx>,
+                          location: Location,
+                          pointer: Lvalue<'tcx>,
+                          align: u64) {
+    let (source_info, is_cleanup, cleanup) = {
+        let data = &mir[location.block];
+        let source_info = match data.statements.get(location.statement_index) {
+            Some(statement) => statement.source_info,
+            None => data.terminator().source_info
+        };
+        let cleanup = match data.terminator().kind {
+            TerminatorKind::Call { cleanup, .. } |
+            TerminatorKind::Assert { cleanup, .. } => cleanup,
+            TerminatorKind::Drop { unwind, .. } |
+            TerminatorKind::DropAndReplace { unwind, .. } => unwind,
+            _ => None
+        };
+        (source_info, data.is_cleanup, if data.is_cleanup { None } else { cleanup })
+    };
+
+    let rest = {
+        let data = &mut mir[location.block];
+        BasicBlockData {
+            statements: data.statements.split_off(location.statement_index),
+            terminator: data.terminator.take(),
+            is_cleanup: is_cleanup,
+        }
+    };
+    let rest = mir.basic_blocks_mut().push(rest);
+
+    let (usize_ty, bool_ty) = (tcx.types.usize, tcx.types.bool);
+    let addr = Lvalue::Local(mir.local_decls.push(LocalDecl::new_temp(usize_ty)));
+    let null = Lvalue::Local(mir.local_decls.push(LocalDecl::new_temp(bool_ty)));
+    let zero = usize_constant(tcx, source_info.span, 0);
+    let mut check = vec![
+        assign(source_info, addr.clone(),
+               Rvalue::Cast(CastKind::Misc, Operand::Consume(pointer), usize_ty)),
+        assign(source_info, null.clone(),
+               Rvalue::BinaryOp(BinOp::Eq, Operand::Consume(addr.clone()), zero.clone())),
+    ];
+    let mut message = AssertMessage::NullPointerDeref;
+    let mut cond = null;
+    let mut block = location.block;
+
+    if align > 1 {
+        let aligned = mir.basic_blocks_mut().push(BasicBlockData {
+            statements: vec![],
+            terminator: None,
+            is_cleanup: is_cleanup,
+        });
+        finish_check(mir, block, check, source_info, cond, message, aligned, cleanup);
+
+        let low = Lvalue::Local(mir.local_decls.push(LocalDecl::new_temp(usize_ty)));
+        let misaligned = Lvalue::Local(mir.local_decls.push(LocalDecl::new_temp(bool_ty)));
+        let mask = usize_constant(tcx, source_info.span, align - 1);
+        check = vec![
+            assign(source_info, low.clone(),
+                   Rvalue::BinaryOp(BinOp::BitAnd, Operand::Consume(addr), mask)),
+            assign(source_info, misaligned.clone(),
+                   Rvalue::BinaryOp(BinOp::Ne, Operand::Consume(low), zero)),
+        ];
+        message = AssertMessage::MisalignedPointerDeref;
+        cond = misaligned;
+        block = aligned;
+    }
+    finish_check(mir, block, check, source_info, cond, message, rest, cleanup);
+}
+
+/// Ends `block` with `statements` and an assertion that `cond` is false.
+fn finish_check<'tcx>(mir: &mut Mir<'tcx>,
+                      block: BasicBlock,
+                      statements: Vec<Statement<'tcx>>,
+                      source_info: SourceInfo,
+                      cond: Lvalue<'tcx>,
+                      message: AssertMessage<'tcx>,
+                      target: BasicBlock,
+                      cleanup: Option<BasicBlock>) {
+    let data = &mut mir[block];
+    data.statements.extend(statements);
+    data.terminator = Some(Terminator {
+        source_info: source_info,
+        kind: TerminatorKind::Assert {
+            cond: Operand::Consume(cond),
+            expected: false,
+            msg: Box::new(message),
+            target: target,
+            cleanup: cleanup,
+        }
+    });
+}
+
+fn assign<'tcx>(source_info: SourceInfo, lvalue: Lvalue<'tcx>, rvalue: Rvalue<'tcx>)
+                -> Statement<'tcx> {
+    Statement {
+        source_info: source_info,
+        kind: StatementKind::Assign(lvalue, rvalue),
+    }
+}
+
+fn usize_constant<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, span: Span, value: u64)
+                            -> Operand<'tcx> {
+    let value = ConstUsize::new(value, tcx.sess.target.uint_type).unwrap();
+    Operand::Constant(Constant {
+        span: span,
+        ty: tcx.types.usize,
+        literal: Literal::Value { value: ConstVal::Integral(ConstInt::Usize(value)) },
+    })
+}
@@ -19,62 +19,89 @@
 //!     ...
 //!     USE(DEST)
 //!
-//! where `DEST` and `SRC` are both locals of some form. We replace that with:
+//! where `DEST` is a local and `SRC` is a local or, in case (b) below, a field of one. We replace
+//! that with:
 //!
 //!     NOP
 //!     ...
 //!     USE(SRC)
 //!
-//! The assignment `DEST = SRC` must be (a) the only mutation of `DEST` and (b) the only
-//! (non-mutating) use of `SRC`. These restrictions are conservative and may be relaxed in the
-//! future.
+//! The assignment `DEST = SRC` must be the only mutation of `DEST`. Then either (a) it is also
+//! the only (non-mutating) use of `SRC` and the only mutation of `SRC`, or (b) all the uses of
+//! `DEST` follow it in the same basic block, none of them borrows or drops `DEST`, and the
+//! region-erased borrow analysis in `borrows` shows that `SRC` can't change, directly or
+//! through a reference, before the last of them.
+//!
+//! In case (a), the storage of `SRC` is extended to cover that of `DEST`, by keeping the
+//! `StorageLive` of `SRC` and the `StorageDead` of `DEST`, so trans can still emit lifetime
+//! markers for it. In case (b), `SRC` is already live wherever `DEST` was used, so the markers
+//! of `DEST` are removed.
+//!
+//! If `DEST` is a user variable, its debuginfo is pointed at `SRC`, or at the propagated constant.
+//! In case (b), `SRC` may be assigned again later, and the variable is then shown as optimized
+//! out instead.
+//!
+//! Copies the other way around, out of a temporary into a field, are propagated backwards:
+//!
+//!     TMP = RVALUE
+//!     ...
+//!     DEST.f = TMP
+//!
+//! becomes `DEST.f = RVALUE`, when `TMP` is used for nothing else, the copy follows in the same
+//! basic block, and `DEST` is neither used nor borrowed in between.
 
+use borrows::{BorrowAnalysis, BorrowState};
 use def_use::DefUseAnalysis;
-use rustc::mir::{Constant, Local, Location, Lvalue, Mir, Operand, Rvalue, StatementKind};
+use rustc::mir::{Constant, Local, LocalKind, Location, Lvalue, Mir, Operand, Rvalue};
+use rustc::mir::{ProjectionElem, StatementKind, VarDebugInfoContents};
 use rustc::mir::transform::{MirPass, MirSource, Pass};
-use rustc::mir::visit::MutVisitor;
+use rustc::mir::visit::{LvalueContext, MutVisitor};
 use rustc::ty::TyCtxt;
 use transform::qualify_consts;
 
 pub struct CopyPropagation;
 
-impl Pass for CopyPropagation {}
+impl Pass for CopyPropagation {
+    fn is_cleanup(&self) -> bool { true }
+}
 
 impl<'tcx> MirPass<'tcx> for CopyPropagation {
     fn run_pass<'a>(&mut self,
                     tcx: TyCtxt<'a, 'tcx, 'tcx>,
                     source: MirSource,
-                    mir: &mut Mir<'tcx>) {
+                    mir: &mut Mir<'tcx>) -> bool {
         match source {
             MirSource::Const(_) => {
                 // Don't run on constants, because constant qualification might reject the
                 // optimized IR.
-                return
+                return false
             }
             MirSource::Static(..) | MirSource::Promoted(..) => {
                 // Don't run on statics and promoted statics, because trans might not be able to
                 // evaluate the optimized IR.
-                return
+                return false
             }
             MirSource::Fn(function_node_id) => {
                 if qualify_consts::is_const_fn(tcx, tcx.map.local_def_id(function_node_id)) {
                     // Don't run on const functions, as, again, trans might not be able to evaluate
                     // the optimized IR.
-                    return
+                    return false
                 }
             }
+            MirSource::Shim => {}
         }
 
-        // We only run when the MIR optimization level is at least 1. This avoids messing up debug
+        // We only run when the MIR optimization level is at least 2. This avoids messing up debug
         // info.
-        match tcx.sess.opts.debugging_opts.mir_opt_level {
-            Some(0) | None => return,
-            _ => {}
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
         }
 
+        let mut any_changed = false;
         loop {
             let mut def_use_analysis = DefUseAnalysis::new(mir);
             def_use_analysis.analyze(mir);
+            let borrow_analysis = BorrowAnalysis::new(tcx, mir);
 
             let mut changed = false;
             for dest_local in mir.local_decls.indices() {
@@ -123,7 +150,12 @@ impl<'tcx> MirPass<'tcx> for CopyPropagation {
                                 local == dest_local => {
                             let maybe_action = match *operand {
                                 Operand::Consume(ref src_lvalue) => {
-                                    Action::local_copy(&def_use_analysis, src_lvalue)
+                                    Action::local_copy(mir,
+                                                       &def_use_analysis,
+                                                       &borrow_analysis,
+                                                       dest_local,
+                                                       location,
+                                                       src_lvalue)
                                 }
                                 Operand::Constant(ref src_constant) => {
                                     Action::constant(src_constant)
@@ -147,27 +179,57 @@ impl<'tcx> MirPass<'tcx> for CopyPropagation {
                 // regenerating the chains.
                 break
             }
+            if !changed {
+                for tmp_local in mir.local_decls.indices() {
+                    let store = Action::store(mir, &def_use_analysis, &borrow_analysis, tmp_local);
+                    if let Some((action, location)) = store {
+                        changed = action.perform(mir, &def_use_analysis, tmp_local, location);
+                        break
+                    }
+                }
+            }
             if !changed {
                 break
             }
+            any_changed = true;
         }
+        any_changed
     }
 }
 
 enum Action<'tcx> {
     PropagateLocalCopy(Local),
+    /// The flag tells whether the source keeps the copied value for the rest of the function.
+    PropagateLocalCopyInBlock(Lvalue<'tcx>, bool),
     PropagateConstant(Constant<'tcx>),
+    /// The destination of the copy out of the temporary, and where that copy is.
+    PropagateStore(Lvalue<'tcx>, Location),
 }
 
 impl<'tcx> Action<'tcx> {
-    fn local_copy(def_use_analysis: &DefUseAnalysis, src_lvalue: &Lvalue<'tcx>)
+    fn local_copy(mir: &Mir<'tcx>,
+                  def_use_analysis: &DefUseAnalysis,
+                  borrow_analysis: &BorrowAnalysis,
+                  dest_local: Local,
+                  location: Location,
+                  src_lvalue: &Lvalue<'tcx>)
                   -> Option<Action<'tcx>> {
-        // The source must be a local.
-        let src_local = if let Lvalue::Local(local) = *src_lvalue {
-            local
-        } else {
-            debug!("  Can't copy-propagate local: source is not a local");
-            return None;
+        // The source must be a local, or a field of one if all the uses of the destination
+        // follow the copy in its block.
+        let src_local = match *src_lvalue {
+            Lvalue::Local(local) => local,
+            Lvalue::Projection(_) => {
+                return Action::local_copy_in_block(mir,
+                                                   def_use_analysis,
+                                                   borrow_analysis,
+                                                   dest_local,
+                                                   location,
+                                                   src_lvalue)
+            }
+            Lvalue::Static(_) => {
+                debug!("  Can't copy-propagate local: source is a static");
+                return None
+            }
         };
 
         // We're trying to copy propagate a local.
@@ -179,8 +241,13 @@ impl<'tcx> Action<'tcx> {
             return None
         }
         if src_use_count != 1 {
-            debug!("  Can't copy-propagate local: {} uses", src_use_info.use_count());
-            return None
+            debug!("  Source has {} uses", src_use_info.use_count());
+            return Action::local_copy_in_block(mir,
+                                               def_use_analysis,
+                                               borrow_analysis,
+                                               dest_local,
+                                               location,
+                                               src_lvalue)
         }
 
         // Verify that the source doesn't change in between. This is done conservatively for now,
@@ -197,14 +264,150 @@ impl<'tcx> Action<'tcx> {
         //     USE(SRC);
         let src_def_count = src_use_info.def_count_not_including_drop();
         if src_def_count != 1 {
-            debug!("  Can't copy-propagate local: {} defs of src",
-                   src_use_info.def_count_not_including_drop());
-            return None
+            debug!("  Source has {} defs", src_use_info.def_count_not_including_drop());
+            return Action::local_copy_in_block(mir,
+                                               def_use_analysis,
+                                               borrow_analysis,
+                                               dest_local,
+                                               location,
+                                               src_lvalue)
         }
 
         Some(Action::PropagateLocalCopy(src_local))
     }
 
+    /// Propagates a copy whose source may be used or mutated elsewhere, or be a field of a
+    /// local, as long as the destination is only used later in the same basic block and the
+    /// source can't change before the last of those uses.
+    fn local_copy_in_block(mir: &Mir<'tcx>,
+                           def_use_analysis: &DefUseAnalysis,
+                           borrow_analysis: &BorrowAnalysis,
+                           dest_local: Local,
+                           location: Location,
+                           src_lvalue: &Lvalue<'tcx>)
+                           -> Option<Action<'tcx>> {
+        let src_local = match field_root(src_lvalue) {
+            Some(local) => local,
+            None => {
+                debug!("  Can't copy-propagate local: source is not a field of a local");
+                return None
+            }
+        };
+        if mir.local_kind(dest_local) == LocalKind::ReturnPointer {
+            debug!("  Can't copy-propagate local: dest is the return pointer");
+            return None
+        }
+
+        let mut last_use = location;
+        for lvalue_use in &def_use_analysis.local_info(dest_local).defs_and_uses {
+            let use_location = lvalue_use.location;
+            match lvalue_use.context {
+                LvalueContext::StorageLive | LvalueContext::StorageDead => continue,
+                LvalueContext::Borrow { .. } | LvalueContext::Drop => {
+                    debug!("  Can't copy-propagate local: dest is borrowed or dropped");
+                    return None
+                }
+                _ if use_location == location => continue,
+                _ => {}
+            }
+            if use_location.block != location.block ||
+                    use_location.statement_index < location.statement_index {
+                debug!("  Can't copy-propagate local: dest used outside of the copy's block");
+                return None
+            }
+            if use_location.statement_index > last_use.statement_index {
+                last_use = use_location;
+            }
+        }
+
+        if last_use == location {
+            debug!("  Can't copy-propagate local: dest unused");
+            return None
+        }
+        if borrow_analysis.may_change_between(mir, src_local, location, last_use) {
+            debug!("  Can't copy-propagate local: src may change before the last use of dest");
+            return None
+        }
+
+        let src_defs = def_use_analysis.local_info(src_local).def_count_not_including_drop();
+        let src_keeps_value = match mir.local_kind(src_local) {
+            LocalKind::Arg => src_defs == 0,
+            _ => src_defs == 1,
+        };
+
+        Some(Action::PropagateLocalCopyInBlock(src_lvalue.clone(), src_keeps_value))
+    }
+
+    /// Finds whether `tmp_local` is assigned once, and then only moved into a field of a local
+    /// later in the same basic block, which can be computed in place instead. Returns the
+    /// action along with the location of the assignment.
+    fn store(mir: &Mir<'tcx>,
+             def_use_analysis: &DefUseAnalysis,
+             borrow_analysis: &BorrowAnalysis,
+             tmp_local: Local)
+             -> Option<(Action<'tcx>, Location)> {
+        if mir.local_kind(tmp_local) != LocalKind::Temp {
+            return None
+        }
+
+        let mut def = None;
+        let mut store = None;
+        for lvalue_use in &def_use_analysis.local_info(tmp_local).defs_and_uses {
+            match lvalue_use.context {
+                LvalueContext::StorageLive | LvalueContext::StorageDead => {}
+                LvalueContext::Store if def.is_none() => def = Some(lvalue_use.location),
+                LvalueContext::Consume if store.is_none() => store = Some(lvalue_use.location),
+                _ => return None,
+            }
+        }
+        let (def, store) = match (def, store) {
+            (Some(def), Some(store)) => (def, store),
+            _ => return None,
+        };
+        if def.block != store.block || def.statement_index >= store.statement_index {
+            return None
+        }
+
+        // Both the assignment and the copy must be statements, and the copy must be a move of
+        // the temporary into a field.
+        let statements = &mir[def.block].statements;
+        match statements.get(def.statement_index).map(|statement| &statement.kind) {
+            Some(&StatementKind::Assign(Lvalue::Local(local), _)) if local == tmp_local => {}
+            _ => return None,
+        }
+        let dest_lvalue = match statements.get(store.statement_index).map(|s| &s.kind) {
+            Some(&StatementKind::Assign(ref lvalue @ Lvalue::Projection(_),
+                                        Rvalue::Use(Operand::Consume(Lvalue::Local(local)))))
+                    if local == tmp_local => lvalue,
+            _ => return None,
+        };
+        let dest_local = match field_root(dest_lvalue) {
+            Some(local) => local,
+            None => {
+                debug!("  Can't propagate store: dest is not a field of a local");
+                return None
+            }
+        };
+
+        // Writing the destination earlier must not be observable, so it can't be accessed
+        // from the assignment up to the copy, not even through a reference.
+        for lvalue_use in &def_use_analysis.local_info(dest_local).defs_and_uses {
+            let use_location = lvalue_use.location;
+            if use_location.block == def.block &&
+                    use_location.statement_index >= def.statement_index &&
+                    use_location.statement_index < store.statement_index {
+                debug!("  Can't propagate store: dest {:?} used before the copy", dest_local);
+                return None
+            }
+        }
+        if borrow_analysis.state_at(mir, dest_local, def) != BorrowState::NotBorrowed {
+            debug!("  Can't propagate store: dest {:?} may be borrowed", dest_local);
+            return None
+        }
+
+        Some((Action::PropagateStore(dest_lvalue.clone(), store), def))
+    }
+
     fn constant(src_constant: &Constant<'tcx>) -> Option<Action<'tcx>> {
         Some(Action::PropagateConstant((*src_constant).clone()))
     }
@@ -219,19 +422,25 @@ impl<'tcx> Action<'tcx> {
             Action::PropagateLocalCopy(src_local) => {
                 // Eliminate the destination and the assignment.
                 //
-                // First, remove all markers.
-                //
-                // FIXME(pcwalton): Don't do this. Merge live ranges instead.
+                // First, merge the live ranges: the source now lives from its own
+                // `StorageLive` up to the `StorageDead` of the destination. Arguments and
+                // the return pointer have no markers, in which case the merged local has
+                // to live for the whole function.
                 debug!("  Replacing all uses of {:?} with {:?} (local)",
                        dest_local,
                        src_local);
+                let src_info = def_use_analysis.local_info(src_local);
+                let src_has_storage = src_info.defs_and_uses.iter().any(|lvalue_use| {
+                    lvalue_use.context == LvalueContext::StorageLive
+                });
                 for lvalue_use in &def_use_analysis.local_info(dest_local).defs_and_uses {
-                    if lvalue_use.context.is_storage_marker() {
+                    if lvalue_use.context == LvalueContext::StorageLive ||
+                            (lvalue_use.context.is_storage_marker() && !src_has_storage) {
                         mir.make_statement_nop(lvalue_use.location)
                     }
                 }
-                for lvalue_use in &def_use_analysis.local_info(src_local).defs_and_uses {
-                    if lvalue_use.context.is_storage_marker() {
+                for lvalue_use in &src_info.defs_and_uses {
+                    if lvalue_use.context == LvalueContext::StorageDead {
                         mir.make_statement_nop(lvalue_use.location)
                     }
                 }
@@ -239,6 +448,8 @@ impl<'tcx> Action<'tcx> {
                 // Replace all uses of the destination local with the source local.
                 let src_lvalue = Lvalue::Local(src_local);
                 def_use_analysis.replace_all_defs_and_uses_with(dest_local, mir, src_lvalue);
+                let debug_value = VarDebugInfoContents::Lvalue(Lvalue::Local(src_local));
+                mir.replace_var_debug_info(dest_local, debug_value);
 
                 // Finally, zap the now-useless assignment instruction.
                 debug!("  Deleting assignment");
@@ -246,6 +457,54 @@ impl<'tcx> Action<'tcx> {
 
                 true
             }
+            Action::PropagateLocalCopyInBlock(src_lvalue, src_keeps_value) => {
+                // The source is live wherever the destination was used, so the markers of the
+                // destination can simply go.
+                debug!("  Replacing all uses of {:?} with {:?} (lvalue, in block)",
+                       dest_local,
+                       src_lvalue);
+                for lvalue_use in &def_use_analysis.local_info(dest_local).defs_and_uses {
+                    if lvalue_use.context.is_storage_marker() {
+                        mir.make_statement_nop(lvalue_use.location)
+                    }
+                }
+
+                def_use_analysis.replace_all_defs_and_uses_with(dest_local,
+                                                                mir,
+                                                                src_lvalue.clone());
+                let debug_value = if src_keeps_value {
+                    VarDebugInfoContents::Lvalue(src_lvalue)
+                } else {
+                    VarDebugInfoContents::OptimizedOut
+                };
+                mir.replace_var_debug_info(dest_local, debug_value);
+
+                debug!("  Deleting assignment");
+                mir.make_statement_nop(location);
+
+                true
+            }
+            Action::PropagateStore(dest_lvalue, store) => {
+                // The temporary goes away entirely, along with its markers.
+                debug!("  Computing {:?} in {:?} directly", dest_local, dest_lvalue);
+                for lvalue_use in &def_use_analysis.local_info(dest_local).defs_and_uses {
+                    if lvalue_use.context.is_storage_marker() {
+                        mir.make_statement_nop(lvalue_use.location)
+                    }
+                }
+
+                {
+                    let statement = &mut mir[location.block].statements[location.statement_index];
+                    if let StatementKind::Assign(ref mut lvalue, _) = statement.kind {
+                        *lvalue = dest_lvalue;
+                    }
+                }
+
+                debug!("  Deleting copy");
+                mir.make_statement_nop(store);
+
+                true
+            }
             Action::PropagateConstant(src_constant) => {
                 // First, remove all markers.
                 //
@@ -276,6 +535,8 @@ impl<'tcx> Action<'tcx> {
                            visitor.uses_replaced,
                            use_count);
                     mir.make_statement_nop(location);
+                    mir.replace_var_debug_info(dest_local,
+                                               VarDebugInfoContents::Const(visitor.constant));
                     true
                 } else if visitor.uses_replaced == 0 {
                     debug!("  No uses replaced; not deleting assignment");
@@ -291,6 +552,18 @@ impl<'tcx> Action<'tcx> {
     }
 }
 
+/// The local `lvalue` is a field of, possibly nested, or the local itself.
+fn field_root(lvalue: &Lvalue) -> Option<Local> {
+    let mut base = lvalue;
+    while let Lvalue::Projection(ref proj) = *base {
+        match proj.elem {
+            ProjectionElem::Field(..) => base = &proj.base,
+            _ => return None
+        }
+    }
+    base.base_local()
+}
+
 struct ConstantPropagationVisitor<'tcx> {
     dest_local: Local,
     constant: Constant<'tcx>,
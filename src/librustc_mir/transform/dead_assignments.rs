@@ -0,0 +1,365 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports the `dead_assignments` lint for assignments whose value is
+//! never read, using the liveness of the MIR locals (see `mir::liveness`).
+//!
+//! Plain assignments to variables are already covered by the HIR liveness
+//! behind `unused_assignments`, which sees any write to a field as a use
+//! of the whole variable. This pass reports what it misses: writes to a
+//! field of a variable, and writes through a reference to a variable,
+//! which are never read before the variable is overwritten or goes out of
+//! scope.
+//!
+//! A reference only counts as the variable it points to if it is taken
+//! once, assigned once, and otherwise only ever dereferenced. Variables
+//! borrowed in any other way may be read behind our back, so they are
+//! never reported.
+
+use rustc::lint::builtin::DEAD_ASSIGNMENTS;
+use rustc::mir::*;
+use rustc::mir::liveness::{DefsUses, Liveness, LocalEffects};
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::traversal;
+use rustc::mir::visit::{LvalueContext, Visitor};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::indexed_vec::IndexVec;
+
+pub struct DeadAssignments;
+
+impl Pass for DeadAssignments {}
+
+impl<'tcx> MirPass<'tcx> for DeadAssignments {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+
+        let mir = &*mir;
+        let aliases = find_aliases(mir);
+        let effects = Effects { aliases: &aliases };
+        let liveness = Liveness::compute(mir, &effects);
+
+        for (bb, data) in traversal::reverse_postorder(mir) {
+            let live = liveness.block(mir, bb, &effects);
+            for (i, statement) in data.statements.iter().enumerate() {
+                if let StatementKind::Assign(ref lvalue, _) = statement.kind {
+                    if let Some((local, through_ref)) = partial_target(lvalue, &aliases) {
+                        if reportable(mir, &aliases, local) && !live[i + 1].contains(&local) {
+                            let name = mir.local_decls[local].name.unwrap();
+                            let msg = if through_ref {
+                                format!("value assigned to `{}` through a reference is never read",
+                                        name)
+                            } else {
+                                format!("value assigned to a field of `{}` is never read", name)
+                            };
+                            tcx.sess.add_lint(DEAD_ASSIGNMENTS, id, statement.source_info.span,
+                                              msg);
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// What is known about the borrows of each local: `Borrowed(r)` if `r` is
+/// the one reference to it, which is only ever dereferenced.
+#[derive(Copy, Clone, PartialEq)]
+enum Alias {
+    NotBorrowed,
+    Borrowed(Local),
+    Escapes,
+}
+
+struct Aliases {
+    borrows: IndexVec<Local, Alias>,
+    /// The local each plain reference points to.
+    targets: IndexVec<Local, Option<Local>>,
+}
+
+impl Aliases {
+    fn target(&self, ptr: Local) -> Option<Local> {
+        self.targets[ptr]
+    }
+}
+
+/// The locals read and written by each statement and terminator, a
+/// write through the one reference to a local counting as a write to it.
+struct Effects<'a> {
+    aliases: &'a Aliases,
+}
+
+impl<'a, 'tcx> LocalEffects<'tcx> for Effects<'a> {
+    fn statement_effects(&self, statement: &Statement<'tcx>, _: Location) -> DefsUses {
+        let mut du = Collector { defs_uses: DefsUses::default(), aliases: self.aliases };
+        match statement.kind {
+            StatementKind::Assign(ref lvalue, ref rvalue) => {
+                du.write_lvalue(lvalue);
+                du.read_rvalue(rvalue);
+            }
+            StatementKind::SetDiscriminant { ref lvalue, .. } => du.write_partial(lvalue),
+            StatementKind::StorageLive(ref lvalue) |
+            StatementKind::StorageDead(ref lvalue) |
+            StatementKind::Poison(ref lvalue) => du.write_lvalue(lvalue),
+            StatementKind::IncrementCounter(_) |
+            StatementKind::Nop => {}
+        }
+        du.defs_uses
+    }
+
+    fn terminator_effects(&self, terminator: &Terminator<'tcx>, _: Location) -> DefsUses {
+        let mut du = Collector { defs_uses: DefsUses::default(), aliases: self.aliases };
+        match terminator.kind {
+            TerminatorKind::SwitchInt { ref discr, .. } => du.read_operand(discr),
+            TerminatorKind::Return => du.defs_uses.uses.push(RETURN_POINTER),
+            // Dropping reads the value, unless it was moved out, which
+            // isn't known before drop elaboration.
+            TerminatorKind::Drop { ref location, .. } => du.read_lvalue(location),
+            TerminatorKind::DropAndReplace { ref location, ref value, .. } => {
+                du.read_lvalue(location);
+                du.read_operand(value);
+            }
+            TerminatorKind::Call { ref func, ref args, ref destination, .. } => {
+                if let Some((ref lvalue, _)) = *destination {
+                    du.write_lvalue(lvalue);
+                }
+                du.read_operand(func);
+                for arg in args {
+                    du.read_operand(arg);
+                }
+            }
+            TerminatorKind::Assert { ref cond, ref msg, .. } => {
+                du.read_operand(cond);
+                if let AssertMessage::BoundsCheck { ref len, ref index } = **msg {
+                    du.read_operand(len);
+                    du.read_operand(index);
+                }
+            }
+            TerminatorKind::Goto { .. } |
+            TerminatorKind::Resume |
+            TerminatorKind::Abort |
+            TerminatorKind::Unreachable => {}
+        }
+        du.defs_uses
+    }
+}
+
+struct Collector<'a> {
+    defs_uses: DefsUses,
+    aliases: &'a Aliases,
+}
+
+impl<'a> Collector<'a> {
+    fn read_lvalue(&mut self, lvalue: &Lvalue) {
+        match *lvalue {
+            Lvalue::Local(local) => self.defs_uses.uses.push(local),
+            Lvalue::Static(_) => {}
+            Lvalue::Projection(ref proj) => {
+                if let ProjectionElem::Index(ref index) = proj.elem {
+                    self.read_operand(index);
+                }
+                if let (&Lvalue::Local(ptr), &ProjectionElem::Deref) = (&proj.base, &proj.elem) {
+                    if let Some(target) = self.aliases.target(ptr) {
+                        self.defs_uses.uses.push(target);
+                    }
+                }
+                self.read_lvalue(&proj.base);
+            }
+        }
+    }
+
+    fn write_lvalue(&mut self, lvalue: &Lvalue) {
+        match *lvalue {
+            Lvalue::Local(local) => self.defs_uses.defs.push(local),
+            Lvalue::Static(_) => {}
+            Lvalue::Projection(ref proj) => {
+                if let (&Lvalue::Local(ptr), &ProjectionElem::Deref) = (&proj.base, &proj.elem) {
+                    if let Some(target) = self.aliases.target(ptr) {
+                        self.defs_uses.uses.push(ptr);
+                        self.defs_uses.defs.push(target);
+                        return;
+                    }
+                }
+                self.write_partial(lvalue);
+            }
+        }
+    }
+
+    /// A write to part of a local neither kills nor reads it, but reads
+    /// the pointers and indices on the way there.
+    fn write_partial(&mut self, lvalue: &Lvalue) {
+        if let Lvalue::Projection(ref proj) = *lvalue {
+            if let ProjectionElem::Index(ref index) = proj.elem {
+                self.read_operand(index);
+            }
+            match proj.elem {
+                ProjectionElem::Deref => self.read_lvalue(&proj.base),
+                _ => self.write_partial(&proj.base)
+            }
+        }
+    }
+
+    fn read_operand(&mut self, operand: &Operand) {
+        if let Operand::Consume(ref lvalue) = *operand {
+            self.read_lvalue(lvalue);
+        }
+    }
+
+    fn read_rvalue(&mut self, rvalue: &Rvalue) {
+        match *rvalue {
+            Rvalue::Use(ref operand) |
+            Rvalue::Repeat(ref operand, _) |
+            Rvalue::Cast(_, ref operand, _) |
+            Rvalue::UnaryOp(_, ref operand) => self.read_operand(operand),
+            Rvalue::BinaryOp(_, ref lhs, ref rhs) |
+            Rvalue::CheckedBinaryOp(_, ref lhs, ref rhs) => {
+                self.read_operand(lhs);
+                self.read_operand(rhs);
+            }
+            Rvalue::Ref(_, _, ref lvalue) |
+            Rvalue::Len(ref lvalue) |
+            Rvalue::Discriminant(ref lvalue) => self.read_lvalue(lvalue),
+            Rvalue::Aggregate(_, ref operands) => {
+                for operand in operands {
+                    self.read_operand(operand);
+                }
+            }
+            Rvalue::InlineAsm { ref outputs, ref inputs, .. } => {
+                // Outputs may be read-write, so treat them as reads.
+                for output in outputs {
+                    self.read_lvalue(output);
+                }
+                for input in inputs {
+                    self.read_operand(input);
+                }
+            }
+            Rvalue::Box(_) => {}
+        }
+    }
+}
+
+/// Finds the locals whose only borrow is a reference which is used for
+/// nothing but dereferencing.
+fn find_aliases(mir: &Mir) -> Aliases {
+    let locals = mir.local_decls.len();
+    let mut finder = AliasFinder {
+        aliases: IndexVec::from_elem_n(Alias::NotBorrowed, locals),
+        defs: IndexVec::from_elem_n(0, locals),
+        plain: IndexVec::from_elem_n(true, locals),
+    };
+    finder.visit_mir(mir);
+
+    let AliasFinder { mut aliases, defs, plain } = finder;
+    let mut targets = IndexVec::from_elem_n(None, locals);
+    for (local, alias) in aliases.iter_enumerated_mut() {
+        if let Alias::Borrowed(ptr) = *alias {
+            if defs[ptr] == 1 && plain[ptr] {
+                targets[ptr] = Some(local);
+            } else {
+                *alias = Alias::Escapes;
+            }
+        }
+    }
+    Aliases { borrows: aliases, targets: targets }
+}
+
+struct AliasFinder {
+    aliases: IndexVec<Local, Alias>,
+    /// How many times each local is assigned.
+    defs: IndexVec<Local, usize>,
+    /// Whether each local is only ever assigned or dereferenced.
+    plain: IndexVec<Local, bool>,
+}
+
+impl<'tcx> Visitor<'tcx> for AliasFinder {
+    fn visit_assign(&mut self,
+                    block: BasicBlock,
+                    lvalue: &Lvalue<'tcx>,
+                    rvalue: &Rvalue<'tcx>,
+                    location: Location) {
+        if let Rvalue::Ref(_, _, ref borrowed) = *rvalue {
+            let root = if borrowed.is_direct() { borrowed.base_local() } else { None };
+            if let Some(root) = root {
+                self.aliases[root] = match (self.aliases[root], borrowed, lvalue) {
+                    (Alias::NotBorrowed, &Lvalue::Local(_), &Lvalue::Local(ptr)) => {
+                        Alias::Borrowed(ptr)
+                    }
+                    _ => Alias::Escapes
+                };
+            }
+            // Reborrowing `&*r` makes another reference to the target.
+            if let Some(ptr) = deref_root(borrowed) {
+                self.plain[ptr] = false;
+            }
+        }
+        self.super_assign(block, lvalue, rvalue, location);
+    }
+
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        match *lvalue {
+            Lvalue::Local(local) => match context {
+                LvalueContext::Store | LvalueContext::Call => self.defs[local] += 1,
+                LvalueContext::StorageLive | LvalueContext::StorageDead => {}
+                _ => self.plain[local] = false
+            },
+            Lvalue::Projection(ref proj) => {
+                if let (&Lvalue::Local(_), &ProjectionElem::Deref) = (&proj.base, &proj.elem) {
+                    return;
+                }
+            }
+            Lvalue::Static(_) => {}
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+/// The pointer local dereferenced first on the way to `lvalue`, if any.
+fn deref_root(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(_) | Lvalue::Static(_) => None,
+        Lvalue::Projection(ref proj) => match (&proj.base, &proj.elem) {
+            (&Lvalue::Local(ptr), &ProjectionElem::Deref) => Some(ptr),
+            _ => deref_root(&proj.base)
+        }
+    }
+}
+
+/// The local written by an assignment to part of it, or through its one
+/// reference, and whether it's the latter.
+fn partial_target(lvalue: &Lvalue, aliases: &Aliases) -> Option<(Local, bool)> {
+    if let Lvalue::Projection(ref proj) = *lvalue {
+        if let (&Lvalue::Local(ptr), &ProjectionElem::Deref) = (&proj.base, &proj.elem) {
+            return aliases.target(ptr).map(|target| (target, true));
+        }
+    }
+    match *lvalue {
+        Lvalue::Projection(_) if lvalue.is_direct() => {
+            lvalue.base_local().map(|local| (local, false))
+        }
+        _ => None
+    }
+}
+
+/// Whether dead assignments to `local` are reported: it must be a named
+/// variable not starting with `_`, which isn't borrowed behind our back.
+fn reportable(mir: &Mir, aliases: &Aliases, local: Local) -> bool {
+    let named = match mir.local_decls[local].name {
+        Some(name) => !name.as_str().starts_with('_'),
+        None => false
+    };
+    named && aliases.borrows[local] != Alias::Escapes
+}
@@ -0,0 +1,133 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Removes calls to pure functions whose result is never used, turning the
+//! `Call` terminators into gotos to their return block.
+//!
+//! A function is pure if it's marked `#[rustc_pure]`, or if the effects
+//! inferred for it say so: it only reads memory, and never drops, panics or
+//! loops, and only calls functions which are pure as well. The arguments of
+//! a call being removed must not need dropping, as the callee would have
+//! dropped them.
+//!
+//! Removing a call may leave the locals computing its arguments unused,
+//! so this runs until nothing changes.
+
+use def_use::DefUseAnalysis;
+use rustc::hir::def_id::DefId;
+use rustc::mir::*;
+use rustc::mir::transform::{self, MirPass, MirSource, Pass};
+use rustc::mir::visit::LvalueContext;
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::DefIdMap;
+
+pub struct DeadCalls;
+
+impl Pass for DeadCalls {}
+
+impl<'tcx> MirPass<'tcx> for DeadCalls {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+
+        let def_id = tcx.map.local_def_id(id);
+        let param_env = ty::ParameterEnvironment::for_item(tcx, id);
+        let mut pure_fns = DefIdMap();
+        let mut changed = false;
+
+        loop {
+            let mut def_use_analysis = DefUseAnalysis::new(mir);
+            def_use_analysis.analyze(mir);
+
+            let mut dead_calls = vec![];
+            for (bb, data) in mir.basic_blocks().iter_enumerated() {
+                let (func, args, local, target) = match data.terminator().kind {
+                    TerminatorKind::Call {
+                        func: Operand::Constant(ref func),
+                        ref args,
+                        destination: Some((Lvalue::Local(local), target)),
+                        ..
+                    } => (func, args, local, target),
+                    _ => continue
+                };
+                let callee = match func.ty.sty {
+                    ty::TyFnDef(callee, _, _) => callee,
+                    _ => continue
+                };
+
+                // The result must only ever be overwritten as a whole.
+                if mir.local_kind(local) == LocalKind::ReturnPointer {
+                    continue;
+                }
+                let info = def_use_analysis.local_info(local);
+                let unused = info.defs_and_uses.iter().all(|lvalue_use| {
+                    match lvalue_use.context {
+                        LvalueContext::Store |
+                        LvalueContext::Call |
+                        LvalueContext::StorageLive |
+                        LvalueContext::StorageDead => true,
+                        _ => false
+                    }
+                });
+                if !unused {
+                    continue;
+                }
+
+                let moves_drop = args.iter().any(|arg| {
+                    tcx.type_needs_drop_given_env(arg.ty(mir, tcx), &param_env)
+                });
+                if moves_drop {
+                    debug!("DeadCalls: call in {:?} moves a value needing drop", bb);
+                    continue;
+                }
+
+                let pure = *pure_fns.entry(callee).or_insert_with(|| {
+                    is_pure(tcx, def_id, callee)
+                });
+                if pure {
+                    dead_calls.push((bb, target));
+                }
+            }
+
+            if dead_calls.is_empty() {
+                return changed;
+            }
+            changed = true;
+            for (bb, target) in dead_calls {
+                debug!("DeadCalls: removing unused call in {:?}", bb);
+                transform::suggest(tcx, src, mir[bb].terminator().source_info.span,
+                                   "this call has no effect and its result is unused".to_string());
+                mir[bb].terminator_mut().kind = TerminatorKind::Goto { target: target };
+            }
+        }
+    }
+}
+
+/// Whether calling `callee` from `caller` has no effect besides computing
+/// its return value.
+fn is_pure<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, caller: DefId, callee: DefId) -> bool {
+    if tcx.has_attr(callee, "rustc_pure") {
+        return true;
+    }
+
+    // The MIR of a trait method, if any, is only its default body, which
+    // impls may override. The MIR of the caller is being changed, so it
+    // can't be summarized.
+    if tcx.trait_of_item(callee).is_some() || callee == caller {
+        return false;
+    }
+    tcx.item_mir_effects(callee).is_removable()
+}
@@ -10,6 +10,7 @@
 
 use rustc::ty::TyCtxt;
 use rustc::mir::*;
+use rustc::mir::patch::MirPatch;
 use rustc::mir::transform::{MirPass, MirSource, Pass};
 use rustc_data_structures::indexed_vec::Idx;
 
@@ -19,116 +20,116 @@ impl Pass for Deaggregator {}
 
 impl<'tcx> MirPass<'tcx> for Deaggregator {
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    source: MirSource, mir: &mut Mir<'tcx>) {
+                    source: MirSource, mir: &mut Mir<'tcx>) -> bool {
         let node_id = source.item_id();
         let node_path = tcx.item_path_str(tcx.map.local_def_id(node_id));
         debug!("running on: {:?}", node_path);
         // we only run when mir_opt_level > 1
-        match tcx.sess.opts.debugging_opts.mir_opt_level {
-            Some(0) |
-            Some(1) |
-            None => { return; },
-            _ => {}
-        };
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
 
         // Do not trigger on constants.  Could be revised in future
-        if let MirSource::Fn(_) = source {} else { return; }
+        match source {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            _ => return false
+        }
         // In fact, we might not want to trigger in other cases.
         // Ex: when we could use SROA.  See issue #35259
 
-        let mut curr: usize = 0;
-        for bb in mir.basic_blocks_mut() {
-            let idx = match get_aggregate_statement_index(curr, &bb.statements) {
-                Some(idx) => idx,
-                None => continue,
-            };
-            // do the replacement
-            debug!("removing statement {:?}", idx);
-            let src_info = bb.statements[idx].source_info;
-            let suffix_stmts = bb.statements.split_off(idx+1);
-            let orig_stmt = bb.statements.pop().unwrap();
-            let (lhs, rhs) = match orig_stmt.kind {
-                StatementKind::Assign(ref lhs, ref rhs) => (lhs, rhs),
-                _ => span_bug!(src_info.span, "expected assign, not {:?}", orig_stmt),
-            };
-            let (agg_kind, operands) = match rhs {
-                &Rvalue::Aggregate(ref agg_kind, ref operands) => (agg_kind, operands),
-                _ => span_bug!(src_info.span, "expected aggregate, not {:?}", rhs),
-            };
-            let (adt_def, variant, substs) = match agg_kind {
-                &AggregateKind::Adt(adt_def, variant, substs, None) => (adt_def, variant, substs),
-                _ => span_bug!(src_info.span, "expected struct, not {:?}", rhs),
-            };
-            let n = bb.statements.len();
-            bb.statements.reserve(n + operands.len() + suffix_stmts.len());
-            for (i, op) in operands.iter().enumerate() {
-                let ref variant_def = adt_def.variants[variant];
-                let ty = variant_def.fields[i].ty(tcx, substs);
-                let rhs = Rvalue::Use(op.clone());
+        let mut patch = MirPatch::new(mir);
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            for (idx, statement) in data.statements.iter().enumerate() {
+                if !is_deaggregable(statement) {
+                    continue;
+                }
+                let loc = Location { block: bb, statement_index: idx };
+                deaggregate(tcx, statement, loc, &mut patch);
+            }
+        }
+        let changed = !patch.is_empty();
+        patch.apply(mir);
+        changed
+    }
+}
 
-                let lhs_cast = if adt_def.variants.len() > 1 {
-                    Lvalue::Projection(Box::new(LvalueProjection {
-                        base: lhs.clone(),
-                        elem: ProjectionElem::Downcast(adt_def, variant),
-                    }))
-                } else {
-                    lhs.clone()
-                };
+/// Queues the statements replacing the aggregate assignment `statement`
+/// at `loc`: an assignment to each of the fields, and to the discriminant
+/// for enums. The last of them takes the place of the aggregate.
+fn deaggregate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                         statement: &Statement<'tcx>,
+                         loc: Location,
+                         patch: &mut MirPatch<'tcx>) {
+    let src_info = statement.source_info;
+    let (lhs, rhs) = match statement.kind {
+        StatementKind::Assign(ref lhs, ref rhs) => (lhs, rhs),
+        _ => span_bug!(src_info.span, "expected assign, not {:?}", statement),
+    };
+    let (agg_kind, operands) = match rhs {
+        &Rvalue::Aggregate(ref agg_kind, ref operands) => (agg_kind, operands),
+        _ => span_bug!(src_info.span, "expected aggregate, not {:?}", rhs),
+    };
+    let (adt_def, variant, substs) = match agg_kind {
+        &AggregateKind::Adt(adt_def, variant, substs, None) => (adt_def, variant, substs),
+        _ => span_bug!(src_info.span, "expected struct, not {:?}", rhs),
+    };
+    let mut new_statements = Vec::with_capacity(operands.len() + 1);
+    for (i, op) in operands.iter().enumerate() {
+        let ref variant_def = adt_def.variants[variant];
+        let ty = variant_def.fields[i].ty(tcx, substs);
+        let rhs = Rvalue::Use(op.clone());
 
-                let lhs_proj = Lvalue::Projection(Box::new(LvalueProjection {
-                    base: lhs_cast,
-                    elem: ProjectionElem::Field(Field::new(i), ty),
-                }));
-                let new_statement = Statement {
-                    source_info: src_info,
-                    kind: StatementKind::Assign(lhs_proj, rhs),
-                };
-                debug!("inserting: {:?} @ {:?}", new_statement, idx + i);
-                bb.statements.push(new_statement);
-            }
+        let lhs_cast = if adt_def.variants.len() > 1 {
+            Lvalue::Projection(Box::new(LvalueProjection {
+                base: lhs.clone(),
+                elem: ProjectionElem::Downcast(adt_def, variant),
+            }))
+        } else {
+            lhs.clone()
+        };
 
-            // if the aggregate was an enum, we need to set the discriminant
-            if adt_def.variants.len() > 1 {
-                let set_discriminant = Statement {
-                    kind: StatementKind::SetDiscriminant {
-                        lvalue: lhs.clone(),
-                        variant_index: variant,
-                    },
-                    source_info: src_info,
-                };
-                bb.statements.push(set_discriminant);
-            };
+        let lhs_proj = Lvalue::Projection(Box::new(LvalueProjection {
+            base: lhs_cast,
+            elem: ProjectionElem::Field(Field::new(i), ty),
+        }));
+        new_statements.push(StatementKind::Assign(lhs_proj, rhs));
+    }
 
-            curr = bb.statements.len();
-            bb.statements.extend(suffix_stmts);
-        }
+    // if the aggregate was an enum, we need to set the discriminant
+    if adt_def.variants.len() > 1 {
+        new_statements.push(StatementKind::SetDiscriminant {
+            lvalue: lhs.clone(),
+            variant_index: variant,
+        });
+    };
+
+    let last = new_statements.pop().unwrap();
+    for new_statement in new_statements {
+        debug!("inserting: {:?} @ {:?}", new_statement, loc);
+        patch.add_statement(loc, new_statement);
     }
+    debug!("replacing {:?} with {:?}", statement, last);
+    patch.patch_statement(loc, last);
 }
 
-fn get_aggregate_statement_index<'a, 'tcx, 'b>(start: usize,
-                                         statements: &Vec<Statement<'tcx>>)
-                                         -> Option<usize> {
-    for i in start..statements.len() {
-        let ref statement = statements[i];
-        let rhs = match statement.kind {
-            StatementKind::Assign(_, ref rhs) => rhs,
-            _ => continue,
-        };
-        let (kind, operands) = match rhs {
-            &Rvalue::Aggregate(ref kind, ref operands) => (kind, operands),
-            _ => continue,
-        };
-        let (adt_def, variant) = match kind {
-            &AggregateKind::Adt(adt_def, variant, _, None) => (adt_def, variant),
-            _ => continue,
-        };
-        if operands.len() == 0 {
-            // don't deaggregate ()
-            continue;
-        }
-        debug!("getting variant {:?}", variant);
-        debug!("for adt_def {:?}", adt_def);
-        return Some(i);
+fn is_deaggregable(statement: &Statement) -> bool {
+    let rhs = match statement.kind {
+        StatementKind::Assign(_, ref rhs) => rhs,
+        _ => return false,
+    };
+    let (kind, operands) = match rhs {
+        &Rvalue::Aggregate(ref kind, ref operands) => (kind, operands),
+        _ => return false,
     };
-    None
+    let (adt_def, variant) = match kind {
+        &AggregateKind::Adt(adt_def, variant, _, None) => (adt_def, variant),
+        _ => return false,
+    };
+    if operands.len() == 0 {
+        // don't deaggregate ()
+        return false;
+    }
+    debug!("getting variant {:?}", variant);
+    debug!("for adt_def {:?}", adt_def);
+    true
 }
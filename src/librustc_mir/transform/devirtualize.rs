@@ -0,0 +1,184 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Devirtualizes the calls of closures through `Fn` and `FnMut` trait
+//! objects made in the same function, as in
+//!
+//!     let f: Box<Fn(u32) -> u32> = Box::new(|x| x + 1);
+//!     f(2)
+//!
+//! The call of `Fn::call` through the vtable becomes a direct call of the
+//! `Fn::call` of the closure, with the data pointer of the trait object,
+//! which LLVM can then inline.
+//!
+//! The closure is known when the trait object pointer, a reference or a
+//! box, is only assigned once, by the coercion of a pointer to the closure,
+//! and either is the receiver of the call or is reborrowed for it. Where
+//! the pointer goes afterwards doesn't matter, as what it points to can't
+//! be replaced by something of another type.
+
+use def_use::DefUseAnalysis;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::LvalueContext;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::subst::Subst;
+use rustc_data_structures::indexed_vec::Idx;
+
+pub struct Devirtualize;
+
+impl Pass for Devirtualize {}
+
+impl<'tcx> MirPass<'tcx> for Devirtualize {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+
+        let mut def_use_analysis = DefUseAnalysis::new(mir);
+        def_use_analysis.analyze(mir);
+
+        let mut calls = vec![];
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            if let TerminatorKind::Call { func: Operand::Constant(ref func), ref args, .. } =
+                data.terminator().kind {
+                if let Some(call) = devirtualize(tcx, mir, &def_use_analysis, func, args) {
+                    calls.push((bb, call));
+                }
+            }
+        }
+
+        let changed = !calls.is_empty();
+        for (bb, (func, receiver, receiver_ty)) in calls {
+            debug!("Devirtualize: calling {:?} in {:?} of {:?}", func, bb, src);
+            let temp = mir.local_decls.push(LocalDecl::new_temp(receiver_ty));
+            let data = &mut mir[bb];
+            let source_info = data.terminator().source_info;
+            data.statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(
+                    Lvalue::Local(temp),
+                    Rvalue::Cast(CastKind::Misc, Operand::Consume(Lvalue::Local(receiver)),
+                                 receiver_ty))
+            });
+            if let TerminatorKind::Call { func: ref mut callee, ref mut args, .. } =
+                data.terminator_mut().kind {
+                *callee = Operand::Constant(func);
+                args[0] = Operand::Consume(Lvalue::Local(temp));
+            }
+        }
+        changed
+    }
+}
+
+/// The direct call replacing the call of `func` with `args`, if it calls
+/// a closure through a trait object made in the function: the callee, the
+/// receiver, and the type of the reference to the closure it is cast to.
+fn devirtualize<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          mir: &Mir<'tcx>,
+                          def_use_analysis: &DefUseAnalysis<'tcx>,
+                          func: &Constant<'tcx>,
+                          args: &[Operand<'tcx>])
+                          -> Option<(Constant<'tcx>, Local, Ty<'tcx>)> {
+    let (method, substs) = match func.ty.sty {
+        ty::TyFnDef(def_id, substs, _) => (def_id, substs),
+        _ => return None
+    };
+    let trait_id = tcx.trait_of_item(method);
+    if trait_id.is_none() ||
+       (trait_id != tcx.lang_items.fn_trait() && trait_id != tcx.lang_items.fn_mut_trait()) {
+        return None;
+    }
+    match substs.type_at(0).sty {
+        ty::TyDynamic(..) => {}
+        _ => return None
+    }
+
+    let receiver = match args.first() {
+        Some(&Operand::Consume(Lvalue::Local(local))) => local,
+        _ => return None
+    };
+    let object = match assignment(mir, def_use_analysis, receiver) {
+        Some(&Rvalue::Ref(_, _, Lvalue::Projection(box Projection {
+            base: Lvalue::Local(object), elem: ProjectionElem::Deref
+        }))) => object,
+        _ => receiver
+    };
+    let closure_ty = match assignment(mir, def_use_analysis, object) {
+        Some(&Rvalue::Cast(CastKind::Unsize, ref operand, _)) => {
+            match operand.ty(mir, tcx).builtin_deref(true, ty::NoPreference) {
+                Some(mt) => mt.ty,
+                None => return None
+            }
+        }
+        _ => return None
+    };
+    match closure_ty.sty {
+        ty::TyClosure(..) => {}
+        _ => return None
+    }
+    let receiver_ty = match mir.local_decls[receiver].ty.sty {
+        ty::TyRef(region, mt) => tcx.mk_ref(region, ty::TypeAndMut {
+            ty: closure_ty,
+            mutbl: mt.mutbl
+        }),
+        _ => return None
+    };
+
+    let substs = tcx.mk_substs_trait(closure_ty, &[substs.type_at(1)]);
+    let func = Constant {
+        span: func.span,
+        ty: tcx.item_type(method).subst(tcx, substs),
+        literal: Literal::Item {
+            def_id: method,
+            substs: substs
+        }
+    };
+    Some((func, receiver, receiver_ty))
+}
+
+/// The rvalue assigned to `local`, if it's only assigned once, by an
+/// `Assign` statement.
+fn assignment<'a, 'tcx>(mir: &'a Mir<'tcx>,
+                        def_use_analysis: &DefUseAnalysis<'tcx>,
+                        local: Local)
+                        -> Option<&'a Rvalue<'tcx>> {
+    // The arguments are assigned on entry too.
+    if local.index() <= mir.arg_count {
+        return None;
+    }
+    let info = def_use_analysis.local_info(local);
+    let mut assigns = info.defs_and_uses.iter().filter(|lvalue_use| {
+        match lvalue_use.context {
+            LvalueContext::Store |
+            LvalueContext::Call |
+            LvalueContext::Borrow { kind: BorrowKind::Mut, .. } => true,
+            LvalueContext::Drop => match mir[lvalue_use.location.block].terminator().kind {
+                TerminatorKind::DropAndReplace { .. } => true,
+                _ => false
+            },
+            _ => false
+        }
+    });
+    let location = match (assigns.next(), assigns.next()) {
+        (Some(assign), None) => assign.location,
+        _ => return None
+    };
+    match mir[location.block].statements.get(location.statement_index) {
+        Some(&Statement { kind: StatementKind::Assign(Lvalue::Local(assigned), ref rvalue), .. })
+            if assigned == local => Some(rvalue),
+        _ => None
+    }
+}
@@ -21,8 +21,10 @@ pub struct Marker<'a>(pub &'a str);
 
 impl<'b, 'tcx> MirPass<'tcx> for Marker<'b> {
     fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    _src: MirSource, _mir: &mut Mir<'tcx>)
-    {}
+                    _src: MirSource, _mir: &mut Mir<'tcx>) -> bool
+    {
+        false
+    }
 }
 
 impl<'b> Pass for Marker<'b> {
@@ -0,0 +1,79 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Removes the overflow and bounds check `Assert`s which the session
+//! turned off (see `Session::overflow_checks` and `Session::bounds_checks`).
+//!
+//! MIR construction already leaves out most overflow checks when they're
+//! off, but not those of `#[rustc_inherit_overflow_checks]` functions,
+//! and bounds checks are always built.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use rustc_const_math::{ConstMathErr, Op};
+
+use super::qualify_consts::is_const_fn;
+
+pub struct ElideAsserts;
+
+impl Pass for ElideAsserts {}
+
+impl<'tcx> MirPass<'tcx> for ElideAsserts {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let def_id = match src {
+            MirSource::Fn(id) => tcx.map.local_def_id(id),
+            // Constants are evaluated from their MIR, asserts included.
+            _ => return false
+        };
+
+        // Same for `const fn`, while inherited overflow checks have to
+        // stay for the crates which this MIR may be instantiated in.
+        let const_fn = is_const_fn(tcx, def_id);
+        let overflow_checks = tcx.sess.overflow_checks() || const_fn ||
+            tcx.has_attr(def_id, "rustc_inherit_overflow_checks");
+        let bounds_checks = tcx.sess.bounds_checks() || const_fn;
+        if overflow_checks && bounds_checks {
+            return false;
+        }
+
+        let mut elided = 0;
+        for data in mir.basic_blocks_mut() {
+            let terminator = data.terminator_mut();
+            let target = match terminator.kind {
+                TerminatorKind::Assert { ref msg, target, .. } => {
+                    let is_checked = match **msg {
+                        AssertMessage::BoundsCheck { .. } => bounds_checks,
+                        AssertMessage::Math(ConstMathErr::Overflow(ref op)) => match *op {
+                            Op::Add | Op::Sub | Op::Mul |
+                            Op::Shl | Op::Shr | Op::Neg => overflow_checks,
+                            // Division overflow is checked regardless.
+                            _ => true,
+                        },
+                        AssertMessage::Math(_) |
+                        AssertMessage::NullPointerDeref |
+                        AssertMessage::MisalignedPointerDeref => true,
+                    };
+                    if is_checked {
+                        continue;
+                    }
+                    target
+                }
+                _ => continue,
+            };
+            terminator.kind = TerminatorKind::Goto { target: target };
+            elided += 1;
+        }
+
+        debug!("ElideAsserts: elided {} asserts in {:?}", elided, src);
+        elided > 0
+    }
+}
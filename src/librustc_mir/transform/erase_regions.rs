@@ -47,7 +47,8 @@ impl Pass for EraseRegions {}
 
 impl<'tcx> MirPass<'tcx> for EraseRegions {
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    _: MirSource, mir: &mut Mir<'tcx>) {
+                    _: MirSource, mir: &mut Mir<'tcx>) -> bool {
         EraseRegionsVisitor::new(tcx).visit_mir(mir);
+        true
     }
 }
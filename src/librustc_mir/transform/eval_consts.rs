@@ -0,0 +1,85 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Replaces the constants the functions of the crate refer to by their
+//! values, as computed by the MIR interpreter, so that trans uses those
+//! rather than evaluating the constants itself. Associated constants are
+//! resolved to the impl defining them for the types they are used with.
+//!
+//! Constants whose value still depends on type parameters are left to
+//! trans, as are those whose value isn't a scalar, and those the
+//! interpreter can't evaluate; the errors among the latter are reported
+//! by `interpret::check_crate`.
+
+use interpret::{EvalContext, Value};
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::visit::MutVisitor;
+use rustc::ty::{self, TyCtxt};
+use rustc::ty::fold::TypeFoldable;
+
+pub struct EvalConsts;
+
+impl Pass for EvalConsts {}
+
+impl<'tcx> MirPass<'tcx> for EvalConsts {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) => {}
+            // Constants are evaluated as a whole by the interpreter.
+            _ => return false
+        }
+
+        let mut evaluator = Evaluator {
+            ecx: EvalContext::new(tcx, true),
+            evaluated: 0,
+        };
+        evaluator.visit_mir(mir);
+        for promoted in mir.promoted.iter_mut() {
+            evaluator.visit_mir(promoted);
+        }
+        debug!("EvalConsts: evaluated {} constants in {:?}", evaluator.evaluated, src);
+        evaluator.evaluated > 0
+    }
+}
+
+struct Evaluator<'a, 'tcx: 'a> {
+    ecx: EvalContext<'a, 'tcx>,
+    evaluated: usize,
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for Evaluator<'a, 'tcx> {
+    fn visit_constant(&mut self, constant: &mut Constant<'tcx>, location: Location) {
+        self.super_constant(constant, location);
+
+        let (def_id, substs) = match constant.literal {
+            Literal::Item { def_id, substs } => (def_id, substs),
+            _ => return
+        };
+        if let ty::TyFnDef(..) = constant.ty.sty {
+            return;
+        }
+        if substs.needs_subst() || constant.ty.needs_subst() {
+            return;
+        }
+        let value = match self.ecx.eval_const(def_id, substs, constant.span) {
+            Ok(value @ Value::Int(_)) |
+            Ok(value @ Value::Float(_)) |
+            Ok(value @ Value::Bool(_)) |
+            Ok(value @ Value::Char(_)) => value.to_const_val(),
+            _ => None
+        };
+        if let Some(value) = value {
+            constant.literal = Literal::Value { value: value };
+            self.evaluated += 1;
+        }
+    }
+}
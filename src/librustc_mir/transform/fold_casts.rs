@@ -0,0 +1,120 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Folds the casts of constants to integers and `char`s, e.g. `200u8 as i8`
+//! or `b'a' as char`, as well as the casts of C-like enums whose variant
+//! was set earlier in the block, so that the `SwitchInt`s over them are
+//! folded by `SimplifyBranches` and the arms they can't take removed.
+//! As in `FoldFloats`, temporaries assigned a constant are replaced by it
+//! in the rest of their block, as tracked by `KnownTemps`, so that chains
+//! of casts fold as a whole.
+//!
+//! The casts are evaluated by `cast_const`, which truncates, sign-extends
+//! and zero-extends the bits as the casts do at runtime, `isize` and
+//! `usize` taking the width of the target. Casts to floats are left to
+//! `FoldFloats`, and casts to pointers have no constant to give.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, AdtDef, TyCtxt};
+use rustc_const_eval::cast_const;
+use syntax_pos::Span;
+use transform::known_temps::{is_scalar, KnownTemps};
+
+pub struct FoldCasts;
+
+impl Pass for FoldCasts {}
+
+impl<'tcx> MirPass<'tcx> for FoldCasts {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            // Constants are evaluated as a whole anyway.
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+
+        let mut known = KnownTemps::new(mir);
+        let mut variants = KnownTemps::new(mir);
+        let mut folded = 0;
+        for data in mir.basic_blocks_mut() {
+            known.clear();
+            variants.clear();
+            for statement in &mut data.statements {
+                if let StatementKind::Assign(_, ref mut rvalue) = statement.kind {
+                    if let Rvalue::Cast(_, ref mut operand, _) = *rvalue {
+                        known.replace(operand);
+                    }
+                    let span = statement.source_info.span;
+                    if let Some(constant) = fold(tcx, &variants, rvalue, span) {
+                        *rvalue = Rvalue::Use(Operand::Constant(constant));
+                        folded += 1;
+                    }
+                }
+                known.record(statement, |rvalue| match *rvalue {
+                    Rvalue::Use(Operand::Constant(ref constant)) if is_scalar(constant) => {
+                        Some(constant.clone())
+                    }
+                    _ => None
+                });
+                variants.record(statement, |rvalue| match *rvalue {
+                    Rvalue::Aggregate(AggregateKind::Adt(adt_def, variant, ..), ref operands)
+                        if adt_def.is_enum() && operands.is_empty() => Some((adt_def, variant)),
+                    _ => None
+                });
+            }
+        }
+
+        debug!("FoldCasts: folded {} casts in {:?}", folded, src);
+        folded > 0
+    }
+}
+
+/// The constant `rvalue` evaluates to, if it casts a constant integer,
+/// `bool` or `char`, or one of the enums in `variants`, to an integer or
+/// a `char`.
+fn fold<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                  variants: &KnownTemps<(&'tcx AdtDef, usize)>,
+                  rvalue: &Rvalue<'tcx>,
+                  span: Span)
+                  -> Option<Constant<'tcx>> {
+    let (operand, ty) = match *rvalue {
+        Rvalue::Cast(CastKind::Misc, ref operand, ty) => (operand, ty),
+        _ => return None
+    };
+    match ty.sty {
+        ty::TyInt(_) | ty::TyUint(_) | ty::TyChar => {}
+        _ => return None
+    }
+
+    let value = match *operand {
+        Operand::Constant(ref constant) if is_scalar(constant) => match constant.literal {
+            Literal::Value { ref value } => value.clone(),
+            _ => return None
+        },
+        Operand::Consume(Lvalue::Local(local)) => match variants.get(local) {
+            Some(&(adt_def, variant)) => ConstVal::Integral(adt_def.variants[variant].disr_val),
+            None => return None
+        },
+        _ => return None
+    };
+    match cast_const(tcx, value, ty) {
+        Ok(value) => Some(Constant {
+            span: span,
+            ty: ty,
+            literal: Literal::Value { value: value }
+        }),
+        Err(_) => None
+    }
+}
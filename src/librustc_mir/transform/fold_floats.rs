@@ -0,0 +1,238 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Folds `f32` and `f64` arithmetic, comparisons, negation and casts whose
+//! operands are all constants, e.g. `2.0 * PI` or `x as f32` for a
+//! constant `x`. Temporaries assigned a constant are replaced by it in the
+//! rest of their block, so that chains like `1.0 + 2.0 + 3.0` fold as a
+//! whole.
+//!
+//! The arithmetic is done on the host in the type of the operation, and
+//! IEEE 754 arithmetic is correctly rounded, to nearest by default, so the
+//! result is the one the target would compute. Operations producing a NaN
+//! are left alone, as which NaN is produced differs between targets, and so
+//! are casts from floats to integers, which are undefined when the value
+//! doesn't fit. `-Z no-mir-fold-floats` turns the pass off altogether.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_const_math::{ConstFloat, ConstInt};
+use syntax::ast::FloatTy;
+use syntax_pos::Span;
+use transform::known_temps::KnownTemps;
+
+pub struct FoldFloats;
+
+impl Pass for FoldFloats {}
+
+impl<'tcx> MirPass<'tcx> for FoldFloats {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            // Constants are evaluated as a whole anyway.
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+        if tcx.sess.opts.debugging_opts.no_mir_fold_floats {
+            return false;
+        }
+
+        let mut known = KnownTemps::new(mir);
+        let mut folded = 0;
+        for data in mir.basic_blocks_mut() {
+            known.clear();
+            for statement in &mut data.statements {
+                if let StatementKind::Assign(_, ref mut rvalue) = statement.kind {
+                    propagate(&known, rvalue);
+                    if let Some(constant) = fold(tcx, rvalue, statement.source_info.span) {
+                        *rvalue = Rvalue::Use(Operand::Constant(constant));
+                        folded += 1;
+                    }
+                }
+                known.record(statement, |rvalue| match *rvalue {
+                    Rvalue::Use(Operand::Constant(ref constant)) if is_float(constant.ty) => {
+                        Some(constant.clone())
+                    }
+                    _ => None
+                });
+            }
+        }
+
+        debug!("FoldFloats: folded {} operations in {:?}", folded, src);
+        folded > 0
+    }
+}
+
+/// Replaces the reads of the float temporaries in `known` by their value.
+fn propagate<'tcx>(known: &KnownTemps<Constant<'tcx>>, rvalue: &mut Rvalue<'tcx>) {
+    match *rvalue {
+        Rvalue::BinaryOp(_, ref mut lhs, ref mut rhs) => {
+            known.replace(lhs);
+            known.replace(rhs);
+        }
+        Rvalue::UnaryOp(_, ref mut operand) |
+        Rvalue::Cast(_, ref mut operand, _) |
+        Rvalue::Use(ref mut operand) => known.replace(operand),
+        _ => {}
+    }
+}
+
+/// The constant `rvalue` evaluates to, if it is a float operation on
+/// constants, or a cast of an integer constant to a float.
+fn fold<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, rvalue: &Rvalue<'tcx>, span: Span)
+                  -> Option<Constant<'tcx>> {
+    let (value, ty) = match *rvalue {
+        Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+            let (lhs, rhs) = match (float_constant(lhs), float_constant(rhs)) {
+                (Some(lhs), Some(rhs)) => (lhs, rhs),
+                _ => return None
+            };
+            let result = match op {
+                BinOp::Add => lhs + rhs,
+                BinOp::Sub => lhs - rhs,
+                BinOp::Mul => lhs * rhs,
+                BinOp::Div => lhs / rhs,
+                BinOp::Rem => lhs % rhs,
+                _ => return compare(op, lhs, rhs).map(|result| {
+                    (ConstVal::Bool(result), tcx.types.bool)
+                })
+            };
+            let result = match result {
+                Ok(result) => result,
+                Err(_) => return None
+            };
+            (ConstVal::Float(result), float_const_ty(tcx, &lhs))
+        }
+        Rvalue::UnaryOp(UnOp::Neg, ref operand) => {
+            let value = match float_constant(operand) {
+                Some(value) => value,
+                None => return None
+            };
+            (ConstVal::Float(-value), float_const_ty(tcx, &value))
+        }
+        Rvalue::Cast(CastKind::Misc, ref operand, ty) => {
+            let float_ty = match ty.sty {
+                ty::TyFloat(float_ty) => float_ty,
+                _ => return None
+            };
+            let value = match float_constant(operand) {
+                Some(ConstFloat::F32(value)) => Some(cast(value as f64, float_ty)),
+                Some(ConstFloat::F64(value)) => Some(cast(value, float_ty)),
+                Some(ConstFloat::FInfer { .. }) => bug!("float of unknown type"),
+                None => match *operand {
+                    Operand::Constant(Constant {
+                        literal: Literal::Value { value: ConstVal::Integral(value) }, ..
+                    }) => int_to_float(value, float_ty),
+                    _ => return None
+                }
+            };
+            match value {
+                Some(value) => (ConstVal::Float(value), ty),
+                None => return None
+            }
+        }
+        _ => return None
+    };
+
+    if let ConstVal::Float(ref value) = value {
+        if value.is_nan() {
+            return None;
+        }
+    }
+    Some(Constant {
+        span: span,
+        ty: ty,
+        literal: Literal::Value { value: value }
+    })
+}
+
+/// The result of the comparison `op` of two floats, with the semantics of
+/// IEEE 754: a NaN is unordered with respect to anything, itself included.
+fn compare(op: BinOp, lhs: ConstFloat, rhs: ConstFloat) -> Option<bool> {
+    macro_rules! compare {
+        ($lhs:expr, $rhs:expr) => {
+            match op {
+                BinOp::Eq => Some($lhs == $rhs),
+                BinOp::Ne => Some($lhs != $rhs),
+                BinOp::Lt => Some($lhs < $rhs),
+                BinOp::Le => Some($lhs <= $rhs),
+                BinOp::Gt => Some($lhs > $rhs),
+                BinOp::Ge => Some($lhs >= $rhs),
+                _ => None
+            }
+        }
+    }
+    match (lhs, rhs) {
+        (ConstFloat::F32(lhs), ConstFloat::F32(rhs)) => compare!(lhs, rhs),
+        (ConstFloat::F64(lhs), ConstFloat::F64(rhs)) => compare!(lhs, rhs),
+        _ => None
+    }
+}
+
+/// Casts `value` to `ty`. Casting an `f64` to an `f32` rounds to nearest,
+/// and casting an `f32` to an `f64` is exact.
+fn cast(value: f64, ty: FloatTy) -> ConstFloat {
+    match ty {
+        FloatTy::F32 => ConstFloat::F32(value as f32),
+        FloatTy::F64 => ConstFloat::F64(value),
+    }
+}
+
+/// Casts the integer `value` to `ty`, rounding to nearest. The integer is
+/// converted directly to the target type, as going through `f64` could
+/// round twice.
+fn int_to_float(value: ConstInt, ty: FloatTy) -> Option<ConstFloat> {
+    match value {
+        ConstInt::Infer(_) | ConstInt::InferSigned(_) => None,
+        _ if value.is_negative() => {
+            let value = value.to_u64_unchecked() as i64;
+            Some(match ty {
+                FloatTy::F32 => ConstFloat::F32(value as f32),
+                FloatTy::F64 => ConstFloat::F64(value as f64),
+            })
+        }
+        _ => value.to_u64().map(|value| match ty {
+            FloatTy::F32 => ConstFloat::F32(value as f32),
+            FloatTy::F64 => ConstFloat::F64(value as f64),
+        })
+    }
+}
+
+/// The value of `operand` if it is a float constant of a known type.
+fn float_constant(operand: &Operand) -> Option<ConstFloat> {
+    match *operand {
+        Operand::Constant(Constant {
+            literal: Literal::Value { value: ConstVal::Float(value) }, ..
+        }) => match value {
+            ConstFloat::FInfer { .. } => None,
+            _ => Some(value)
+        },
+        _ => None
+    }
+}
+
+fn is_float(ty: Ty) -> bool {
+    match ty.sty {
+        ty::TyFloat(_) => true,
+        _ => false
+    }
+}
+
+fn float_const_ty<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, value: &ConstFloat) -> Ty<'tcx> {
+    match *value {
+        ConstFloat::F32(_) => tcx.types.f32,
+        _ => tcx.types.f64,
+    }
+}
@@ -0,0 +1,601 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Removes the overflow checks of arithmetic which provably can't
+//! overflow, e.g. the addition in `(x as u16) + (y as u16)` for `u8`s.
+//!
+//! The value ranges of integer locals are tracked through each block,
+//! starting from the exit state of its predecessor if it has only one.
+//! A `CheckedBinaryOp` whose result range fits its type is replaced by
+//! the plain operation and a constant `false` overflow flag, and the
+//! `Assert` of that flag becomes a `Goto`.
+//!
+//! Conversely, an operation whose result can only be out of range, e.g.
+//! `x + 1` right after `let x = u8::MAX;`, reports the deny-by-default
+//! `arithmetic_overflow` lint. Unchecked operations are reported as well,
+//! so the lint doesn't depend on whether overflow checks are enabled.
+//! Operations on constants alone are left to constant evaluation, and to
+//! trans for the constants depending on type parameters. Likewise,
+//! indexing an array with an index known to be past its end reports
+//! `index_out_of_bounds`, literal indices included.
+
+use rustc::lint;
+use rustc::lint::builtin::{ARITHMETIC_OVERFLOW, INDEX_OUT_OF_BOUNDS};
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::traversal;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_const_math::ConstInt;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax::ast::{IntTy, UintTy};
+use syntax_pos::Span;
+
+pub struct FoldOverflowChecks;
+
+impl Pass for FoldOverflowChecks {}
+
+impl<'tcx> MirPass<'tcx> for FoldOverflowChecks {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            // Constants report overflow when evaluated, so leave them be.
+            _ => return false
+        };
+
+        let rpo: Vec<_> = traversal::reverse_postorder(mir).map(|(bb, _)| bb).collect();
+        let local_decls = mir.local_decls.clone();
+        let mut exit_states: IndexVec<BasicBlock, Option<Ranges>> =
+            IndexVec::from_elem_n(None, mir.basic_blocks().len());
+        let mut folded = 0;
+
+        for bb in rpo {
+            let mut ranges = {
+                let predecessors = mir.predecessors_for(bb);
+                match (predecessors.len(), predecessors.first()) {
+                    (1, Some(&pred)) => exit_states[pred].clone().unwrap_or_else(Ranges::default),
+                    _ => Ranges::default()
+                }
+            };
+
+            let data = &mut mir.basic_blocks_mut()[bb];
+            let mut statements = Vec::with_capacity(data.statements.len());
+            for statement in data.statements.drain(..) {
+                match ranges.visit_statement(tcx, &local_decls, &statement) {
+                    Some((value, overflow)) => {
+                        statements.push(value);
+                        statements.push(overflow);
+                        folded += 1;
+                    }
+                    None => statements.push(statement)
+                }
+            }
+            data.statements = statements;
+
+            for (span, op) in ranges.overflows.drain(..) {
+                lint::emit_node_lint(tcx, ARITHMETIC_OVERFLOW, id, span,
+                                     &format!("this arithmetic operation will overflow: {}",
+                                              op.overflow_err().unwrap().description()));
+            }
+
+            if let Some((span, msg)) = ranges.always_panics(&data.statements,
+                                                           data.terminator()) {
+                if lint::emit_node_lint(tcx, INDEX_OUT_OF_BOUNDS, id, span, &msg) {
+                    let assert_span = data.terminator().source_info.span;
+                    tcx.reported_panics.borrow_mut().insert(assert_span);
+                }
+            }
+
+            let terminator = data.terminator_mut();
+            let assert_target = match terminator.kind {
+                TerminatorKind::Assert { cond: Operand::Consume(ref cond), expected: false,
+                                         target, .. } => {
+                    if ranges.is_overflow_flag(cond) { Some(target) } else { None }
+                }
+                _ => None
+            };
+            match assert_target {
+                Some(target) => {
+                    terminator.kind = TerminatorKind::Goto { target: target };
+                    folded += 1;
+                }
+                None => ranges.terminate(&terminator.kind)
+            }
+            exit_states[bb] = Some(ranges);
+        }
+
+        debug!("FoldOverflowChecks: folded {} checks in {:?}", folded, src);
+        folded > 0
+    }
+}
+
+/// An inclusive range of integer values.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Range {
+    Unsigned(u64, u64),
+    Signed(i64, i64),
+}
+
+/// What is known at some point of a block: the ranges of integer locals
+/// and of the results of checked operations (`Field(0)` of a local),
+/// which checked operations were folded and so can't have overflowed,
+/// and which comparisons are an index known to be out of bounds, along
+/// with the length and index. The operations which overflow whenever
+/// they run are collected, to be reported.
+#[derive(Clone, Default)]
+struct Ranges {
+    values: FxHashMap<(Local, bool), Range>,
+    no_overflow: FxHashSet<Local>,
+    out_of_bounds: FxHashMap<Local, (u64, Range)>,
+    overflows: Vec<(Span, BinOp)>,
+}
+
+impl Ranges {
+    /// Records the effect of `statement`, returning the two statements
+    /// replacing it if it is a checked operation which can't overflow.
+    fn visit_statement<'a, 'tcx>(&mut self,
+                                 tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 local_decls: &IndexVec<Local, LocalDecl<'tcx>>,
+                                 statement: &Statement<'tcx>)
+                                 -> Option<(Statement<'tcx>, Statement<'tcx>)> {
+        let (lvalue, rvalue) = match statement.kind {
+            StatementKind::Assign(ref lvalue, ref rvalue) => (lvalue, rvalue),
+            StatementKind::SetDiscriminant { ref lvalue, .. } => {
+                self.invalidate(lvalue);
+                return None;
+            }
+            _ => return None
+        };
+
+        match *rvalue {
+            // Borrowed locals may change behind our back, and inline
+            // assembly may write to anything.
+            Rvalue::Ref(..) | Rvalue::InlineAsm { .. } => {
+                self.clear();
+                return None;
+            }
+            _ => {}
+        }
+        self.invalidate(lvalue);
+
+        let local = match *lvalue {
+            Lvalue::Local(local) => local,
+            _ => return None
+        };
+        let ty = local_decls[local].ty;
+
+        if let Rvalue::BinaryOp(BinOp::Lt, ref index, ref len) = *rvalue {
+            let index = self.operand_range(tcx, local_decls, index);
+            if let (Some(Range::Unsigned(lo, hi)), Some(Range::Unsigned(len, len_hi))) =
+                   (index, self.operand_range(tcx, local_decls, len)) {
+                if len == len_hi && lo >= len {
+                    self.out_of_bounds.insert(local, (len, Range::Unsigned(lo, hi)));
+                }
+            }
+            return None;
+        }
+
+        if let Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) = *rvalue {
+            let result_ty = match ty.sty {
+                ty::TyTuple(tys) => tys[0],
+                _ => return None
+            };
+            let range = match self.checked_op_range(tcx, local_decls, op, lhs, rhs, result_ty) {
+                Some(range) => range,
+                None => {
+                    if self.always_overflows(tcx, local_decls, op, lhs, rhs, result_ty) {
+                        self.overflows.push((statement.source_info.span, op));
+                    }
+                    return None;
+                }
+            };
+            self.values.insert((local, true), range);
+            self.no_overflow.insert(local);
+
+            // `t = CheckedOp(a, b)` becomes `t.0 = Op(a, b); t.1 = false`.
+            let value = lvalue.clone().field(Field::new(0), result_ty);
+            let overflow = lvalue.clone().field(Field::new(1), tcx.types.bool);
+            let no = Operand::Constant(Constant {
+                span: statement.source_info.span,
+                ty: tcx.types.bool,
+                literal: Literal::Value { value: ConstVal::Bool(false) }
+            });
+            return Some((Statement {
+                source_info: statement.source_info,
+                kind: StatementKind::Assign(value, Rvalue::BinaryOp(op, lhs.clone(), rhs.clone()))
+            }, Statement {
+                source_info: statement.source_info,
+                kind: StatementKind::Assign(overflow, Rvalue::Use(no))
+            }));
+        }
+
+        // Without overflow checks, the same operations are unchecked.
+        if let Rvalue::BinaryOp(op, ref lhs, ref rhs) = *rvalue {
+            if op.is_checkable() && ty.is_integral() &&
+               self.always_overflows(tcx, local_decls, op, lhs, rhs, ty) {
+                self.overflows.push((statement.source_info.span, op));
+            }
+        }
+
+        if let Some(range) = self.rvalue_range(tcx, local_decls, rvalue, ty) {
+            self.values.insert((local, false), range);
+        }
+        None
+    }
+
+    fn checked_op_range<'a, 'tcx>(&self,
+                                  tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                  local_decls: &IndexVec<Local, LocalDecl<'tcx>>,
+                                  op: BinOp,
+                                  lhs: &Operand<'tcx>,
+                                  rhs: &Operand<'tcx>,
+                                  ty: Ty<'tcx>)
+                                  -> Option<Range> {
+        let bounds = match type_range(tcx, ty) {
+            Some(bounds) => bounds,
+            None => return None
+        };
+        let lhs = self.operand_range(tcx, local_decls, lhs);
+        let rhs = self.operand_range(tcx, local_decls, rhs);
+        match op {
+            BinOp::Shl | BinOp::Shr => {
+                // Shifts only overflow when shifting by the bit width or more.
+                let bits = ty_bits(tcx, ty);
+                let in_range = match rhs {
+                    Some(Range::Unsigned(_, hi)) => hi < bits,
+                    Some(Range::Signed(lo, hi)) => lo >= 0 && (hi as u64) < bits,
+                    None => false
+                };
+                if in_range { Some(bounds) } else { None }
+            }
+            _ => match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => arith_range(op, lhs, rhs).and_then(|range| {
+                    if contains(bounds, range) { Some(range) } else { None }
+                }),
+                _ => None
+            }
+        }
+    }
+
+    /// Whether `lhs op rhs` overflows `ty` for all values the operands may
+    /// have. Signed multiplication is never considered to, for simplicity.
+    fn always_overflows<'a, 'tcx>(&self,
+                                  tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                  local_decls: &IndexVec<Local, LocalDecl<'tcx>>,
+                                  op: BinOp,
+                                  lhs: &Operand<'tcx>,
+                                  rhs: &Operand<'tcx>,
+                                  ty: Ty<'tcx>)
+                                  -> bool {
+        let is_const = |operand: &Operand| match *operand {
+            Operand::Constant(_) => true,
+            Operand::Consume(_) => false
+        };
+        let bounds = match type_range(tcx, ty) {
+            Some(bounds) => bounds,
+            None => return false
+        };
+        let rhs_range = self.operand_range(tcx, local_decls, rhs);
+        match op {
+            BinOp::Shl | BinOp::Shr => {
+                if is_const(rhs) {
+                    return false;
+                }
+                let bits = ty_bits(tcx, ty);
+                match rhs_range {
+                    Some(Range::Unsigned(lo, _)) => lo >= bits,
+                    Some(Range::Signed(lo, hi)) => hi < 0 || (lo >= 0 && lo as u64 >= bits),
+                    None => false
+                }
+            }
+            _ => {
+                if is_const(lhs) && is_const(rhs) {
+                    return false;
+                }
+                match (self.operand_range(tcx, local_decls, lhs), rhs_range) {
+                    (Some(lhs), Some(rhs)) => arith_out_of(op, lhs, rhs, bounds),
+                    _ => false
+                }
+            }
+        }
+    }
+
+    fn rvalue_range<'a, 'tcx>(&self,
+                              tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                              local_decls: &IndexVec<Local, LocalDecl<'tcx>>,
+                              rvalue: &Rvalue<'tcx>,
+                              ty: Ty<'tcx>)
+                              -> Option<Range> {
+        let bounds = match type_range(tcx, ty) {
+            Some(bounds) => bounds,
+            None => return None
+        };
+        let range = match *rvalue {
+            Rvalue::Use(ref operand) => self.operand_range(tcx, local_decls, operand),
+            Rvalue::Len(Lvalue::Local(array)) => match local_decls[array].ty.sty {
+                ty::TyArray(_, len) => Some(Range::Unsigned(len as u64, len as u64)),
+                _ => None
+            },
+            Rvalue::Cast(CastKind::Misc, ref operand, _) => {
+                self.operand_range(tcx, local_decls, operand)
+                    .and_then(|range| convert(range, bounds))
+            }
+            Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+                let lhs = self.operand_range(tcx, local_decls, lhs);
+                let rhs = self.operand_range(tcx, local_decls, rhs);
+                match (op, lhs, rhs) {
+                    (BinOp::BitAnd, Some(Range::Unsigned(_, a)), Some(Range::Unsigned(_, b))) => {
+                        Some(Range::Unsigned(0, if a < b { a } else { b }))
+                    }
+                    (BinOp::Rem, Some(Range::Unsigned(_, a)), Some(Range::Unsigned(lo, b)))
+                        if lo > 0 => {
+                        Some(Range::Unsigned(0, if a < b - 1 { a } else { b - 1 }))
+                    }
+                    (BinOp::Shr, Some(Range::Unsigned(lo, hi)), Some(Range::Unsigned(b, b2)))
+                        if b == b2 && b < 64 => {
+                        Some(Range::Unsigned(lo >> b, hi >> b))
+                    }
+                    (BinOp::Add, Some(lhs), Some(rhs)) |
+                    (BinOp::Sub, Some(lhs), Some(rhs)) |
+                    (BinOp::Mul, Some(lhs), Some(rhs)) => arith_range(op, lhs, rhs),
+                    _ => None
+                }
+            }
+            _ => None
+        };
+        range.and_then(|range| if contains(bounds, range) { Some(range) } else { None })
+    }
+
+    fn operand_range<'a, 'tcx>(&self,
+                               tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               local_decls: &IndexVec<Local, LocalDecl<'tcx>>,
+                               operand: &Operand<'tcx>)
+                               -> Option<Range> {
+        match *operand {
+            Operand::Constant(Constant {
+                literal: Literal::Value { value: ConstVal::Integral(value) }, ..
+            }) => const_range(tcx, value),
+            Operand::Constant(ref constant) => type_range(tcx, constant.ty),
+            Operand::Consume(Lvalue::Local(local)) => {
+                self.values.get(&(local, false)).cloned()
+                    .or_else(|| type_range(tcx, local_decls[local].ty))
+            }
+            Operand::Consume(Lvalue::Projection(ref proj)) => match (&proj.base, &proj.elem) {
+                (&Lvalue::Local(local), &ProjectionElem::Field(field, ty))
+                    if field.index() == 0 => {
+                    self.values.get(&(local, true)).cloned().or_else(|| type_range(tcx, ty))
+                }
+                (_, &ProjectionElem::Field(_, ty)) => type_range(tcx, ty),
+                _ => None
+            },
+            Operand::Consume(Lvalue::Static(_)) => None
+        }
+    }
+
+    /// Whether `lvalue` is the overflow flag of a folded checked operation.
+    fn is_overflow_flag(&self, lvalue: &Lvalue) -> bool {
+        match *lvalue {
+            Lvalue::Projection(ref proj) => match (&proj.base, &proj.elem) {
+                (&Lvalue::Local(local), &ProjectionElem::Field(field, _)) => {
+                    field.index() == 1 && self.no_overflow.contains(&local)
+                }
+                _ => false
+            },
+            _ => false
+        }
+    }
+
+    /// The span and message to report if `terminator` is a bounds check
+    /// which fails whenever it is reached.
+    fn always_panics(&self, statements: &[Statement], terminator: &Terminator)
+                     -> Option<(Span, String)> {
+        let (cond, expected, msg) = match terminator.kind {
+            TerminatorKind::Assert { cond: Operand::Consume(Lvalue::Local(cond)),
+                                     expected, ref msg, .. } => (cond, expected, msg),
+            _ => return None
+        };
+        let index = match (expected, &**msg) {
+            (true, &AssertMessage::BoundsCheck { ref index, .. }) => index,
+            _ => return None
+        };
+        let (len, range) = match self.out_of_bounds.get(&cond) {
+            Some(&bounds) => bounds,
+            None => return None
+        };
+        let index_desc = match range {
+            Range::Unsigned(lo, hi) if lo == hi => format!("{}", lo),
+            Range::Unsigned(lo, _) => format!("at least {}", lo),
+            Range::Signed(..) => bug!("always_panics: signed index")
+        };
+        Some((operand_span(statements, index, terminator.source_info.span),
+              format!("this indexing operation will panic: index out of bounds: the len is {} \
+                       but the index is {}", len, index_desc)))
+    }
+
+    /// Forgets what is known about the local written through `lvalue`.
+    fn invalidate(&mut self, lvalue: &Lvalue) {
+        match *lvalue {
+            Lvalue::Local(local) => {
+                self.values.remove(&(local, false));
+                self.values.remove(&(local, true));
+                self.no_overflow.remove(&local);
+                self.out_of_bounds.remove(&local);
+            }
+            Lvalue::Projection(ref proj) => match proj.elem {
+                // A write through a pointer could be anywhere.
+                ProjectionElem::Deref => self.clear(),
+                _ => self.invalidate(&proj.base)
+            },
+            Lvalue::Static(_) => {}
+        }
+    }
+
+    fn terminate(&mut self, kind: &TerminatorKind) {
+        match *kind {
+            TerminatorKind::Call { destination: Some((ref lvalue, _)), .. } => {
+                // The callee may have written through a pointer as well.
+                self.clear();
+                self.invalidate(lvalue);
+            }
+            TerminatorKind::Drop { .. } |
+            TerminatorKind::DropAndReplace { .. } |
+            TerminatorKind::Call { .. } => self.clear(),
+            _ => {}
+        }
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.no_overflow.clear();
+        self.out_of_bounds.clear();
+    }
+}
+
+/// The span of the expression `operand` was computed from, which for a
+/// temporary is that of its assignment in `statements`.
+fn operand_span(statements: &[Statement], operand: &Operand, default: Span) -> Span {
+    let local = match *operand {
+        Operand::Constant(ref constant) => return constant.span,
+        Operand::Consume(Lvalue::Local(local)) => local,
+        Operand::Consume(_) => return default
+    };
+    statements.iter().rev().filter_map(|statement| match statement.kind {
+        StatementKind::Assign(Lvalue::Local(l), _) if l == local => {
+            Some(statement.source_info.span)
+        }
+        _ => None
+    }).next().unwrap_or(default)
+}
+
+fn contains(outer: Range, inner: Range) -> bool {
+    match (outer, inner) {
+        (Range::Unsigned(lo, hi), Range::Unsigned(a, b)) => lo <= a && b <= hi,
+        (Range::Signed(lo, hi), Range::Signed(a, b)) => lo <= a && b <= hi,
+        _ => false
+    }
+}
+
+/// Converts `range` to the signedness of `bounds`, if it fits.
+fn convert(range: Range, bounds: Range) -> Option<Range> {
+    let range = match (range, bounds) {
+        (Range::Unsigned(lo, hi), Range::Signed(..)) => {
+            if hi > i64::max_value() as u64 { return None; }
+            Range::Signed(lo as i64, hi as i64)
+        }
+        (Range::Signed(lo, hi), Range::Unsigned(..)) => {
+            if lo < 0 { return None; }
+            Range::Unsigned(lo as u64, hi as u64)
+        }
+        _ => range
+    };
+    if contains(bounds, range) { Some(range) } else { None }
+}
+
+/// The range of `lhs op rhs`, unless computing it overflows 64 bits.
+fn arith_range(op: BinOp, lhs: Range, rhs: Range) -> Option<Range> {
+    match (lhs, rhs) {
+        (Range::Unsigned(a, b), Range::Unsigned(c, d)) => match op {
+            BinOp::Add => a.checked_add(c).and_then(|lo| b.checked_add(d).map(|hi| (lo, hi))),
+            BinOp::Sub => a.checked_sub(d).and_then(|lo| b.checked_sub(c).map(|hi| (lo, hi))),
+            BinOp::Mul => a.checked_mul(c).and_then(|lo| b.checked_mul(d).map(|hi| (lo, hi))),
+            _ => None
+        }.map(|(lo, hi)| Range::Unsigned(lo, hi)),
+        (Range::Signed(a, b), Range::Signed(c, d)) => match op {
+            BinOp::Add => a.checked_add(c).and_then(|lo| b.checked_add(d).map(|hi| (lo, hi))),
+            BinOp::Sub => a.checked_sub(d).and_then(|lo| b.checked_sub(c).map(|hi| (lo, hi))),
+            BinOp::Mul => {
+                let products = [a.checked_mul(c), a.checked_mul(d),
+                                b.checked_mul(c), b.checked_mul(d)];
+                if products.iter().any(|p| p.is_none()) {
+                    return None;
+                }
+                let products: Vec<i64> = products.iter().map(|p| p.unwrap()).collect();
+                Some((*products.iter().min().unwrap(), *products.iter().max().unwrap()))
+            }
+            _ => None
+        }.map(|(lo, hi)| Range::Signed(lo, hi)),
+        _ => None
+    }
+}
+
+/// Whether every value `lhs op rhs` may take lies outside `bounds`.
+fn arith_out_of(op: BinOp, lhs: Range, rhs: Range, bounds: Range) -> bool {
+    match (lhs, rhs, bounds) {
+        (Range::Unsigned(a, b), Range::Unsigned(c, _), Range::Unsigned(_, max)) => match op {
+            BinOp::Add => a.checked_add(c).map_or(true, |lo| lo > max),
+            BinOp::Sub => b < c,
+            BinOp::Mul => a.checked_mul(c).map_or(true, |lo| lo > max),
+            _ => false
+        },
+        (Range::Signed(a, b), Range::Signed(c, d), Range::Signed(min, max)) => match op {
+            // An overflowing lower bound of the sum is too large, and an
+            // overflowing upper bound too small.
+            BinOp::Add => {
+                a.checked_add(c).map_or(c > 0, |lo| lo > max) ||
+                b.checked_add(d).map_or(d < 0, |hi| hi < min)
+            }
+            BinOp::Sub => {
+                a.checked_sub(d).map_or(d < 0, |lo| lo > max) ||
+                b.checked_sub(c).map_or(c > 0, |hi| hi < min)
+            }
+            _ => false
+        },
+        _ => false
+    }
+}
+
+fn const_range<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, value: ConstInt) -> Option<Range> {
+    let signed = match value {
+        ConstInt::I8(v) => v as i64,
+        ConstInt::I16(v) => v as i64,
+        ConstInt::I32(v) => v as i64,
+        ConstInt::I64(v) => v,
+        ConstInt::Isize(v) => v.as_i64(tcx.sess.target.int_type),
+        ConstInt::U8(v) => return Some(Range::Unsigned(v as u64, v as u64)),
+        ConstInt::U16(v) => return Some(Range::Unsigned(v as u64, v as u64)),
+        ConstInt::U32(v) => return Some(Range::Unsigned(v as u64, v as u64)),
+        ConstInt::U64(v) => return Some(Range::Unsigned(v, v)),
+        ConstInt::Usize(v) => {
+            let v = v.as_u64(tcx.sess.target.uint_type);
+            return Some(Range::Unsigned(v, v));
+        }
+        ConstInt::Infer(_) | ConstInt::InferSigned(_) => return None,
+    };
+    Some(Range::Signed(signed, signed))
+}
+
+/// The full range of the integer type `ty`.
+fn type_range<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> Option<Range> {
+    match ty.sty {
+        ty::TyUint(_) => {
+            let bits = ty_bits(tcx, ty);
+            Some(Range::Unsigned(0, !0 >> (64 - bits)))
+        }
+        ty::TyInt(_) => {
+            let bits = ty_bits(tcx, ty);
+            let max = (!0u64 >> (65 - bits)) as i64;
+            Some(Range::Signed(-max - 1, max))
+        }
+        _ => None
+    }
+}
+
+fn ty_bits<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> u64 {
+    match ty.sty {
+        ty::TyInt(IntTy::I8) | ty::TyUint(UintTy::U8) => 8,
+        ty::TyInt(IntTy::I16) | ty::TyUint(UintTy::U16) => 16,
+        ty::TyInt(IntTy::I32) | ty::TyUint(UintTy::U32) => 32,
+        ty::TyInt(IntTy::I64) | ty::TyUint(UintTy::U64) => 64,
+        ty::TyInt(IntTy::Is) | ty::TyUint(UintTy::Us) => tcx.data_layout.pointer_size.bits(),
+        _ => bug!("ty_bits: {:?} is not an integer", ty)
+    }
+}
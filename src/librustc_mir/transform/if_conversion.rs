@@ -0,0 +1,195 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! If-conversion of branches which only choose the value of a local.
+//!
+//! We look for patterns that look like:
+//!
+//!     bb0: { ...; switchInt(COND) -> [false: bb2, otherwise: bb1]; }
+//!     bb1: { DEST = A; goto -> bb3; }
+//!     bb2: { DEST = B; goto -> bb3; }
+//!
+//! where `DEST` is a local of integer or boolean type, and `A` and `B` are
+//! constants or locals, so reading both of them has no effect. MIR has no
+//! select, so the choice is made with arithmetic on the condition instead:
+//!
+//!     bb0: {
+//!         ...;
+//!         TMP0 = BitXor(A, B);
+//!         TMP1 = COND as T;
+//!         TMP2 = Mul(TMP0, TMP1);
+//!         DEST = BitXor(B, TMP2);
+//!         goto -> bb3;
+//!     }
+//!
+//! For booleans, `BitAnd(TMP0, COND)` takes the place of the cast and the
+//! multiplication. None of these operations can overflow. The arms are left
+//! unreachable, for `SimplifyCfg` to remove.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{Ty, TyCtxt};
+use transform::qualify_consts;
+
+pub struct IfConversion;
+
+impl Pass for IfConversion {}
+
+impl<'tcx> MirPass<'tcx> for IfConversion {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(id) => {
+                if qualify_consts::is_const_fn(tcx, tcx.map.local_def_id(id)) {
+                    return false;
+                }
+            }
+            MirSource::Shim => {}
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+
+        let candidates: Vec<_> = mir.basic_blocks().indices().filter_map(|bb| {
+            find_select(mir, bb).map(|select| (bb, select))
+        }).collect();
+        let changed = !candidates.is_empty();
+
+        for (bb, select) in candidates {
+            debug!("IfConversion: converting the switch of {:?}: {:?}", bb, select);
+            let source_info = mir[bb].terminator().source_info;
+            let ty = mir.local_decls[select.dest].ty;
+
+            let mut statements = vec![];
+            let difference = push_temp(mir, &mut statements, source_info, ty,
+                                       Rvalue::BinaryOp(BinOp::BitXor,
+                                                        select.if_true,
+                                                        select.if_false.clone()));
+            let masked = if ty.is_bool() {
+                push_temp(mir, &mut statements, source_info, ty,
+                          Rvalue::BinaryOp(BinOp::BitAnd, difference, select.cond))
+            } else {
+                let cond = push_temp(mir, &mut statements, source_info, ty,
+                                     Rvalue::Cast(CastKind::Misc, select.cond, ty));
+                push_temp(mir, &mut statements, source_info, ty,
+                          Rvalue::BinaryOp(BinOp::Mul, difference, cond))
+            };
+            statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(Lvalue::Local(select.dest),
+                                            Rvalue::BinaryOp(BinOp::BitXor,
+                                                             select.if_false,
+                                                             masked)),
+            });
+
+            let data = &mut mir[bb];
+            data.statements.extend(statements);
+            data.terminator_mut().kind = TerminatorKind::Goto { target: select.join };
+        }
+        changed
+    }
+}
+
+/// Assigns `rvalue` to a new temporary of type `ty`, and returns it.
+fn push_temp<'tcx>(mir: &mut Mir<'tcx>,
+                   statements: &mut Vec<Statement<'tcx>>,
+                   source_info: SourceInfo,
+                   ty: Ty<'tcx>,
+                   rvalue: Rvalue<'tcx>)
+                   -> Operand<'tcx> {
+    let temp = mir.local_decls.push(LocalDecl::new_temp(ty));
+    statements.push(Statement {
+        source_info: source_info,
+        kind: StatementKind::Assign(Lvalue::Local(temp), rvalue),
+    });
+    Operand::Consume(Lvalue::Local(temp))
+}
+
+#[derive(Debug)]
+struct Select<'tcx> {
+    cond: Operand<'tcx>,
+    dest: Local,
+    if_true: Operand<'tcx>,
+    if_false: Operand<'tcx>,
+    join: BasicBlock,
+}
+
+/// Finds whether the terminator of `bb` branches on a boolean only to
+/// assign one of two values to a local.
+fn find_select<'tcx>(mir: &Mir<'tcx>, bb: BasicBlock) -> Option<Select<'tcx>> {
+    let (cond, if_false, if_true) = match mir[bb].terminator().kind {
+        TerminatorKind::SwitchInt { ref discr, switch_ty, ref values, ref targets }
+                if switch_ty.is_bool() && values.len() == 1 &&
+                   values[0] == ConstVal::Bool(false) => (discr, targets[0], targets[1]),
+        _ => return None
+    };
+    if if_false == if_true {
+        return None;
+    }
+
+    let (true_dest, true_value, true_join) = match single_assignment(mir, bb, if_true) {
+        Some(arm) => arm,
+        None => return None
+    };
+    let (false_dest, false_value, false_join) = match single_assignment(mir, bb, if_false) {
+        Some(arm) => arm,
+        None => return None
+    };
+    let ty = mir.local_decls[true_dest].ty;
+    if true_dest != false_dest || true_join != false_join || !(ty.is_integral() || ty.is_bool()) {
+        return None;
+    }
+
+    Some(Select {
+        cond: cond.clone(),
+        dest: true_dest,
+        if_true: true_value.clone(),
+        if_false: false_value.clone(),
+        join: true_join,
+    })
+}
+
+/// If `arm` is only reached from `switch`, and does nothing but assign a
+/// constant or a local to a local before jumping to another block, returns
+/// the local assigned, the value, and the block jumped to.
+fn single_assignment<'a, 'tcx>(mir: &'a Mir<'tcx>, switch: BasicBlock, arm: BasicBlock)
+                               -> Option<(Local, &'a Operand<'tcx>, BasicBlock)> {
+    let data = &mir[arm];
+    if data.is_cleanup || *mir.predecessors_for(arm) != [switch] {
+        return None;
+    }
+    let join = match data.terminator().kind {
+        TerminatorKind::Goto { target } => target,
+        _ => return None
+    };
+
+    let mut statements = data.statements.iter().filter(|statement| {
+        match statement.kind {
+            StatementKind::Nop => false,
+            _ => true
+        }
+    });
+    let assignment = match (statements.next(), statements.next()) {
+        (Some(statement), None) => statement,
+        _ => return None
+    };
+    match assignment.kind {
+        StatementKind::Assign(Lvalue::Local(dest), Rvalue::Use(ref value)) => {
+            match *value {
+                Operand::Constant(_) |
+                Operand::Consume(Lvalue::Local(_)) => Some((dest, value, join)),
+                _ => None
+            }
+        }
+        _ => None
+    }
+}
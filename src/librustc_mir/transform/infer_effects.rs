@@ -0,0 +1,208 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Infers the effects of the functions of the crate, i.e. whether they may
+//! write memory other than their locals, access statics, panic or not
+//! return, for `TyCtxt::item_mir_effects`. They are stored in
+//! `tcx.mir_effects`, and encoded in metadata as part of `InlineSummary`.
+//!
+//! The functions are summarized bottom-up over the strongly connected
+//! components of the call graph, so that the effects of the callees are
+//! known before those of their callers. The functions of a component start
+//! without any effect, and are summarized again until nothing changes. A
+//! function calling itself, directly or not, may not return, as nothing
+//! says the recursion ends. Calls to functions of other crates use their
+//! summary from metadata, and calls through pointers or to trait methods,
+//! as well as drops, may do anything.
+//!
+//! The summaries only ever gain effects, so this ends, but a large component
+//! could take many rounds. After `ROUND_LIMIT` of them, or the number given
+//! with `-Z mir-effects-round-limit`, the functions of the component are
+//! assumed to do anything, and a warning lists them.
+//!
+//! The pass runs before the optimizations using the effects, and again at
+//! the end of the pipeline, as some passes add effects, e.g. the counters
+//! of `-Z profile-mir`.
+
+use rustc::hir::def_id::DefId;
+use rustc::mir::*;
+use rustc::mir::summary::EffectSummary;
+use rustc::mir::transform::{MirMapPass, MirPassHook, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::{DefIdMap, DefIdSet};
+use std::cmp;
+
+/// How many times the functions of a component are summarized before
+/// giving up on them, by default.
+pub const ROUND_LIMIT: usize = 100;
+
+pub struct InferEffects;
+
+impl Pass for InferEffects {}
+
+impl<'tcx> MirMapPass<'tcx> for InferEffects {
+    fn run_pass<'a>(&mut self,
+                    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    _: &mut [Box<for<'s> MirPassHook<'s>>]) {
+        let _ignore = tcx.dep_graph.in_ignore();
+
+        let def_ids = tcx.mir_map.borrow().keys();
+        let callees: DefIdMap<Vec<DefId>> = def_ids.into_iter().filter(|&def_id| {
+            match tcx.map.as_local_node_id(def_id) {
+                Some(id) => match MirSource::from_node(tcx, id) {
+                    MirSource::Fn(_) => true,
+                    _ => false
+                },
+                None => false
+            }
+        }).map(|def_id| {
+            let mut callees = vec![];
+            local_callees(tcx, &tcx.item_mir(def_id), &mut callees);
+            (def_id, callees)
+        }).collect();
+
+        let mut components = Components {
+            callees: &callees,
+            index: DefIdMap(),
+            low: DefIdMap(),
+            stack: vec![],
+            on_stack: DefIdSet(),
+            components: vec![],
+        };
+        for &def_id in callees.keys() {
+            if !components.index.contains_key(&def_id) {
+                components.visit(def_id);
+            }
+        }
+
+        for component in &components.components {
+            let first = component[0];
+            let recursive = component.len() > 1 || callees[&first].contains(&first);
+            for &def_id in component {
+                tcx.mir_effects.borrow_mut().insert(def_id, EffectSummary::none());
+            }
+
+            let limit = tcx.sess.opts.debugging_opts.mir_effects_round_limit
+                .unwrap_or(ROUND_LIMIT);
+            let mut rounds = 0;
+            loop {
+                if rounds == limit {
+                    give_up(tcx, component, limit);
+                    break;
+                }
+                rounds += 1;
+                let mut changed = false;
+                for &def_id in component {
+                    let mut effects = EffectSummary::of_body(tcx, &tcx.item_mir(def_id));
+                    effects.may_not_return |= recursive;
+                    let previous = tcx.mir_effects.borrow_mut().insert(def_id, effects);
+                    changed |= previous != Some(effects);
+                }
+                if !changed {
+                    break;
+                }
+            }
+            debug!("InferEffects: {:?} may {:?}", component, tcx.item_mir_effects(first));
+        }
+    }
+}
+
+/// Assumes the functions of `component`, which didn't settle within
+/// `limit` rounds, may do anything, and says which they are.
+fn give_up<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, component: &[DefId], limit: usize) {
+    for &def_id in component {
+        tcx.mir_effects.borrow_mut().insert(def_id, EffectSummary::unknown());
+    }
+    let span = tcx.def_span(component[0]);
+    let mut err = tcx.sess.struct_span_warn(span, &format!(
+        "the effects of {} mutually recursive functions didn't settle after {} rounds",
+        component.len(), limit));
+    for &def_id in component {
+        err.span_note(tcx.def_span(def_id),
+                      &format!("`{}` is one of them", tcx.item_path_str(def_id)));
+    }
+    err.note("their effects are assumed to be anything, which may pessimize calls to them; \
+              `-Z mir-effects-round-limit` raises the limit");
+    err.emit();
+}
+
+/// Pushes the local functions `mir` calls directly to `callees`, along
+/// with those its outlined cold parts call.
+fn local_callees<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>,
+                           callees: &mut Vec<DefId>) {
+    for block in mir.basic_blocks() {
+        if let TerminatorKind::Call { func: Operand::Constant(ref func), .. } =
+            block.terminator().kind {
+            if let ty::TyFnDef(callee, _, _) = func.ty.sty {
+                if callee.is_local() && tcx.trait_of_item(callee).is_none() {
+                    callees.push(callee);
+                }
+            }
+        }
+    }
+    for outlined in &mir.outlined {
+        local_callees(tcx, outlined, callees);
+    }
+}
+
+/// Tarjan's algorithm for the strongly connected components of the call
+/// graph, which finds the components of the callees before those of their
+/// callers.
+struct Components<'a> {
+    callees: &'a DefIdMap<Vec<DefId>>,
+    /// The order the functions were visited in.
+    index: DefIdMap<usize>,
+    /// The lowest index of a function on the stack reachable from each one.
+    low: DefIdMap<usize>,
+    stack: Vec<DefId>,
+    on_stack: DefIdSet,
+    components: Vec<Vec<DefId>>,
+}
+
+impl<'a> Components<'a> {
+    fn visit(&mut self, def_id: DefId) {
+        let index = self.index.len();
+        self.index.insert(def_id, index);
+        self.low.insert(def_id, index);
+        self.stack.push(def_id);
+        self.on_stack.insert(def_id);
+
+        let callees = self.callees;
+        for &callee in &callees[&def_id] {
+            // Functions without MIR have no callees to look at.
+            if !callees.contains_key(&callee) {
+                continue;
+            }
+            let low = if !self.index.contains_key(&callee) {
+                self.visit(callee);
+                self.low[&callee]
+            } else if self.on_stack.contains(&callee) {
+                self.index[&callee]
+            } else {
+                continue;
+            };
+            let low = cmp::min(self.low[&def_id], low);
+            self.low.insert(def_id, low);
+        }
+
+        if self.low[&def_id] == index {
+            let mut component = vec![];
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack.remove(&member);
+                component.push(member);
+                if member == def_id {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
@@ -36,10 +36,10 @@ impl<'tcx> MirPass<'tcx> for InstCombine {
     fn run_pass<'a>(&mut self,
                     tcx: TyCtxt<'a, 'tcx, 'tcx>,
                     _: MirSource,
-                    mir: &mut Mir<'tcx>) {
+                    mir: &mut Mir<'tcx>) -> bool {
         // We only run when optimizing MIR (at any level).
-        if tcx.sess.opts.debugging_opts.mir_opt_level == Some(0) {
-            return
+        if tcx.sess.opts.mir_opt_level == 0 {
+            return false
         }
 
         // First, find optimization opportunities. This is done in a pre-pass to keep the MIR
@@ -52,7 +52,9 @@ impl<'tcx> MirPass<'tcx> for InstCombine {
         }
 
         // Then carry out those optimizations.
+        let changed = !self.optimizations.and_stars.is_empty();
         MutVisitor::visit_mir(&mut *self, mir);
+        changed
     }
 }
 
@@ -0,0 +1,103 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! What the temporaries of a block are known to hold, for the passes
+//! folding constants to replace their reads by it, so that chains of
+//! operations fold as a whole. A temporary is known from its assignment
+//! until the end of the block, or until it may be written otherwise: by
+//! an assignment to part of it, through a reference to it, or by its
+//! storage ending.
+
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::util::nodemap::{FxHashMap, FxHashSet};
+use rustc_const_math::ConstInt;
+
+pub struct KnownTemps<T> {
+    temps: FxHashSet<Local>,
+    known: FxHashMap<Local, T>,
+}
+
+impl<T> KnownTemps<T> {
+    pub fn new(mir: &Mir) -> KnownTemps<T> {
+        KnownTemps {
+            temps: mir.temps_iter().collect(),
+            known: FxHashMap(),
+        }
+    }
+
+    /// Forgets all the temporaries, at the start of a block.
+    pub fn clear(&mut self) {
+        self.known.clear();
+    }
+
+    pub fn get(&self, local: Local) -> Option<&T> {
+        self.known.get(&local)
+    }
+
+    /// Records the effect of `statement`: a temporary it assigns becomes
+    /// known to hold what `value` gives for its rvalue, if anything, and
+    /// the locals it may write otherwise are forgotten.
+    pub fn record<'tcx, F>(&mut self, statement: &Statement<'tcx>, value: F)
+        where F: FnOnce(&Rvalue<'tcx>) -> Option<T>
+    {
+        match statement.kind {
+            StatementKind::Assign(ref lvalue, ref rvalue) => {
+                if let Rvalue::Ref(_, _, ref borrowed) = *rvalue {
+                    // It may be written through the reference.
+                    self.forget(borrowed);
+                }
+                self.forget(lvalue);
+                if let Lvalue::Local(local) = *lvalue {
+                    if self.temps.contains(&local) {
+                        if let Some(value) = value(rvalue) {
+                            self.known.insert(local, value);
+                        }
+                    }
+                }
+            }
+            StatementKind::SetDiscriminant { ref lvalue, .. } |
+            StatementKind::StorageDead(ref lvalue) => self.forget(lvalue),
+            _ => {}
+        }
+    }
+
+    fn forget(&mut self, lvalue: &Lvalue) {
+        if let Some(local) = lvalue.base_local() {
+            self.known.remove(&local);
+        }
+    }
+}
+
+impl<'tcx> KnownTemps<Constant<'tcx>> {
+    /// Replaces `operand` by its value, if it reads a known temporary.
+    pub fn replace(&self, operand: &mut Operand<'tcx>) {
+        let constant = match *operand {
+            Operand::Consume(Lvalue::Local(local)) => self.known.get(&local).cloned(),
+            _ => None
+        };
+        if let Some(constant) = constant {
+            *operand = Operand::Constant(constant);
+        }
+    }
+}
+
+/// Whether `constant` is an integer of a known type, a `bool` or a `char`.
+pub fn is_scalar(constant: &Constant) -> bool {
+    match constant.literal {
+        Literal::Value { value: ConstVal::Integral(value) } => match value {
+            ConstInt::Infer(_) | ConstInt::InferSigned(_) => false,
+            _ => true
+        },
+        Literal::Value { value: ConstVal::Bool(_) } |
+        Literal::Value { value: ConstVal::Char(_) } => true,
+        _ => false
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports the `large_moves` lint for moves and copies of values larger
+//! than `#![move_size_limit]`, e.g. a big array passed by value where a
+//! reference would do.
+//!
+//! Only values whose size is known without monomorphization are checked,
+//! i.e. not those whose type depends on type parameters.
+
+use rustc::lint;
+use rustc::lint::builtin::LARGE_MOVES;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::traits::Reveal;
+use rustc::ty::TyCtxt;
+use syntax_pos::Span;
+
+pub struct LargeMoves;
+
+impl Pass for LargeMoves {}
+
+impl<'tcx> MirPass<'tcx> for LargeMoves {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        let limit = tcx.sess.move_size_limit.get() as u64;
+        let mir = &*mir;
+
+        // The operands moved or copied, along with the span to report them at.
+        let mut moves: Vec<(&Operand<'tcx>, Span)> = vec![];
+        for data in mir.basic_blocks() {
+            for statement in &data.statements {
+                let span = statement.source_info.span;
+                match statement.kind {
+                    StatementKind::Assign(_, Rvalue::Use(ref operand)) |
+                    StatementKind::Assign(_, Rvalue::Repeat(ref operand, _)) => {
+                        moves.push((operand, span));
+                    }
+                    StatementKind::Assign(_, Rvalue::Aggregate(_, ref operands)) => {
+                        moves.extend(operands.iter().map(|operand| (operand, span)));
+                    }
+                    _ => {}
+                }
+            }
+            if let TerminatorKind::Call { ref args, .. } = data.terminator().kind {
+                let span = data.terminator().source_info.span;
+                moves.extend(args.iter().map(|arg| (arg, span)));
+            }
+        }
+
+        tcx.infer_ctxt(None, None, Reveal::All).enter(|infcx| {
+            for (operand, span) in moves {
+                match *operand {
+                    // Constants are materialized in place.
+                    Operand::Constant(_) => continue,
+                    // Temporaries are reported where they're filled in,
+                    // not again when they're moved on.
+                    Operand::Consume(Lvalue::Local(local))
+                        if mir.local_kind(local) == LocalKind::Temp => continue,
+                    Operand::Consume(_) => {}
+                }
+                let ty = operand.ty(mir, tcx);
+                let size = match ty.layout(&infcx) {
+                    Ok(layout) => layout.size(&tcx.data_layout).bytes(),
+                    Err(_) => continue
+                };
+                if size > limit {
+                    lint::emit_node_lint(tcx, LARGE_MOVES, id, span,
+                                         &format!("moving a value of type `{}` copies {} bytes, \
+                                                   above the limit of {} bytes",
+                                                  ty, size, limit));
+                }
+            }
+        });
+        false
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lowers calls to `mem::swap`, `ptr::read` and `ptr::write` on small
+//! values into plain MIR assignments through their pointer arguments:
+//!
+//!     DEST = swap(A, B)   =>   TMP = (*A); (*A) = (*B); (*B) = TMP;
+//!     DEST = read(P)      =>   DEST = (*P);
+//!     DEST = write(P, V)  =>   (*P) = V;
+//!
+//! The bodies of these functions shuffle the value through raw pointers and
+//! `copy_nonoverlapping`, which none of the MIR optimizations look through,
+//! while the assignments are moves like any other. Without a MIR inliner,
+//! the calls are recognized before their bodies would be inlined. The
+//! functions are marked `#[rustc_mem_fn = "swap"]`, `"read"` and `"write"`.
+//!
+//! This runs after drop elaboration, so the assignments neither drop the
+//! values they overwrite nor leave drop flags to update, matching the
+//! functions they replace.
+
+use rustc::hir::def_id::DefId;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::traits::Reveal;
+use rustc::ty::{self, Ty, TyCtxt};
+use syntax::attr;
+
+/// The largest values, in pointer sizes, which are moved directly.
+const MAX_SIZE_IN_POINTERS: u64 = 4;
+
+pub struct LowerMemCalls;
+
+impl Pass for LowerMemCalls {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum MemFn {
+    Swap,
+    Read,
+    Write,
+}
+
+impl<'tcx> MirPass<'tcx> for LowerMemCalls {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+
+        let mut calls = vec![];
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            let (func, args, destination) = match data.terminator().kind {
+                TerminatorKind::Call {
+                    func: Operand::Constant(ref func),
+                    ref args,
+                    destination: Some(ref destination),
+                    ..
+                } => (func, args, destination),
+                _ => continue
+            };
+            let (callee, substs) = match func.ty.sty {
+                ty::TyFnDef(callee, substs, _) => (callee, substs),
+                _ => continue
+            };
+            let mem_fn = match mem_fn(tcx, callee) {
+                Some(mem_fn) => mem_fn,
+                None => continue
+            };
+            if !is_small(tcx, substs.type_at(0)) {
+                debug!("LowerMemCalls: {:?} in {:?} moves a large or unknown type", mem_fn, bb);
+                continue;
+            }
+            // The pointers are dereferenced in place, so they must be lvalues.
+            let pointers = if mem_fn == MemFn::Swap { &args[..] } else { &args[..1] };
+            let constant_pointer = pointers.iter().any(|arg| match *arg {
+                Operand::Constant(_) => true,
+                Operand::Consume(_) => false
+            });
+            if !constant_pointer {
+                calls.push((bb, mem_fn, substs.type_at(0), args.clone(), destination.clone()));
+            }
+        }
+
+        let changed = !calls.is_empty();
+        for (bb, mem_fn, ty, mut args, (dest, target)) in calls {
+            debug!("LowerMemCalls: lowering {:?} in {:?}", mem_fn, bb);
+            let source_info = mir[bb].terminator().source_info;
+            let pointee = |arg: Operand<'tcx>| match arg {
+                Operand::Consume(lvalue) => lvalue.deref(),
+                Operand::Constant(_) => bug!("LowerMemCalls: constant pointer")
+            };
+
+            let mut assignments = vec![];
+            match mem_fn {
+                MemFn::Swap => {
+                    let y = pointee(args.pop().unwrap());
+                    let x = pointee(args.pop().unwrap());
+                    let temp = Lvalue::Local(mir.local_decls.push(LocalDecl::new_temp(ty)));
+                    assignments.push((temp.clone(), Rvalue::Use(Operand::Consume(x.clone()))));
+                    assignments.push((x, Rvalue::Use(Operand::Consume(y.clone()))));
+                    assignments.push((y, Rvalue::Use(Operand::Consume(temp))));
+                    assignments.push((dest, Rvalue::Aggregate(AggregateKind::Tuple, vec![])));
+                }
+                MemFn::Read => {
+                    let src = pointee(args.pop().unwrap());
+                    assignments.push((dest, Rvalue::Use(Operand::Consume(src))));
+                }
+                MemFn::Write => {
+                    let value = args.pop().unwrap();
+                    let dst = pointee(args.pop().unwrap());
+                    assignments.push((dst, Rvalue::Use(value)));
+                    assignments.push((dest, Rvalue::Aggregate(AggregateKind::Tuple, vec![])));
+                }
+            }
+
+            let data = &mut mir[bb];
+            data.statements.extend(assignments.into_iter().map(|(lvalue, rvalue)| Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(lvalue, rvalue),
+            }));
+            data.terminator_mut().kind = TerminatorKind::Goto { target: target };
+        }
+        changed
+    }
+}
+
+/// Which of the functions lowered here `def_id` is, if any.
+fn mem_fn<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> Option<MemFn> {
+    let attrs = tcx.get_attrs(def_id);
+    let name = match attr::first_attr_value_str_by_name(&attrs, "rustc_mem_fn") {
+        Some(name) => name,
+        None => return None
+    };
+    match &*name.as_str() {
+        "swap" => Some(MemFn::Swap),
+        "read" => Some(MemFn::Read),
+        "write" => Some(MemFn::Write),
+        _ => None
+    }
+}
+
+/// Whether the size of `ty` is known, and small enough for a move.
+fn is_small<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: Ty<'tcx>) -> bool {
+    let limit = tcx.data_layout.pointer_size.bytes() * MAX_SIZE_IN_POINTERS;
+    tcx.infer_ctxt(None, None, Reveal::All).enter(|infcx| {
+        match ty.layout(&infcx) {
+            Ok(layout) => layout.size(&tcx.data_layout).bytes() <= limit,
+            Err(_) => false
+        }
+    })
+}
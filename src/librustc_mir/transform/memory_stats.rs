@@ -0,0 +1,211 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Tracks how much memory the MIR of the crate holds on to after each
+//! optimization pass, for `-Z mir-memory-stats`, and which functions grew
+//! the most over the whole pipeline.
+//!
+//! The sizes count the memory allocated for the vectors of a MIR body, at
+//! their capacity, but not what the nodes in them point to, e.g. boxed
+//! projections and the operands of calls and aggregates. They are meant to
+//! tell which pass makes the MIR grow, not to match the RSS of the compiler.
+
+use std::cell::RefCell;
+use std::mem;
+use std::ops::{Add, Sub};
+use std::rc::Rc;
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPassHook, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use rustc::util::common::to_readable_str;
+use rustc::util::nodemap::NodeMap;
+
+/// How many of the functions which grew the most are listed.
+const TOP_FUNCTIONS: usize = 10;
+
+/// The hook tracking the sizes. Its clones add to the same statistics.
+#[derive(Clone)]
+pub struct MemoryStats {
+    stats: Rc<RefCell<Stats>>,
+}
+
+#[derive(Default)]
+struct Stats {
+    /// The total sizes after each pass, labelled by the pass.
+    passes: Vec<(String, Bytes)>,
+    /// The total size of the MIR of the functions seen so far.
+    total: Bytes,
+    /// The size of each function when it was first seen, and its current size.
+    functions: NodeMap<(Bytes, Bytes)>,
+}
+
+/// The bytes held by the MIR of a function, by what they are used for.
+#[derive(Copy, Clone, Default, Debug)]
+struct Bytes {
+    /// The blocks along with their terminators.
+    blocks: usize,
+    statements: usize,
+    /// The declarations of the locals and the debuginfo of the variables.
+    decls: usize,
+    scopes: usize,
+    /// The bodies of promoted constants and outlined cold code.
+    promoted: usize,
+}
+
+impl Bytes {
+    fn of(mir: &Mir) -> Bytes {
+        let statements = mir.basic_blocks().iter().map(|data| {
+            data.statements.capacity() * mem::size_of::<Statement>()
+        }).sum();
+        let promoted = mir.promoted.iter().chain(mir.outlined.iter()).map(|body| {
+            mem::size_of::<Mir>() + Bytes::of(body).total()
+        }).sum();
+        Bytes {
+            blocks: mir.basic_blocks().raw.capacity() * mem::size_of::<BasicBlockData>(),
+            statements: statements,
+            decls: mir.local_decls.raw.capacity() * mem::size_of::<LocalDecl>() +
+                   mir.var_debug_info.capacity() * mem::size_of::<VarDebugInfo>(),
+            scopes: mir.visibility_scopes.raw.capacity() * mem::size_of::<VisibilityScopeData>(),
+            promoted: promoted,
+        }
+    }
+
+    fn total(&self) -> usize {
+        self.blocks + self.statements + self.decls + self.scopes + self.promoted
+    }
+}
+
+impl Add for Bytes {
+    type Output = Bytes;
+
+    fn add(self, other: Bytes) -> Bytes {
+        Bytes {
+            blocks: self.blocks + other.blocks,
+            statements: self.statements + other.statements,
+            decls: self.decls + other.decls,
+            scopes: self.scopes + other.scopes,
+            promoted: self.promoted + other.promoted,
+        }
+    }
+}
+
+impl Sub for Bytes {
+    type Output = Bytes;
+
+    fn sub(self, other: Bytes) -> Bytes {
+        Bytes {
+            blocks: self.blocks - other.blocks,
+            statements: self.statements - other.statements,
+            decls: self.decls - other.decls,
+            scopes: self.scopes - other.scopes,
+            promoted: self.promoted - other.promoted,
+        }
+    }
+}
+
+impl MemoryStats {
+    pub fn new() -> MemoryStats {
+        MemoryStats { stats: Rc::new(RefCell::new(Stats::default())) }
+    }
+
+    pub fn print<'a, 'tcx>(&self, tcx: TyCtxt<'a, 'tcx, 'tcx>) {
+        let stats = self.stats.borrow();
+
+        println!("\nMIR MEMORY STATS\n");
+        println!("{:<40}{:>12}{:>12}{:>12}{:>12}{:>12}{:>14}",
+                 "Pass", "Blocks", "Statements", "Decls", "Scopes", "Promoted", "Total");
+        println!("--------------------------------------------------------------------------------\
+                  ----------------------------------");
+        for &(ref pass, bytes) in &stats.passes {
+            println!("{:<40}{:>12}{:>12}{:>12}{:>12}{:>12}{:>14}",
+                     pass,
+                     to_readable_str(bytes.blocks),
+                     to_readable_str(bytes.statements),
+                     to_readable_str(bytes.decls),
+                     to_readable_str(bytes.scopes),
+                     to_readable_str(bytes.promoted),
+                     to_readable_str(bytes.total()));
+        }
+
+        let mut grown: Vec<_> = stats.functions.iter().filter_map(|(&id, &(first, last))| {
+            if last.total() > first.total() {
+                Some((last.total() - first.total(), id, first.total(), last.total()))
+            } else {
+                None
+            }
+        }).collect();
+        grown.sort_by(|a, b| b.cmp(a));
+
+        println!("\nFunctions which grew the most\n");
+        println!("{:<70}{:>14}{:>14}{:>14}", "Function", "Before", "After", "Growth");
+        println!("--------------------------------------------------------------------------------\
+                  --------------------------------");
+        for &(growth, id, before, after) in grown.iter().take(TOP_FUNCTIONS) {
+            println!("{:<70}{:>14}{:>14}{:>14}",
+                     tcx.node_path_str(id),
+                     to_readable_str(before),
+                     to_readable_str(after),
+                     to_readable_str(growth));
+        }
+    }
+}
+
+impl Pass for MemoryStats {}
+
+impl<'tcx> MirPassHook<'tcx> for MemoryStats {
+    fn on_mir_pass<'a>(&mut self,
+                       _: TyCtxt<'a, 'tcx, 'tcx>,
+                       src: MirSource,
+                       mir: &Mir<'tcx>,
+                       pass: &Pass,
+                       is_after: bool)
+    {
+        // Promoted constants are counted along with their function.
+        if let MirSource::Promoted(..) = src {
+            return;
+        }
+
+        let mut stats = self.stats.borrow_mut();
+        let stats = &mut *stats;
+        let bytes = Bytes::of(mir);
+        let previous = match stats.functions.get(&src.item_id()) {
+            Some(&(_, previous)) => Some(previous),
+            None => None
+        };
+        match previous {
+            Some(previous) => {
+                stats.total = stats.total - previous + bytes;
+                stats.functions.get_mut(&src.item_id()).unwrap().1 = bytes;
+            }
+            None => {
+                stats.total = stats.total + bytes;
+                stats.functions.insert(src.item_id(), (bytes, bytes));
+            }
+        }
+        if !is_after {
+            return;
+        }
+
+        let label = match pass.disambiguator() {
+            Some(disambiguator) => format!("{}-{}", pass.name(), disambiguator),
+            None => pass.name().into_owned()
+        };
+        let total = stats.total;
+        match stats.passes.last_mut() {
+            Some(&mut (ref last, ref mut bytes)) if *last == label => {
+                *bytes = total;
+                return;
+            }
+            _ => {}
+        }
+        stats.passes.push((label, total));
+    }
+}
@@ -12,11 +12,41 @@ pub mod simplify_branches;
 pub mod simplify;
 pub mod erase_regions;
 pub mod no_landing_pads;
+pub mod opt_report;
+pub mod memory_stats;
 pub mod type_check;
 pub mod add_call_guards;
 pub mod promote_consts;
 pub mod qualify_consts;
 pub mod dump_mir;
 pub mod deaggregator;
+pub mod eval_consts;
 pub mod instcombine;
 pub mod copy_prop;
+pub mod branch_weights;
+pub mod profile_mir;
+pub mod elide_asserts;
+pub mod fold_overflow_checks;
+pub mod known_temps;
+pub mod fold_floats;
+pub mod fold_casts;
+pub mod propagate_const_args;
+pub mod devirtualize;
+pub mod optimized_away;
+pub mod check_refs;
+pub mod check_mir_asserts;
+pub mod poison_uninit;
+pub mod unconditional_panic;
+pub mod dead_assignments;
+pub mod dead_calls;
+pub mod infer_effects;
+pub mod no_unwind_calls;
+pub mod if_conversion;
+pub mod large_moves;
+pub mod lower_mem_calls;
+pub mod simplify_try;
+pub mod canonicalize_switches;
+pub mod outline_cold;
+pub mod verify_cleanup_edges;
+pub mod verify_equivalence;
+pub mod pipeline;
@@ -9,27 +9,44 @@
 // except according to those terms.
 
 //! This pass removes the unwind branch of all the terminators when the no-landing-pads option is
-//! specified.
+//! specified, which `-C panic=abort` implies. The cleanup blocks are deleted along with them, so
+//! the later passes don't have to look at code which can never run.
 
 use rustc::ty::TyCtxt;
 use rustc::mir::*;
 use rustc::mir::visit::MutVisitor;
 use rustc::mir::transform::{Pass, MirPass, MirSource};
+use rustc_back::PanicStrategy;
+
+use super::simplify::remove_dead_blocks;
 
 pub struct NoLandingPads;
 
-impl<'tcx> MutVisitor<'tcx> for NoLandingPads {
+struct RemoveLandingPads {
+    /// Whether the crate is built with `-C panic=abort`.
+    abort: bool,
+}
+
+impl<'tcx> MutVisitor<'tcx> for RemoveLandingPads {
     fn visit_terminator(&mut self,
                         bb: BasicBlock,
                         terminator: &mut Terminator<'tcx>,
                         location: Location) {
         match terminator.kind {
+            TerminatorKind::Resume => {
+                // Only reachable by unwinding into this frame. With `-C panic=abort`, that can
+                // only be a foreign exception, which must not unwind any further. With just
+                // `-Z no-landing-pads`, unwinding skips the cleanup of this frame altogether.
+                terminator.kind = if self.abort {
+                    TerminatorKind::Abort
+                } else {
+                    TerminatorKind::Unreachable
+                };
+            }
             TerminatorKind::Goto { .. } |
-            TerminatorKind::Resume |
             TerminatorKind::Return |
+            TerminatorKind::Abort |
             TerminatorKind::Unreachable |
-            TerminatorKind::If { .. } |
-            TerminatorKind::Switch { .. } |
             TerminatorKind::SwitchInt { .. } => {
                 /* nothing to do */
             },
@@ -46,10 +63,14 @@ impl<'tcx> MutVisitor<'tcx> for NoLandingPads {
 
 impl<'tcx> MirPass<'tcx> for NoLandingPads {
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    _: MirSource, mir: &mut Mir<'tcx>) {
-        if tcx.sess.no_landing_pads() {
-            self.visit_mir(mir);
+                    _: MirSource, mir: &mut Mir<'tcx>) -> bool {
+        if !tcx.sess.no_landing_pads() {
+            return false;
         }
+        let abort = tcx.sess.panic_strategy() == PanicStrategy::Abort;
+        RemoveLandingPads { abort: abort }.visit_mir(mir);
+        remove_dead_blocks(mir);
+        true
     }
 }
 
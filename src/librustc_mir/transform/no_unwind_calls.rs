@@ -0,0 +1,77 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Removes the unwind edge of the calls to functions which can't panic,
+//! according to the effects inferred for them by `InferEffects`, along with
+//! the cleanup blocks nothing unwinds to anymore. The calls are then
+//! translated as plain calls rather than invokes with a landing pad.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::DefIdMap;
+
+use super::simplify::remove_dead_blocks;
+
+pub struct NoUnwindCalls;
+
+impl Pass for NoUnwindCalls {}
+
+impl<'tcx> MirPass<'tcx> for NoUnwindCalls {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+        // `NoLandingPads` already removed every unwind edge.
+        if tcx.sess.no_landing_pads() {
+            return false;
+        }
+
+        let mut can_unwind = DefIdMap();
+        let mut removed = 0;
+        for data in mir.basic_blocks_mut() {
+            let (func, cleanup) = match data.terminator_mut().kind {
+                TerminatorKind::Call {
+                    func: Operand::Constant(ref func),
+                    ref mut cleanup,
+                    ..
+                } => (func, cleanup),
+                _ => continue
+            };
+            if cleanup.is_none() {
+                continue;
+            }
+            let callee = match func.ty.sty {
+                ty::TyFnDef(callee, _, _) => callee,
+                _ => continue
+            };
+            let unwinds = *can_unwind.entry(callee).or_insert_with(|| {
+                // The MIR of a trait method is only its default body.
+                tcx.trait_of_item(callee).is_some() ||
+                tcx.item_mir_effects(callee).can_unwind
+            });
+            if !unwinds {
+                *cleanup = None;
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            debug!("NoUnwindCalls: removed {} unwind edges in {:?}", removed, src);
+            remove_dead_blocks(mir);
+        }
+        removed > 0
+    }
+}
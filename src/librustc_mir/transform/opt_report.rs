@@ -0,0 +1,156 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Records what the MIR optimizations did, for `-Z mir-opt-report`, in
+//! the spirit of LLVM's optimization records. The MIR of each function is
+//! compared before and after each pass, and every change gives a remark:
+//! an assert removed by the pass, or the number of blocks or statements it
+//! eliminated.
+//!
+//! The report is a JSON object holding a `format_version` and the array
+//! of `remarks`, in the order the passes ran. Every remark has the `pass`
+//! and `function` it is about and its `kind`.
+
+use std::cell::RefCell;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPassHook, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use serialize::json::Json;
+use syntax_pos::Span;
+
+use json::{object, span_to_json, string};
+
+/// The version of the report format, bumped whenever a key is removed or
+/// its meaning changes.
+pub const FORMAT_VERSION: u64 = 1;
+
+/// The hook recording the report. Its clones add to the same report.
+#[derive(Clone)]
+pub struct OptReport {
+    remarks: Rc<RefCell<Vec<Json>>>,
+    /// What the MIR the current pass runs on looked like before it ran.
+    before: Option<Summary>,
+}
+
+#[derive(Clone)]
+struct Summary {
+    blocks: usize,
+    statements: usize,
+    /// The span and message of each `Assert` terminator.
+    asserts: Vec<(Span, &'static str)>,
+}
+
+impl Summary {
+    fn new(mir: &Mir) -> Summary {
+        let mut summary = Summary { blocks: 0, statements: 0, asserts: vec![] };
+        for data in mir.basic_blocks() {
+            summary.blocks += 1;
+            summary.statements += data.statements.iter().filter(|statement| {
+                match statement.kind {
+                    StatementKind::Nop => false,
+                    _ => true
+                }
+            }).count();
+            if let TerminatorKind::Assert { ref msg, .. } = data.terminator().kind {
+                let message = match **msg {
+                    AssertMessage::BoundsCheck { .. } => "index out of bounds",
+                    AssertMessage::Math(ref err) => err.description(),
+                    AssertMessage::NullPointerDeref => "null pointer dereference",
+                    AssertMessage::MisalignedPointerDeref => "misaligned pointer dereference",
+                };
+                summary.asserts.push((data.terminator().source_info.span, message));
+            }
+        }
+        summary
+    }
+}
+
+impl OptReport {
+    pub fn new() -> OptReport {
+        OptReport { remarks: Rc::new(RefCell::new(vec![])), before: None }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let report = object(vec![
+            ("format_version", Json::U64(FORMAT_VERSION)),
+            ("remarks", Json::Array(self.remarks.borrow().clone())),
+        ]);
+        let mut file = fs::File::create(path)?;
+        writeln!(file, "{}", report.pretty())
+    }
+}
+
+impl Pass for OptReport {}
+
+impl<'tcx> MirPassHook<'tcx> for OptReport {
+    fn on_mir_pass<'a>(&mut self,
+                       tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                       src: MirSource,
+                       mir: &Mir<'tcx>,
+                       pass: &Pass,
+                       is_after: bool)
+    {
+        if !is_after {
+            self.before = Some(Summary::new(mir));
+            return;
+        }
+        let before = match self.before.take() {
+            Some(before) => before,
+            None => return
+        };
+        let after = Summary::new(mir);
+
+        let pass_name = match pass.disambiguator() {
+            Some(disambiguator) => format!("{}-{}", pass.name(), disambiguator),
+            None => pass.name().into_owned()
+        };
+        let function = match src {
+            MirSource::Promoted(id, promoted) => {
+                format!("{} {:?}", tcx.node_path_str(id), promoted)
+            }
+            _ => tcx.node_path_str(src.item_id())
+        };
+        let remark = |kind: &str, mut fields: Vec<(&str, Json)>| {
+            fields.push(("pass", string(&pass_name)));
+            fields.push(("function", string(&function)));
+            fields.push(("kind", string(kind)));
+            object(fields)
+        };
+
+        let mut remarks = self.remarks.borrow_mut();
+        let mut remaining = after.asserts;
+        for (span, message) in before.asserts {
+            match remaining.iter().position(|&assert| assert == (span, message)) {
+                Some(index) => { remaining.swap_remove(index); }
+                None => {
+                    remarks.push(remark("RemovedAssert", vec![
+                        ("span", span_to_json(tcx, span)),
+                        ("message", string(message)),
+                    ]));
+                }
+            }
+        }
+        if after.blocks < before.blocks {
+            remarks.push(remark("RemovedBlocks", vec![
+                ("count", Json::U64((before.blocks - after.blocks) as u64)),
+            ]));
+        }
+        if after.statements < before.statements {
+            remarks.push(remark("RemovedStatements", vec![
+                ("count", Json::U64((before.statements - after.statements) as u64)),
+            ]));
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports the `optimized_away` lint for functions which the MIR
+//! optimizations reduced to returning, at most a constant, although their
+//! source does more. That is often code behind a configuration flag which
+//! is always off, or a stub left in by mistake.
+//!
+//! Functions whose source is trivial already, a block without statements
+//! whose value is at most a literal or a path, aren't reported, and neither
+//! are closures, `#[inline]` functions, which are expected to be small, and
+//! functions coming from macro expansions.
+
+use rustc::hir;
+use rustc::hir::intravisit::FnKind;
+use rustc::hir::map::blocks::FnLikeNode;
+use rustc::lint;
+use rustc::lint::builtin::OPTIMIZED_AWAY;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use syntax::attr;
+use syntax_pos::NO_EXPANSION;
+
+pub struct OptimizedAway;
+
+impl Pass for OptimizedAway {}
+
+impl<'tcx> MirPass<'tcx> for OptimizedAway {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        let returns_constant = match trivial_result(mir) {
+            Some(returns_constant) => returns_constant,
+            None => return false
+        };
+
+        let fn_like = match FnLikeNode::from_node(tcx.map.get(id)) {
+            Some(fn_like) => fn_like,
+            None => return false
+        };
+        if let FnKind::Closure(_) = fn_like.kind() {
+            return false;
+        }
+        let span = fn_like.span();
+        if span.expn_id != NO_EXPANSION ||
+           attr::find_inline_attr(None, tcx.map.attrs(id)) != attr::InlineAttr::None ||
+           is_trivial(tcx.map.expr(fn_like.body())) {
+            return false;
+        }
+
+        let msg = if returns_constant {
+            "the optimizations reduced this function to returning a constant"
+        } else {
+            "the optimizations reduced this function to doing nothing"
+        };
+        lint::emit_node_lint(tcx, OPTIMIZED_AWAY, id, span, msg);
+        false
+    }
+}
+
+/// Whether `mir` only assigns constants to locals and returns: `Some(true)`
+/// if it assigns one to the return value, `Some(false)` if it returns `()`
+/// or nothing at all.
+fn trivial_result(mir: &Mir) -> Option<bool> {
+    if mir.basic_blocks().len() != 1 {
+        return None;
+    }
+    let data = &mir[START_BLOCK];
+    match data.terminator().kind {
+        TerminatorKind::Return => {}
+        _ => return None
+    }
+
+    let mut returns_constant = false;
+    for statement in &data.statements {
+        match statement.kind {
+            StatementKind::Assign(Lvalue::Local(local), Rvalue::Use(Operand::Constant(_))) => {
+                returns_constant |= local == RETURN_POINTER;
+            }
+            StatementKind::Assign(Lvalue::Local(_),
+                                  Rvalue::Aggregate(AggregateKind::Tuple, ref operands))
+                if operands.is_empty() => {}
+            StatementKind::StorageLive(_) |
+            StatementKind::StorageDead(_) |
+            StatementKind::Nop => {}
+            _ => return None
+        }
+    }
+    Some(returns_constant && !mir.return_ty.is_nil())
+}
+
+/// Whether the body `expr` of a function is trivial: a block without
+/// statements, whose value is at most a literal or a path.
+fn is_trivial(expr: &hir::Expr) -> bool {
+    match expr.node {
+        hir::ExprBlock(ref block) => {
+            block.stmts.is_empty() && block.expr.as_ref().map_or(true, |expr| is_trivial(expr))
+        }
+        hir::ExprTup(ref exprs) => exprs.iter().all(is_trivial),
+        hir::ExprLit(_) | hir::ExprPath(..) => true,
+        _ => false
+    }
+}
@@ -0,0 +1,496 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Moves the cold parts of a function into functions of their own under
+//! `-Z mir-outline-cold`, so that the code which normally runs is smaller.
+//!
+//! A part is cold when it can't reach the `Return` of the function, i.e. it
+//! ends in a call which diverges, usually to panic after formatting the
+//! message or building the value to panic with. It is outlined when it has a
+//! single entry block, no loops, and at least `MIN_OUTLINED_SIZE` statements
+//! and terminators. Its terminators must all unwind to the same cleanup
+//! block, or all not at all, because the call to the outlined function
+//! unwinds there instead.
+//!
+//! Functions marked `#[optimize(size)]` have their cold parts outlined
+//! without the flag, down to `MIN_OUTLINED_SIZE_FOR_SIZE`. Functions with
+//! an attribute applying to them as a whole, such as `#[naked]`, are left
+//! alone.
+//!
+//! The outlined function takes a reference to each local the part shares
+//! with the rest of the function, and gets the locals only the part uses,
+//! along with the promoted constants only the part uses. Its MIR is kept in
+//! `Mir::outlined`, and the entry block of the part becomes a call to it
+//! through a `Literal::Outlined` constant. The rest of the part is left
+//! unreachable for `SimplifyCfg` to remove.
+//!
+//! The panics of `Assert` terminators have no blocks of their own in MIR,
+//! and are left to trans.
+
+use rustc::hir;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::traversal;
+use rustc::mir::visit::{LvalueContext, MutVisitor, Visitor};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_data_structures::bitvec::BitVector;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax::abi::Abi;
+use syntax::attr::{self, OptimizeAttr};
+use syntax_pos::Span;
+use std::mem;
+
+/// The smallest number of statements and terminators a cold part must have
+/// to be outlined.
+const MIN_OUTLINED_SIZE: usize = 16;
+
+/// The smallest cold part outlined in a function optimized for size, which
+/// still leaves the call to the outlined function smaller than the part.
+const MIN_OUTLINED_SIZE_FOR_SIZE: usize = 6;
+
+pub struct OutlineCold;
+
+impl Pass for OutlineCold {}
+
+impl<'tcx> MirPass<'tcx> for OutlineCold {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        let attrs = tcx.map.attrs(id);
+        if attr::has_function_granular_attr(attrs) {
+            return false;
+        }
+        let min_size = match attr::find_optimize_attr(None, attrs) {
+            OptimizeAttr::Size => MIN_OUTLINED_SIZE_FOR_SIZE,
+            OptimizeAttr::Speed |
+            OptimizeAttr::None if tcx.sess.opts.debugging_opts.mir_outline_cold => {
+                MIN_OUTLINED_SIZE
+            }
+            _ => return false
+        };
+
+        let returning = returning_blocks(mir);
+        let mut uses = LocalUses { blocks: IndexVec::from_elem(vec![], &mir.local_decls) };
+        uses.visit_mir(mir);
+
+        // Entries are looked for in reverse postorder, so that a part is
+        // outlined as a whole rather than from one of its inner blocks.
+        let order: Vec<_> = traversal::reverse_postorder(mir).map(|(bb, _)| bb).collect();
+        let mut outlined = BitVector::new(mir.basic_blocks().len());
+        let mut changed = false;
+        for entry in order {
+            if entry == START_BLOCK || returning.contains(entry.index()) ||
+               outlined.contains(entry.index()) || mir[entry].is_cleanup {
+                continue;
+            }
+            let part = match cold_part(mir, &outlined, entry, min_size) {
+                Some(part) => part,
+                None => continue
+            };
+            if outline(tcx, mir, &part, &uses.blocks, &outlined) {
+                debug!("OutlineCold: outlined {:?}", part.blocks);
+                for bb in part.blocks {
+                    outlined.insert(bb.index());
+                }
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+struct ColdPart {
+    /// The blocks of the part, starting with its entry.
+    blocks: Vec<BasicBlock>,
+
+    /// Where the terminators of the part unwind to.
+    cleanup: Option<BasicBlock>,
+}
+
+/// The blocks from which the `Return` of the function can be reached
+/// without unwinding.
+fn returning_blocks(mir: &Mir) -> BitVector {
+    let mut returning = BitVector::new(mir.basic_blocks().len());
+    let mut worklist = vec![];
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        if let TerminatorKind::Return = data.terminator().kind {
+            returning.insert(bb.index());
+            worklist.push(bb);
+        }
+    }
+    while let Some(bb) = worklist.pop() {
+        for &pred in mir.predecessors_for(bb).iter() {
+            let normal = normal_successors(&mir[pred].terminator().kind).contains(&bb);
+            if normal && returning.insert(pred.index()) {
+                worklist.push(pred);
+            }
+        }
+    }
+    returning
+}
+
+/// The successors of a terminator other than its unwind target.
+fn normal_successors(kind: &TerminatorKind) -> Vec<BasicBlock> {
+    match *kind {
+        TerminatorKind::Goto { target } |
+        TerminatorKind::Drop { target, .. } |
+        TerminatorKind::DropAndReplace { target, .. } |
+        TerminatorKind::Assert { target, .. } |
+        TerminatorKind::Call { destination: Some((_, target)), .. } => vec![target],
+        TerminatorKind::SwitchInt { ref targets, .. } => targets.clone(),
+        TerminatorKind::Call { destination: None, .. } |
+        TerminatorKind::Resume |
+        TerminatorKind::Abort |
+        TerminatorKind::Return |
+        TerminatorKind::Unreachable => vec![],
+    }
+}
+
+/// The cold part starting at `entry`, a block which can't return, if it can
+/// be outlined and has at least `min_size` statements and terminators.
+fn cold_part(mir: &Mir, outlined: &BitVector, entry: BasicBlock, min_size: usize)
+             -> Option<ColdPart> {
+    let mut blocks = vec![entry];
+    let mut in_part = BitVector::new(mir.basic_blocks().len());
+    in_part.insert(entry.index());
+
+    // A depth-first search, which finds loops as edges to the blocks on its stack.
+    let mut on_stack = IndexVec::from_elem(false, mir.basic_blocks());
+    on_stack[entry] = true;
+    let mut stack = vec![(entry, 0)];
+    while let Some((bb, i)) = stack.pop() {
+        let successors = normal_successors(&mir[bb].terminator().kind);
+        if i == successors.len() {
+            on_stack[bb] = false;
+            continue;
+        }
+        stack.push((bb, i + 1));
+
+        let succ = successors[i];
+        if on_stack[succ] {
+            return None;
+        }
+        if in_part.insert(succ.index()) {
+            if mir[succ].is_cleanup || outlined.contains(succ.index()) {
+                return None;
+            }
+            blocks.push(succ);
+            on_stack[succ] = true;
+            stack.push((succ, 0));
+        }
+    }
+
+    // Only the entry may be reached from the rest of the function.
+    for &bb in &blocks[1..] {
+        if mir.predecessors_for(bb).iter().any(|pred| !in_part.contains(pred.index())) {
+            return None;
+        }
+    }
+
+    let mut cleanup = None;
+    let mut unwinds = false;
+    let mut size = 0;
+    for &bb in &blocks {
+        size += mir[bb].statements.len() + 1;
+        let unwind = match mir[bb].terminator().kind {
+            TerminatorKind::Call { cleanup: unwind, .. } |
+            TerminatorKind::Assert { cleanup: unwind, .. } |
+            TerminatorKind::Drop { unwind, .. } |
+            TerminatorKind::DropAndReplace { unwind, .. } => unwind,
+            // Only cleanup blocks may resume unwinding.
+            TerminatorKind::Resume => return None,
+            _ => continue
+        };
+        if unwinds && unwind != cleanup {
+            return None;
+        }
+        unwinds = true;
+        cleanup = unwind;
+    }
+    if size < min_size {
+        return None;
+    }
+
+    Some(ColdPart {
+        blocks: blocks,
+        cleanup: cleanup,
+    })
+}
+
+/// Moves `part` into a function of its own, returning whether it could.
+/// `uses` has the blocks using each of the locals which existed before any
+/// part was outlined, and `outlined` the blocks already moved out.
+fn outline<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                     mir: &mut Mir<'tcx>,
+                     part: &ColdPart,
+                     uses: &IndexVec<Local, Vec<BasicBlock>>,
+                     outlined: &BitVector)
+                     -> bool {
+    let mut in_part = BitVector::new(mir.basic_blocks().len());
+    for bb in &part.blocks {
+        in_part.insert(bb.index());
+    }
+    let elsewhere = |bb: &BasicBlock| !in_part.contains(bb.index()) &&
+                                      !outlined.contains(bb.index());
+
+    // The promoted constants used by the part must not be used by the rest of the function.
+    let part_promoted = promoted_uses(mir, part.blocks.iter().cloned());
+    let other_promoted = promoted_uses(mir, mir.basic_blocks().indices().filter(&elsewhere));
+    if part_promoted.iter().any(|index| other_promoted.contains(index)) {
+        debug!("OutlineCold: {:?} shares promoted constants", part.blocks);
+        return false;
+    }
+
+    // The arguments and return pointer, and the locals the rest of the function uses, are
+    // shared with the part. The others only belong to it.
+    let mut shared = vec![];
+    let mut own = vec![];
+    let mut is_shared = BitVector::new(mir.local_decls.len());
+    for (local, blocks) in uses.iter_enumerated() {
+        if !blocks.iter().any(|bb| in_part.contains(bb.index())) {
+            continue;
+        }
+        if local.index() <= mir.arg_count || blocks.iter().any(&elsewhere) {
+            shared.push(local);
+            is_shared.insert(local.index());
+        } else {
+            own.push(local);
+        }
+    }
+
+    let mut local_decls = IndexVec::new();
+    local_decls.push(LocalDecl::new_return_pointer(tcx.types.never));
+    let mut locals = IndexVec::from_elem(None, &mir.local_decls);
+    let mut arg_tys = vec![];
+    for &local in &shared {
+        let ty = tcx.mk_mut_ref(tcx.mk_region(ty::ReErased), mir.local_decls[local].ty);
+        let arg = local_decls.push(LocalDecl::new_temp(ty));
+        locals[local] = Some(Lvalue::Local(arg).deref());
+        arg_tys.push(ty);
+    }
+    for &local in &own {
+        locals[local] = Some(Lvalue::Local(local_decls.push(mir.local_decls[local].clone())));
+    }
+
+    // The storage of the shared locals is left to the function, which must make those the part
+    // starts live before it calls the outlined function.
+    let mut block_map = IndexVec::from_elem(START_BLOCK, mir.basic_blocks());
+    for (i, &bb) in part.blocks.iter().enumerate() {
+        block_map[bb] = BasicBlock::new(i);
+    }
+    let mut storage_live = vec![];
+    let mut blocks = IndexVec::new();
+    for &bb in &part.blocks {
+        let mut data = mir[bb].clone();
+        for statement in &mut data.statements {
+            let (live, local) = match statement.kind {
+                StatementKind::StorageLive(Lvalue::Local(local)) => (true, local),
+                StatementKind::StorageDead(Lvalue::Local(local)) => (false, local),
+                _ => continue
+            };
+            if is_shared.contains(local.index()) {
+                if live {
+                    storage_live.push(statement.clone());
+                }
+                statement.make_nop();
+            }
+        }
+        {
+            let terminator = data.terminator_mut();
+            match terminator.kind {
+                TerminatorKind::Call { cleanup: ref mut unwind, .. } |
+                TerminatorKind::Assert { cleanup: ref mut unwind, .. } |
+                TerminatorKind::Drop { ref mut unwind, .. } |
+                TerminatorKind::DropAndReplace { ref mut unwind, .. } => {
+                    unwind.take();
+                }
+                _ => {}
+            }
+            for target in terminator.successors_mut() {
+                *target = block_map[*target];
+            }
+        }
+        blocks.push(data);
+    }
+
+    let mut promoted_map = IndexVec::from_elem(None, &mir.promoted);
+    let mut promoted = IndexVec::new();
+    for index in part_promoted.iter() {
+        let index = Promoted::new(index);
+        let placeholder = unreachable_mir(mir.promoted[index].return_ty, mir.span);
+        promoted_map[index] = Some(promoted.push(mem::replace(&mut mir.promoted[index],
+                                                              placeholder)));
+    }
+
+    let mut body = Mir::new(blocks,
+                            mir.visibility_scopes.clone(),
+                            promoted,
+                            tcx.types.never,
+                            local_decls,
+                            shared.len(),
+                            vec![],
+                            mir.span);
+    // The outlined function has no debuginfo to describe variables in.
+    body.var_debug_info.clear();
+    RenameLocals { locals: &locals }.visit_mir(&mut body);
+    let mut renumber = RenumberPromoted { promoted: &promoted_map };
+    renumber.visit_mir(&mut body);
+    for promoted in body.promoted.iter_mut() {
+        renumber.visit_mir(promoted);
+    }
+    let index = mir.outlined.push(body);
+
+    for &local in &own {
+        mir.replace_var_debug_info(local, VarDebugInfoContents::OptimizedOut);
+    }
+
+    let entry = part.blocks[0];
+    let source_info = mir[entry].terminator().source_info;
+    let mut statements = storage_live;
+    let mut args = vec![];
+    for (&local, &ty) in shared.iter().zip(&arg_tys) {
+        let temp = Lvalue::Local(mir.local_decls.push(LocalDecl::new_temp(ty)));
+        statements.push(Statement {
+            source_info: source_info,
+            kind: StatementKind::Assign(temp.clone(), Rvalue::Ref(tcx.mk_region(ty::ReErased),
+                                                                  BorrowKind::Mut,
+                                                                  Lvalue::Local(local))),
+        });
+        args.push(Operand::Consume(temp));
+    }
+    let sig = tcx.mk_fn_sig(arg_tys.into_iter(), tcx.types.never, false);
+    let fn_ty = tcx.mk_fn_ptr(tcx.mk_bare_fn(ty::BareFnTy {
+        unsafety: hir::Unsafety::Normal,
+        abi: Abi::Rust,
+        sig: ty::Binder(sig)
+    }));
+
+    let data = &mut mir[entry];
+    data.statements = statements;
+    data.terminator_mut().kind = TerminatorKind::Call {
+        func: Operand::Constant(Constant {
+            span: source_info.span,
+            ty: fn_ty,
+            literal: Literal::Outlined { index: index },
+        }),
+        args: args,
+        destination: None,
+        cleanup: part.cleanup,
+    };
+    true
+}
+
+/// The promoted constants used by `blocks`, directly or through other promoted constants.
+fn promoted_uses<'tcx, I>(mir: &Mir<'tcx>, blocks: I) -> BitVector
+    where I: Iterator<Item=BasicBlock>
+{
+    let mut uses = PromotedUses { promoted: BitVector::new(mir.promoted.len()) };
+    for bb in blocks {
+        uses.visit_basic_block_data(bb, &mir[bb]);
+    }
+    let mut worklist: Vec<_> = uses.promoted.iter().collect();
+    while let Some(index) = worklist.pop() {
+        let mut inner = PromotedUses { promoted: BitVector::new(mir.promoted.len()) };
+        inner.visit_mir(&mir.promoted[Promoted::new(index)]);
+        for used in inner.promoted.iter() {
+            if uses.promoted.insert(used) {
+                worklist.push(used);
+            }
+        }
+    }
+    uses.promoted
+}
+
+/// A body which is never used, left in place of a promoted constant moved
+/// into an outlined function until `SimplifyCfg` removes it.
+fn unreachable_mir<'tcx>(return_ty: Ty<'tcx>, span: Span) -> Mir<'tcx> {
+    let source_info = SourceInfo {
+        span: span,
+        scope: ARGUMENT_VISIBILITY_SCOPE,
+    };
+    let mut blocks = IndexVec::new();
+    blocks.push(BasicBlockData::new(Some(Terminator {
+        source_info: source_info,
+        kind: TerminatorKind::Unreachable,
+    })));
+    let mut scopes = IndexVec::new();
+    scopes.push(VisibilityScopeData {
+        span: span,
+        parent_scope: None,
+    });
+    let mut local_decls = IndexVec::new();
+    local_decls.push(LocalDecl::new_return_pointer(return_ty));
+    Mir::new(blocks, scopes, IndexVec::new(), return_ty, local_decls, 0, vec![], span)
+}
+
+struct LocalUses {
+    blocks: IndexVec<Local, Vec<BasicBlock>>,
+}
+
+impl<'tcx> Visitor<'tcx> for LocalUses {
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Local(local) = *lvalue {
+            let blocks = &mut self.blocks[local];
+            if blocks.last() != Some(&location.block) {
+                blocks.push(location.block);
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+struct PromotedUses {
+    promoted: BitVector,
+}
+
+impl<'tcx> Visitor<'tcx> for PromotedUses {
+    fn visit_literal(&mut self, literal: &Literal<'tcx>, _: Location) {
+        if let Literal::Promoted { index } = *literal {
+            self.promoted.insert(index.index());
+        }
+    }
+}
+
+/// Replaces the locals of the function in an outlined part by those of the
+/// outlined function.
+struct RenameLocals<'a, 'tcx: 'a> {
+    locals: &'a IndexVec<Local, Option<Lvalue<'tcx>>>,
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for RenameLocals<'a, 'tcx> {
+    fn visit_lvalue(&mut self,
+                    lvalue: &mut Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Local(local) = *lvalue {
+            *lvalue = self.locals[local].clone().expect("local not used by the outlined part");
+            return;
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+}
+
+struct RenumberPromoted<'a> {
+    promoted: &'a IndexVec<Promoted, Option<Promoted>>,
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for RenumberPromoted<'a> {
+    fn visit_literal(&mut self, literal: &mut Literal<'tcx>, _: Location) {
+        if let Literal::Promoted { ref mut index } = *literal {
+            *index = self.promoted[*index].expect("promoted constant not moved");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The passes run once drops are elaborated, in pipeline order. The driver
+//! runs all of them, while shims and `-Z mir-fuzz` only get the
+//! optimizations among them, so all of them see the same passes in the
+//! same order.
+
+use rustc::mir::transform::{MirMapPass, MirPass, Passes};
+use transform::{add_call_guards, branch_weights, canonicalize_switches, check_mir_asserts};
+use transform::{copy_prop, dead_calls, deaggregator, devirtualize, fold_casts, fold_floats};
+use transform::{if_conversion, infer_effects, instcombine, lower_mem_calls, no_unwind_calls};
+use transform::{optimized_away, outline_cold, poison_uninit, profile_mir, propagate_const_args};
+use transform::{simplify, simplify_branches, simplify_try};
+
+/// Receives the passes of the pipeline.
+pub trait PassSink {
+    /// A pass which transforms each function on its own without changing
+    /// what it computes.
+    fn optimization<P>(&mut self, pass: P) where P: 'static + for<'tcx> MirPass<'tcx>;
+
+    /// Any other pass, e.g. one working on the whole crate, adding code for
+    /// trans or reporting lints.
+    fn other<P>(&mut self, pass: P) where P: 'static + for<'tcx> MirMapPass<'tcx>;
+}
+
+impl PassSink for Passes {
+    fn optimization<P>(&mut self, pass: P) where P: 'static + for<'tcx> MirPass<'tcx> {
+        self.push_pass(box pass);
+    }
+
+    fn other<P>(&mut self, pass: P) where P: 'static + for<'tcx> MirMapPass<'tcx> {
+        self.push_pass(box pass);
+    }
+}
+
+/// The optimizations of the pipeline, leaving out the passes working on the
+/// whole crate, adding code for trans or reporting lints.
+pub fn optimizations() -> Vec<Box<for<'tcx> MirPass<'tcx>>> {
+    let mut optimizations = Optimizations(vec![]);
+    push_late_passes(&mut optimizations);
+    optimizations.0
+}
+
+struct Optimizations(Vec<Box<for<'tcx> MirPass<'tcx>>>);
+
+impl PassSink for Optimizations {
+    fn optimization<P>(&mut self, pass: P) where P: 'static + for<'tcx> MirPass<'tcx> {
+        self.0.push(box pass);
+    }
+
+    fn other<P>(&mut self, _: P) where P: 'static + for<'tcx> MirMapPass<'tcx> {}
+}
+
+/// Pushes the passes run once drops are elaborated.
+pub fn push_late_passes<S: PassSink>(sink: &mut S) {
+    sink.optimization(instcombine::InstCombine::new());
+    sink.optimization(devirtualize::Devirtualize);
+    sink.optimization(fold_floats::FoldFloats);
+    sink.optimization(fold_casts::FoldCasts);
+    sink.other(propagate_const_args::PropagateConstArgs);
+    sink.optimization(simplify_try::SimplifyTry);
+    sink.optimization(lower_mem_calls::LowerMemCalls);
+    sink.optimization(deaggregator::Deaggregator);
+    sink.optimization(simplify_branches::SimplifyBranches::new("after-deaggregator"));
+    sink.optimization(simplify::SimplifyCfg::new("simplify-branches"));
+    sink.optimization(copy_prop::CopyPropagation);
+    sink.other(infer_effects::InferEffects);
+    sink.optimization(no_unwind_calls::NoUnwindCalls);
+    sink.optimization(dead_calls::DeadCalls);
+    sink.optimization(simplify::SimplifyCfg::new("dead-calls"));
+    sink.optimization(if_conversion::IfConversion);
+    sink.optimization(simplify::SimplifyCfg::new("if-conversion"));
+    sink.other(outline_cold::OutlineCold);
+    sink.optimization(simplify::SimplifyCfg::new("outline-cold"));
+
+    sink.optimization(simplify::SimplifyLocals);
+    sink.other(add_call_guards::AddCallGuards);
+    sink.optimization(canonicalize_switches::CanonicalizeSwitches);
+    sink.other(branch_weights::BranchWeights);
+    sink.other(profile_mir::ProfileMir);
+    sink.other(poison_uninit::PoisonUninit);
+    sink.optimization(simplify::CompactStatements);
+    sink.other(optimized_away::OptimizedAway);
+    sink.other(infer_effects::InferEffects);
+    sink.other(check_mir_asserts::CheckMirAsserts);
+}
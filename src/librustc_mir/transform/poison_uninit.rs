@@ -0,0 +1,118 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Fills the memory of locals with a byte pattern when it doesn't hold a
+//! value, for `-Z mir-poison-uninit` in builds with debug assertions, so
+//! that unsafe code reading it gets the same recognizable garbage every
+//! time rather than whatever was left on the stack.
+//!
+//! A local is poisoned right after its `StorageLive`, and after a statement
+//! or call moves it out as a whole. Moves out of part of a local, and
+//! calls whose return block can also be reached some other way, leave it
+//! as is. The pass runs after the other optimizations so that they see
+//! neither the `Poison` statements nor the reads they would otherwise
+//! consider dead.
+
+use rustc::mir::*;
+use rustc::mir::patch::MirPatch;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+use syntax_pos::DUMMY_SP;
+
+pub struct PoisonUninit;
+
+impl Pass for PoisonUninit {}
+
+impl<'tcx> MirPass<'tcx> for PoisonUninit {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        if !tcx.sess.opts.debugging_opts.mir_poison_uninit || !tcx.sess.opts.debug_assertions {
+            return false;
+        }
+
+        let mut patch = MirPatch::new(mir);
+        {
+            let param_env = ty::ParameterEnvironment::for_item(tcx, id);
+            let moved_local = |operand: &Operand<'tcx>, dest: &Lvalue<'tcx>| {
+                let local = match *operand {
+                    Operand::Consume(Lvalue::Local(local)) => local,
+                    _ => return None
+                };
+                if mir.local_kind(local) == LocalKind::ReturnPointer {
+                    return None;
+                }
+                if dest.is_direct() && dest.base_local() == Some(local) {
+                    return None;
+                }
+                let ty = mir.local_decls[local].ty;
+                if ty.moves_by_default(tcx, &param_env, DUMMY_SP) {
+                    Some(local)
+                } else {
+                    None
+                }
+            };
+
+            for (bb, data) in mir.basic_blocks().iter_enumerated() {
+                for (index, statement) in data.statements.iter().enumerate() {
+                    let after = Location { block: bb, statement_index: index + 1 };
+                    let poisoned = match statement.kind {
+                        StatementKind::StorageLive(Lvalue::Local(local)) => vec![local],
+                        StatementKind::Assign(ref dest, ref rvalue) => {
+                            rvalue_operands(rvalue).into_iter().filter_map(|operand| {
+                                moved_local(operand, dest)
+                            }).collect()
+                        }
+                        _ => vec![]
+                    };
+                    for local in poisoned {
+                        patch.add_statement(after, StatementKind::Poison(Lvalue::Local(local)));
+                    }
+                }
+
+                if let TerminatorKind::Call {
+                    ref args, destination: Some((ref dest, target)), ..
+                } = data.terminator().kind {
+                    if mir.predecessors_for(target).len() == 1 {
+                        let start = Location { block: target, statement_index: 0 };
+                        for local in args.iter().filter_map(|arg| moved_local(arg, dest)) {
+                            patch.add_statement(start,
+                                                StatementKind::Poison(Lvalue::Local(local)));
+                        }
+                    }
+                }
+            }
+        }
+        let changed = !patch.is_empty();
+        patch.apply(mir);
+        changed
+    }
+}
+
+/// The operands of `rvalue` which it may move out of.
+fn rvalue_operands<'a, 'tcx>(rvalue: &'a Rvalue<'tcx>) -> Vec<&'a Operand<'tcx>> {
+    match *rvalue {
+        Rvalue::Use(ref operand) |
+        Rvalue::Repeat(ref operand, _) |
+        Rvalue::Cast(_, ref operand, _) |
+        Rvalue::UnaryOp(_, ref operand) => vec![operand],
+        Rvalue::BinaryOp(_, ref lhs, ref rhs) |
+        Rvalue::CheckedBinaryOp(_, ref lhs, ref rhs) => vec![lhs, rhs],
+        Rvalue::Aggregate(_, ref operands) => operands.iter().collect(),
+        Rvalue::InlineAsm { ref inputs, .. } => inputs.iter().collect(),
+        Rvalue::Ref(..) |
+        Rvalue::Len(_) |
+        Rvalue::Discriminant(_) |
+        Rvalue::Box(_) => vec![],
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Instruments functions for `-Z profile-mir` and `-Z mir-coverage`, by
+//! counting how often each basic block is entered. Counter `i` of a
+//! function belongs to its block `bbi`; trans allocates the counters and
+//! the runtime dumps them.
+//!
+//! For coverage, the span of each counter is the source region of its
+//! block, which trans records in the coverage map.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::indexed_vec::Idx;
+use syntax::codemap::CodeMap;
+use syntax_pos::Span;
+
+use std::cmp;
+
+pub struct ProfileMir;
+
+impl Pass for ProfileMir {}
+
+impl<'tcx> MirPass<'tcx> for ProfileMir {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let coverage = tcx.sess.opts.debugging_opts.mir_coverage;
+        if !tcx.sess.opts.debugging_opts.profile_mir && !coverage {
+            return false;
+        }
+
+        // Constants are evaluated at compile-time, there's nothing to count.
+        match src {
+            MirSource::Fn(_) => {}
+            _ => return false
+        }
+
+        let codemap = tcx.sess.codemap();
+        let fn_span = codemap.source_callsite(mir.span);
+        for (bb, data) in mir.basic_blocks_mut().iter_enumerated_mut() {
+            // Unwinding is rare enough not to be worth profiling.
+            if data.is_cleanup {
+                continue;
+            }
+
+            let mut source_info = match data.statements.first() {
+                Some(statement) => statement.source_info,
+                None => data.terminator().source_info,
+            };
+            if coverage {
+                source_info.span = block_region(codemap, fn_span, data);
+            }
+            data.statements.insert(0, Statement {
+                source_info: source_info,
+                kind: StatementKind::IncrementCounter(bb.index() as u32),
+            });
+        }
+        true
+    }
+}
+
+/// The smallest source region of the function covering the code of the
+/// block, with macro expansions attributed to their outermost call site.
+fn block_region(codemap: &CodeMap, fn_span: Span, data: &BasicBlockData) -> Span {
+    let spans = data.statements.iter()
+        .map(|statement| statement.source_info.span)
+        .chain(Some(data.terminator().source_info.span));
+
+    let mut region: Option<Span> = None;
+    for span in spans {
+        let span = codemap.source_callsite(span);
+        if span.lo < fn_span.lo || span.hi > fn_span.hi {
+            // Only regions of the function's own source are reported.
+            continue;
+        }
+        region = Some(match region {
+            Some(region) => Span {
+                lo: cmp::min(region.lo, span.lo),
+                hi: cmp::max(region.hi, span.hi),
+                expn_id: region.expn_id,
+            },
+            None => span,
+        });
+    }
+    region.unwrap_or(fn_span)
+}
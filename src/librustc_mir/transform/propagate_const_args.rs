@@ -0,0 +1,224 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Propagates the constant arguments of private functions into them: when
+//! every call of such a function passes the same constant for an argument,
+//! the argument is assigned that constant on entry, for the later passes to
+//! fold into its uses. This catches e.g. a configuration flag threaded
+//! through a few layers of helpers. The argument itself is kept, as
+//! dropping it would change the signature of the function.
+//!
+//! The calls are found in the MIR of the whole crate, promoted and outlined
+//! parts included, so only the functions which can't be called from
+//! elsewhere are rewritten: those which aren't `pub`, generic, exported
+//! under a symbol of their own or of another ABI, nor the entry point, and
+//! whose address isn't taken, as they could be called with anything
+//! through the pointer. Only integers, `bool`s and `char`s are propagated.
+
+use rustc::hir;
+use rustc::hir::def_id::DefId;
+use rustc::hir::map as hir_map;
+use rustc::middle::const_val::ConstVal;
+use rustc::mir::*;
+use rustc::mir::transform::{MirMapPass, MirPass, MirPassHook, MirSource, Pass};
+use rustc::mir::visit::Visitor;
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::{DefIdMap, DefIdSet};
+use rustc_data_structures::indexed_vec::Idx;
+use syntax::abi::Abi;
+use syntax::attr;
+use transform::known_temps::is_scalar;
+
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::mem;
+
+pub struct PropagateConstArgs;
+
+impl Pass for PropagateConstArgs {
+    fn is_interprocedural(&self) -> bool { true }
+}
+
+impl<'tcx> MirMapPass<'tcx> for PropagateConstArgs {
+    fn run_pass<'a>(&mut self,
+                    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                    hooks: &mut [Box<for<'s> MirPassHook<'s>>]) {
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return;
+        }
+
+        let mut calls = Calls {
+            args: DefIdMap(),
+            escaped: DefIdSet(),
+        };
+        {
+            let _ignore = tcx.dep_graph.in_ignore();
+            let def_ids = tcx.mir_map.borrow().keys();
+            for def_id in def_ids {
+                if def_id.is_local() {
+                    calls.visit_all(&tcx.item_mir(def_id));
+                }
+            }
+        }
+
+        let Calls { args, escaped } = calls;
+        let args: DefIdMap<_> = args.into_iter().filter(|&(def_id, ref args)| {
+            args.iter().any(Option::is_some) &&
+                !escaped.contains(&def_id) &&
+                is_private(tcx, def_id)
+        }).collect();
+        debug!("PropagateConstArgs: constant arguments of {:?}", args);
+        if !args.is_empty() {
+            MirMapPass::run_pass(&mut Rewrite { args: args }, tcx, hooks);
+        }
+    }
+}
+
+/// Whether the function `def_id` can only be called from the crate, and
+/// only with the arguments of the calls in its MIR.
+fn is_private<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> bool {
+    let id = match tcx.map.as_local_node_id(def_id) {
+        Some(id) => id,
+        None => return false
+    };
+    let item = match tcx.map.find(id) {
+        Some(hir_map::NodeItem(item)) => item,
+        _ => return false
+    };
+    match item.node {
+        hir::ItemFn(_, _, _, Abi::Rust, ..) => {}
+        _ => return false
+    }
+    let generics = tcx.item_generics(def_id);
+    if !generics.types.is_empty() || item.vis == hir::Public {
+        return false;
+    }
+    if attr::contains_name(&item.attrs, "no_mangle") ||
+       attr::contains_name(&item.attrs, "export_name") ||
+       attr::has_function_granular_attr(&item.attrs) {
+        return false;
+    }
+    tcx.sess.entry_fn.borrow().map(|(entry, _)| entry) != Some(id)
+}
+
+/// The arguments the local functions are called with.
+struct Calls<'tcx> {
+    /// The constant each argument is given by all the calls seen so far,
+    /// by callee.
+    args: DefIdMap<Vec<Option<Constant<'tcx>>>>,
+    /// The functions used other than by calling them.
+    escaped: DefIdSet,
+}
+
+impl<'tcx> Calls<'tcx> {
+    fn visit_all(&mut self, mir: &Mir<'tcx>) {
+        self.visit_mir(mir);
+        for promoted in &mir.promoted {
+            self.visit_all(promoted);
+        }
+        for outlined in &mir.outlined {
+            self.visit_all(outlined);
+        }
+    }
+
+    fn record(&mut self, callee: DefId, args: &[Operand<'tcx>]) {
+        let constants = args.iter().map(|arg| match *arg {
+            Operand::Constant(ref constant) if is_scalar(constant) => Some(constant.clone()),
+            _ => None
+        });
+        match self.args.entry(callee) {
+            Entry::Vacant(entry) => {
+                entry.insert(constants.collect());
+            }
+            Entry::Occupied(mut entry) => {
+                for (known, constant) in entry.get_mut().iter_mut().zip(constants) {
+                    let same = match (known.as_ref(), constant) {
+                        (Some(known), Some(constant)) => known.literal == constant.literal,
+                        _ => false
+                    };
+                    if !same {
+                        *known = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Calls<'tcx> {
+    fn visit_terminator_kind(&mut self,
+                             block: BasicBlock,
+                             kind: &TerminatorKind<'tcx>,
+                             location: Location) {
+        if let TerminatorKind::Call { func: Operand::Constant(ref func), ref args, .. } = *kind {
+            if let ty::TyFnDef(callee, ..) = func.ty.sty {
+                // Visit the arguments, but not the callee, which is called
+                // rather than escaping.
+                self.record(callee, args);
+                for arg in args {
+                    self.visit_operand(arg, location);
+                }
+                return;
+            }
+        }
+        self.super_terminator_kind(block, kind, location);
+    }
+
+    fn visit_constant(&mut self, constant: &Constant<'tcx>, location: Location) {
+        if let ty::TyFnDef(def_id, ..) = constant.ty.sty {
+            self.escaped.insert(def_id);
+        }
+        if let Literal::Value { value: ConstVal::Function(def_id) } = constant.literal {
+            self.escaped.insert(def_id);
+        }
+        self.super_constant(constant, location);
+    }
+}
+
+/// Assigns the constant arguments on entry to the functions in `args`.
+struct Rewrite<'tcx> {
+    args: DefIdMap<Vec<Option<Constant<'tcx>>>>,
+}
+
+impl<'tcx> Pass for Rewrite<'tcx> {
+    fn name(&self) -> Cow<'static, str> { "PropagateConstArgs".into() }
+
+    fn is_interprocedural(&self) -> bool { true }
+}
+
+impl<'tcx> MirPass<'tcx> for Rewrite<'tcx> {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        let args = match self.args.get(&tcx.map.local_def_id(id)) {
+            Some(args) if args.len() == mir.arg_count => args,
+            _ => return false
+        };
+
+        let source_info = SourceInfo {
+            span: mir.span,
+            scope: ARGUMENT_VISIBILITY_SCOPE
+        };
+        let assigns: Vec<_> = args.iter().enumerate().filter_map(|(i, constant)| {
+            constant.as_ref().map(|constant| Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(Lvalue::Local(Local::new(i + 1)),
+                                            Rvalue::Use(Operand::Constant(constant.clone())))
+            })
+        }).collect();
+        let statements = &mut mir[START_BLOCK].statements;
+        let rest = mem::replace(statements, assigns);
+        statements.extend(rest);
+        true
+    }
+}
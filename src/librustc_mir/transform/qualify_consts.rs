@@ -193,6 +193,17 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
         }
     }
 
+    /// Whether the variable `local` is bound by an argument pattern,
+    /// rather than by a `let` in the body.
+    fn is_argument_binding(&self, local: Local) -> bool {
+        // Argument patterns are bound in a scope directly inside the
+        // argument scope, and everything in the body is nested in it.
+        self.mir.local_decls[local].source_info.map_or(false, |source_info| {
+            let scope = &self.mir.visibility_scopes[source_info.scope];
+            scope.parent_scope == Some(ARGUMENT_VISIBILITY_SCOPE)
+        })
+    }
+
     /// Add the given qualification to self.qualif.
     fn add(&mut self, qualif: Qualif) {
         self.qualif = self.qualif | qualif;
@@ -317,11 +328,15 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
     fn assign(&mut self, dest: &Lvalue<'tcx>, location: Location) {
         let qualif = self.qualif;
         let span = self.span;
+        let mode = self.mode;
         let store = |slot: &mut Option<Qualif>| {
-            if slot.is_some() {
-                span_bug!(span, "multiple assignments to {:?}", dest);
+            match (*slot, mode) {
+                // Branches in a `const fn` can assign the same
+                // temp or the return pointer on several paths.
+                (Some(previous), Mode::ConstFn) => *slot = Some(previous | qualif),
+                (Some(_), _) => span_bug!(span, "multiple assignments to {:?}", dest),
+                (None, _) => *slot = Some(qualif)
             }
-            *slot = Some(qualif);
         };
 
         // Only handle promotable temps in non-const functions.
@@ -362,7 +377,11 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
             _ => {
                 // Catch more errors in the destination.
                 self.visit_lvalue(dest, LvalueContext::Store, location);
-                self.statement_like();
+                // The MIR interpreter runs `const fn` bodies statement
+                // by statement, so they can have `let`s and mutate them.
+                if self.mode != Mode::ConstFn {
+                    self.statement_like();
+                }
             }
         }
     }
@@ -394,11 +413,10 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
                     return Qualif::empty();
                 }
 
-                TerminatorKind::If {..} |
-                TerminatorKind::Switch {..} |
                 TerminatorKind::SwitchInt {..} |
                 TerminatorKind::DropAndReplace { .. } |
                 TerminatorKind::Resume |
+                TerminatorKind::Abort |
                 TerminatorKind::Unreachable => None,
 
                 TerminatorKind::Return => {
@@ -619,6 +637,7 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
             Rvalue::Repeat(..) |
             Rvalue::UnaryOp(..) |
             Rvalue::CheckedBinaryOp(..) |
+            Rvalue::Discriminant(..) |
             Rvalue::Cast(CastKind::ReifyFnPointer, ..) |
             Rvalue::Cast(CastKind::UnsafeFnPointer, ..) |
             Rvalue::Cast(CastKind::Unsize, ..) => {}
@@ -890,6 +909,7 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
         // Check the allowed const fn argument forms.
         if let (Mode::ConstFn, &Lvalue::Local(index)) = (self.mode, dest) {
             if self.mir.local_kind(index) == LocalKind::Var &&
+               self.is_argument_binding(index) &&
                self.const_fn_arg_vars.insert(index.index()) {
 
                 // Direct use of an argument is permitted.
@@ -927,6 +947,8 @@ impl<'a, 'tcx> Visitor<'tcx> for Qualifier<'a, 'tcx, 'tcx> {
                 StatementKind::SetDiscriminant { .. } |
                 StatementKind::StorageLive(_) |
                 StatementKind::StorageDead(_) |
+                StatementKind::IncrementCounter(_) |
+                StatementKind::Poison(_) |
                 StatementKind::Nop => {}
             }
         });
@@ -976,7 +998,7 @@ impl Pass for QualifyAndPromoteConstants {}
 
 impl<'tcx> MirPass<'tcx> for QualifyAndPromoteConstants {
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    src: MirSource, mir: &mut Mir<'tcx>) {
+                    src: MirSource, mir: &mut Mir<'tcx>) -> bool {
         let id = src.item_id();
         let def_id = tcx.map.local_def_id(id);
         let mode = match src {
@@ -989,7 +1011,7 @@ impl<'tcx> MirPass<'tcx> for QualifyAndPromoteConstants {
             }
             MirSource::Const(_) => {
                 match self.qualif_map.entry(def_id) {
-                    Entry::Occupied(_) => return,
+                    Entry::Occupied(_) => return false,
                     Entry::Vacant(entry) => {
                         // Guard against `const` recursion.
                         entry.insert(Qualif::RECURSIVE);
@@ -999,28 +1021,35 @@ impl<'tcx> MirPass<'tcx> for QualifyAndPromoteConstants {
             }
             MirSource::Static(_, hir::MutImmutable) => Mode::Static,
             MirSource::Static(_, hir::MutMutable) => Mode::StaticMut,
-            MirSource::Promoted(..) => return
+            MirSource::Promoted(..) | MirSource::Shim => return false
         };
         let param_env = ty::ParameterEnvironment::for_item(tcx, id);
 
         if mode == Mode::Fn || mode == Mode::ConstFn {
             // This is ugly because Qualifier holds onto mir,
             // which can't be mutated until its scope ends.
-            let (temps, candidates) = {
+            let (temps, candidates, const_temps) = {
                 let mut qualifier = Qualifier::new(tcx, param_env,
                                                    &mut self.qualif_map,
                                                    def_id, mir, mode);
-                if mode == Mode::ConstFn {
-                    // Enforce a constant-like CFG for `const fn`.
-                    qualifier.qualify_const();
-                } else {
-                    while let Some((bb, data)) = qualifier.rpo.next() {
-                        qualifier.visit_basic_block_data(bb, data);
-                    }
+                // Unlike constants, `const fn` bodies may branch and loop.
+                while let Some((bb, data)) = qualifier.rpo.next() {
+                    qualifier.visit_basic_block_data(bb, data);
                 }
 
-                (qualifier.temp_promotion_state, qualifier.promotion_candidates)
+                // Keep what was found out about the temporaries for the
+                // optimizations, see `LocalDecl::is_const`.
+                let const_temps: Vec<_> = qualifier.temp_qualif.iter_enumerated()
+                    .filter_map(|(local, qualif)| match *qualif {
+                        Some(qualif) if qualif.is_empty() => Some(local),
+                        _ => None
+                    }).collect();
+
+                (qualifier.temp_promotion_state, qualifier.promotion_candidates, const_temps)
             };
+            for local in const_temps {
+                mir.local_decls[local].is_const = true;
+            }
 
             // Do the actual promotion, now that we know what's viable.
             promote_consts::promote_candidates(mir, tcx, temps, candidates);
@@ -1049,5 +1078,6 @@ impl<'tcx> MirPass<'tcx> for QualifyAndPromoteConstants {
                 }
             });
         }
+        true
     }
 }
@@ -10,8 +10,11 @@
 
 //! A number of passes which remove various redundancies in the CFG.
 //!
-//! The `SimplifyCfg` pass gets rid of unnecessary blocks in the CFG, whereas the `SimplifyLocals`
-//! gets rid of all the unnecessary local variable declarations.
+//! The `SimplifyCfg` pass gets rid of unnecessary blocks in the CFG, along with the locals,
+//! promoted constants and visibility scopes only those blocks used (see `compact_mir`), whereas
+//! the `SimplifyLocals` gets rid of all the unnecessary local variable declarations.
+//! `SimplifyCfg` also drops the `Nop` statements other passes leave behind, and so does
+//! `CompactStatements`, which is meant to run once at the end of the pipeline.
 //!
 //! The `SimplifyLocals` pass is kinda expensive and therefore not very suitable to be run often.
 //! Most of the passes should not care or be impacted in meaningful ways due to extra locals
@@ -54,17 +57,26 @@ impl<'a> SimplifyCfg<'a> {
 }
 
 impl<'l, 'tcx> MirPass<'tcx> for SimplifyCfg<'l> {
-    fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>, _src: MirSource, mir: &mut Mir<'tcx>) {
+    fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>, _src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
         debug!("SimplifyCfg({:?}) - simplifying {:?}", self.label, mir);
-        CfgSimplifier::new(mir).simplify();
-        remove_dead_blocks(mir);
+        let sizes = |mir: &Mir| {
+            (mir.basic_blocks().len(), mir.local_decls.len(), mir.promoted.len(),
+             mir.visibility_scopes.len())
+        };
+        let before = sizes(mir);
+        let simplified = CfgSimplifier::new(mir).simplify();
+        compact_mir(mir);
 
         // FIXME: Should probably be moved into some kind of pass manager
         mir.basic_blocks_mut().raw.shrink_to_fit();
+        simplified || sizes(mir) != before
     }
 }
 
 impl<'l> Pass for SimplifyCfg<'l> {
+    fn is_cleanup(&self) -> bool { true }
+
     fn disambiguator<'a>(&'a self) -> Option<Box<fmt::Display+'a>> {
         Some(Box::new(self.label))
     }
@@ -102,7 +114,10 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
         }
     }
 
-    fn simplify(mut self) {
+    /// Simplifies the blocks, returning whether anything changed.
+    fn simplify(mut self) -> bool {
+        let mut simplified = self.strip_nops();
+
         loop {
             let mut changed = false;
 
@@ -136,43 +151,71 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
             }
 
             if !changed { break }
+            simplified = true;
         }
+        simplified
     }
 
-    // Collapse a goto chain starting from `start`
-    fn collapse_goto_chain(&mut self, start: &mut BasicBlock, changed: &mut bool) {
-        let mut terminator = match self.basic_blocks[*start] {
-            BasicBlockData {
-                ref statements,
-                terminator: ref mut terminator @ Some(Terminator {
-                    kind: TerminatorKind::Goto { .. }, ..
-                }), ..
-            } if statements.is_empty() => terminator.take(),
-            // if `terminator` is None, this means we are in a loop. In that
-            // case, let all the loop collapse to its entry.
-            _ => return
-        };
+    // Blocks are only threaded through or merged away when they are empty, which the `Nop`s
+    // left behind by other passes would prevent.
+    fn strip_nops(&mut self) -> bool {
+        let mut stripped = false;
+        for data in self.basic_blocks.iter_mut() {
+            let len = data.statements.len();
+            data.compact_statements();
+            stripped |= data.statements.len() != len;
+        }
+        stripped
+    }
 
-        let target = match terminator {
-            Some(Terminator { kind: TerminatorKind::Goto { ref mut target }, .. }) => {
-                self.collapse_goto_chain(target, changed);
-                *target
-            }
-            _ => unreachable!()
-        };
-        self.basic_blocks[*start].terminator = terminator;
+    // Collapse a goto chain starting from `start`. Chains can get long, so they are walked in a
+    // loop rather than recursively.
+    fn collapse_goto_chain(&mut self, start: &mut BasicBlock, changed: &mut bool) {
+        // Take the terminators of the empty blocks along the chain, up to its end.
+        let mut chain = vec![];
+        let mut target = *start;
+        loop {
+            let terminator = match self.basic_blocks[target] {
+                BasicBlockData {
+                    ref statements,
+                    terminator: ref mut terminator @ Some(Terminator {
+                        kind: TerminatorKind::Goto { .. }, ..
+                    }), ..
+                } if statements.is_empty() => terminator.take(),
+                // if `terminator` is None, this means we are in a loop. In that
+                // case, let all the loop collapse to its entry.
+                _ => break
+            };
+            let next = match terminator {
+                Some(Terminator { kind: TerminatorKind::Goto { target }, .. }) => target,
+                _ => unreachable!()
+            };
+            chain.push((target, terminator));
+            target = next;
+        }
 
-        debug!("collapsing goto chain from {:?} to {:?}", *start, target);
+        // Each block of the chain is jumped to by the one before it, or by `start` for the
+        // first one. Redirect those jumps to the end of the chain, starting from the back.
+        for &(block, _) in chain.iter().rev() {
+            debug!("collapsing goto chain from {:?} to {:?}", block, target);
 
-        *changed |= *start != target;
+            *changed |= block != target;
 
-        if self.pred_count[*start] == 1 {
-            // This is the last reference to *start, so the pred-count to
-            // to target is moved into the current block.
-            self.pred_count[*start] = 0;
-        } else {
-            self.pred_count[target] += 1;
-            self.pred_count[*start] -= 1;
+            if self.pred_count[block] == 1 {
+                // This is the last reference to `block`, so the pred-count to
+                // to target is moved into the current block.
+                self.pred_count[block] = 0;
+            } else {
+                self.pred_count[target] += 1;
+                self.pred_count[block] -= 1;
+            }
+        }
+        for (block, mut terminator) in chain {
+            if let Some(Terminator { kind: TerminatorKind::Goto { target: ref mut t }, .. }) =
+                terminator {
+                *t = target;
+            }
+            self.basic_blocks[block].terminator = terminator;
         }
 
         *start = target;
@@ -209,8 +252,6 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
     // turn a branch with all successors identical to a goto
     fn simplify_branch(&mut self, terminator: &mut Terminator<'tcx>) -> bool {
         match terminator.kind {
-            TerminatorKind::If { .. } |
-            TerminatorKind::Switch { .. } |
             TerminatorKind::SwitchInt { .. } => {},
             _ => return false
         };
@@ -235,7 +276,16 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
     }
 }
 
-fn remove_dead_blocks(mir: &mut Mir) {
+/// Removes the blocks which can't be reached from the start block, along with the locals,
+/// promoted constants and visibility scopes which only they used, renumbering the rest.
+pub fn compact_mir(mir: &mut Mir) {
+    remove_dead_blocks(mir);
+    remove_dead_locals(mir);
+    remove_dead_promoteds(mir);
+    remove_dead_scopes(mir);
+}
+
+pub fn remove_dead_blocks(mir: &mut Mir) {
     let mut seen = BitVector::new(mir.basic_blocks().len());
     for (bb, _) in traversal::preorder(mir) {
         seen.insert(bb.index());
@@ -272,19 +322,154 @@ impl Pass for SimplifyLocals {
 }
 
 impl<'tcx> MirPass<'tcx> for SimplifyLocals {
-    fn run_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>, _: MirSource, mir: &mut Mir<'tcx>) {
-        let mut marker = DeclMarker { locals: BitVector::new(mir.local_decls.len()) };
-        marker.visit_mir(mir);
-        // Return pointer and arguments are always live
-        marker.locals.insert(0);
-        for idx in mir.args_iter() {
-            marker.locals.insert(idx.index());
+    fn run_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>, _: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let locals = mir.local_decls.len();
+        let removed = remove_dead_constant_temps(mir);
+        remove_dead_locals(mir);
+        removed || mir.local_decls.len() != locals
+    }
+}
+
+/// Removes the nops which the passes run after the last `SimplifyCfg` left behind, so that trans
+/// and the MIR kept around until then don't have to carry them.
+pub struct CompactStatements;
+
+impl Pass for CompactStatements {}
+
+impl<'tcx> MirPass<'tcx> for CompactStatements {
+    fn run_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>, _: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let statements = |mir: &Mir| -> usize {
+            mir.basic_blocks().iter().map(|data| data.statements.len()).sum()
+        };
+        let before = statements(mir);
+        mir.compact_statements();
+        statements(mir) != before
+    }
+}
+
+/// Removes the locals which are never used, or only by storage markers.
+fn remove_dead_locals(mir: &mut Mir) {
+    let mut marker = DeclMarker { locals: BitVector::new(mir.local_decls.len()) };
+    marker.visit_mir(mir);
+    // Return pointer and arguments are always live
+    marker.locals.insert(0);
+    for idx in mir.args_iter() {
+        marker.locals.insert(idx.index());
+    }
+    let map = make_local_map(&mut mir.local_decls, marker.locals);
+    // Update references to all vars and tmps now
+    for var in &mut mir.var_debug_info {
+        let removed = match var.value {
+            VarDebugInfoContents::Lvalue(ref mut lvalue) => !renumber_root(lvalue, &map),
+            _ => false
+        };
+        if removed {
+            var.value = VarDebugInfoContents::OptimizedOut;
+        }
+    }
+    LocalUpdater { map: map }.visit_mir(mir);
+    mir.local_decls.shrink_to_fit();
+}
+
+/// Removes the promoted constants which are never used.
+fn remove_dead_promoteds(mir: &mut Mir) {
+    if mir.promoted.is_empty() {
+        return;
+    }
+
+    let mut marker = PromotedMarker { promoted: BitVector::new(mir.promoted.len()) };
+    marker.visit_mir(mir);
+    for var in &mir.var_debug_info {
+        if let VarDebugInfoContents::Const(Constant {
+            literal: Literal::Promoted { index }, ..
+        }) = var.value {
+            marker.promoted.insert(index.index());
+        }
+    }
+    // A promoted constant may use others in turn.
+    let mut worklist: Vec<_> = marker.promoted.iter().collect();
+    while let Some(index) = worklist.pop() {
+        let mut inner = PromotedMarker { promoted: BitVector::new(mir.promoted.len()) };
+        inner.visit_mir(&mir.promoted[Promoted::new(index)]);
+        for used in inner.promoted.iter() {
+            if marker.promoted.insert(used) {
+                worklist.push(used);
+            }
+        }
+    }
+
+    let map = make_local_map(&mut mir.promoted, marker.promoted);
+    let mut updater = PromotedUpdater { map: map };
+    updater.visit_mir(mir);
+    for promoted in mir.promoted.iter_mut() {
+        updater.visit_mir(promoted);
+    }
+    for var in &mut mir.var_debug_info {
+        if let VarDebugInfoContents::Const(Constant {
+            literal: Literal::Promoted { ref mut index }, ..
+        }) = var.value {
+            *index = Promoted::new(updater.map[index.index()]);
+        }
+    }
+    mir.promoted.shrink_to_fit();
+}
+
+/// Removes the visibility scopes which no statement, terminator, local or variable is in, and
+/// which aren't the parent of one that is.
+fn remove_dead_scopes(mir: &mut Mir) {
+    let mut marker = ScopeMarker { scopes: BitVector::new(mir.visibility_scopes.len()) };
+    marker.visit_mir(mir);
+    for var in &mir.var_debug_info {
+        marker.scopes.insert(var.source_info.scope.index());
+    }
+    // Debuginfo always describes the arguments, in the outermost scope.
+    marker.scopes.insert(ARGUMENT_VISIBILITY_SCOPE.index());
+    // Scopes are created after their parent, so going backwards reaches every parent after the
+    // scopes in it.
+    for index in (0..mir.visibility_scopes.len()).rev() {
+        if marker.scopes.contains(index) {
+            if let Some(parent) = mir.visibility_scopes[VisibilityScope::new(index)].parent_scope {
+                marker.scopes.insert(parent.index());
+            }
+        }
+    }
+
+    let map = make_local_map(&mut mir.visibility_scopes, marker.scopes);
+    for var in &mut mir.var_debug_info {
+        var.source_info.scope = VisibilityScope::new(map[var.source_info.scope.index()]);
+    }
+    ScopeUpdater { map: map }.visit_mir(mir);
+    mir.visibility_scopes.shrink_to_fit();
+}
+
+/// Removes the assignments of discriminants and constants to temporaries
+/// which are never read, e.g. because the switch on them was simplified
+/// away, returning whether there were any.
+fn remove_dead_constant_temps(mir: &mut Mir) -> bool {
+    let mut reads = ReadMarker { locals: BitVector::new(mir.local_decls.len()) };
+    reads.visit_mir(mir);
+    let mut dead = vec![];
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        for (i, statement) in data.statements.iter().enumerate() {
+            let local = match statement.kind {
+                StatementKind::Assign(Lvalue::Local(local), Rvalue::Discriminant(_)) |
+                StatementKind::Assign(Lvalue::Local(local), Rvalue::Use(Operand::Constant(_))) => {
+                    local
+                }
+                _ => continue
+            };
+            if mir.local_kind(local) == LocalKind::Temp && !reads.locals.contains(local.index()) {
+                dead.push((bb, i));
+            }
         }
-        let map = make_local_map(&mut mir.local_decls, marker.locals);
-        // Update references to all vars and tmps now
-        LocalUpdater { map: map }.visit_mir(mir);
-        mir.local_decls.shrink_to_fit();
     }
+    let removed = !dead.is_empty();
+    for (bb, i) in dead {
+        mir[bb].statements[i].kind = StatementKind::Nop;
+    }
+    removed
 }
 
 /// Construct the mapping while swapping out unused stuff out from the `vec`.
@@ -302,6 +487,21 @@ fn make_local_map<'tcx, I: Idx, V>(vec: &mut IndexVec<I, V>, mask: BitVector) ->
     map
 }
 
+/// Renumbers the local `lvalue` is based on, returning false if that local was removed.
+fn renumber_root(lvalue: &mut Lvalue, map: &[usize]) -> bool {
+    match *lvalue {
+        Lvalue::Local(ref mut local) => {
+            if map[local.index()] == !0 {
+                return false;
+            }
+            *local = Local::new(map[local.index()]);
+            true
+        }
+        Lvalue::Static(_) => true,
+        Lvalue::Projection(ref mut proj) => renumber_root(&mut proj.base, map)
+    }
+}
+
 struct DeclMarker {
     pub locals: BitVector,
 }
@@ -319,6 +519,76 @@ impl<'tcx> Visitor<'tcx> for DeclMarker {
     }
 }
 
+struct ReadMarker {
+    locals: BitVector,
+}
+
+impl<'tcx> Visitor<'tcx> for ReadMarker {
+    fn visit_lvalue(&mut self, lval: &Lvalue<'tcx>, ctx: LvalueContext<'tcx>, loc: Location) {
+        let is_read = match ctx {
+            LvalueContext::Store |
+            LvalueContext::StorageLive |
+            LvalueContext::StorageDead => false,
+            _ => true
+        };
+        if let (true, &Lvalue::Local(ref v)) = (is_read, lval) {
+            self.locals.insert(v.index());
+        }
+        self.super_lvalue(lval, ctx, loc);
+    }
+}
+
+struct PromotedMarker {
+    promoted: BitVector,
+}
+
+impl<'tcx> Visitor<'tcx> for PromotedMarker {
+    fn visit_literal(&mut self, literal: &Literal<'tcx>, location: Location) {
+        if let Literal::Promoted { index } = *literal {
+            self.promoted.insert(index.index());
+        }
+        self.super_literal(literal, location);
+    }
+}
+
+struct PromotedUpdater {
+    map: Vec<usize>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for PromotedUpdater {
+    fn visit_literal(&mut self, literal: &mut Literal<'tcx>, location: Location) {
+        if let Literal::Promoted { ref mut index } = *literal {
+            *index = Promoted::new(self.map[index.index()]);
+        }
+        self.super_literal(literal, location);
+    }
+}
+
+struct ScopeMarker {
+    scopes: BitVector,
+}
+
+impl<'tcx> Visitor<'tcx> for ScopeMarker {
+    fn visit_visibility_scope_data(&mut self, _scope_data: &VisibilityScopeData) {
+        // The parent of a scope is only needed if the scope itself is, which
+        // `remove_dead_scopes` checks afterwards.
+    }
+
+    fn visit_visibility_scope(&mut self, scope: &VisibilityScope) {
+        self.scopes.insert(scope.index());
+    }
+}
+
+struct ScopeUpdater {
+    map: Vec<usize>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ScopeUpdater {
+    fn visit_visibility_scope(&mut self, scope: &mut VisibilityScope) {
+        *scope = VisibilityScope::new(self.map[scope.index()]);
+    }
+}
+
 struct LocalUpdater {
     map: Vec<usize>,
 }
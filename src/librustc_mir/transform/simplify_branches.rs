@@ -9,12 +9,19 @@
 // except according to those terms.
 
 //! A pass that simplifies branches when their condition is known.
+//!
+//! This includes switches on a local assigned a constant earlier in the
+//! same block, such as the discriminant of an enum whose variant was set
+//! in that block: the discriminant read is replaced by its value, which
+//! then selects the target of the switch. Negations and comparisons of
+//! known values are evaluated too, such as the condition of `assert!(1 > 2)`.
 
-use rustc::ty::TyCtxt;
+use rustc::ty::{self, AdtDef, TyCtxt};
 use rustc::middle::const_val::ConstVal;
-use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::mir::transform::{self, MirPass, MirSource, Pass};
 use rustc::mir::*;
 
+use std::cmp::Ordering;
 use std::fmt;
 
 pub struct SimplifyBranches<'a> { label: &'a str }
@@ -26,20 +33,38 @@ impl<'a> SimplifyBranches<'a> {
 }
 
 impl<'l, 'tcx> MirPass<'tcx> for SimplifyBranches<'l> {
-    fn run_pass<'a>(&mut self, _tcx: TyCtxt<'a, 'tcx, 'tcx>, _src: MirSource, mir: &mut Mir<'tcx>) {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let mut changed = fold_known_discriminants(tcx, mir);
+
         for block in mir.basic_blocks_mut() {
+            let known_target = match block.terminator().kind {
+                TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. } => {
+                    let known_value = match *discr {
+                        Operand::Constant(Constant {
+                            literal: Literal::Value { ref value }, ..
+                        }) => {
+                            if let ConstVal::Bool(cond) = *value {
+                                transform::suggest(tcx, src, block.terminator().source_info.span,
+                                                   format!("this condition is always `{}`",
+                                                           cond));
+                            }
+                            Some(value.clone())
+                        }
+                        Operand::Consume(Lvalue::Local(discr)) => {
+                            known_value(&block.statements, discr)
+                        }
+                        _ => None
+                    };
+                    known_value.and_then(|value| switch_target(values, targets, &value))
+                }
+                _ => None
+            };
+
             let terminator = block.terminator_mut();
             terminator.kind = match terminator.kind {
-                TerminatorKind::If { ref targets, cond: Operand::Constant(Constant {
-                    literal: Literal::Value {
-                        value: ConstVal::Bool(cond)
-                    }, ..
-                }) } => {
-                    if cond {
-                        TerminatorKind::Goto { target: targets.0 }
-                    } else {
-                        TerminatorKind::Goto { target: targets.1 }
-                    }
+                TerminatorKind::SwitchInt { .. } if known_target.is_some() => {
+                    TerminatorKind::Goto { target: known_target.unwrap() }
                 }
 
                 TerminatorKind::Assert { target, cond: Operand::Constant(Constant {
@@ -52,8 +77,173 @@ impl<'l, 'tcx> MirPass<'tcx> for SimplifyBranches<'l> {
 
                 _ => continue
             };
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Replaces each `Discriminant` read of an enum whose variant was set
+/// earlier in the same block, by an aggregate or `SetDiscriminant`, with
+/// the discriminant of that variant, returning whether there were any.
+fn fold_known_discriminants<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &mut Mir<'tcx>)
+                                      -> bool {
+    let mut folds = vec![];
+    for (bb, data) in mir.basic_blocks().iter_enumerated() {
+        // The enums with a known variant, until they may have been
+        // written to again.
+        let mut variants: Vec<(&Lvalue<'tcx>, &'tcx AdtDef, usize)> = vec![];
+        for (i, statement) in data.statements.iter().enumerate() {
+            match statement.kind {
+                StatementKind::Assign(ref lvalue, ref rvalue) => {
+                    if let Rvalue::Discriminant(ref enum_lvalue) = *rvalue {
+                        let known = variants.iter().find(|&&(lv, ..)| lv == enum_lvalue);
+                        if let Some(&(_, adt_def, variant)) = known {
+                            let location = Location { block: bb, statement_index: i };
+                            folds.push((location, adt_def, variant));
+                        }
+                    }
+                    invalidate(&mut variants, lvalue);
+                    match *rvalue {
+                        Rvalue::Aggregate(AggregateKind::Adt(adt_def, variant, ..), _)
+                            if adt_def.is_enum() && lvalue.is_direct() => {
+                            variants.push((lvalue, adt_def, variant));
+                        }
+                        Rvalue::Ref(_, _, ref borrowed) => invalidate(&mut variants, borrowed),
+                        Rvalue::InlineAsm { .. } => variants.clear(),
+                        _ => {}
+                    }
+                }
+                StatementKind::SetDiscriminant { ref lvalue, variant_index } => {
+                    invalidate(&mut variants, lvalue);
+                    if let ty::TyAdt(adt_def, _) = lvalue.ty(mir, tcx).to_ty(tcx).sty {
+                        if lvalue.is_direct() {
+                            variants.push((lvalue, adt_def, variant_index));
+                        }
+                    }
+                }
+                StatementKind::StorageDead(ref lvalue) => invalidate(&mut variants, lvalue),
+                StatementKind::StorageLive(_) |
+                StatementKind::IncrementCounter(_) |
+                StatementKind::Poison(_) |
+                StatementKind::Nop => {}
+            }
+        }
+    }
+
+    let changed = !folds.is_empty();
+    for (location, adt_def, variant) in folds {
+        let data = &mut mir[location.block];
+        let statement = &mut data.statements[location.statement_index];
+        let span = statement.source_info.span;
+        if let StatementKind::Assign(_, ref mut rvalue) = statement.kind {
+            *rvalue = Rvalue::Use(Operand::Constant(Constant {
+                span: span,
+                ty: adt_def.discr_ty(tcx),
+                literal: Literal::Value {
+                    value: ConstVal::Integral(adt_def.variants[variant].disr_val)
+                },
+            }));
         }
     }
+    changed
+}
+
+/// Forgets the variants which writing to or borrowing `lvalue` may change.
+fn invalidate<'a, 'tcx>(variants: &mut Vec<(&'a Lvalue<'tcx>, &'tcx AdtDef, usize)>,
+                        lvalue: &Lvalue<'tcx>) {
+    if !lvalue.is_direct() {
+        variants.clear();
+    } else {
+        let local = lvalue.base_local();
+        variants.retain(|&(lv, ..)| lv.base_local() != local);
+    }
+}
+
+/// The target a switch with `values` and `targets` takes for `known`, or
+/// `None` if some value can't be compared with it.
+fn switch_target(values: &[ConstVal], targets: &[BasicBlock], known: &ConstVal)
+                 -> Option<BasicBlock> {
+    for (value, &target) in values.iter().zip(targets) {
+        let equal = match (value, known) {
+            (&ConstVal::Integral(a), &ConstVal::Integral(b)) => match a.try_cmp(b) {
+                Ok(ordering) => ordering == Ordering::Equal,
+                Err(_) => return None,
+            },
+            _ => value == known,
+        };
+        if equal {
+            return Some(target);
+        }
+    }
+    targets.last().cloned()
+}
+
+/// The value the block assigns to `discr` last, if it's a constant.
+fn known_value<'tcx>(statements: &[Statement<'tcx>], discr: Local) -> Option<ConstVal> {
+    for (i, statement) in statements.iter().enumerate().rev() {
+        match statement.kind {
+            StatementKind::Assign(Lvalue::Local(local), ref rvalue) if local == discr => {
+                let earlier = &statements[..i];
+                return match *rvalue {
+                    Rvalue::Use(ref operand) => operand_value(earlier, operand),
+                    Rvalue::UnaryOp(UnOp::Not, ref operand) => {
+                        match operand_value(earlier, operand) {
+                            Some(ConstVal::Bool(value)) => Some(ConstVal::Bool(!value)),
+                            _ => None
+                        }
+                    }
+                    Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
+                        match (operand_value(earlier, lhs), operand_value(earlier, rhs)) {
+                            (Some(lhs), Some(rhs)) => compare(op, &lhs, &rhs),
+                            _ => None
+                        }
+                    }
+                    _ => None
+                };
+            }
+            // Either may write to `discr` through a pointer.
+            StatementKind::Assign(ref lvalue, _) if !lvalue.is_direct() => return None,
+            StatementKind::Assign(_, Rvalue::InlineAsm { .. }) => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The value of `operand` at the end of `statements`, if it's a constant
+/// or a local assigned a known value in them.
+fn operand_value<'tcx>(statements: &[Statement<'tcx>], operand: &Operand<'tcx>)
+                       -> Option<ConstVal> {
+    match *operand {
+        Operand::Constant(Constant { literal: Literal::Value { ref value }, .. }) => {
+            Some(value.clone())
+        }
+        Operand::Consume(Lvalue::Local(local)) => known_value(statements, local),
+        _ => None
+    }
+}
+
+/// The result of the comparison `op` of `lhs` and `rhs`, if it is one.
+fn compare(op: BinOp, lhs: &ConstVal, rhs: &ConstVal) -> Option<ConstVal> {
+    let ordering = match (lhs, rhs) {
+        (&ConstVal::Integral(lhs), &ConstVal::Integral(rhs)) => match lhs.try_cmp(rhs) {
+            Ok(ordering) => ordering,
+            Err(_) => return None
+        },
+        (&ConstVal::Bool(lhs), &ConstVal::Bool(rhs)) => lhs.cmp(&rhs),
+        _ => return None
+    };
+    let value = match op {
+        BinOp::Eq => ordering == Ordering::Equal,
+        BinOp::Ne => ordering != Ordering::Equal,
+        BinOp::Lt => ordering == Ordering::Less,
+        BinOp::Le => ordering != Ordering::Greater,
+        BinOp::Gt => ordering == Ordering::Greater,
+        BinOp::Ge => ordering != Ordering::Less,
+        _ => return None
+    };
+    Some(ConstVal::Bool(value))
 }
 
 impl<'l> Pass for SimplifyBranches<'l> {
@@ -0,0 +1,138 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Removes the calls which the desugaring of `?` leaves behind when it is
+//! used on a `Result` in a function returning a `Result`:
+//!
+//!     match Carrier::translate(<expr>) {
+//!         Ok(val) => val,
+//!         Err(err) => return Carrier::from_error(From::from(err))
+//!     }
+//!
+//! `translate` from a `Result` to the same `Result`, and `From::from` from a
+//! type to itself, are identities and become moves. `from_error` and
+//! `from_success` of a `Result` become `Err` and `Ok` aggregates. Each of
+//! these calls is a block of its own, so what is left after the CFG is
+//! simplified is the switch on the discriminant and the return block.
+//!
+//! MIR construction already skips these calls for the `?`s it builds; this
+//! pass cleans up the ones it could not, those whose types only become
+//! identical once their regions are erased.
+//!
+//! The calls are matched by the `from` and `carrier` lang items of the trait
+//! methods, as the impl is only selected in trans. `From::from` between
+//! identical types can only resolve to the reflexive impl, and `Result`,
+//! the `result` lang item, has a single `Carrier` impl.
+
+use rustc::hir::def_id::DefId;
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, AdtDef, TyCtxt};
+use rustc::ty::subst::Substs;
+
+pub struct SimplifyTry;
+
+impl Pass for SimplifyTry {}
+
+impl<'tcx> MirPass<'tcx> for SimplifyTry {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        match src {
+            MirSource::Fn(_) | MirSource::Shim => {}
+            _ => return false
+        }
+        if tcx.sess.opts.mir_opt_level < 2 {
+            return false;
+        }
+
+        let mut calls = vec![];
+        for (bb, data) in mir.basic_blocks().iter_enumerated() {
+            let (func, args, destination) = match data.terminator().kind {
+                TerminatorKind::Call {
+                    func: Operand::Constant(ref func),
+                    ref args,
+                    destination: Some(ref destination),
+                    ..
+                } => (func, args, destination),
+                _ => continue
+            };
+            let (callee, substs) = match func.ty.sty {
+                ty::TyFnDef(callee, substs, _) => (callee, substs),
+                _ => continue
+            };
+            let rvalue = match try_call(tcx, callee, substs) {
+                Some(TryCall::Identity) => Rvalue::Use(args[0].clone()),
+                Some(TryCall::Variant(adt, variant, adt_substs)) => {
+                    Rvalue::Aggregate(AggregateKind::Adt(adt, variant, adt_substs, None),
+                                      vec![args[0].clone()])
+                }
+                None => continue
+            };
+            calls.push((bb, rvalue, destination.clone()));
+        }
+
+        let changed = !calls.is_empty();
+        for (bb, rvalue, (dest, target)) in calls {
+            debug!("SimplifyTry: simplifying the call in {:?} to {:?}", bb, rvalue);
+            let data = &mut mir[bb];
+            let source_info = data.terminator().source_info;
+            data.statements.push(Statement {
+                source_info: source_info,
+                kind: StatementKind::Assign(dest, rvalue),
+            });
+            data.terminator_mut().kind = TerminatorKind::Goto { target: target };
+        }
+        changed
+    }
+}
+
+/// What a call to one of the functions called by the desugaring of `?` can
+/// be replaced with.
+#[derive(Copy, Clone, Debug)]
+pub enum TryCall<'tcx> {
+    /// The call returns its argument.
+    Identity,
+    /// The call wraps its argument in a variant of `Result`.
+    Variant(&'tcx AdtDef, usize, &'tcx Substs<'tcx>),
+}
+
+/// What a call to `callee` with `substs` can be replaced with, if it is one
+/// of the calls which can be done without the call. This is shared with the
+/// construction of `?`, which uses it to skip the calls in the first place.
+pub fn try_call<'a, 'gcx, 'tcx>(tcx: TyCtxt<'a, 'gcx, 'tcx>,
+                                callee: DefId,
+                                substs: &'tcx Substs<'tcx>)
+                                -> Option<TryCall<'tcx>> {
+    let trait_id = match tcx.trait_of_item(callee) {
+        Some(trait_id) => trait_id,
+        None => return None
+    };
+    let self_ty = substs.type_at(0);
+    if Some(trait_id) == tcx.lang_items.from_trait() {
+        return if self_ty == substs.type_at(1) { Some(TryCall::Identity) } else { None };
+    }
+    if Some(trait_id) != tcx.lang_items.carrier_trait() {
+        return None;
+    }
+    let (adt, adt_substs) = match self_ty.sty {
+        ty::TyAdt(adt, adt_substs) if Some(adt.did) == tcx.lang_items.result_type() => {
+            (adt, adt_substs)
+        }
+        _ => return None
+    };
+    let variant = match &*tcx.item_name(callee).as_str() {
+        "translate" if self_ty == substs.type_at(1) => return Some(TryCall::Identity),
+        "from_success" => "Ok",
+        "from_error" => "Err",
+        _ => return None
+    };
+    let variant = adt.variants.iter().position(|v| v.name == variant).unwrap();
+    Some(TryCall::Variant(adt, variant, adt_substs))
+}
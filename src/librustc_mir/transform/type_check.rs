@@ -354,6 +354,16 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
         let tcx = self.tcx();
         match stmt.kind {
             StatementKind::Assign(ref lv, ref rv) => {
+                if let Rvalue::Discriminant(ref enum_lv) = *rv {
+                    let enum_ty = enum_lv.ty(mir, tcx).to_ty(tcx);
+                    match enum_ty.sty {
+                        ty::TyAdt(def, _) if def.is_enum() => {}
+                        _ => {
+                            span_mirbug!(self, stmt, "bad discriminant read of {:?}", enum_ty);
+                            return;
+                        }
+                    }
+                }
                 let lv_ty = lv.ty(mir, tcx).to_ty(tcx);
                 let rv_ty = rv.ty(mir, tcx);
                 if let Some(rv_ty) = rv_ty {
@@ -384,7 +394,8 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                 };
             }
             StatementKind::StorageLive(ref lv) |
-            StatementKind::StorageDead(ref lv) => {
+            StatementKind::StorageDead(ref lv) |
+            StatementKind::Poison(ref lv) => {
                 match *lv {
                     Lvalue::Local(_) => {}
                     _ => {
@@ -392,6 +403,7 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                     }
                 }
             }
+            StatementKind::IncrementCounter(_) |
             StatementKind::Nop => {}
         }
     }
@@ -404,6 +416,7 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
         match term.kind {
             TerminatorKind::Goto { .. } |
             TerminatorKind::Resume |
+            TerminatorKind::Abort |
             TerminatorKind::Return |
             TerminatorKind::Unreachable |
             TerminatorKind::Drop { .. } => {
@@ -424,17 +437,8 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                 }
             }
 
-            TerminatorKind::If { ref cond, .. } => {
-                let cond_ty = cond.ty(mir, tcx);
-                match cond_ty.sty {
-                    ty::TyBool => {}
-                    _ => {
-                        span_mirbug!(self, term, "bad If ({:?}, not bool", cond_ty);
-                    }
-                }
-            }
-            TerminatorKind::SwitchInt { ref discr, switch_ty, .. } => {
-                let discr_ty = discr.ty(mir, tcx).to_ty(tcx);
+            TerminatorKind::SwitchInt { ref discr, switch_ty, ref values, ref targets } => {
+                let discr_ty = discr.ty(mir, tcx);
                 if let Err(terr) = self.sub_types(discr_ty, switch_ty) {
                     span_mirbug!(self, term, "bad SwitchInt ({:?} on {:?}): {:?}",
                                  switch_ty, discr_ty, terr);
@@ -444,20 +448,11 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                 {
                     span_mirbug!(self, term, "bad SwitchInt discr ty {:?}",switch_ty);
                 }
-                // FIXME: check the values
-            }
-            TerminatorKind::Switch { ref discr, adt_def, ref targets } => {
-                let discr_ty = discr.ty(mir, tcx).to_ty(tcx);
-                match discr_ty.sty {
-                    ty::TyAdt(def, _) if def.is_enum() &&
-                                         def == adt_def &&
-                                         adt_def.variants.len() == targets.len()
-                      => {},
-                    _ => {
-                        span_mirbug!(self, term, "bad Switch ({:?} on {:?})",
-                                     adt_def, discr_ty);
-                    }
+                if values.len() + 1 != targets.len() {
+                    span_mirbug!(self, term, "bad SwitchInt ({} values, {} targets)",
+                                 values.len(), targets.len());
                 }
+                // FIXME: check the values
             }
             TerminatorKind::Call { ref func, ref args, ref destination, .. } => {
                 let func_ty = func.ty(mir, tcx);
@@ -485,7 +480,7 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                     span_mirbug!(self, term, "bad Assert ({:?}, not bool", cond_ty);
                 }
 
-                if let AssertMessage::BoundsCheck { ref len, ref index } = *msg {
+                if let AssertMessage::BoundsCheck { ref len, ref index } = **msg {
                     if len.ty(mir, tcx) != tcx.types.usize {
                         span_mirbug!(self, len, "bounds-check length non-usize {:?}", len)
                     }
@@ -602,11 +597,6 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
         match block.terminator().kind {
             TerminatorKind::Goto { target } =>
                 self.assert_iscleanup(mir, block, target, is_cleanup),
-            TerminatorKind::If { targets: (on_true, on_false), .. } => {
-                self.assert_iscleanup(mir, block, on_true, is_cleanup);
-                self.assert_iscleanup(mir, block, on_false, is_cleanup);
-            }
-            TerminatorKind::Switch { ref targets, .. } |
             TerminatorKind::SwitchInt { ref targets, .. } => {
                 for target in targets {
                     self.assert_iscleanup(mir, block, *target, is_cleanup);
@@ -617,6 +607,11 @@ impl<'a, 'gcx, 'tcx> TypeChecker<'a, 'gcx, 'tcx> {
                     span_mirbug!(self, block, "resume on non-cleanup block!")
                 }
             }
+            TerminatorKind::Abort => {
+                if !is_cleanup {
+                    span_mirbug!(self, block, "abort on non-cleanup block!")
+                }
+            }
             TerminatorKind::Return => {
                 if is_cleanup {
                     span_mirbug!(self, block, "return on cleanup block")
@@ -716,13 +711,13 @@ impl TypeckMir {
 
 impl<'tcx> MirPass<'tcx> for TypeckMir {
     fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                    src: MirSource, mir: &mut Mir<'tcx>) {
+                    src: MirSource, mir: &mut Mir<'tcx>) -> bool {
         debug!("run_pass: {}", tcx.node_path_str(src.item_id()));
 
         if tcx.sess.err_count() > 0 {
             // compiling a broken program can obviously result in a
             // broken MIR, so try not to report duplicate errors.
-            return;
+            return false;
         }
         let param_env = ty::ParameterEnvironment::for_item(tcx, src.item_id());
         tcx.infer_ctxt(None, Some(param_env), Reveal::NotSpecializable).enter(|infcx| {
@@ -738,6 +733,7 @@ impl<'tcx> MirPass<'tcx> for TypeckMir {
             checker.typeck_mir(mir);
             checker.verify_obligations(mir);
         });
+        false
     }
 }
 
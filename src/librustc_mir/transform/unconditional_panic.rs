@@ -0,0 +1,101 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports the `unconditional_panic` lint for functions which panic
+//! whenever they are called, usually an `unimplemented!()` left in or an
+//! assertion which is always false.
+//!
+//! The pass runs after `SimplifyBranches` has followed the branches whose
+//! condition is known, e.g. that of `assert!(1 > 2)`, and `SimplifyCfg`
+//! has removed the blocks they no longer reach. A function is reported
+//! when `InlineSummary` says it diverges, i.e. it can't return, and every
+//! path from its entry block ends in a call to a panic entry point, rather
+//! than e.g. an infinite loop. The entry points are the `panic`,
+//! `panic_bounds_check` and `panic_fmt` lang items, and the functions
+//! marked `#[rustc_panic_entry_point]` which `panic!` calls.
+
+use rustc::lint::builtin::UNCONDITIONAL_PANIC;
+use rustc::mir::*;
+use rustc::mir::summary::InlineSummary;
+use rustc::mir::transform::{MirPass, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+use rustc_data_structures::bitvec::BitVector;
+use rustc_data_structures::indexed_vec::Idx;
+
+pub struct UnconditionalPanic;
+
+impl Pass for UnconditionalPanic {}
+
+impl<'tcx> MirPass<'tcx> for UnconditionalPanic {
+    fn run_pass<'a>(&mut self, tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &mut Mir<'tcx>)
+                    -> bool {
+        let id = match src {
+            MirSource::Fn(id) => id,
+            _ => return false
+        };
+        // Functions returning `!` are expected to diverge.
+        if mir.return_ty.is_never() {
+            return false;
+        }
+        if !InlineSummary::compute(tcx, mir).diverges {
+            return false;
+        }
+
+        // The blocks which panic whichever way they go, other than by
+        // unwinding. Loops never get in, as they may go around forever.
+        let mut panics = BitVector::new(mir.basic_blocks().len());
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (bb, data) in mir.basic_blocks().iter_enumerated() {
+                if data.is_cleanup || panics.contains(bb.index()) {
+                    continue;
+                }
+                let terminator = data.terminator();
+                let always = match terminator.kind {
+                    TerminatorKind::Call { ref func, destination: None, .. } => {
+                        calls_panic_fn(tcx, func)
+                    }
+                    _ => {
+                        let mut successors = terminator.successors().into_owned().into_iter()
+                            .filter(|&succ| !mir[succ].is_cleanup).peekable();
+                        successors.peek().is_some() &&
+                            successors.all(|succ| panics.contains(succ.index()))
+                    }
+                };
+                if always {
+                    panics.insert(bb.index());
+                    changed = true;
+                }
+            }
+        }
+        if panics.contains(START_BLOCK.index()) {
+            tcx.sess.add_lint(UNCONDITIONAL_PANIC, id, tcx.map.span(id),
+                              "this function panics whenever it is called".to_string());
+        }
+        false
+    }
+}
+
+/// Whether `func` is one of the functions `panic!` and the
+/// compiler-generated checks call to start panicking.
+fn calls_panic_fn<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, func: &Operand<'tcx>) -> bool {
+    let def_id = match *func {
+        Operand::Constant(ref func) => match func.ty.sty {
+            ty::TyFnDef(def_id, ..) => def_id,
+            _ => return false
+        },
+        _ => return false
+    };
+    Some(def_id) == tcx.lang_items.panic_fn() ||
+        Some(def_id) == tcx.lang_items.panic_bounds_check_fn() ||
+        Some(def_id) == tcx.lang_items.panic_fmt() ||
+        tcx.has_attr(def_id, "rustc_panic_entry_point")
+}
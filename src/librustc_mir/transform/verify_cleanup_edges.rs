@@ -0,0 +1,118 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A debugging aid for the passes rewriting the control flow graph,
+//! enabled with `-Z verify-cleanup-edges`.
+//!
+//! After every pass, the blocks reachable from the start of each function
+//! are checked to keep cleanup code apart from the rest of it:
+//!
+//! - a cleanup block only jumps to cleanup blocks, and never unwinds, as
+//!   a panic while unwinding aborts;
+//! - the unwind edge of a call, drop or assertion outside of cleanup code
+//!   leads to a cleanup block;
+//! - `resume` only happens in cleanup code;
+//! - once `NoLandingPads` has run and landing pads are disabled, e.g. with
+//!   `-C panic=abort`, nothing unwinds to a cleanup block anymore.
+//!
+//! A call, drop or assertion without an unwind edge is not reported: it
+//! unwinds straight to the caller, which is right when there is nothing
+//! left to drop, and telling whether there is needs the drop elaboration
+//! analysis, see `-Z verify-mir-drops` for that.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPassHook, MirSource, Pass};
+use rustc::mir::traversal;
+use rustc::ty::TyCtxt;
+
+pub struct VerifyCleanupEdges {
+    /// Whether `NoLandingPads` has run.
+    landing_pads_removed: bool
+}
+
+impl VerifyCleanupEdges {
+    pub fn new() -> VerifyCleanupEdges {
+        VerifyCleanupEdges { landing_pads_removed: false }
+    }
+}
+
+impl Pass for VerifyCleanupEdges {}
+
+impl<'tcx> MirPassHook<'tcx> for VerifyCleanupEdges {
+    fn on_mir_pass<'a>(&mut self,
+                       tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                       _: MirSource,
+                       mir: &Mir<'tcx>,
+                       pass: &Pass,
+                       is_after: bool)
+    {
+        if !is_after {
+            return;
+        }
+        if pass.name() == "NoLandingPads" {
+            self.landing_pads_removed = true;
+        }
+        let no_landing_pads = self.landing_pads_removed && tcx.sess.no_landing_pads();
+
+        let mut errors = vec![];
+        for (bb, data) in traversal::preorder(mir) {
+            let terminator = data.terminator();
+            let (targets, unwind) = edges(&terminator.kind);
+            let mut error = |msg: String| errors.push((terminator.source_info.span, msg));
+
+            if data.is_cleanup {
+                for &target in targets.iter().filter(|&&target| !mir[target].is_cleanup) {
+                    error(format!("cleanup block {:?} jumps to non-cleanup block {:?}",
+                                  bb, target));
+                }
+                if let Some(unwind) = unwind {
+                    error(format!("cleanup block {:?} unwinds to {:?}", bb, unwind));
+                }
+                continue;
+            }
+
+            if let TerminatorKind::Resume = terminator.kind {
+                error(format!("non-cleanup block {:?} resumes unwinding", bb));
+            }
+            if let Some(unwind) = unwind {
+                if !mir[unwind].is_cleanup {
+                    error(format!("{:?} unwinds to non-cleanup block {:?}", bb, unwind));
+                } else if no_landing_pads {
+                    error(format!("{:?} unwinds to {:?} with landing pads disabled",
+                                  bb, unwind));
+                }
+            }
+        }
+
+        for (span, msg) in errors {
+            tcx.sess.span_err(span, &format!("MIR verification after `{}` failed: {}",
+                                             pass.name(), msg));
+        }
+    }
+}
+
+/// The blocks `kind` goes to when nothing panics, and the one it unwinds
+/// to otherwise, if any.
+fn edges(kind: &TerminatorKind) -> (Vec<BasicBlock>, Option<BasicBlock>) {
+    match *kind {
+        TerminatorKind::Goto { target } => (vec![target], None),
+        TerminatorKind::SwitchInt { ref targets, .. } => (targets.clone(), None),
+        TerminatorKind::Drop { target, unwind, .. } |
+        TerminatorKind::DropAndReplace { target, unwind, .. } => (vec![target], unwind),
+        TerminatorKind::Assert { target, cleanup, .. } => (vec![target], cleanup),
+        TerminatorKind::Call { ref destination, cleanup, .. } => {
+            (destination.iter().map(|&(_, target)| target).collect(), cleanup)
+        }
+        TerminatorKind::Resume |
+        TerminatorKind::Abort |
+        TerminatorKind::Return |
+        TerminatorKind::Unreachable => (vec![], None),
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A debugging aid for bisecting miscompilations to a MIR pass, enabled
+//! with `-Z mir-opt-verify-equivalence`.
+//!
+//! Around every pass, each function is run in the interpreter before and
+//! after the pass, on a few inputs, and the compiler ICEs naming the pass
+//! if the results differ. Only the functions which can be run that way
+//! are checked: those without type parameters whose arguments are all
+//! integers or booleans, which are given the same handful of values,
+//! from 0 and 1 to the extremes of their type.
+//!
+//! A run proves nothing when it calls something without MIR, such as a
+//! foreign function, or takes more than the step limit (the one of
+//! `-Z const-eval-limit`, if given), which a pass may well change. Such
+//! runs, and those returning pointers, aren't compared. Neither are the
+//! functions before and after interprocedural passes, which only keep what
+//! they give for the arguments the crate calls them with.
+
+use interpret::{EvalContext, EvalError, EvalErrorKind, Value, STEP_LIMIT};
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPassHook, MirSource, Pass};
+use rustc::ty::{self, TyCtxt};
+use rustc_const_math::{ConstInt, ConstIsize, ConstUsize};
+use rustc_data_structures::indexed_vec::Idx;
+use syntax::ast::{IntTy, UintTy};
+
+use std::u64;
+
+/// The bits the arguments are given, truncated to their type.
+const INPUTS: [u64; 6] = [0, 1, 2, 0x7f, u64::MAX, 1 << 63];
+
+pub struct VerifyEquivalence {
+    /// What each input gave before the current pass, if it can be
+    /// compared.
+    before: Vec<Option<String>>,
+}
+
+impl VerifyEquivalence {
+    pub fn new() -> VerifyEquivalence {
+        VerifyEquivalence { before: vec![] }
+    }
+}
+
+impl Pass for VerifyEquivalence {}
+
+impl<'tcx> MirPassHook<'tcx> for VerifyEquivalence {
+    fn on_mir_pass<'a>(&mut self,
+                       tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                       src: MirSource,
+                       mir: &Mir<'tcx>,
+                       pass: &Pass,
+                       is_after: bool)
+    {
+        if pass.is_interprocedural() {
+            return;
+        }
+        let inputs = match inputs(tcx, src, mir) {
+            Some(inputs) => inputs,
+            None => return
+        };
+        let _ignore = tcx.dep_graph.in_ignore();
+        let results = run(tcx, src, mir, &inputs);
+        if !is_after {
+            self.before = results;
+            return;
+        }
+
+        for (args, (before, after)) in inputs.iter().zip(self.before.iter().zip(results)) {
+            if let (&Some(ref before), Some(after)) = (before, after) {
+                if *before != after {
+                    let def_id = tcx.map.local_def_id(src.item_id());
+                    span_bug!(mir.span, "`{}` changed what `{}` gives for the arguments {:?}: \
+                                         {} before, {} after",
+                              pass.name(), tcx.item_path_str(def_id), args, before, after);
+                }
+            }
+        }
+    }
+}
+
+/// The arguments to run the function of `mir` with, if it can be run.
+fn inputs<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, src: MirSource, mir: &Mir<'tcx>)
+                    -> Option<Vec<Vec<Value<'tcx>>>> {
+    let id = match src {
+        MirSource::Fn(id) => id,
+        _ => return None
+    };
+    let generics = tcx.item_generics(tcx.map.local_def_id(id));
+    if generics.parent_types > 0 || !generics.types.is_empty() {
+        return None;
+    }
+
+    let arg_tys: Vec<_> = (1..mir.arg_count + 1).map(|i| {
+        mir.local_decls[Local::new(i)].ty
+    }).collect();
+    if mir.arg_count == 0 {
+        return Some(vec![vec![]]);
+    }
+    (0..INPUTS.len()).map(|input| {
+        // Rotate the values between the arguments, so that they aren't
+        // always all equal.
+        arg_tys.iter().enumerate().map(|(i, &ty)| {
+            scalar(tcx, ty, INPUTS[(input + i) % INPUTS.len()])
+        }).collect()
+    }).collect()
+}
+
+/// The value of type `ty` with the low bits of `bits`, if `ty` is an
+/// integer or a boolean.
+fn scalar<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, ty: ty::Ty<'tcx>, bits: u64)
+                    -> Option<Value<'tcx>> {
+    let target = &tcx.sess.target;
+    let int = match ty.sty {
+        ty::TyBool => return Some(Value::Bool(bits & 1 == 1)),
+        ty::TyInt(IntTy::I8) => ConstInt::I8(bits as i8),
+        ty::TyInt(IntTy::I16) => ConstInt::I16(bits as i16),
+        ty::TyInt(IntTy::I32) => ConstInt::I32(bits as i32),
+        ty::TyInt(IntTy::I64) => ConstInt::I64(bits as i64),
+        ty::TyInt(IntTy::Is) => {
+            ConstInt::Isize(ConstIsize::new_truncating(bits as i64, target.int_type))
+        }
+        ty::TyUint(UintTy::U8) => ConstInt::U8(bits as u8),
+        ty::TyUint(UintTy::U16) => ConstInt::U16(bits as u16),
+        ty::TyUint(UintTy::U32) => ConstInt::U32(bits as u32),
+        ty::TyUint(UintTy::U64) => ConstInt::U64(bits),
+        ty::TyUint(UintTy::Us) => {
+            ConstInt::Usize(ConstUsize::new_truncating(bits, target.uint_type))
+        }
+        _ => return None
+    };
+    Some(Value::Int(int))
+}
+
+/// Runs `mir` on each of `inputs`, describing what it gave if that can be
+/// compared.
+fn run<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                 src: MirSource,
+                 mir: &Mir<'tcx>,
+                 inputs: &[Vec<Value<'tcx>>])
+                 -> Vec<Option<String>> {
+    let def_id = tcx.map.local_def_id(src.item_id());
+    let limit = tcx.sess.opts.debugging_opts.const_eval_limit.unwrap_or(STEP_LIMIT);
+    let mut body = mir.duplicate();
+    let mut results = vec![];
+    for args in inputs {
+        let mut ecx = EvalContext::new(tcx, false);
+        ecx.set_step_limit(limit);
+        let (ran, result) = ecx.call_body(def_id, body, args.clone(), mir.span);
+        body = ran;
+        results.push(describe(tcx, result));
+    }
+    results
+}
+
+fn describe<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, result: Result<Value<'tcx>, EvalError>)
+                      -> Option<String> {
+    match result {
+        Ok(ref value) if has_pointers(value) => None,
+        Ok(value) => Some(format!("`{:?}`", value)),
+        Err(ref err) if err.is_unsupported() => None,
+        Err(EvalError { kind: EvalErrorKind::StepLimitReached, .. }) => None,
+        Err(err) => Some(format!("an error ({})", err.description(tcx)))
+    }
+}
+
+/// Whether `value` holds pointers, whose allocations differ between runs.
+fn has_pointers(value: &Value) -> bool {
+    match *value {
+        Value::Ptr(..) => true,
+        Value::Aggregate(_, ref fields) => fields.iter().any(has_pointers),
+        _ => false
+    }
+}
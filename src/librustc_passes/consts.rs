@@ -109,6 +109,17 @@ impl<'a, 'gcx> CheckCrateVisitor<'a, 'gcx> {
                 entry.insert(ConstQualif::empty());
             }
         }
+        self.with_mode(mode, |this| {
+            this.with_euv(None, |euv| euv.consume_expr(expr));
+            this.visit_expr(expr);
+            this.qualif
+        })
+    }
+
+    /// Reports evaluation errors in a constant the MIR interpreter doesn't
+    /// evaluate: pattern constants, which have no MIR, and associated
+    /// constants whose value depends on type parameters.
+    fn check_const_eval(&mut self, expr: &'gcx hir::Expr) {
         if let Err(err) = eval_const_expr_partial(self.tcx, expr, ExprTypeChecked, None) {
             match err.kind {
                 UnimplementedConstVal(_) => {}
@@ -124,11 +135,6 @@ impl<'a, 'gcx> CheckCrateVisitor<'a, 'gcx> {
                 }
             }
         }
-        self.with_mode(mode, |this| {
-            this.with_euv(None, |euv| euv.consume_expr(expr));
-            this.visit_expr(expr);
-            this.qualif
-        })
     }
 
     fn fn_like(&mut self,
@@ -267,6 +273,9 @@ impl<'a, 'tcx> Visitor<'tcx> for CheckCrateVisitor<'a, 'tcx> {
         match t.node {
             hir::ConstTraitItem(_, ref default) => {
                 if let Some(ref expr) = *default {
+                    // Impls may override the default, so it is only ever
+                    // evaluated by the interpreter for a given type.
+                    self.check_const_eval(&expr);
                     self.global_expr(Mode::Const, &expr);
                 } else {
                     intravisit::walk_trait_item(self, t);
@@ -279,6 +288,10 @@ impl<'a, 'tcx> Visitor<'tcx> for CheckCrateVisitor<'a, 'tcx> {
     fn visit_impl_item(&mut self, i: &'tcx hir::ImplItem) {
         match i.node {
             hir::ImplItemKind::Const(_, ref expr) => {
+                let generics = self.tcx.item_generics(self.tcx.map.local_def_id(i.id));
+                if generics.parent_types > 0 || !generics.types.is_empty() {
+                    self.check_const_eval(&expr);
+                }
                 self.global_expr(Mode::Const, &expr);
             }
             _ => self.with_mode(Mode::Var, |v| intravisit::walk_impl_item(v, i)),
@@ -297,9 +310,12 @@ impl<'a, 'tcx> Visitor<'tcx> for CheckCrateVisitor<'a, 'tcx> {
     fn visit_pat(&mut self, p: &'tcx hir::Pat) {
         match p.node {
             PatKind::Lit(ref lit) => {
+                self.check_const_eval(&lit);
                 self.global_expr(Mode::Const, &lit);
             }
             PatKind::Range(ref start, ref end) => {
+                self.check_const_eval(&start);
+                self.check_const_eval(&end);
                 self.global_expr(Mode::Const, &start);
                 self.global_expr(Mode::Const, &end);
 
@@ -13,7 +13,7 @@
 use rustc::lint::{EarlyLintPassObject, LateLintPassObject, LintId, Lint};
 use rustc::session::Session;
 
-use rustc::mir::transform::MirMapPass;
+use rustc::mir::transform::{MirMapPass, MirPassHookFactory, PassPoint};
 
 use syntax::ext::base::{SyntaxExtension, NamedSyntaxExtension, NormalTT, IdentTT};
 use syntax::ext::base::MacroExpanderFn;
@@ -54,7 +54,10 @@ pub struct Registry<'a> {
     pub late_lint_passes: Vec<LateLintPassObject>,
 
     #[doc(hidden)]
-    pub mir_passes: Vec<Box<for<'pcx> MirMapPass<'pcx>>>,
+    pub mir_passes: Vec<(PassPoint, Box<for<'pcx> MirMapPass<'pcx>>)>,
+
+    #[doc(hidden)]
+    pub mir_pass_hooks: Vec<MirPassHookFactory>,
 
     #[doc(hidden)]
     pub lint_groups: HashMap<&'static str, Vec<LintId>>,
@@ -80,6 +83,7 @@ impl<'a> Registry<'a> {
             llvm_passes: vec![],
             attributes: vec![],
             mir_passes: Vec::new(),
+            mir_pass_hooks: Vec::new(),
         }
     }
 
@@ -139,9 +143,24 @@ impl<'a> Registry<'a> {
         self.lint_groups.insert(name, to.into_iter().map(|x| LintId::of(x)).collect());
     }
 
-    /// Register a MIR pass
+    /// Register a MIR pass, which runs first, on the MIR as it was built.
     pub fn register_mir_pass(&mut self, pass: Box<for<'pcx> MirMapPass<'pcx>>) {
-        self.mir_passes.push(pass);
+        self.register_mir_pass_at(PassPoint::Built, pass);
+    }
+
+    /// Register a MIR pass to run at `point` in the pipeline. The passes
+    /// registered for the same point run in the order they were registered
+    /// in.
+    pub fn register_mir_pass_at(&mut self, point: PassPoint,
+                                pass: Box<for<'pcx> MirMapPass<'pcx>>) {
+        self.mir_passes.push((point, pass));
+    }
+
+    /// Register a hook called around every MIR pass, the built-in ones
+    /// included, e.g. to look at the MIR each pass leaves. `new_hook` is
+    /// called once for each of the pass managers.
+    pub fn register_mir_pass_hook(&mut self, new_hook: MirPassHookFactory) {
+        self.mir_pass_hooks.push(new_hook);
     }
 
     /// Register an LLVM pass.
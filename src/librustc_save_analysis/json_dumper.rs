@@ -148,7 +148,7 @@ impl Analysis {
 // DefId::index is a newtype and so the JSON serialisation is ugly. Therefore
 // we use our own Id which is the same, but without the newtype.
 #[derive(Clone, Copy, Debug, RustcEncodable)]
-struct Id {
+pub struct Id {
     krate: u32,
     index: u32,
 }
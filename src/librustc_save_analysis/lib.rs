@@ -33,6 +33,7 @@ extern crate syntax_pos;
 mod csv_dumper;
 mod json_api_dumper;
 mod json_dumper;
+mod mir_facts;
 mod data;
 mod dump;
 mod dump_visitor;
@@ -63,6 +64,7 @@ use syntax_pos::*;
 pub use self::csv_dumper::CsvDumper;
 pub use self::json_api_dumper::JsonApiDumper;
 pub use self::json_dumper::JsonDumper;
+pub use self::mir_facts::process_mir;
 pub use self::data::*;
 pub use self::external_data::make_def_id;
 pub use self::dump::Dump;
@@ -823,6 +825,32 @@ pub fn process_crate<'l, 'tcx>(tcx: TyCtxt<'l, 'tcx, 'tcx>,
 
     info!("Dumping crate {}", cratename);
 
+    let mut output_file = create_output_file(tcx, cratename, odir, format.extension());
+    let output = &mut output_file;
+
+    let save_ctxt = SaveContext::new(tcx, analysis);
+
+    macro_rules! dump {
+        ($new_dumper: expr) => {{
+            let mut dumper = $new_dumper;
+            let mut visitor = DumpVisitor::new(save_ctxt, &mut dumper);
+
+            visitor.dump_crate_info(cratename, krate);
+            visit::walk_crate(&mut visitor, krate);
+        }}
+    }
+
+    match format {
+        Format::Csv => dump!(CsvDumper::new(output)),
+        Format::Json => dump!(JsonDumper::new(output)),
+        Format::JsonApi => dump!(JsonApiDumper::new(output)),
+    }
+}
+
+/// Creates the file the analysis of `cratename` with the given extension
+/// is written to, in the save-analysis directory.
+fn create_output_file(tcx: TyCtxt, cratename: &str, odir: Option<&Path>, extension: &str)
+                      -> File {
     // find a path to dump our data to
     let mut root_path = match env::var_os("RUST_SAVE_ANALYSIS_FOLDER") {
         Some(val) => PathBuf::from(val),
@@ -852,32 +880,12 @@ pub fn process_crate<'l, 'tcx>(tcx: TyCtxt<'l, 'tcx, 'tcx>,
     };
     out_name.push_str(&cratename);
     out_name.push_str(&tcx.sess.opts.cg.extra_filename);
-    out_name.push_str(format.extension());
+    out_name.push_str(extension);
     root_path.push(&out_name);
-    let mut output_file = File::create(&root_path).unwrap_or_else(|e| {
+    File::create(&root_path).unwrap_or_else(|e| {
         let disp = root_path.display();
         tcx.sess.fatal(&format!("Could not open {}: {}", disp, e));
-    });
-    root_path.pop();
-    let output = &mut output_file;
-
-    let save_ctxt = SaveContext::new(tcx, analysis);
-
-    macro_rules! dump {
-        ($new_dumper: expr) => {{
-            let mut dumper = $new_dumper;
-            let mut visitor = DumpVisitor::new(save_ctxt, &mut dumper);
-
-            visitor.dump_crate_info(cratename, krate);
-            visit::walk_crate(&mut visitor, krate);
-        }}
-    }
-
-    match format {
-        Format::Csv => dump!(CsvDumper::new(output)),
-        Format::Json => dump!(JsonDumper::new(output)),
-        Format::JsonApi => dump!(JsonApiDumper::new(output)),
-    }
+    })
 }
 
 // Utility functions for the module.
@@ -0,0 +1,99 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Facts about the optimized MIR of the functions of a crate, for tools
+//! which want feedback from the optimizer. They are written with
+//! `-Z save-analysis-mir`, once the MIR has been optimized, to a
+//! `.mir.json` file next to the rest of the save-analysis data.
+
+use std::io::Write;
+use std::path::Path;
+
+use rustc::mir::{Mir, VarDebugInfoContents};
+use rustc::mir::transform::MirSource;
+use rustc::ty::TyCtxt;
+use rustc_serialize::json::as_json;
+
+use external_data::SpanData;
+use json_dumper::Id;
+
+#[derive(Debug, RustcEncodable)]
+struct MirAnalysis {
+    crate_name: String,
+    functions: Vec<FunctionFacts>,
+}
+
+#[derive(Debug, RustcEncodable)]
+struct FunctionFacts {
+    id: Id,
+    qualname: String,
+    span: SpanData,
+    /// The size of the stack frame estimated by trans, the largest of its
+    /// instances for a generic function, unless it wasn't translated.
+    estimated_stack_size: Option<u64>,
+    /// The variables which the optimized code doesn't store anywhere.
+    dead_variables: Vec<DeadVariable>,
+}
+
+#[derive(Debug, RustcEncodable)]
+struct DeadVariable {
+    name: String,
+    span: SpanData,
+    /// The constant the variable always holds, if it was replaced by one.
+    value: Option<String>,
+}
+
+pub fn process_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, cratename: &str, odir: Option<&Path>) {
+    let _ignore = tcx.dep_graph.in_ignore();
+
+    let mut def_ids: Vec<_> = tcx.mir_map.borrow().keys().into_iter()
+                                 .filter(|def_id| def_id.is_local())
+                                 .collect();
+    def_ids.sort();
+
+    let mut functions = vec![];
+    for def_id in def_ids {
+        let id = tcx.map.as_local_node_id(def_id).unwrap();
+        if let MirSource::Fn(_) = MirSource::from_node(tcx, id) {
+            let mir = tcx.item_mir(def_id);
+            functions.push(FunctionFacts {
+                id: From::from(def_id),
+                qualname: format!("::{}", tcx.node_path_str(id)),
+                span: SpanData::from_span(mir.span, tcx.sess.codemap()),
+                estimated_stack_size: tcx.sess.code_stats.borrow().frame_size(def_id),
+                dead_variables: dead_variables(tcx, &mir),
+            });
+        }
+    }
+
+    let analysis = MirAnalysis {
+        crate_name: cratename.to_string(),
+        functions: functions,
+    };
+    let mut output = super::create_output_file(tcx, cratename, odir, ".mir.json");
+    if let Err(_) = write!(output, "{}", as_json(&analysis)) {
+        error!("Error writing output");
+    }
+}
+
+fn dead_variables<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) -> Vec<DeadVariable> {
+    mir.var_debug_info.iter().filter_map(|var| {
+        let value = match var.value {
+            VarDebugInfoContents::Lvalue(_) => return None,
+            VarDebugInfoContents::Const(ref constant) => Some(format!("{:?}", constant)),
+            VarDebugInfoContents::OptimizedOut => None,
+        };
+        Some(DeadVariable {
+            name: var.name.to_string(),
+            span: SpanData::from_span(var.source_info.span, tcx.sess.codemap()),
+            value: value,
+        })
+    }).collect()
+}
@@ -13,7 +13,7 @@ use std::ffi::{CStr, CString};
 
 use llvm::{self, Attribute, ValueRef};
 use llvm::AttributePlace::Function;
-pub use syntax::attr::InlineAttr;
+pub use syntax::attr::{InlineAttr, OptimizeAttr};
 use syntax::ast;
 use context::CrateContext;
 
@@ -33,6 +33,26 @@ pub fn inline(val: ValueRef, inline: InlineAttr) {
     };
 }
 
+/// Tell LLVM whether to optimise function for size or for speed, whatever the
+/// crate-wide `-C opt-level` is.
+#[inline]
+pub fn optimize(val: ValueRef, optimize: OptimizeAttr) {
+    match optimize {
+        OptimizeAttr::Size => Attribute::OptimizeForSize.apply_llfn(Function, val),
+        OptimizeAttr::Speed => {
+            Attribute::MinSize.unapply_llfn(Function, val);
+            Attribute::OptimizeForSize.unapply_llfn(Function, val);
+        }
+        OptimizeAttr::None => {}
+    }
+}
+
+/// Tell LLVM that the function is rarely called.
+#[inline]
+pub fn cold(val: ValueRef) {
+    Attribute::Cold.apply_llfn(Function, val);
+}
+
 /// Tell LLVM to emit or not emit the information necessary to unwind the stack for the function.
 #[inline]
 pub fn emit_uwtable(val: ValueRef, emit: bool) {
@@ -73,6 +93,7 @@ pub fn set_frame_pointer_elimination(ccx: &CrateContext, llfn: ValueRef) {
 pub fn from_fn_attrs(ccx: &CrateContext, attrs: &[ast::Attribute], llfn: ValueRef) {
     use syntax::attr::*;
     inline(llfn, find_inline_attr(Some(ccx.sess().diagnostic()), attrs));
+    optimize(llfn, find_optimize_attr(Some(ccx.sess().diagnostic()), attrs));
 
     set_frame_pointer_elimination(ccx, llfn);
     let mut target_features = vec![];
@@ -86,7 +107,7 @@ pub fn from_fn_attrs(ccx: &CrateContext, attrs: &[ast::Attribute], llfn: ValueRe
                 }
             }
         } else if attr.check_name("cold") {
-            Attribute::Cold.apply_llfn(Function, llfn);
+            cold(llfn);
         } else if attr.check_name("naked") {
             naked(llfn, true);
         } else if attr.check_name("allocator") {
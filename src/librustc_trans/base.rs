@@ -37,6 +37,7 @@ use back::symbol_export::{self, ExportedSymbols};
 use llvm::{Linkage, ValueRef, Vector, get_param};
 use llvm;
 use rustc::hir::def_id::{DefId, LOCAL_CRATE};
+use rustc::lint;
 use middle::lang_items::{LangItem, ExchangeMallocFnLangItem, StartFnLangItem};
 use rustc::ty::subst::Substs;
 use rustc::traits;
@@ -72,6 +73,7 @@ use meth;
 use mir;
 use monomorphize::{self, Instance};
 use partitioning::{self, PartitioningStrategy, CodegenUnit};
+use profile;
 use symbol_map::SymbolMap;
 use symbol_names_test;
 use trans_item::{TransItem, DefPathBasedNames};
@@ -787,7 +789,7 @@ pub fn init_zero_mem<'blk, 'tcx>(cx: Block<'blk, 'tcx>, llptr: ValueRef, t: Ty<'
 // allocation for large data structures, and the generated code will be
 // awful. (A telltale sign of this is large quantities of
 // `mov [byte ptr foo],0` in the generated code.)
-fn memfill<'a, 'tcx>(b: &Builder<'a, 'tcx>, llptr: ValueRef, ty: Ty<'tcx>, byte: u8) {
+pub fn memfill<'a, 'tcx>(b: &Builder<'a, 'tcx>, llptr: ValueRef, ty: Ty<'tcx>, byte: u8) {
     let _icx = push_ctxt("memfill");
     let ccx = b.ccx;
     let llty = type_of::type_of(ccx, ty);
@@ -881,6 +883,7 @@ impl<'blk, 'tcx> FunctionContext<'blk, 'tcx> {
             landingpad_alloca: Cell::new(None),
             fn_ty: fn_ty,
             param_substs: param_substs,
+            def_id: def_id,
             span: None,
             block_arena: block_arena,
             lpad_arena: TypedArena::new(),
@@ -1050,7 +1053,32 @@ pub fn trans_instance<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>, instance: Instance
         bug!("attempted translation of `{}` w/o MIR", instance);
     }
 
-    mir::trans_mir(&fcx);
+    let frame_size = mir::trans_mir(&fcx);
+    check_frame_size(ccx, instance, frame_size);
+}
+
+fn check_frame_size<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
+                              instance: Instance<'tcx>,
+                              frame_size: u64) {
+    ccx.sess().code_stats.borrow_mut().record_frame_size(instance.def, frame_size);
+
+    let limit = ccx.sess().stack_frame_limit.get() as u64;
+    if frame_size <= limit {
+        return;
+    }
+
+    // Only functions of this crate have a lint level. Generic functions
+    // are only reported for the first instance over the limit.
+    let tcx = ccx.tcx();
+    if !ccx.large_stack_frames().borrow_mut().insert(instance.def) {
+        return;
+    }
+    if let Some(id) = tcx.map.as_local_node_id(instance.def) {
+        lint::emit_node_lint(tcx, lint::builtin::LARGE_STACK_FRAME, id, tcx.map.span(id),
+                             &format!("the stack frame of `{}` is estimated at {} bytes, \
+                                       above the limit of {} bytes",
+                                      instance, frame_size, limit));
+    }
 }
 
 pub fn trans_ctor_shim<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
@@ -1573,14 +1601,10 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     // particular items that will be processed.
     let krate = tcx.map.krate();
 
-    let ty::CrateAnalysis { export_map, reachable, name, .. } = analysis;
+    let ty::CrateAnalysis { export_map, reachable, unreferenced_fns, name, .. } = analysis;
     let exported_symbols = find_exported_symbols(tcx, reachable);
 
-    let check_overflow = if let Some(v) = tcx.sess.opts.debugging_opts.force_overflow_checks {
-        v
-    } else {
-        tcx.sess.opts.debug_assertions
-    };
+    let check_overflow = tcx.sess.overflow_checks();
 
     let link_meta = link::build_link_meta(incremental_hashes_map, &name);
 
@@ -1588,6 +1612,7 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                                              export_map,
                                              link_meta.clone(),
                                              exported_symbols,
+                                             unreferenced_fns,
                                              check_overflow);
     // Translate the metadata.
     let metadata = time(tcx.sess.time_passes(), "write metadata", || {
@@ -1679,6 +1704,10 @@ pub fn trans_crate<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
             // wrapper here
             maybe_create_entry_wrapper(&ccx);
 
+            // Register the `-Z profile-mir` counters of this codegen unit
+            profile::emit_registration(&ccx);
+            profile::emit_coverage_map(&ccx);
+
             // Run replace-all-uses-with for statics that need it
             for &(old_g, new_g) in ccx.statics_to_rauw().borrow().iter() {
                 unsafe {
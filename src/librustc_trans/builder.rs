@@ -141,10 +141,11 @@ impl<'a, 'tcx> Builder<'a, 'tcx> {
         }
     }
 
-    pub fn cond_br(&self, cond: ValueRef, then_llbb: BasicBlockRef, else_llbb: BasicBlockRef) {
+    pub fn cond_br(&self, cond: ValueRef, then_llbb: BasicBlockRef, else_llbb: BasicBlockRef)
+                   -> ValueRef {
         self.count_insn("condbr");
         unsafe {
-            llvm::LLVMBuildCondBr(self.llbuilder, cond, then_llbb, else_llbb);
+            llvm::LLVMBuildCondBr(self.llbuilder, cond, then_llbb, else_llbb)
         }
     }
 
@@ -529,6 +530,23 @@ impl<'a, 'tcx> Builder<'a, 'tcx> {
         value
     }
 
+    /// Attaches `!prof` branch weights to a `br` or `switch` instruction,
+    /// in the order of its successors (the default case first for `switch`).
+    pub fn set_branch_weights(&self, branch: ValueRef, weights: &[u32]) {
+        let name = "branch_weights";
+        unsafe {
+            let mut v = Vec::with_capacity(weights.len() + 1);
+            v.push(llvm::LLVMMDStringInContext(self.ccx.llcx(),
+                                               name.as_ptr() as *const c_char,
+                                               name.len() as c_uint));
+            v.extend(weights.iter().map(|&w| C_u32(self.ccx, w)));
+            llvm::LLVMSetMetadata(branch, llvm::MD_prof as c_uint,
+                                  llvm::LLVMMDNodeInContext(self.ccx.llcx(),
+                                                            v.as_ptr(),
+                                                            v.len() as c_uint));
+        }
+    }
+
     pub fn load_nonnull(&self, ptr: ValueRef) -> ValueRef {
         let value = self.load(ptr);
         unsafe {
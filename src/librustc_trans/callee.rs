@@ -17,10 +17,11 @@
 pub use self::CalleeData::*;
 
 use arena::TypedArena;
-use llvm::{self, ValueRef, get_params};
+use llvm::{self, ValueRef};
 use rustc::hir::def_id::DefId;
 use rustc::ty::subst::Substs;
 use rustc::traits;
+use rustc::mir::Shim;
 use abi::{Abi, FnType};
 use attributes;
 use base;
@@ -32,12 +33,14 @@ use debuginfo::DebugLoc;
 use declare;
 use value::Value;
 use meth;
+use mir;
 use monomorphize::{self, Instance};
 use trans_item::TransItem;
 use type_of;
 use Disr;
 use rustc::ty::{self, Ty, TypeFoldable};
 use rustc::hir;
+use rustc_mir::shim;
 use std::iter;
 
 use syntax_pos::DUMMY_SP;
@@ -97,6 +100,15 @@ impl<'tcx> Callee<'tcx> {
             return Callee::trait_method(ccx, trait_id, def_id, substs);
         }
 
+        if common::is_closure(tcx, def_id) {
+            // A closure body called directly, as its `FnOnce` adapter does.
+            let closure_ty = def_ty(ccx.shared(), def_id, substs);
+            let bare_fn = common::ty_fn_ty(ccx, closure_ty).into_owned();
+            let fn_ty = tcx.mk_fn_def(def_id, substs, tcx.mk_bare_fn(bare_fn));
+            let (llfn, _) = get_fn(ccx, def_id, substs);
+            return Callee::ptr(llfn, fn_ty);
+        }
+
         let fn_ty = def_ty(ccx.shared(), def_id, substs);
         if let ty::TyFnDef(.., f) = fn_ty.sty {
             if f.abi == Abi::RustIntrinsic || f.abi == Abi::PlatformIntrinsic {
@@ -296,7 +308,7 @@ fn trans_closure_method<'a, 'tcx>(ccx: &'a CrateContext<'a, 'tcx>,
             //     fn call_once(mut self, ...) { call_mut(&mut self, ...) }
             //
             // These are both the same at trans time.
-            trans_fn_once_adapter_shim(ccx, def_id, substs, method_instance, llfn)
+            trans_fn_once_adapter_shim(ccx, def_id, substs, method_instance)
         }
         _ => {
             bug!("trans_closure_adapter_shim: cannot convert {:?} to {:?}",
@@ -310,50 +322,29 @@ fn trans_fn_once_adapter_shim<'a, 'tcx>(
     ccx: &'a CrateContext<'a, 'tcx>,
     def_id: DefId,
     substs: ty::ClosureSubsts<'tcx>,
-    method_instance: Instance<'tcx>,
-    llreffn: ValueRef)
+    method_instance: Instance<'tcx>)
     -> ValueRef
 {
     if let Some(&llfn) = ccx.instances().borrow().get(&method_instance) {
         return llfn;
     }
 
-    debug!("trans_fn_once_adapter_shim(def_id={:?}, substs={:?}, llreffn={:?})",
-           def_id, substs, Value(llreffn));
+    debug!("trans_fn_once_adapter_shim(def_id={:?}, substs={:?})",
+           def_id, substs);
 
     let tcx = ccx.tcx();
 
-    // Find a version of the closure type. Substitute static for the
-    // region since it doesn't really matter.
-    let closure_ty = tcx.mk_closure_from_closure_substs(def_id, substs);
-    let ref_closure_ty = tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), closure_ty);
-
-    // Make a version with the type of by-ref closure.
-    let ty::ClosureTy { unsafety, abi, mut sig } = tcx.closure_type(def_id, substs);
-    sig.0 = tcx.mk_fn_sig(
-        iter::once(ref_closure_ty).chain(sig.0.inputs().iter().cloned()),
-        sig.0.output(),
-        sig.0.variadic
-    );
-    let llref_fn_ty = tcx.mk_fn_ptr(tcx.mk_bare_fn(ty::BareFnTy {
-        unsafety: unsafety,
-        abi: abi,
-        sig: sig.clone()
-    }));
-    debug!("trans_fn_once_adapter_shim: llref_fn_ty={:?}",
-           llref_fn_ty);
-
-
     // Make a version of the closure type with the same arguments, but
     // with argument #0 being by value.
+    let closure_ty = tcx.mk_closure_from_closure_substs(def_id, substs);
+    let ty::ClosureTy { unsafety, abi, sig } = tcx.closure_type(def_id, substs);
     assert_eq!(abi, Abi::RustCall);
-    sig.0 = tcx.mk_fn_sig(
-        iter::once(closure_ty).chain(sig.0.inputs().iter().skip(1).cloned()),
-        sig.0.output(),
-        sig.0.variadic
-    );
-
     let sig = tcx.erase_late_bound_regions_and_normalize(&sig);
+    let sig = tcx.mk_fn_sig(
+        iter::once(closure_ty).chain(sig.inputs().iter().cloned()),
+        sig.output(),
+        sig.variadic
+    );
     let fn_ty = FnType::new(ccx, abi, &sig, &[]);
 
     let llonce_fn_ty = tcx.mk_fn_ptr(tcx.mk_bare_fn(ty::BareFnTy {
@@ -367,57 +358,14 @@ fn trans_fn_once_adapter_shim<'a, 'tcx>(
     let lloncefn = declare::define_internal_fn(ccx, &function_name, llonce_fn_ty);
     attributes::set_frame_pointer_elimination(ccx, lloncefn);
 
-    let (block_arena, fcx): (TypedArena<_>, FunctionContext);
+    // Its body calls the by-ref closure body and drops `self` when that
+    // returns, or in case it unwinds.
+    let (block_arena, mut fcx): (TypedArena<_>, FunctionContext);
     block_arena = TypedArena::new();
     fcx = FunctionContext::new(ccx, lloncefn, fn_ty, None, &block_arena);
-    let mut bcx = fcx.init(false);
-
-
-    // the first argument (`self`) will be the (by value) closure env.
-
-    let mut llargs = get_params(fcx.llfn);
-    let mut self_idx = fcx.fn_ty.ret.is_indirect() as usize;
-    let env_arg = &fcx.fn_ty.args[0];
-    let llenv = if env_arg.is_indirect() {
-        llargs[self_idx]
-    } else {
-        let scratch = alloc_ty(bcx, closure_ty, "self");
-        let mut llarg_idx = self_idx;
-        env_arg.store_fn_arg(&bcx.build(), &mut llarg_idx, scratch);
-        scratch
-    };
-
-    debug!("trans_fn_once_adapter_shim: env={:?}", Value(llenv));
-    // Adjust llargs such that llargs[self_idx..] has the call arguments.
-    // For zero-sized closures that means sneaking in a new argument.
-    if env_arg.is_ignore() {
-        if self_idx > 0 {
-            self_idx -= 1;
-            llargs[self_idx] = llenv;
-        } else {
-            llargs.insert(0, llenv);
-        }
-    } else {
-        llargs[self_idx] = llenv;
-    }
-
-    let dest = fcx.llretslotptr.get();
-
-    let callee = Callee {
-        data: Fn(llreffn),
-        ty: llref_fn_ty
-    };
-
-    // Call the by-ref closure body with `self` in a cleanup scope,
-    // to drop `self` when the body returns, or in case it unwinds.
-    let self_scope = fcx.push_custom_cleanup_scope();
-    fcx.schedule_drop_mem(self_scope, llenv, closure_ty);
-
-    bcx = callee.call(bcx, DebugLoc::None, &llargs[self_idx..], dest).bcx;
-
-    fcx.pop_and_trans_custom_cleanup_scope(bcx, self_scope);
-
-    fcx.finish(bcx, DebugLoc::None);
+    let mir = shim::shim_mir(tcx, Shim::FnOnceAdapter(def_id, substs)).unwrap();
+    fcx.mir = Some(mir.borrow());
+    mir::trans_mir(&fcx);
 
     ccx.instances().borrow_mut().insert(method_instance, lloncefn);
 
@@ -455,19 +403,13 @@ fn trans_fn_pointer_shim<'a, 'tcx>(
         ty::ClosureKind::FnOnce => false,
     };
 
-    let llfnpointer = match bare_fn_ty.sty {
-        ty::TyFnDef(def_id, substs, _) => {
-            // Function definitions have to be turned into a pointer.
-            let llfn = Callee::def(ccx, def_id, substs).reify(ccx);
-            if !is_by_ref {
-                // A by-value fn item is ignored, so the shim has
-                // the same signature as the original function.
-                return llfn;
-            }
-            Some(llfn)
+    if let ty::TyFnDef(def_id, substs, _) = bare_fn_ty.sty {
+        if !is_by_ref {
+            // A by-value fn item is ignored, so the shim has
+            // the same signature as the original function.
+            return Callee::def(ccx, def_id, substs).reify(ccx);
         }
-        _ => None
-    };
+    }
 
     let bare_fn_ty_maybe_ref = if is_by_ref {
         tcx.mk_imm_ref(tcx.mk_region(ty::ReErased), bare_fn_ty)
@@ -519,32 +461,12 @@ fn trans_fn_pointer_shim<'a, 'tcx>(
     let llfn = declare::define_internal_fn(ccx, &function_name, tuple_fn_ty);
     attributes::set_frame_pointer_elimination(ccx, llfn);
     //
-    let (block_arena, fcx): (TypedArena<_>, FunctionContext);
+    let (block_arena, mut fcx): (TypedArena<_>, FunctionContext);
     block_arena = TypedArena::new();
     fcx = FunctionContext::new(ccx, llfn, fn_ty, None, &block_arena);
-    let mut bcx = fcx.init(false);
-
-    let llargs = get_params(fcx.llfn);
-
-    let self_idx = fcx.fn_ty.ret.is_indirect() as usize;
-    let llfnpointer = llfnpointer.unwrap_or_else(|| {
-        // the first argument (`self`) will be ptr to the fn pointer
-        if is_by_ref {
-            Load(bcx, llargs[self_idx])
-        } else {
-            llargs[self_idx]
-        }
-    });
-
-    let dest = fcx.llretslotptr.get();
-
-    let callee = Callee {
-        data: Fn(llfnpointer),
-        ty: bare_fn_ty
-    };
-    bcx = callee.call(bcx, DebugLoc::None, &llargs[(self_idx + 1)..], dest).bcx;
-
-    fcx.finish(bcx, DebugLoc::None);
+    let mir = shim::shim_mir(tcx, Shim::FnPtr(bare_fn_ty, is_by_ref)).unwrap();
+    fcx.mir = Some(mir.borrow());
+    mir::trans_mir(&fcx);
 
     ccx.fn_pointer_shims().borrow_mut().insert(bare_fn_ty_maybe_ref, llfn);
 
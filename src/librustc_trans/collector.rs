@@ -194,6 +194,7 @@ use rustc::hir::itemlikevisit::ItemLikeVisitor;
 use rustc::hir::map as hir_map;
 use rustc::hir::def_id::DefId;
 use rustc::middle::lang_items::{ExchangeFreeFnLangItem, ExchangeMallocFnLangItem};
+use rustc::session::config;
 use rustc::traits;
 use rustc::ty::subst::{Substs, Subst};
 use rustc::ty::{self, TypeFoldable, TyCtxt};
@@ -709,7 +710,17 @@ fn can_have_local_instance<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     // Take a look if we have the definition available. If not, we
     // will not emit code for this item in the local crate, and thus
     // don't create a translation item for it.
-    def_id.is_local() || tcx.sess.cstore.is_item_mir_available(def_id)
+    if def_id.is_local() {
+        return true;
+    }
+    if !tcx.sess.cstore.is_item_mir_available(def_id) {
+        return false;
+    }
+    // The functions only made available by `-Z cross-crate-auto-inline`
+    // are exported by their crate as well, and copying them is only worth
+    // it if LLVM may inline the copies.
+    tcx.sess.opts.optimize != config::OptLevel::No ||
+        !tcx.item_mir_summary(def_id).map_or(false, |summary| summary.auto_inline)
 }
 
 fn find_drop_glue_neighbors<'a, 'tcx>(scx: &SharedCrateContext<'a, 'tcx>,
@@ -1116,7 +1127,8 @@ impl<'b, 'a, 'v> ItemLikeVisitor<'v> for RootCollector<'b, 'a, 'v> {
                 // actually used somewhere. Just declaring them is insufficient.
             }
             hir::ItemFn(.., ref generics, _) => {
-                if !generics.is_type_parameterized() {
+                if !generics.is_type_parameterized() &&
+                   !self.scx.unreferenced_fns().contains(&item.id) {
                     let def_id = self.scx.tcx().map.local_def_id(item.id);
 
                     debug!("RootCollector: ItemFn({})",
@@ -1149,7 +1161,8 @@ impl<'b, 'a, 'v> ItemLikeVisitor<'v> for RootCollector<'b, 'a, 'v> {
                     }
                 };
 
-                if !generics.is_type_parameterized() && !is_impl_generic {
+                if !generics.is_type_parameterized() && !is_impl_generic &&
+                   !self.scx.unreferenced_fns().contains(&ii.id) {
                     let def_id = self.scx.tcx().map.local_def_id(ii.id);
 
                     debug!("RootCollector: MethodImplItem({})",
@@ -1257,11 +1270,21 @@ fn collect_const_item_neighbours<'a, 'tcx>(scx: &SharedCrateContext<'a, 'tcx>,
     visit_mir_and_promoted(visitor, &mir);
 }
 
-fn visit_mir_and_promoted<'tcx, V: MirVisitor<'tcx>>(mut visitor: V, mir: &mir::Mir<'tcx>) {
+fn visit_mir_and_promoted<'a, 'tcx>(mut visitor: MirNeighborCollector<'a, 'tcx>,
+                                    mir: &'a mir::Mir<'tcx>) {
     visitor.visit_mir(&mir);
     for promoted in &mir.promoted {
         visitor.visit_mir(promoted);
     }
+    // The cold parts moved out of the function are translated along with it,
+    // but have locals of their own.
+    for outlined in &mir.outlined {
+        visitor.mir = outlined;
+        visitor.visit_mir(outlined);
+        for promoted in &outlined.promoted {
+            visitor.visit_mir(promoted);
+        }
+    }
 }
 
 fn def_id_to_string<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
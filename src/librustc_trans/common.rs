@@ -301,6 +301,9 @@ pub struct FunctionContext<'a, 'tcx: 'a> {
     // substitutions used.
     pub param_substs: &'tcx Substs<'tcx>,
 
+    // The function being translated, if this isn't a shim.
+    pub def_id: Option<DefId>,
+
     // The source span and nesting context where this function comes from, for
     // error reporting and symbol generation.
     pub span: Option<Span>,
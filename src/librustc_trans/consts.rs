@@ -18,7 +18,7 @@ use rustc::hir::map as hir_map;
 use {debuginfo, machine};
 use base::{self, push_ctxt};
 use trans_item::TransItem;
-use common::{CrateContext, val_ty};
+use common::{CrateContext, val_ty, C_str_slice, C_struct, C_u32};
 use declare;
 use monomorphize::{Instance};
 use type_::Type;
@@ -30,6 +30,7 @@ use rustc::hir;
 use std::ffi::{CStr, CString};
 use syntax::ast;
 use syntax::attr;
+use syntax::symbol::{InternedString, Symbol};
 
 pub fn ptrcast(val: ValueRef, ty: Type) -> ValueRef {
     unsafe {
@@ -78,6 +79,34 @@ pub fn addr_of(ccx: &CrateContext,
     gv
 }
 
+/// The `&(msg, file, line)` argument of `panic`, or the `&(file, line)`
+/// one of `panic_bounds_check` if there is no message, from the table of
+/// panic locations of the codegen unit, so each one is only emitted once.
+pub fn panic_location(ccx: &CrateContext,
+                      msg: Option<&'static str>,
+                      file: InternedString,
+                      line: u32)
+                      -> ValueRef {
+    let key = (msg, file.clone(), line);
+    if let Some(&gv) = ccx.panic_locations().borrow().get(&key) {
+        return gv;
+    }
+
+    let file = C_str_slice(ccx, file);
+    let line = C_u32(ccx, line);
+    let (location, kind) = match msg {
+        Some(msg) => {
+            let msg = C_str_slice(ccx, Symbol::intern(msg).as_str());
+            (C_struct(ccx, &[msg, file, line], false), "panic_loc")
+        }
+        None => (C_struct(ccx, &[file, line], false), "panic_bounds_check_loc")
+    };
+    let align = machine::llalign_of_min(ccx, val_ty(location));
+    let gv = addr_of(ccx, location, align, kind);
+    ccx.panic_locations().borrow_mut().insert(key, gv);
+    gv
+}
+
 pub fn get_static(ccx: &CrateContext, def_id: DefId) -> ValueRef {
     let instance = Instance::mono(ccx.shared(), def_id);
     if let Some(&g) = ccx.instances().borrow().get(&instance) {
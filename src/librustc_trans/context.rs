@@ -33,7 +33,7 @@ use session::config::NoDebugInfo;
 use session::Session;
 use session::config;
 use symbol_map::SymbolMap;
-use util::nodemap::{NodeSet, DefIdMap, FxHashMap, FxHashSet};
+use util::nodemap::{NodeSet, DefIdMap, DefIdSet, FxHashMap, FxHashSet};
 
 use std::ffi::{CStr, CString};
 use std::cell::{Cell, RefCell};
@@ -43,6 +43,7 @@ use std::rc::Rc;
 use std::str;
 use syntax::ast;
 use syntax::symbol::InternedString;
+use syntax_pos::Span;
 use abi::FnType;
 
 pub struct Stats {
@@ -68,6 +69,7 @@ pub struct SharedCrateContext<'a, 'tcx: 'a> {
 
     export_map: ExportMap,
     exported_symbols: NodeSet,
+    unreferenced_fns: NodeSet,
     link_meta: LinkMeta,
     tcx: TyCtxt<'a, 'tcx, 'tcx>,
     stats: Stats,
@@ -78,6 +80,12 @@ pub struct SharedCrateContext<'a, 'tcx: 'a> {
     translation_items: RefCell<FxHashSet<TransItem<'tcx>>>,
     trait_cache: RefCell<DepTrackingMap<TraitSelectionCache<'tcx>>>,
     project_cache: RefCell<DepTrackingMap<ProjectionCache<'tcx>>>,
+
+    /// Functions already reported by the `large_stack_frame` lint, so
+    /// that each is reported once rather than once per instance.
+    large_stack_frames: RefCell<DefIdSet>,
+    /// Likewise for the operations reported by `arithmetic_overflow`.
+    arithmetic_overflows: RefCell<FxHashSet<Span>>,
 }
 
 /// The local portion of a `CrateContext`.  There is one `LocalCrateContext`
@@ -113,6 +121,10 @@ pub struct LocalCrateContext<'tcx> {
     /// Cache of emitted const globals (value -> global)
     const_globals: RefCell<FxHashMap<ValueRef, ValueRef>>,
 
+    /// Table of the locations passed to the panic entry points, by message,
+    /// file and line (see `consts::panic_location`)
+    panic_locations: RefCell<FxHashMap<(Option<&'static str>, InternedString, u32), ValueRef>>,
+
     /// Cache of emitted const values
     const_values: RefCell<FxHashMap<(ast::NodeId, &'tcx Substs<'tcx>), ValueRef>>,
 
@@ -164,6 +176,13 @@ pub struct LocalCrateContext<'tcx> {
 
     /// A counter that is used for generating local symbol names
     local_gen_sym_counter: Cell<usize>,
+
+    /// The `-Z profile-mir` counter tables defined in this codegen unit,
+    /// with the symbol of their function and their length.
+    mir_profile_tables: RefCell<Vec<(String, ValueRef, u64)>>,
+
+    /// The `-Z mir-coverage` map of this codegen unit.
+    mir_coverage_map: RefCell<String>,
 }
 
 // Implement DepTrackingMapConfig for `trait_cache`
@@ -438,6 +457,7 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
                export_map: ExportMap,
                link_meta: LinkMeta,
                exported_symbols: NodeSet,
+               unreferenced_fns: NodeSet,
                check_overflow: bool)
                -> SharedCrateContext<'b, 'tcx> {
         let (metadata_llcx, metadata_llmod) = unsafe {
@@ -494,6 +514,7 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
             metadata_llcx: metadata_llcx,
             export_map: export_map,
             exported_symbols: exported_symbols,
+            unreferenced_fns: unreferenced_fns,
             link_meta: link_meta,
             tcx: tcx,
             stats: Stats {
@@ -512,6 +533,8 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
             translation_items: RefCell::new(FxHashSet()),
             trait_cache: RefCell::new(DepTrackingMap::new(tcx.dep_graph.clone())),
             project_cache: RefCell::new(DepTrackingMap::new(tcx.dep_graph.clone())),
+            large_stack_frames: RefCell::new(DefIdSet()),
+            arithmetic_overflows: RefCell::new(FxHashSet()),
         }
     }
 
@@ -531,6 +554,12 @@ impl<'b, 'tcx> SharedCrateContext<'b, 'tcx> {
         &self.exported_symbols
     }
 
+    /// Private functions which no translated MIR refers to, see
+    /// `rustc_mir::internalize`. They need not be translation roots.
+    pub fn unreferenced_fns<'a>(&'a self) -> &'a NodeSet {
+        &self.unreferenced_fns
+    }
+
     pub fn trait_cache(&self) -> &RefCell<DepTrackingMap<TraitSelectionCache<'tcx>>> {
         &self.trait_cache
     }
@@ -621,6 +650,7 @@ impl<'tcx> LocalCrateContext<'tcx> {
                 const_cstr_cache: RefCell::new(FxHashMap()),
                 const_unsized: RefCell::new(FxHashMap()),
                 const_globals: RefCell::new(FxHashMap()),
+                panic_locations: RefCell::new(FxHashMap()),
                 const_values: RefCell::new(FxHashMap()),
                 extern_const_values: RefCell::new(DefIdMap()),
                 statics: RefCell::new(FxHashMap()),
@@ -644,6 +674,8 @@ impl<'tcx> LocalCrateContext<'tcx> {
                 type_of_depth: Cell::new(0),
                 symbol_map: symbol_map,
                 local_gen_sym_counter: Cell::new(0),
+                mir_profile_tables: RefCell::new(Vec::new()),
+                mir_coverage_map: RefCell::new(String::new()),
             };
 
             let (int_type, opaque_vec_type, str_slice_ty, mut local_ccx) = {
@@ -819,6 +851,12 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         &self.local().const_globals
     }
 
+    pub fn panic_locations<'a>(&'a self)
+                               -> &'a RefCell<FxHashMap<(Option<&'static str>, InternedString, u32),
+                                                        ValueRef>> {
+        &self.local().panic_locations
+    }
+
     pub fn const_values<'a>(&'a self) -> &'a RefCell<FxHashMap<(ast::NodeId, &'tcx Substs<'tcx>),
                                                                ValueRef>> {
         &self.local().const_values
@@ -845,6 +883,14 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         &self.local().statics_to_rauw
     }
 
+    pub fn mir_profile_tables<'a>(&'a self) -> &'a RefCell<Vec<(String, ValueRef, u64)>> {
+        &self.local().mir_profile_tables
+    }
+
+    pub fn mir_coverage_map<'a>(&'a self) -> &'a RefCell<String> {
+        &self.local().mir_coverage_map
+    }
+
     pub fn lltypes<'a>(&'a self) -> &'a RefCell<FxHashMap<Ty<'tcx>, Type>> {
         &self.local().lltypes
     }
@@ -950,6 +996,14 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         &self.shared.translation_items
     }
 
+    pub fn large_stack_frames(&self) -> &RefCell<DefIdSet> {
+        &self.shared.large_stack_frames
+    }
+
+    pub fn arithmetic_overflows(&self) -> &RefCell<FxHashSet<Span>> {
+        &self.shared.arithmetic_overflows
+    }
+
     /// Given the def-id of some item that has no type parameters, make
     /// a suitable "empty substs" for it.
     pub fn empty_substs_for_def_id(&self, item_def_id: DefId) -> &'tcx Substs<'tcx> {
@@ -19,6 +19,7 @@ use llvm::{ValueRef, get_param};
 use middle::lang_items::ExchangeFreeFnLangItem;
 use rustc::ty::subst::{Substs};
 use rustc::traits;
+use rustc::mir::Shim;
 use rustc::ty::{self, AdtKind, Ty, TyCtxt, TypeFoldable};
 use adt;
 use base::*;
@@ -27,6 +28,7 @@ use callee::{Callee};
 use common::*;
 use debuginfo::DebugLoc;
 use machine::*;
+use mir;
 use monomorphize;
 use trans_item::TransItem;
 use tvec;
@@ -36,6 +38,7 @@ use value::Value;
 use Disr;
 
 use arena::TypedArena;
+use rustc_mir::shim;
 use syntax_pos::DUMMY_SP;
 
 pub fn trans_exchange_free_dyn<'blk, 'tcx>(bcx: Block<'blk, 'tcx>,
@@ -232,13 +235,25 @@ pub fn implement_drop_glue<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
     assert_eq!(g.ty(), get_drop_glue_type(tcx, g.ty()));
     let (llfn, fn_ty) = ccx.drop_glues().borrow().get(&g).unwrap().clone();
 
-    let (arena, fcx): (TypedArena<_>, FunctionContext);
+    let (arena, mut fcx): (TypedArena<_>, FunctionContext);
     arena = TypedArena::new();
     fcx = FunctionContext::new(ccx, llfn, fn_ty, None, &arena);
 
+    ccx.stats().n_glues_created.set(ccx.stats().n_glues_created.get() + 1);
+
+    // Prefer the glue built as MIR, which trans handles like any other
+    // function. Shallow drops and the types it doesn't cover are still
+    // built by hand below.
+    if let DropGlueKind::Ty(t) = g {
+        if let Some(mir) = shim::shim_mir(tcx, Shim::DropGlue(t)) {
+            fcx.mir = Some(mir.borrow());
+            mir::trans_mir(&fcx);
+            return;
+        }
+    }
+
     let bcx = fcx.init(false);
 
-    ccx.stats().n_glues_created.set(ccx.stats().n_glues_created.get() + 1);
     // All glue functions take values passed *by alias*; this is a
     // requirement since in many contexts glue is invoked indirectly and
     // the caller has no idea if it's dealing with something that can be
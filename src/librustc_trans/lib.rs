@@ -50,6 +50,7 @@ extern crate rustc_back;
 extern crate rustc_data_structures;
 extern crate rustc_incremental;
 pub extern crate rustc_llvm as llvm;
+extern crate rustc_mir;
 extern crate rustc_platform_intrinsics as intrinsics;
 extern crate serialize;
 extern crate rustc_const_math;
@@ -127,6 +128,7 @@ mod meth;
 mod mir;
 mod monomorphize;
 mod partitioning;
+mod profile;
 mod symbol_map;
 mod symbol_names_test;
 mod trans_item;
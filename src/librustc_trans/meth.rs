@@ -10,23 +10,25 @@
 
 use attributes;
 use arena::TypedArena;
-use llvm::{ValueRef, get_params};
+use llvm::ValueRef;
 use rustc::traits;
+use rustc::mir::Shim;
 use abi::FnType;
 use base::*;
 use build::*;
 use callee::Callee;
 use common::*;
 use consts;
-use debuginfo::DebugLoc;
 use declare;
 use glue;
 use machine;
+use mir;
 use monomorphize::Instance;
 use type_::Type;
 use type_of::*;
 use value::Value;
 use rustc::ty;
+use rustc_mir::shim;
 
 // drop_glue pointer, size, align.
 const VTABLE_OFFSET: usize = 3;
@@ -72,10 +74,10 @@ pub fn trans_object_shim<'a, 'tcx>(ccx: &'a CrateContext<'a, 'tcx>,
 
     debug!("trans_object_shim({:?})", callee);
 
-    let (sig, abi, function_name) = match callee.ty.sty {
+    let (def_id, substs, sig, abi, function_name) = match callee.ty.sty {
         ty::TyFnDef(def_id, substs, f) => {
             let instance = Instance::new(def_id, substs);
-            (&f.sig, f.abi, instance.symbol_name(ccx.shared()))
+            (def_id, substs, &f.sig, f.abi, instance.symbol_name(ccx.shared()))
         }
         _ => bug!()
     };
@@ -86,17 +88,12 @@ pub fn trans_object_shim<'a, 'tcx>(ccx: &'a CrateContext<'a, 'tcx>,
     let llfn = declare::define_internal_fn(ccx, &function_name, callee.ty);
     attributes::set_frame_pointer_elimination(ccx, llfn);
 
-    let (block_arena, fcx): (TypedArena<_>, FunctionContext);
+    let (block_arena, mut fcx): (TypedArena<_>, FunctionContext);
     block_arena = TypedArena::new();
     fcx = FunctionContext::new(ccx, llfn, fn_ty, None, &block_arena);
-    let mut bcx = fcx.init(false);
-
-    let dest = fcx.llretslotptr.get();
-    let llargs = get_params(fcx.llfn);
-    bcx = callee.call(bcx, DebugLoc::None,
-                      &llargs[fcx.fn_ty.ret.is_indirect() as usize..], dest).bcx;
-
-    fcx.finish(bcx, DebugLoc::None);
+    let mir = shim::shim_mir(tcx, Shim::Object(def_id, substs)).unwrap();
+    fcx.mir = Some(mir.borrow());
+    mir::trans_mir(&fcx);
 
     llfn
 }
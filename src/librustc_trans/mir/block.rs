@@ -8,7 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use llvm::{self, ValueRef};
+use llvm::{self, BasicBlockRef, OperandBundleDef, ValueRef};
 use rustc_const_eval::{ErrKind, ConstEvalErr, note_const_eval_err};
 use rustc::middle::lang_items;
 use rustc::ty;
@@ -19,7 +19,7 @@ use base;
 use build;
 use callee::{Callee, CalleeData, Fn, Intrinsic, NamedTupleConstructor, Virtual};
 use common::{self, Block, BlockAndBuilder, LandingPad};
-use common::{C_bool, C_str_slice, C_struct, C_u32, C_undef};
+use common::{C_bool, C_undef};
 use consts;
 use debuginfo::DebugLoc;
 use Disr;
@@ -29,10 +29,10 @@ use type_of;
 use glue;
 use type_::Type;
 
-use rustc_data_structures::fx::FxHashMap;
 use syntax::symbol::Symbol;
+use syntax_pos::Span;
 
-use super::{MirContext, LocalRef};
+use super::{MirContext, LocalRef, PanicKey};
 use super::analyze::CleanupKind;
 use super::constant::Const;
 use super::lvalue::{LvalueRef};
@@ -107,6 +107,8 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
         let terminator = data.terminator();
         debug!("trans_block: terminator: {:?}", terminator);
 
+        let branch_weights = self.mir.branch_weights.get(&bb).cloned();
+
         let span = terminator.source_info.span;
         let debug_loc = self.debug_loc(terminator.source_info);
         debug_loc.apply_to_bcx(&bcx);
@@ -128,63 +130,55 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 }
             }
 
-            mir::TerminatorKind::Goto { target } => {
-                funclet_br(self, bcx, target);
+            mir::TerminatorKind::Abort => {
+                let llfn = bcx.ccx().get_intrinsic(&("llvm.trap"));
+                bcx.call(llfn, &[], cleanup_bundle);
+                bcx.unreachable();
             }
 
-            mir::TerminatorKind::If { ref cond, targets: (true_bb, false_bb) } => {
-                let cond = self.trans_operand(&bcx, cond);
-
-                let lltrue = llblock(self, true_bb);
-                let llfalse = llblock(self, false_bb);
-                bcx.cond_br(cond.immediate(), lltrue, llfalse);
+            mir::TerminatorKind::Goto { target } => {
+                funclet_br(self, bcx, target);
             }
 
-            mir::TerminatorKind::Switch { ref discr, ref adt_def, ref targets } => {
-                let discr_lvalue = self.trans_lvalue(&bcx, discr);
-                let ty = discr_lvalue.ty.to_ty(bcx.tcx());
-                let discr = bcx.with_block(|bcx|
-                    adt::trans_get_discr(bcx, ty, discr_lvalue.llval, None, true)
-                );
-
-                let mut bb_hist = FxHashMap();
-                for target in targets {
-                    *bb_hist.entry(target).or_insert(0) += 1;
-                }
-                let (default_bb, default_blk) = match bb_hist.iter().max_by_key(|&(_, c)| c) {
-                    // If a single target basic blocks is predominant, promote that to be the
-                    // default case for the switch instruction to reduce the size of the generated
-                    // code. This is especially helpful in cases like an if-let on a huge enum.
-                    // Note: This optimization is only valid for exhaustive matches.
-                    Some((&&bb, &c)) if c > targets.len() / 2 => {
-                        (Some(bb), llblock(self, bb))
+            mir::TerminatorKind::SwitchInt { ref discr, switch_ty, ref values, ref targets } => {
+                let discr = self.trans_operand(&bcx, discr);
+                if switch_ty == bcx.tcx().types.bool {
+                    // A boolean switch only tests for `false`; see `TerminatorKind::if_`.
+                    let lltrue = llblock(self, targets[1]);
+                    let llfalse = llblock(self, targets[0]);
+                    let br = bcx.cond_br(discr.immediate(), lltrue, llfalse);
+                    if let Some(ref weights) = branch_weights {
+                        bcx.set_branch_weights(br, &[weights[1], weights[0]]);
                     }
-                    // We're generating an exhaustive switch, so the else branch
-                    // can't be hit.  Branching to an unreachable instruction
-                    // lets LLVM know this
-                    _ => (None, self.unreachable_block().llbb)
-                };
-                let switch = bcx.switch(discr, default_blk, targets.len());
-                assert_eq!(adt_def.variants.len(), targets.len());
-                for (adt_variant, &target) in adt_def.variants.iter().zip(targets) {
-                    if default_bb != Some(target) {
-                        let llbb = llblock(self, target);
-                        let llval = bcx.with_block(|bcx| adt::trans_case(
-                                bcx, ty, Disr::from(adt_variant.disr_val)));
-                        build::AddCase(switch, llval, llbb)
+                } else {
+                    let (&otherwise, targets) = targets.split_last().unwrap();
+                    let switch = bcx.switch(discr.immediate(), llblock(self, otherwise),
+                                            values.len());
+                    // Values branching to the `otherwise` block need no case of their own, which
+                    // keeps e.g. an `if let` on a huge enum small, unless they fill the gaps of a
+                    // jump table.
+                    let dense = self.mir.dense_switches.contains(&bb);
+                    let has_case = |target: mir::BasicBlock| dense || target != otherwise;
+                    for (value, &target) in values.iter().zip(targets) {
+                        if has_case(target) {
+                            let val = Const::from_constval(bcx.ccx(), value.clone(), switch_ty);
+                            build::AddCase(switch, val.llval, llblock(self, target))
+                        }
+                    }
+                    if let Some(ref weights) = branch_weights {
+                        // The default case comes first, and accounts for
+                        // all the targets that were folded into it.
+                        let (&otherwise_weight, case_weights) = weights.split_last().unwrap();
+                        let default_weight: u32 = targets.iter().zip(case_weights)
+                            .filter(|&(&target, _)| !has_case(target))
+                            .map(|(_, &weight)| weight)
+                            .sum();
+                        let mut llweights = vec![otherwise_weight + default_weight];
+                        llweights.extend(targets.iter().zip(case_weights)
+                            .filter(|&(&target, _)| has_case(target))
+                            .map(|(_, &weight)| weight));
+                        bcx.set_branch_weights(switch, &llweights);
                     }
-                }
-            }
-
-            mir::TerminatorKind::SwitchInt { ref discr, switch_ty, ref values, ref targets } => {
-                let (otherwise, targets) = targets.split_last().unwrap();
-                let discr = bcx.load(self.trans_lvalue(&bcx, discr).llval);
-                let discr = bcx.with_block(|bcx| base::to_immediate(bcx, discr, switch_ty));
-                let switch = bcx.switch(discr, llblock(self, *otherwise), values.len());
-                for (value, target) in values.iter().zip(targets) {
-                    let val = Const::from_constval(bcx.ccx(), value.clone(), switch_ty);
-                    let llbb = llblock(self, *target);
-                    build::AddCase(switch, val.llval, llbb)
                 }
             }
 
@@ -281,23 +275,7 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
 
             mir::TerminatorKind::Assert { ref cond, expected, ref msg, target, cleanup } => {
                 let cond = self.trans_operand(&bcx, cond).immediate();
-                let mut const_cond = common::const_to_opt_uint(cond).map(|c| c == 1);
-
-                // This case can currently arise only from functions marked
-                // with #[rustc_inherit_overflow_checks] and inlined from
-                // another crate (mostly core::num generic/#[inline] fns),
-                // while the current crate doesn't use overflow checks.
-                // NOTE: Unlike binops, negation doesn't have its own
-                // checked operation, just a comparison with the minimum
-                // value, so we have to check for the assert message.
-                if !bcx.ccx().check_overflow() {
-                    use rustc_const_math::ConstMathErr::Overflow;
-                    use rustc_const_math::Op::Neg;
-
-                    if let mir::AssertMessage::Math(Overflow(Neg)) = *msg {
-                        const_cond = Some(expected);
-                    }
-                }
+                let const_cond = common::const_to_opt_uint(cond).map(|c| c == 1);
 
                 // Don't translate the panic block if success if known.
                 if const_cond == Some(expected) {
@@ -309,27 +287,14 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 let expect = bcx.ccx().get_intrinsic(&"llvm.expect.i1");
                 let cond = bcx.call(expect, &[cond, C_bool(bcx.ccx(), expected)], None);
 
-                // Create the failure block and the conditional branch to it.
-                let lltarget = llblock(self, target);
-                let panic_block = self.fcx.new_block("panic");
-                if expected {
-                    bcx.cond_br(cond, lltarget, panic_block.llbb);
-                } else {
-                    bcx.cond_br(cond, panic_block.llbb, lltarget);
-                }
-
-                // After this point, bcx is the block for the call to panic.
-                bcx = panic_block.build();
-                debug_loc.apply_to_bcx(&bcx);
-
                 // Get the location information.
                 let loc = bcx.sess().codemap().lookup_char_pos(span.lo);
                 let filename = Symbol::intern(&loc.file.name).as_str();
-                let filename = C_str_slice(bcx.ccx(), filename);
-                let line = C_u32(bcx.ccx(), loc.line as u32);
+                let line = loc.line as u32;
 
-                // Put together the arguments to the panic entry point.
-                let (lang_item, args, const_err) = match *msg {
+                // Put together the arguments to the panic entry point. They are computed
+                // before branching, as the panic block may be shared with other asserts.
+                let (lang_item, msg_str, args, const_err) = match **msg {
                     mir::AssertMessage::BoundsCheck { ref len, ref index } => {
                         let len = self.trans_operand(&mut bcx, len).immediate();
                         let index = self.trans_operand(&mut bcx, index).immediate();
@@ -343,36 +308,45 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                             })
                         });
 
-                        let file_line = C_struct(bcx.ccx(), &[filename, line], false);
-                        let align = llalign_of_min(bcx.ccx(), common::val_ty(file_line));
-                        let file_line = consts::addr_of(bcx.ccx(),
-                                                        file_line,
-                                                        align,
-                                                        "panic_bounds_check_loc");
+                        let file_line = consts::panic_location(bcx.ccx(), None, filename, line);
                         (lang_items::PanicBoundsCheckFnLangItem,
+                         None,
                          vec![file_line, index, len],
                          const_err)
                     }
                     mir::AssertMessage::Math(ref err) => {
-                        let msg_str = Symbol::intern(err.description()).as_str();
-                        let msg_str = C_str_slice(bcx.ccx(), msg_str);
-                        let msg_file_line = C_struct(bcx.ccx(),
-                                                     &[msg_str, filename, line],
-                                                     false);
-                        let align = llalign_of_min(bcx.ccx(), common::val_ty(msg_file_line));
-                        let msg_file_line = consts::addr_of(bcx.ccx(),
-                                                            msg_file_line,
-                                                            align,
-                                                            "panic_loc");
+                        let msg_str = err.description();
+                        let msg_file_line = consts::panic_location(bcx.ccx(),
+                                                                   Some(msg_str),
+                                                                   filename,
+                                                                   line);
                         (lang_items::PanicFnLangItem,
+                         Some(msg_str),
                          vec![msg_file_line],
                          Some(ErrKind::Math(err.clone())))
                     }
+                    mir::AssertMessage::NullPointerDeref |
+                    mir::AssertMessage::MisalignedPointerDeref => {
+                        let msg_str = match **msg {
+                            mir::AssertMessage::NullPointerDeref => "null pointer dereference",
+                            _ => "misaligned pointer dereference"
+                        };
+                        let msg_file_line = consts::panic_location(bcx.ccx(),
+                                                                   Some(msg_str),
+                                                                   filename,
+                                                                   line);
+                        (lang_items::PanicFnLangItem,
+                         Some(msg_str),
+                         vec![msg_file_line],
+                         None)
+                    }
                 };
 
                 // If we know we always panic, and the error message
-                // is also constant, then we can produce a warning.
-                if const_cond == Some(!expected) {
+                // is also constant, then we can produce a warning, unless
+                // a MIR lint already reported it.
+                if const_cond == Some(!expected) &&
+                   !bcx.tcx().reported_panics.borrow().contains(&span) {
                     if let Some(err) = const_err {
                         let err = ConstEvalErr{ span: span, kind: err };
                         let mut diag = bcx.tcx().sess.struct_span_warn(
@@ -382,22 +356,32 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                     }
                 }
 
-                // Obtain the panic entry point.
-                let def_id = common::langcall(bcx.tcx(), Some(span), "", lang_item);
-                let callee = Callee::def(bcx.ccx(), def_id,
-                    bcx.ccx().empty_substs_for_def_id(def_id));
-                let llfn = callee.reify(bcx.ccx());
-
-                // Translate the actual panic invoke/call.
-                if let Some(unwind) = cleanup {
-                    bcx.invoke(llfn,
-                               &args,
-                               self.unreachable_block().llbb,
-                               llblock(self, unwind),
-                               cleanup_bundle);
+                // Find the failure block, shared with the other asserts of the function
+                // panicking in the same way unless in an MSVC cleanup funclet, and create the
+                // conditional branch to it.
+                let lltarget = llblock(self, target);
+                let llunwind = cleanup.map(|unwind| llblock(self, unwind));
+                let panic_llbb = if cleanup_pad.is_none() {
+                    let key = (lang_item, msg_str, cleanup);
+                    self.shared_panic_block(key, &args, llunwind, span, debug_loc, bcx.llbb())
                 } else {
-                    bcx.call(llfn, &args, cleanup_bundle);
-                    bcx.unreachable();
+                    let panic_bcx = self.fcx.new_block("panic").build();
+                    debug_loc.apply_to_bcx(&panic_bcx);
+                    self.call_panic(&panic_bcx, lang_item, &args, llunwind, cleanup_bundle, span);
+                    panic_bcx.llbb()
+                };
+                let br = if expected {
+                    bcx.cond_br(cond, lltarget, panic_llbb)
+                } else {
+                    bcx.cond_br(cond, panic_llbb, lltarget)
+                };
+                if let Some(ref weights) = branch_weights {
+                    let (success, failure) = (weights[0], weights[1]);
+                    if expected {
+                        bcx.set_branch_weights(br, &[success, failure]);
+                    } else {
+                        bcx.set_branch_weights(br, &[failure, success]);
+                    }
                 }
             }
 
@@ -827,6 +811,53 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
         };
     }
 
+    /// The panic block for the failed asserts described by `key`, now also
+    /// reached from `llbb`, which passes it `args` through its phis.
+    fn shared_panic_block(&mut self,
+                          key: PanicKey,
+                          args: &[ValueRef],
+                          llunwind: Option<BasicBlockRef>,
+                          span: Span,
+                          debug_loc: DebugLoc,
+                          llbb: BasicBlockRef)
+                          -> BasicBlockRef {
+        if !self.panic_blocks.contains_key(&key) {
+            let bcx = self.fcx.new_block("panic").build();
+            debug_loc.apply_to_bcx(&bcx);
+            let phis: Vec<_> = args.iter().map(|&arg| {
+                bcx.empty_phi(common::val_ty(arg))
+            }).collect();
+            self.call_panic(&bcx, key.0, &phis, llunwind, None, span);
+            self.panic_blocks.insert(key, (bcx.llbb(), phis));
+        }
+
+        let (panic_llbb, ref phis) = self.panic_blocks[&key];
+        for (&phi, &arg) in phis.iter().zip(args) {
+            build::AddIncomingToPhi(phi, arg, llbb);
+        }
+        panic_llbb
+    }
+
+    /// Calls the panic entry point `lang_item` at the end of `bcx`.
+    fn call_panic(&mut self,
+                  bcx: &BlockAndBuilder<'bcx, 'tcx>,
+                  lang_item: lang_items::LangItem,
+                  args: &[ValueRef],
+                  llunwind: Option<BasicBlockRef>,
+                  cleanup_bundle: Option<&OperandBundleDef>,
+                  span: Span) {
+        let def_id = common::langcall(bcx.tcx(), Some(span), "", lang_item);
+        let callee = Callee::def(bcx.ccx(), def_id, bcx.ccx().empty_substs_for_def_id(def_id));
+        let llfn = callee.reify(bcx.ccx());
+
+        if let Some(llunwind) = llunwind {
+            bcx.invoke(llfn, args, self.unreachable_block().llbb, llunwind, cleanup_bundle);
+        } else {
+            bcx.call(llfn, args, cleanup_bundle);
+            bcx.unreachable();
+        }
+    }
+
     fn unreachable_block(&mut self) -> Block<'bcx, 'tcx> {
         self.unreachable_block.unwrap_or_else(|| {
             let bl = self.fcx.new_block("unreachable");
@@ -23,6 +23,7 @@ use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
 use rustc::ty::cast::{CastTy, IntTy};
 use rustc::ty::subst::Substs;
 use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use rustc_mir::interpret::STEP_LIMIT;
 use {abi, adt, base, Disr, machine};
 use callee::Callee;
 use common::{self, BlockAndBuilder, CrateContext, const_get_elt, val_ty, type_is_sized};
@@ -38,6 +39,7 @@ use value::Value;
 use syntax::ast;
 use syntax_pos::{Span, DUMMY_SP};
 
+use std::cell::Ref;
 use std::fmt;
 use std::ptr;
 
@@ -276,6 +278,12 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
         // report as many errors as we possibly can.
         let mut failure = Ok(());
 
+        // `const fn`s can loop, so bound the blocks we execute.
+        let mut steps_remaining = tcx.sess.opts.debugging_opts.const_eval_limit
+            .unwrap_or(STEP_LIMIT);
+        let dominators = self.mir.dominators();
+        let mut back_edge = None;
+
         loop {
             let data = &self.mir[bb];
             for statement in &data.statements {
@@ -291,6 +299,8 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                     }
                     mir::StatementKind::StorageLive(_) |
                     mir::StatementKind::StorageDead(_) |
+                    mir::StatementKind::IncrementCounter(_) |
+                    mir::StatementKind::Poison(_) |
                     mir::StatementKind::Nop => {}
                     mir::StatementKind::SetDiscriminant{ .. } => {
                         span_bug!(span, "SetDiscriminant should not appear in constants?");
@@ -300,7 +310,8 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
 
             let terminator = data.terminator();
             let span = terminator.source_info.span;
-            bb = match terminator.kind {
+            steps_remaining = steps_remaining.saturating_sub(data.statements.len() + 1);
+            let target = match terminator.kind {
                 mir::TerminatorKind::Drop { target, .. } | // No dropping.
                 mir::TerminatorKind::Goto { target } => target,
                 mir::TerminatorKind::Return => {
@@ -314,7 +325,7 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                     let cond = self.const_operand(cond, span)?;
                     let cond_bool = common::const_to_uint(cond.llval) != 0;
                     if cond_bool != expected {
-                        let err = match *msg {
+                        let err = match **msg {
                             mir::AssertMessage::BoundsCheck { ref len, ref index } => {
                                 let len = self.const_operand(len, span)?;
                                 let index = self.const_operand(index, span)?;
@@ -326,6 +337,10 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                             mir::AssertMessage::Math(ref err) => {
                                 ErrKind::Math(err.clone())
                             }
+                            mir::AssertMessage::NullPointerDeref |
+                            mir::AssertMessage::MisalignedPointerDeref => {
+                                span_bug!(span, "dereference of a raw pointer in a constant")
+                            }
                         };
 
                         let err = ConstEvalErr{ span: span, kind: err };
@@ -363,8 +378,39 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                         span_bug!(span, "diverging {:?} in constant", terminator.kind);
                     }
                 }
+                mir::TerminatorKind::SwitchInt {
+                    ref discr, switch_ty, ref values, ref targets
+                } => {
+                    let discr = self.const_operand(discr, span)?;
+                    let switch_ty = self.monomorphize(&switch_ty);
+                    // LLVM uniques constants, so equal values are the same `ValueRef`.
+                    let index = values.iter().position(|value| {
+                        Const::from_constval(self.ccx, value.clone(), switch_ty).llval ==
+                            discr.llval
+                    });
+                    targets[index.unwrap_or(values.len())]
+                }
+
                 _ => span_bug!(span, "{:?} in constant", terminator.kind)
             };
+
+            if dominators.is_dominated_by(bb, target) {
+                // Values computed after an error are bogus, so
+                // don't go around a loop again with them.
+                failure.clone()?;
+                back_edge = Some(span);
+            }
+
+            // The budget is charged for every block, however the blocks of
+            // a loop are numbered, and the error points at the last loop
+            // taken, which is the one that didn't terminate.
+            if steps_remaining == 0 {
+                let span = back_edge.unwrap_or(span);
+                let err = ConstEvalErr { span: span, kind: ErrKind::StepLimitReached };
+                report_const_eval_err(tcx, &err, span, "expression").emit();
+                return Err(err);
+            }
+            bb = target;
         }
     }
 
@@ -498,6 +544,9 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                         let mir = &self.mir.promoted[index];
                         MirConstContext::new(self.ccx, mir, self.substs, IndexVec::new()).trans()
                     }
+                    mir::Literal::Outlined { .. } => {
+                        span_bug!(span, "outlined code in a constant")
+                    }
                     mir::Literal::Value { value } => {
                         Ok(Const::from_constval(self.ccx, value, ty))
                     }
@@ -739,6 +788,13 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                 Const::new(ptr, ref_ty)
             }
 
+            mir::Rvalue::Discriminant(_) => {
+                return Err(ConstEvalErr {
+                    span: span,
+                    kind: ErrKind::UnimplementedConstVal("match on an enum")
+                });
+            }
+
             mir::Rvalue::Len(ref lvalue) => {
                 let tr_lvalue = self.const_lvalue(lvalue, span)?;
                 Const::new(tr_lvalue.len(self.ccx), tcx.types.usize)
@@ -971,6 +1027,11 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 MirConstContext::new(bcx.ccx(), mir, bcx.fcx().param_substs,
                                      IndexVec::new()).trans()
             }
+            mir::Literal::Outlined { index } => {
+                let mir = Ref::map(Ref::clone(&self.mir), |mir| &mir.outlined[index]);
+                let llfn = super::trans_outlined(bcx.ccx(), mir, ty, bcx.fcx().param_substs);
+                Ok(Const::new(llfn, ty))
+            }
             mir::Literal::Value { value } => {
                 Ok(Const::from_constval(bcx.ccx(), value, ty))
             }
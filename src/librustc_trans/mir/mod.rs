@@ -9,15 +9,24 @@
 // except according to those terms.
 
 use libc::c_uint;
-use llvm::{self, ValueRef};
-use rustc::ty;
+use llvm::{self, BasicBlockRef, ValueRef};
+use rustc::middle::lang_items::LangItem;
+use arena::TypedArena;
+use rustc::ty::{self, Ty};
+use rustc::ty::subst::Substs;
 use rustc::mir;
+use rustc::util::nodemap::FxHashMap;
 use rustc::mir::tcx::LvalueTy;
+use rustc::ty::layout::Layout;
 use session::config::FullDebugInfo;
+use abi::{Abi, FnType};
+use attributes;
 use base;
 use common::{self, Block, BlockAndBuilder, CrateContext, FunctionContext, C_null};
 use debuginfo::{self, declare_local, DebugLoc, VariableAccess, VariableKind, FunctionDebugContext};
+use declare;
 use machine;
+use profile;
 use type_of;
 
 use syntax_pos::{DUMMY_SP, NO_EXPANSION, COMMAND_LINE_EXPN, BytePos};
@@ -38,6 +47,10 @@ use rustc::mir::traversal;
 
 use self::operand::{OperandRef, OperandValue};
 
+/// Failed asserts calling the same panic entry point, with the same
+/// message and unwind target, can share their panic block.
+type PanicKey = (LangItem, Option<&'static str>, Option<mir::BasicBlock>);
+
 /// Master context for translating MIR.
 pub struct MirContext<'bcx, 'tcx:'bcx> {
     mir: Ref<'tcx, mir::Mir<'tcx>>,
@@ -67,6 +80,10 @@ pub struct MirContext<'bcx, 'tcx:'bcx> {
     /// Cached unreachable block
     unreachable_block: Option<Block<'bcx, 'tcx>>,
 
+    /// The panic blocks shared by failed asserts, along with the phis
+    /// receiving the arguments of the panic entry point from each of them.
+    panic_blocks: FxHashMap<PanicKey, (BasicBlockRef, Vec<ValueRef>)>,
+
     /// The location where each MIR arg/var/tmp/ret is stored. This is
     /// usually an `LvalueRef` representing an alloca, but not always:
     /// sometimes we can skip the alloca and just store the value
@@ -86,6 +103,9 @@ pub struct MirContext<'bcx, 'tcx:'bcx> {
 
     /// Debug information for MIR scopes.
     scopes: IndexVec<mir::VisibilityScope, debuginfo::MirDebugScope>,
+
+    /// The `-Z profile-mir` counters of this function, if it was instrumented.
+    profile_counters: Option<ValueRef>,
 }
 
 impl<'blk, 'tcx> MirContext<'blk, 'tcx> {
@@ -181,7 +201,9 @@ impl<'tcx> LocalRef<'tcx> {
 
 ///////////////////////////////////////////////////////////////////////////
 
-pub fn trans_mir<'blk, 'tcx: 'blk>(fcx: &'blk FunctionContext<'blk, 'tcx>) {
+/// Translates the MIR of `fcx`, returning the estimated size of its stack
+/// frame (see `estimate_frame_size`).
+pub fn trans_mir<'blk, 'tcx: 'blk>(fcx: &'blk FunctionContext<'blk, 'tcx>) -> u64 {
     let bcx = fcx.init(true).build();
     let mir = bcx.mir();
 
@@ -211,53 +233,54 @@ pub fn trans_mir<'blk, 'tcx: 'blk>(fcx: &'blk FunctionContext<'blk, 'tcx>) {
         llpersonalityslot: None,
         blocks: block_bcxs,
         unreachable_block: None,
+        panic_blocks: FxHashMap(),
         cleanup_kinds: cleanup_kinds,
         landing_pads: IndexVec::from_elem(None, mir.basic_blocks()),
         scopes: scopes,
         locals: IndexVec::new(),
+        profile_counters: profile::define_counters(fcx.ccx, fcx.llfn, &mir),
     };
 
+    // The locals holding user variables get allocas when emitting debuginfo,
+    // so that they can be declared.
+    let mut debug_locals = BitVector::new(mir.local_decls.len());
+    if bcx.sess().opts.debuginfo == FullDebugInfo {
+        for var in &mir.var_debug_info {
+            if let mir::VarDebugInfoContents::Lvalue(ref lvalue) = var.value {
+                if let Some((local, _)) = var_location(&bcx, &mir, lvalue) {
+                    if mircx.scopes[var.source_info.scope].is_valid() {
+                        debug_locals.insert(local.index());
+                    }
+                }
+            }
+        }
+    }
+
     // Allocate variable and temp allocas
     mircx.locals = {
         let args = arg_local_refs(&bcx, &mir, &mircx.scopes, &lvalue_locals);
 
-        let mut allocate_local = |local| {
+        let allocate_local = |local| {
             let decl = &mir.local_decls[local];
             let ty = bcx.monomorphize(&decl.ty);
+            let dbg = debug_locals.contains(local.index());
 
             if let Some(name) = decl.name {
                 // User variable
-                let source_info = decl.source_info.unwrap();
-                let debug_scope = mircx.scopes[source_info.scope];
-                let dbg = debug_scope.is_valid() && bcx.sess().opts.debuginfo == FullDebugInfo;
-
                 if !lvalue_locals.contains(local.index()) && !dbg {
                     debug!("alloc: {:?} ({}) -> operand", local, name);
                     return LocalRef::new_operand(bcx.ccx(), ty);
                 }
 
                 debug!("alloc: {:?} ({}) -> lvalue", local, name);
-                let lvalue = LvalueRef::alloca(&bcx, ty, &name.as_str());
-                if dbg {
-                    let dbg_loc = mircx.debug_loc(source_info);
-                    if let DebugLoc::ScopeAt(scope, span) = dbg_loc {
-                        bcx.with_block(|bcx| {
-                            declare_local(bcx, name, ty, scope,
-                                        VariableAccess::DirectVariable { alloca: lvalue.llval },
-                                        VariableKind::LocalVariable, span);
-                        });
-                    } else {
-                        panic!("Unexpected");
-                    }
-                }
-                LocalRef::Lvalue(lvalue)
+                LocalRef::Lvalue(LvalueRef::alloca(&bcx, ty, &name.as_str()))
             } else {
                 // Temporary or return pointer
                 if local == mir::RETURN_POINTER && fcx.fn_ty.ret.is_indirect() {
                     debug!("alloc: {:?} (return pointer) -> lvalue", local);
                     let llretptr = llvm::get_param(fcx.llfn, 0);
                     LocalRef::Lvalue(LvalueRef::new_sized(llretptr, LvalueTy::from_ty(ty)))
-                } else if lvalue_locals.contains(local.index()) {
+                } else if lvalue_locals.contains(local.index()) || dbg {
                     debug!("alloc: {:?} -> lvalue", local);
                     LocalRef::Lvalue(LvalueRef::alloca(&bcx, ty, &format!("{:?}", local)))
                 } else {
@@ -277,6 +300,12 @@ pub fn trans_mir<'blk, 'tcx: 'blk>(fcx: &'blk FunctionContext<'blk, 'tcx>) {
             .collect()
     };
 
+    if bcx.sess().opts.debuginfo == FullDebugInfo {
+        declare_vars(&mut mircx, &bcx);
+    }
+
+    let frame_size = estimate_frame_size(fcx.ccx, &mir, &mircx.locals);
+
     // Branch to the START block
     let start_bcx = mircx.blocks[mir::START_BLOCK];
     bcx.br(start_bcx.llbb);
@@ -316,6 +345,140 @@ pub fn trans_mir<'blk, 'tcx: 'blk>(fcx: &'blk FunctionContext<'blk, 'tcx>) {
 
     DebugLoc::None.apply(fcx);
     fcx.cleanup();
+    frame_size
+}
+
+/// Defines the function for a cold part of a MIR body which `OutlineCold` moved out of
+/// it, and translates it with the type parameters of the function it came from. `fn_ptr_ty` is
+/// the monomorphized type of the constant referring to it.
+pub fn trans_outlined<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
+                                mir: Ref<'tcx, mir::Mir<'tcx>>,
+                                fn_ptr_ty: Ty<'tcx>,
+                                param_substs: &'tcx Substs<'tcx>)
+                                -> ValueRef {
+    let sig = ccx.tcx().erase_late_bound_regions_and_normalize(fn_ptr_ty.fn_sig());
+    let fn_ty = FnType::new(ccx, Abi::Rust, &sig, &[]);
+
+    let llfn = declare::define_internal_fn(ccx, &ccx.generate_local_symbol_name("cold"),
+                                           fn_ptr_ty);
+    attributes::inline(llfn, attributes::InlineAttr::Never);
+    attributes::cold(llfn);
+    attributes::set_frame_pointer_elimination(ccx, llfn);
+
+    let (block_arena, mut fcx): (TypedArena<_>, FunctionContext);
+    block_arena = TypedArena::new();
+    fcx = FunctionContext::new(ccx, llfn, fn_ty, None, &block_arena);
+    fcx.param_substs = param_substs;
+    fcx.mir = Some(mir);
+    trans_mir(&fcx);
+
+    llfn
+}
+
+/// Declares the user variables to debuginfo, at the lvalues the MIR says
+/// they are stored in. Variables stored in a projection of a local are
+/// described by a DWARF expression computing their address from the
+/// alloca of the local. Variables known to be constant get an alloca of
+/// their own holding the constant.
+fn declare_vars<'bcx, 'tcx>(mircx: &mut MirContext<'bcx, 'tcx>,
+                            bcx: &BlockAndBuilder<'bcx, 'tcx>) {
+    let mir = Ref::clone(&mircx.mir);
+    for var in &mir.var_debug_info {
+        if !mircx.scopes[var.source_info.scope].is_valid() {
+            continue;
+        }
+        let (alloca, ops, ty) = match var.value {
+            mir::VarDebugInfoContents::Lvalue(ref lvalue) => {
+                let (local, ops) = match var_location(bcx, &mir, lvalue) {
+                    Some(location) => location,
+                    None => continue
+                };
+                let ty = bcx.monomorphize(&lvalue.ty(&mir, bcx.tcx()).to_ty(bcx.tcx()));
+                match mircx.locals[local] {
+                    LocalRef::Lvalue(lvalue) => (lvalue.llval, ops, ty),
+                    LocalRef::Operand(_) => continue
+                }
+            }
+            mir::VarDebugInfoContents::Const(ref constant) => {
+                let ty = bcx.monomorphize(&constant.ty);
+                let operand = mircx.trans_constant(bcx, constant).to_operand(bcx.ccx());
+                let lltemp = bcx.with_block(|bcx| base::alloc_ty(bcx, ty, &var.name.as_str()));
+                mircx.store_operand(bcx, lltemp, operand);
+                (lltemp, vec![], ty)
+            }
+            mir::VarDebugInfoContents::OptimizedOut => continue
+        };
+        if let DebugLoc::ScopeAt(scope, span) = mircx.debug_loc(var.source_info) {
+            bcx.with_block(|bcx| {
+                let variable_access = if ops.is_empty() {
+                    VariableAccess::DirectVariable { alloca: alloca }
+                } else {
+                    VariableAccess::IndirectVariable { alloca: alloca, address_operations: &ops }
+                };
+                declare_local(bcx, var.name, ty, scope, variable_access,
+                              VariableKind::LocalVariable, span);
+            });
+        } else {
+            panic!("Unexpected");
+        }
+    }
+}
+
+/// The local `lvalue` is based on, and the DWARF operations computing the
+/// address of `lvalue` from the address of that local. `None` if a
+/// debugger couldn't follow one of its projections.
+fn var_location<'bcx, 'tcx>(bcx: &BlockAndBuilder<'bcx, 'tcx>,
+                            mir: &mir::Mir<'tcx>,
+                            lvalue: &mir::Lvalue<'tcx>)
+                            -> Option<(mir::Local, Vec<i64>)> {
+    match *lvalue {
+        mir::Lvalue::Local(local) => Some((local, vec![])),
+        mir::Lvalue::Static(_) => None,
+        mir::Lvalue::Projection(ref proj) => {
+            let (local, mut ops) = match var_location(bcx, mir, &proj.base) {
+                Some(location) => location,
+                None => return None
+            };
+            let base_ty = bcx.monomorphize(&proj.base.ty(mir, bcx.tcx()).to_ty(bcx.tcx()));
+            match proj.elem {
+                mir::ProjectionElem::Deref => {
+                    if common::type_is_fat_ptr(bcx.tcx(), base_ty) {
+                        return None;
+                    }
+                    ops.push(unsafe { llvm::LLVMRustDIBuilderCreateOpDeref() });
+                }
+                mir::ProjectionElem::Field(field, _) => {
+                    let offset = match *bcx.ccx().layout_of(base_ty) {
+                        Layout::Univariant { ref variant, .. } => {
+                            variant.offsets[field.index()].bytes()
+                        }
+                        _ => return None
+                    };
+                    if offset != 0 {
+                        ops.push(unsafe { llvm::LLVMRustDIBuilderCreateOpPlus() });
+                        ops.push(offset as i64);
+                    }
+                }
+                _ => return None
+            }
+            Some((local, ops))
+        }
+    }
+}
+
+/// Sums up the sizes of the variables and temporaries which are kept in
+/// memory, as an upper bound on the stack usage of the function before
+/// LLVM gets to overlap their allocas.
+fn estimate_frame_size<'tcx>(ccx: &CrateContext,
+                             mir: &mir::Mir<'tcx>,
+                             locals: &IndexVec<mir::Local, LocalRef<'tcx>>)
+                             -> u64 {
+    mir.vars_and_temps_iter().map(|local| match locals[local] {
+        LocalRef::Lvalue(lvalue) => {
+            machine::llsize_of_alloc(ccx, common::val_ty(lvalue.llval).element_type())
+        }
+        LocalRef::Operand(_) => 0,
+    }).sum()
 }
 
 /// Produce, for each argument, a `ValueRef` pointing at the
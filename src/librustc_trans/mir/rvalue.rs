@@ -388,6 +388,24 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
                 (bcx, operand)
             }
 
+            mir::Rvalue::Discriminant(ref lvalue) => {
+                let tr_lvalue = self.trans_lvalue(&bcx, lvalue);
+                let enum_ty = tr_lvalue.ty.to_ty(bcx.tcx());
+                let discr_ty = match enum_ty.sty {
+                    ty::TyAdt(adt_def, _) => adt_def.discr_ty(bcx.tcx()),
+                    _ => bug!("discriminant of non-enum {:?}", enum_ty)
+                };
+                let discr_type = type_of::immediate_type_of(bcx.ccx(), discr_ty);
+                let discr = bcx.with_block(|bcx| {
+                    adt::trans_get_discr(bcx, enum_ty, tr_lvalue.llval, Some(discr_type), true)
+                });
+                let operand = OperandRef {
+                    val: OperandValue::Immediate(discr),
+                    ty: discr_ty,
+                };
+                (bcx, operand)
+            }
+
             mir::Rvalue::BinaryOp(op, ref lhs, ref rhs) => {
                 let lhs = self.trans_operand(&bcx, lhs);
                 let rhs = self.trans_operand(&bcx, rhs);
@@ -690,6 +708,7 @@ pub fn rvalue_creates_operand<'bcx, 'tcx>(_mir: &mir::Mir<'tcx>,
     match *rvalue {
         mir::Rvalue::Ref(..) |
         mir::Rvalue::Len(..) |
+        mir::Rvalue::Discriminant(..) |
         mir::Rvalue::Cast(..) | // (*)
         mir::Rvalue::BinaryOp(..) |
         mir::Rvalue::CheckedBinaryOp(..) |
@@ -8,16 +8,24 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use rustc::lint;
 use rustc::mir;
+use rustc::ty::fold::TypeFoldable;
+use syntax_pos::Span;
 
 use base;
-use common::{self, BlockAndBuilder};
+use common::{self, BlockAndBuilder, C_i32, C_u64};
 
 use super::MirContext;
+use super::constant::const_scalar_checked_binop;
 use super::LocalRef;
 use super::super::adt;
 use super::super::disr::Disr;
 
+/// The byte `Poison` statements fill memory with. It makes for unlikely
+/// integers, and for pointers which are neither null nor aligned.
+const POISON_BYTE: u8 = 0xa5;
+
 impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
     pub fn trans_statement(&mut self,
                            bcx: BlockAndBuilder<'bcx, 'tcx>,
@@ -30,6 +38,7 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
         debug_loc.apply(bcx.fcx());
         match statement.kind {
             mir::StatementKind::Assign(ref lvalue, ref rvalue) => {
+                self.check_arithmetic_overflow(&bcx, rvalue, statement.source_info.span);
                 if let mir::Lvalue::Local(index) = *lvalue {
                     match self.locals[index] {
                         LocalRef::Lvalue(tr_dest) => {
@@ -77,10 +86,75 @@ impl<'bcx, 'tcx> MirContext<'bcx, 'tcx> {
             mir::StatementKind::StorageDead(ref lvalue) => {
                 self.trans_storage_liveness(bcx, lvalue, base::Lifetime::End)
             }
+            mir::StatementKind::IncrementCounter(index) => {
+                if let Some(counters) = self.profile_counters {
+                    let ccx = bcx.ccx();
+                    let counter = bcx.inbounds_gep(counters, &[C_i32(ccx, 0),
+                                                               C_i32(ccx, index as i32)]);
+                    let count = bcx.load(counter);
+                    bcx.store(bcx.add(count, C_u64(ccx, 1)), counter);
+                }
+                bcx
+            }
+            mir::StatementKind::Poison(ref lvalue) => {
+                if let mir::Lvalue::Local(index) = *lvalue {
+                    if let LocalRef::Lvalue(tr_lval) = self.locals[index] {
+                        let ty = tr_lval.ty.to_ty(bcx.tcx());
+                        base::memfill(&bcx, tr_lval.llval, ty, POISON_BYTE);
+                    }
+                }
+                bcx
+            }
             mir::StatementKind::Nop => bcx,
         }
     }
 
+    /// Reports the `arithmetic_overflow` lint for arithmetic on constants
+    /// which only overflows once the type parameters the constants depend
+    /// on are substituted, e.g. `T::MAX + 1`. The rest of the arithmetic
+    /// is checked by `FoldOverflowChecks` on the generic MIR.
+    fn check_arithmetic_overflow(&mut self,
+                                 bcx: &BlockAndBuilder<'bcx, 'tcx>,
+                                 rvalue: &mir::Rvalue<'tcx>,
+                                 span: Span) {
+        let (op, lhs, rhs) = match *rvalue {
+            mir::Rvalue::BinaryOp(op, ref lhs, ref rhs) |
+            mir::Rvalue::CheckedBinaryOp(op, ref lhs, ref rhs) => (op, lhs, rhs),
+            _ => return
+        };
+        let (lhs, rhs) = match (lhs, rhs) {
+            (&mir::Operand::Constant(ref lhs), &mir::Operand::Constant(ref rhs)) => (lhs, rhs),
+            _ => return
+        };
+        let depends_on_params = |constant: &mir::Constant<'tcx>| match constant.literal {
+            mir::Literal::Item { substs, .. } => substs.needs_subst(),
+            _ => false
+        };
+        if !op.is_checkable() || !(depends_on_params(lhs) || depends_on_params(rhs)) {
+            return;
+        }
+
+        // Only functions of this crate have a lint level.
+        let tcx = bcx.tcx();
+        let id = match self.fcx.def_id.and_then(|def_id| tcx.map.as_local_node_id(def_id)) {
+            Some(id) => id,
+            None => return
+        };
+        let lhs = self.trans_constant(bcx, lhs);
+        let rhs = self.trans_constant(bcx, rhs);
+        if !lhs.ty.is_integral() {
+            return;
+        }
+        let overflows = const_scalar_checked_binop(tcx, op, lhs.llval, rhs.llval, lhs.ty)
+            .map_or(false, |(_, overflows)| overflows);
+        // Every instance overflowing would report the same span.
+        if overflows && bcx.ccx().arithmetic_overflows().borrow_mut().insert(span) {
+            lint::emit_node_lint(tcx, lint::builtin::ARITHMETIC_OVERFLOW, id, span,
+                                 &format!("this arithmetic operation will overflow: {}",
+                                          op.overflow_err().unwrap().description()));
+        }
+    }
+
     fn trans_storage_liveness(&self,
                               bcx: BlockAndBuilder<'bcx, 'tcx>,
                               lvalue: &mir::Lvalue<'tcx>,
@@ -0,0 +1,172 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Translation support for `-Z profile-mir` and `-Z mir-coverage`.
+//!
+//! Every instrumented function gets an internal table of `u64` counters,
+//! bumped by its `IncrementCounter` statements. Each codegen unit then
+//! gets a static constructor handing its tables, along with the symbol
+//! of their function, to the `profile_mir_register` lang item, which
+//! writes the counts out when the program exits.
+//!
+//! For coverage, each codegen unit also embeds a coverage map in its own
+//! section, with one `<symbol>\t<counter>\t<file>:<line>:<col>-<line>:<col>`
+//! line per counter, giving the source region the counter covers.
+
+use llvm::{self, ValueRef};
+use rustc::middle::lang_items::ProfileMirRegisterFnLangItem;
+use rustc::mir;
+use builder::noname;
+use callee::Callee;
+use common::*;
+use consts;
+use declare;
+use type_::Type;
+
+use libc::c_uint;
+use std::ffi::CStr;
+use std::ptr;
+use syntax::symbol::Symbol;
+
+/// Defines the counter table of `llfn`, if its MIR was instrumented.
+pub fn define_counters(ccx: &CrateContext, llfn: ValueRef, mir: &mir::Mir) -> Option<ValueRef> {
+    let num_counters = mir.basic_blocks().iter()
+        .flat_map(|data| &data.statements)
+        .filter_map(|statement| match statement.kind {
+            mir::StatementKind::IncrementCounter(index) => Some(index as u64 + 1),
+            _ => None,
+        })
+        .max();
+    let num_counters = match num_counters {
+        Some(num_counters) => num_counters,
+        None => return None,
+    };
+
+    let llty = Type::array(&Type::i64(ccx), num_counters);
+    let name = ccx.generate_local_symbol_name("mir_profile");
+    let counters = declare::define_global(ccx, &name, llty).unwrap_or_else(|| {
+        bug!("symbol `{}` is already defined", name)
+    });
+    unsafe {
+        llvm::LLVMSetInitializer(counters, C_null(llty));
+        llvm::LLVMRustSetLinkage(counters, llvm::Linkage::InternalLinkage);
+    }
+
+    let fn_name = unsafe {
+        CStr::from_ptr(llvm::LLVMGetValueName(llfn)).to_string_lossy().into_owned()
+    };
+
+    if ccx.sess().opts.debugging_opts.mir_coverage {
+        let codemap = ccx.sess().codemap();
+        let mut coverage_map = ccx.mir_coverage_map().borrow_mut();
+        for statement in mir.basic_blocks().iter().flat_map(|data| &data.statements) {
+            if let mir::StatementKind::IncrementCounter(index) = statement.kind {
+                let span = statement.source_info.span;
+                let lo = codemap.lookup_char_pos(span.lo);
+                let hi = codemap.lookup_char_pos(span.hi);
+                coverage_map.push_str(&format!("{}\t{}\t{}:{}:{}-{}:{}\n",
+                                               fn_name, index, lo.file.name,
+                                               lo.line, lo.col.0 + 1,
+                                               hi.line, hi.col.0 + 1));
+            }
+        }
+    }
+
+    ccx.mir_profile_tables().borrow_mut().push((fn_name, counters, num_counters));
+    Some(counters)
+}
+
+/// Emits the constructor registering the counter tables of this codegen
+/// unit, if there are any.
+pub fn emit_registration(ccx: &CrateContext) {
+    let tables = ccx.mir_profile_tables().borrow();
+    if tables.is_empty() {
+        return;
+    }
+
+    let register_def_id = match ccx.tcx().lang_items.require(ProfileMirRegisterFnLangItem) {
+        Ok(id) => id,
+        Err(s) => ccx.sess().fatal(&s)
+    };
+    let empty_substs = ccx.tcx().intern_substs(&[]);
+    let register = Callee::def(ccx, register_def_id, empty_substs).reify(ccx);
+
+    let llfty = Type::func(&[], &Type::void(ccx));
+    let name = ccx.generate_local_symbol_name("mir_profile_init");
+    let llfn = declare::declare_cfn(ccx, &name, llfty);
+
+    unsafe {
+        llvm::LLVMRustSetLinkage(llfn, llvm::Linkage::InternalLinkage);
+        let llbb = llvm::LLVMAppendBasicBlockInContext(ccx.llcx(), llfn,
+                                                       "top\0".as_ptr() as *const _);
+        let bld = ccx.raw_builder();
+        llvm::LLVMPositionBuilderAtEnd(bld, llbb);
+
+        for &(ref fn_name, counters, num_counters) in tables.iter() {
+            let fn_name = Symbol::intern(fn_name).as_str();
+            let args = [consts::ptrcast(C_cstr(ccx, fn_name.clone(), false), Type::i8p(ccx)),
+                        C_uint(ccx, fn_name.len()),
+                        consts::ptrcast(counters, Type::i64(ccx).ptr_to()),
+                        C_uint(ccx, num_counters)];
+            llvm::LLVMRustBuildCall(bld,
+                                    register,
+                                    args.as_ptr(),
+                                    args.len() as c_uint,
+                                    ptr::null_mut(),
+                                    noname());
+        }
+        llvm::LLVMBuildRetVoid(bld);
+
+        // Have the constructor run before `main`.
+        let ctor_ty = Type::struct_(ccx, &[Type::i32(ccx), llfty.ptr_to(), Type::i8p(ccx)], false);
+        let ctor = C_struct(ccx, &[C_i32(ccx, 65535), llfn, C_null(Type::i8p(ccx))], false);
+        let ctors = declare::define_global(ccx, "llvm.global_ctors", Type::array(&ctor_ty, 1))
+            .unwrap_or_else(|| bug!("symbol `llvm.global_ctors` is already defined"));
+        llvm::LLVMSetInitializer(ctors, C_array(ctor_ty, &[ctor]));
+        llvm::LLVMRustSetLinkage(ctors, llvm::Linkage::AppendingLinkage);
+    }
+}
+
+/// Embeds the coverage map of this codegen unit, if there is one.
+pub fn emit_coverage_map(ccx: &CrateContext) {
+    let coverage_map = ccx.mir_coverage_map().borrow();
+    if coverage_map.is_empty() {
+        return;
+    }
+
+    let section_name = if ccx.sess().target.target.options.is_like_osx {
+        "__DATA,__rust_mir_cov\0"
+    } else if ccx.sess().target.target.options.is_like_windows {
+        ".rmircov\0"
+    } else {
+        "__rust_mir_cov\0"
+    };
+
+    let contents = C_bytes(ccx, coverage_map.as_bytes());
+    let name = ccx.generate_local_symbol_name("mir_coverage_map");
+    unsafe {
+        let map = declare::define_global(ccx, &name, val_ty(contents)).unwrap_or_else(|| {
+            bug!("symbol `{}` is already defined", name)
+        });
+        llvm::LLVMSetInitializer(map, contents);
+        llvm::LLVMSetGlobalConstant(map, llvm::True);
+        llvm::LLVMRustSetLinkage(map, llvm::Linkage::InternalLinkage);
+        llvm::LLVMSetSection(map, section_name.as_ptr() as *const _);
+        llvm::LLVMSetAlignment(map, 1);
+
+        // Nothing refers to the map, so keep LLVM from dropping it.
+        let used = declare::define_global(ccx, "llvm.used", Type::array(&Type::i8p(ccx), 1))
+            .unwrap_or_else(|| bug!("symbol `llvm.used` is already defined"));
+        llvm::LLVMSetInitializer(used, C_array(Type::i8p(ccx),
+                                               &[consts::ptrcast(map, Type::i8p(ccx))]));
+        llvm::LLVMRustSetLinkage(used, llvm::Linkage::AppendingLinkage);
+        llvm::LLVMSetSection(used, "llvm.metadata\0".as_ptr() as *const _);
+    }
+}
@@ -383,6 +383,7 @@ pub extern fn rust_begin_panic(msg: fmt::Arguments,
            reason = "used by the panic! macro",
            issue = "0")]
 #[inline(never)] #[cold]
+#[rustc_panic_entry_point]
 pub fn begin_panic_fmt(msg: &fmt::Arguments,
                        file_line: &(&'static str, u32)) -> ! {
     use fmt::Write;
@@ -402,6 +403,7 @@ pub fn begin_panic_fmt(msg: &fmt::Arguments,
            reason = "used by the panic! macro",
            issue = "0")]
 #[inline(never)] #[cold] // avoid code bloat at the call sites as much as possible
+#[rustc_panic_entry_point]
 pub fn begin_panic<M: Any + Send>(msg: M, file_line: &(&'static str, u32)) -> ! {
     // Note that this should be the only allocation performed in this code path.
     // Currently this means that panic!() on OOM will invoke this code path,
@@ -36,6 +36,9 @@ pub mod memchr;
 pub mod mutex;
 pub mod net;
 pub mod poison;
+// The stage0 compiler doesn't know the lang item it registers.
+#[cfg(all(not(test), not(stage0)))]
+pub mod profile_mir;
 pub mod remutex;
 pub mod rwlock;
 pub mod thread;
@@ -0,0 +1,84 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Runtime support for `-Z profile-mir`.
+//!
+//! Instrumented code registers the basic block counters of its functions
+//! before `main` runs. Once the main thread exits, the counts are written
+//! to the file named by `RUST_MIR_PROFILE` (`mir-profile.txt` by default),
+//! as one `<symbol> <block> <count>` line per basic block.
+
+use env;
+use fs::File;
+use io::{BufWriter, Write};
+use ptr;
+use slice;
+use str;
+use sys_common;
+use sys_common::mutex::Mutex;
+
+struct Table {
+    symbol: &'static str,
+    counters: &'static [u64],
+}
+
+// NB these are not types from `std::sync`, as registration happens from
+// static constructors, before the runtime is set up.
+static LOCK: Mutex = Mutex::new();
+static mut TABLES: *mut Vec<Table> = ptr::null_mut();
+
+#[cfg_attr(not(stage0), lang = "profile_mir_register")]
+unsafe extern "C" fn register(symbol: *const u8,
+                              symbol_len: usize,
+                              counters: *const u64,
+                              num_counters: usize) {
+    let table = Table {
+        symbol: str::from_utf8_unchecked(slice::from_raw_parts(symbol, symbol_len)),
+        counters: slice::from_raw_parts(counters, num_counters),
+    };
+
+    LOCK.lock();
+    let first = TABLES.is_null();
+    if first {
+        TABLES = Box::into_raw(box Vec::new());
+    }
+    (*TABLES).push(table);
+    LOCK.unlock();
+
+    if first {
+        let _ = sys_common::at_exit(dump);
+    }
+}
+
+fn dump() {
+    let tables = unsafe {
+        LOCK.lock();
+        let tables = TABLES;
+        TABLES = ptr::null_mut();
+        LOCK.unlock();
+        if tables.is_null() {
+            return;
+        }
+        Box::from_raw(tables)
+    };
+
+    let path = env::var_os("RUST_MIR_PROFILE").unwrap_or_else(|| "mir-profile.txt".into());
+    let mut out = match File::create(&path) {
+        Ok(file) => BufWriter::new(file),
+        Err(_) => return,
+    };
+    for table in tables.iter() {
+        for (block, count) in table.counters.iter().enumerate() {
+            if writeln!(out, "{} {} {}", table.symbol, block, count).is_err() {
+                return;
+            }
+        }
+    }
+}
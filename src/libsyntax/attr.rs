@@ -506,6 +506,59 @@ pub fn requests_inline(attrs: &[Attribute]) -> bool {
     }
 }
 
+/// The attributes which give a function a meaning as a whole, beyond what
+/// its body does: a `#[naked]` function is exactly its code, without a
+/// prologue, and `#[linkage]` and `#[unwind]` apply to its symbol and its
+/// frame. Copying the body into other functions, or moving parts of it out
+/// into new ones, would lose that meaning, so the optimizations doing it
+/// keep out of the functions with one of these. Attributes of this kind
+/// added later belong here rather than in checks of their own.
+pub const FUNCTION_GRANULAR_ATTRS: &'static [&'static str] = &["naked", "linkage", "unwind"];
+
+/// True if one of the `FUNCTION_GRANULAR_ATTRS` is present in `attrs`.
+pub fn has_function_granular_attr(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        FUNCTION_GRANULAR_ATTRS.iter().any(|&name| attr.value.name == name)
+    })
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum OptimizeAttr {
+    None,
+    Speed,
+    Size,
+}
+
+/// Determine what `#[optimize]` attribute is present in `attrs`, if any.
+pub fn find_optimize_attr(diagnostic: Option<&Handler>, attrs: &[Attribute]) -> OptimizeAttr {
+    attrs.iter().fold(OptimizeAttr::None, |oa, attr| {
+        if attr.value.name != "optimize" {
+            return oa;
+        }
+        mark_used(attr);
+        match attr.value.node {
+            MetaItemKind::List(ref items) if items.len() == 1 => {
+                if list_contains_name(&items[..], "speed") {
+                    OptimizeAttr::Speed
+                } else if list_contains_name(&items[..], "size") {
+                    OptimizeAttr::Size
+                } else {
+                    diagnostic.map(|d| {
+                        span_err!(d, items[0].span, E0572, "invalid argument");
+                    });
+                    OptimizeAttr::None
+                }
+            }
+            _ => {
+                diagnostic.map(|d| {
+                    span_err!(d, attr.span, E0572, "expected one argument");
+                });
+                OptimizeAttr::None
+            }
+        }
+    })
+}
+
 /// Tests if a cfg-pattern matches the cfg set
 pub fn cfg_matches(cfg: &ast::MetaItem, sess: &ParseSess, features: Option<&Features>) -> bool {
     match cfg.node {
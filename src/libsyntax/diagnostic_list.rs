@@ -179,6 +179,33 @@ where appropriate is ongoing. Try using an unquoted name instead:
 pub fn something() {}
 ```
 "##,
+
+E0572: r##"
+The `optimize` attribute was malformed.
+
+Erroneous code example:
+
+```compile_fail,E0572
+#![feature(optimize_attribute)]
+
+#[optimize(fast)] // error: invalid argument
+pub fn something() {}
+
+fn main() {}
+```
+
+The `optimize` attribute takes a single argument, telling whether the function
+should be optimized for `speed` or for `size`:
+
+```
+#![feature(optimize_attribute)]
+
+#[optimize(size)]
+fn something() {}
+
+fn main() {}
+```
+"##,
 }
 
 register_diagnostics! {
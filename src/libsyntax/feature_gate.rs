@@ -314,6 +314,15 @@ declare_features! (
 
     // Allows #[target_feature(...)]
     (active, target_feature, "1.15.0", None),
+
+    // The #![stack_frame_limit] attribute
+    (active, stack_frame_limit, "1.15.0", None),
+
+    // The #![move_size_limit] attribute
+    (active, move_size_limit, "1.15.0", None),
+
+    // Allows #[optimize(size)] and #[optimize(speed)] on functions
+    (active, optimize_attribute, "1.15.0", None),
 );
 
 declare_features! (
@@ -610,6 +619,36 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
                                       is just used for rustc unit tests \
                                       and will never be stable",
                                      cfg_fn!(rustc_attrs))),
+    ("rustc_dump_mir", Whitelisted, Gated(Stability::Unstable,
+                                          "rustc_attrs",
+                                          "the `#[rustc_dump_mir]` attribute \
+                                           is just used for debugging the compiler \
+                                           and will never be stable",
+                                          cfg_fn!(rustc_attrs))),
+    ("rustc_mir_assert", Whitelisted, Gated(Stability::Unstable,
+                                            "rustc_attrs",
+                                            "the `#[rustc_mir_assert]` attribute \
+                                             is just used for rustc unit tests \
+                                             and will never be stable",
+                                            cfg_fn!(rustc_attrs))),
+    ("rustc_pure", Whitelisted, Gated(Stability::Unstable,
+                                      "rustc_attrs",
+                                      "the `#[rustc_pure]` attribute \
+                                       is just used to let the compiler \
+                                       remove unused calls and will never be stable",
+                                      cfg_fn!(rustc_attrs))),
+    ("rustc_mem_fn", Whitelisted, Gated(Stability::Unstable,
+                                        "rustc_attrs",
+                                        "the `#[rustc_mem_fn]` attribute \
+                                         is just used to let the compiler lower \
+                                         calls to memory functions and will never be stable",
+                                        cfg_fn!(rustc_attrs))),
+    ("rustc_panic_entry_point", Whitelisted, Gated(Stability::Unstable,
+                                                   "rustc_attrs",
+                                                   "the `#[rustc_panic_entry_point]` attribute \
+                                                    is just used to recognize the functions \
+                                                    `panic!` calls and will never be stable",
+                                                   cfg_fn!(rustc_attrs))),
     ("rustc_inherit_overflow_checks", Whitelisted, Gated(Stability::Unstable,
                                                          "rustc_attrs",
                                                          "the `#[rustc_inherit_overflow_checks]` \
@@ -663,6 +702,10 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
         Stability::Unstable, "target_feature",
         "the `#[target_feature]` attribute is an experimental feature",
         cfg_fn!(target_feature))),
+    ("optimize", Whitelisted, Gated(Stability::Unstable,
+                                    "optimize_attribute",
+                                    "the `#[optimize]` attribute is an experimental feature",
+                                    cfg_fn!(optimize_attribute))),
     ("export_name", Whitelisted, Ungated),
     ("inline", Whitelisted, Ungated),
     ("link", Whitelisted, Ungated),
@@ -738,6 +781,16 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
     ("no_builtins", CrateLevel, Ungated),
     ("recursion_limit", CrateLevel, Ungated),
     ("type_length_limit", CrateLevel, Ungated),
+    ("stack_frame_limit", CrateLevel, Gated(Stability::Unstable,
+                                            "stack_frame_limit",
+                                            "the `#[stack_frame_limit]` attribute \
+                                             is experimental",
+                                            cfg_fn!(stack_frame_limit))),
+    ("move_size_limit", CrateLevel, Gated(Stability::Unstable,
+                                          "move_size_limit",
+                                          "the `#[move_size_limit]` attribute \
+                                           is experimental",
+                                          cfg_fn!(move_size_limit))),
 ];
 
 // cfg(...)'s that are feature gated
@@ -0,0 +1,27 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes -C debug-assertions -Z force-debug-assertions-mir=off
+
+#![crate_type = "lib"]
+
+// CHECK-LABEL: @index
+#[no_mangle]
+pub fn index(x: &[u32; 4], i: usize) -> u32 {
+// CHECK-NOT: panic_bounds_check
+    x[i]
+}
+
+// CHECK-LABEL: @add
+#[no_mangle]
+pub fn add(x: u32, y: u32) -> u32 {
+// CHECK-NOT: panic
+    x + y
+}
@@ -0,0 +1,40 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes -Z mir-opt-level=3
+
+#![crate_type = "lib"]
+
+// A private function whose only call was removed from the optimized MIR is
+// not translated at all, while one which is still called is translated
+// with internal linkage.
+
+// CHECK-NOT: {{.*}}7removed
+// CHECK: define internal {{.*}}_ZN23internalize_unreferenced4kept
+// CHECK-NOT: {{.*}}7removed
+
+fn removed(y: u32) -> u32 {
+    y & 3
+}
+
+fn kept(y: u32) -> u32 {
+    y / 3
+}
+
+#[no_mangle]
+pub fn call_removed(x: u32) -> u32 {
+    removed(x);
+    x
+}
+
+#[no_mangle]
+pub fn call_kept(x: u32) -> u32 {
+    kept(x)
+}
@@ -0,0 +1,42 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes -Z mir-branch-prob
+
+#![crate_type = "lib"]
+#![feature(core_intrinsics)]
+
+use std::intrinsics::unlikely;
+
+// CHECK-LABEL: @check_unlikely
+#[no_mangle]
+pub fn check_unlikely(x: i32, y: i32) -> i32 {
+    unsafe {
+        // CHECK: br i1 %{{.*}}, label %{{.*}}, label %{{.*}}, !prof ![[UNLIKELY:[0-9]+]]
+        if unlikely(x == y) {
+            1
+        } else {
+            2
+        }
+    }
+}
+
+// CHECK-LABEL: @check_panic_is_cold
+#[no_mangle]
+pub fn check_panic_is_cold(x: bool) {
+    // CHECK: br i1 %{{.*}}, label %{{.*}}, label %{{.*}}, !prof ![[PANIC:[0-9]+]]
+    if x {
+        return;
+    }
+    panic!();
+}
+
+// CHECK: ![[UNLIKELY]] = !{!"branch_weights", i32 1, i32 2000}
+// CHECK: ![[PANIC]] = !{!"branch_weights", i32 64, i32 1}
@@ -0,0 +1,48 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes -Z mir-opt-level=2
+
+#![crate_type = "lib"]
+
+pub struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {}
+}
+
+// A call to a function which can't panic needs no landing pad, even with
+// values to drop in scope.
+
+// CHECK-LABEL: @no_unwind
+#[no_mangle]
+pub fn no_unwind(_guard: Guard, x: u32) -> u32 {
+// CHECK-NOT: invoke
+// CHECK: call {{.*}}mask
+    mask(x)
+}
+
+// CHECK-LABEL: @may_unwind
+#[no_mangle]
+pub fn may_unwind(_guard: Guard, x: u32) -> u32 {
+// CHECK: invoke {{.*}}check
+    check(x)
+}
+
+#[inline(never)]
+fn mask(x: u32) -> u32 {
+    x & 3
+}
+
+#[inline(never)]
+fn check(x: u32) -> u32 {
+    assert!(x < 4);
+    x
+}
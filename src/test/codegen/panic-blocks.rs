@@ -0,0 +1,26 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes -C debug-assertions
+
+#![crate_type = "lib"]
+
+// Both overflow checks panic with the same message, so they share a panic
+// block, which receives the location of the failed check through a phi.
+
+// CHECK-LABEL: @add3
+#[no_mangle]
+pub fn add3(x: u32, y: u32, z: u32) -> u32 {
+// CHECK: phi {{.*}} [ @{{.*}}panic_loc
+// CHECK-NEXT: call void @{{.*}}panicking5panic
+// CHECK-NOT: panicking5panic
+// CHECK: {{^}}}
+    x + y + z
+}
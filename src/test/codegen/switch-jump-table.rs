@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C no-prepopulate-passes
+
+#![crate_type = "lib"]
+
+// The cases are sorted, and the missing value gets a case going to the
+// default block, so the switch can become a jump table.
+
+// CHECK-LABEL: @lookup
+#[no_mangle]
+pub fn lookup(x: u8) -> u32 {
+// CHECK: switch i8 %{{.*}}, label %[[OTHERWISE:[^ ]+]] [
+// CHECK-NEXT: i8 1, label
+// CHECK-NEXT: i8 2, label
+// CHECK-NEXT: i8 3, label %[[OTHERWISE]]
+// CHECK-NEXT: i8 4, label
+// CHECK-NEXT: i8 5, label
+    match x {
+        5 => 13,
+        1 => 7,
+        2 => 17,
+        4 => 3,
+        _ => 0,
+    }
+}
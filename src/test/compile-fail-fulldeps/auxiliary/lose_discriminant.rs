@@ -0,0 +1,49 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+extern crate rustc;
+extern crate rustc_plugin;
+
+use rustc::mir::transform::{self, MirPass, MirSource, PassPoint};
+use rustc::mir::{Mir, StatementKind};
+use rustc::ty::TyCtxt;
+use rustc_plugin::Registry;
+
+/// A broken optimization, which forgets to set the discriminants of the
+/// enums built by the deaggregator.
+struct LoseDiscriminant;
+
+impl transform::Pass for LoseDiscriminant {}
+
+impl<'tcx> MirPass<'tcx> for LoseDiscriminant {
+    fn run_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>,
+                    _: MirSource, mir: &mut Mir<'tcx>) -> bool {
+        let mut changed = false;
+        for data in mir.basic_blocks_mut() {
+            for statement in &mut data.statements {
+                if let StatementKind::SetDiscriminant { .. } = statement.kind {
+                    statement.kind = StatementKind::Nop;
+                    changed = true;
+                }
+            }
+        }
+        changed
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    reg.register_mir_pass_at(PassPoint::Optimized, box LoseDiscriminant);
+}
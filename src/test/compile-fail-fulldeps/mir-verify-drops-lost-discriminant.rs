@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:lose_discriminant.rs
+// ignore-stage1
+// compile-flags: -Z verify-mir-drops -Z mir-opt-level=3
+
+// `-Z verify-mir-drops` rejects a pass which leaves an enum without its
+// discriminant, and keeps rejecting the MIR after the passes following it.
+
+#![feature(plugin)]
+#![plugin(lose_discriminant)]
+
+fn is_some(x: Option<u32>) -> bool {
+    x.is_some()
+}
+
+fn wrap(x: u32) -> bool {
+    let y = Some(x);
+    is_some(y)
+    //~^ ERROR MIR verification after `LoseDiscriminant` failed: use of a moved value
+    //~| ERROR MIR verification after `PreTrans` failed: use of a moved value
+}
+
+fn main() {
+    wrap(0);
+}
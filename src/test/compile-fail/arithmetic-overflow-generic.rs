@@ -0,0 +1,45 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// revisions: checked unchecked
+//[checked] compile-flags: -Z force-overflow-checks=on
+//[unchecked] compile-flags: -Z force-overflow-checks=off
+
+// Arithmetic on constants which only overflows once the type parameters
+// are substituted is reported once, whatever the overflow checks.
+
+#![feature(associated_consts)]
+
+trait Max {
+    const MAX: u8;
+}
+
+struct Byte;
+struct Nibble;
+
+impl Max for Byte {
+    const MAX: u8 = 255;
+}
+
+impl Max for Nibble {
+    const MAX: u8 = 15;
+}
+
+fn bump<T: Max>() -> u8 {
+    T::MAX + 1
+    //[checked]~^ ERROR this arithmetic operation will overflow: attempt to add with overflow
+    //[unchecked]~^^ ERROR this arithmetic operation will overflow: attempt to add with overflow
+}
+
+fn main() {
+    bump::<Byte>();
+    bump::<Byte>();
+    bump::<Nibble>();
+}
@@ -0,0 +1,40 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z force-overflow-checks=on
+
+fn scale(b: u8) -> u16 {
+    let x = b as u16 + 1000;
+    x * 100 //~ ERROR this arithmetic operation will overflow: attempt to multiply with overflow
+}
+
+fn lower(b: i8) -> i16 {
+    let x = b as i16 - 1000;
+    let _ = x - 31000;
+    x - 32000 //~ ERROR this arithmetic operation will overflow: attempt to subtract with overflow
+}
+
+fn shift(b: u8) -> u32 {
+    let n = b as u32 + 32;
+    1 << n //~ ERROR this arithmetic operation will overflow: attempt to shift left with overflow
+}
+
+#[allow(arithmetic_overflow)]
+fn allowed(b: u8) -> u8 {
+    let x = b % 2 + 200;
+    x + 100
+}
+
+fn main() {
+    scale(0);
+    lower(0);
+    shift(0);
+    allowed(0);
+}
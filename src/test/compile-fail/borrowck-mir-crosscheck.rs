@@ -0,0 +1,40 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z borrowck-mir
+
+// The MIR borrow checker runs alongside the AST one: conflicting loans are
+// reported by both, while a loan whose reference is no longer used only
+// conflicts with later accesses lexically.
+
+fn read(_: &i32) {}
+fn write(_: &mut i32) {}
+
+fn both_report() {
+    let mut x = 0;
+    let r = &mut x;
+    let y = &x; //~ ERROR cannot borrow `x` as immutable because it is also borrowed as mutable
+    //~| ERROR cannot borrow `x` as immutable because it is also borrowed as mutable (Mir)
+    read(y);
+    write(r);
+}
+
+fn only_ast_reports() { //~ WARN AST borrowck reported 1 error(s) for this function, but MIR
+    let mut x = 0;
+    let r = &mut x;
+    write(r);
+    let y = &x; //~ ERROR cannot borrow `x` as immutable because it is also borrowed as mutable
+    read(y);
+}
+
+fn main() {
+    both_report();
+    only_ast_reports();
+}
@@ -0,0 +1,54 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-definite-init
+
+// Uses of possibly uninitialized variables are reported from MIR.
+
+fn foo(x: isize) { println!("{}", x); }
+
+fn if_no_else() {
+    let x: isize; if 1 > 2 { x = 10; }
+    foo(x); //~ ERROR use of possibly uninitialized variable: `x`
+}
+
+fn deref() {
+    let x: Box<isize>;
+    foo(*x); //~ ERROR use of possibly uninitialized variable: `*x`
+}
+
+fn redeclared_in_loop(first: bool) {
+    loop {
+        let x: isize;
+        if first {
+            x = 1;
+            foo(x);
+        } else {
+            foo(x); //~ ERROR use of possibly uninitialized variable: `x`
+        }
+    }
+}
+
+// The MIR check doesn't run on functions with other borrowck errors, so
+// the AST check reports these uses.
+fn with_moved_value() {
+    let x: isize;
+    let v = vec![1];
+    drop(v);
+    drop(v); //~ ERROR use of moved value: `v`
+    foo(x); //~ ERROR use of possibly uninitialized variable: `x`
+}
+
+fn main() {
+    if_no_else();
+    deref();
+    redeclared_in_loop(true);
+    with_moved_value();
+}
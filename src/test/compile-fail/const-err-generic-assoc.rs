@@ -0,0 +1,27 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Associated constants the MIR interpreter can't evaluate on their own,
+// trait defaults and those of generic impls, are still checked.
+
+#![feature(associated_consts)]
+#![deny(const_err)]
+
+trait Limits {
+    const MAX: u8 = 200u8 + 100; //~ ERROR attempt to add with overflow
+}
+
+struct Wrapper<T>(T);
+
+impl<T> Wrapper<T> {
+    const MIN: u8 = 0u8 - 1; //~ ERROR attempt to subtract with overflow
+}
+
+fn main() {}
@@ -15,6 +15,8 @@
 
 #![allow(exceeding_bitshifts)]
 #![allow(const_err)]
+#![allow(arithmetic_overflow)]
+#![allow(index_out_of_bounds)]
 
 fn black_box<T>(_: T) {
     unimplemented!()
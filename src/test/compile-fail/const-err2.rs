@@ -10,6 +10,7 @@
 
 #![feature(rustc_attrs)]
 #![allow(exceeding_bitshifts)]
+#![allow(arithmetic_overflow)]
 #![deny(const_err)]
 
 fn black_box<T>(_: T) {
@@ -0,0 +1,22 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Overflow inside a `const fn` is found by evaluating the constant's MIR.
+
+#![feature(const_fn)]
+#![deny(const_err)]
+
+const fn double(x: u8) -> u8 {
+    x * 2 //~ ERROR constant evaluation error: attempt to multiply with overflow
+}
+
+const DOUBLED: u8 = double(200);
+
+fn main() {}
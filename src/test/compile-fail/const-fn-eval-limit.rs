@@ -0,0 +1,27 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z const-eval-limit=1000
+
+#![feature(const_fn)]
+
+const fn never_equal(n: u32) -> u32 {
+    let mut i = 0;
+    while i != n { //~ ERROR constant evaluation exceeded limit
+        i += 2;
+    }
+    i
+}
+
+const ODD: u32 = never_equal(1);
+
+fn main() {
+    println!("{}", ODD);
+}
@@ -15,6 +15,10 @@
 #[rustc_variance] //~ ERROR the `#[rustc_variance]` attribute is just used for rustc unit tests and will never be stable
 #[rustc_error] //~ ERROR the `#[rustc_error]` attribute is just used for rustc unit tests and will never be stable
 #[rustc_move_fragments] //~ ERROR the `#[rustc_move_fragments]` attribute is just used for rustc unit tests and will never be stable
+#[rustc_dump_mir] //~ ERROR the `#[rustc_dump_mir]` attribute is just used for debugging the compiler and will never be stable
+#[rustc_pure] //~ ERROR the `#[rustc_pure]` attribute is just used to let the compiler remove unused calls and will never be stable
+#[rustc_mem_fn = "swap"] //~ ERROR the `#[rustc_mem_fn]` attribute is just used to let the compiler lower calls to memory functions and will never be stable
+#[rustc_panic_entry_point] //~ ERROR the `#[rustc_panic_entry_point]` attribute is just used to recognize the functions `panic!` calls and will never be stable
 #[rustc_foo]
 //~^ ERROR unless otherwise specified, attributes with the prefix `rustc_` are reserved for internal compiler diagnostics
 
@@ -0,0 +1,28 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn index(a: [u8; 4], j: u8) {
+    let _ = a[j as usize % 4];
+    let _ = a[j as usize + 4];
+    //~^ ERROR index out of bounds: the len is 4 but the index is at least 4
+}
+
+fn main() {
+    let a = [1u8, 2, 3, 4];
+    let _ = a[3];
+    let _ = a[4];
+    //~^ ERROR index out of bounds: the len is 4 but the index is 4
+
+    let i = 2;
+    let _ = a[i + 2];
+    //~^ ERROR index out of bounds: the len is 4 but the index is 4
+
+    index(a, 0);
+}
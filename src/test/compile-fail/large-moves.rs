@@ -0,0 +1,45 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the large_moves lint fires when a value bigger than
+// #![move_size_limit] is moved or copied.
+
+#![feature(move_size_limit)]
+#![move_size_limit = "1024"]
+#![deny(large_moves)]
+
+fn by_value(buf: [u8; 4096]) -> u8 {
+    buf[0]
+}
+
+fn by_ref(buf: &[u8; 4096]) -> u8 {
+    buf[0]
+}
+
+fn moves() {
+    let small = [0u8; 512];
+    let _copy = small;
+
+    let big = [0u8; 4096];
+    let _copy = big; //~ ERROR moving a value of type `[u8; 4096]` copies 4096 bytes
+    by_value(big); //~ ERROR moving a value of type `[u8; 4096]` copies 4096 bytes
+    by_ref(&big);
+}
+
+#[allow(large_moves)]
+fn allowed() {
+    let big = [0u8; 4096];
+    let _copy = big;
+}
+
+fn main() {
+    moves();
+    allowed();
+}
@@ -0,0 +1,47 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the large_stack_frame lint fires when a function's stack frame
+// is estimated to exceed #![stack_frame_limit].
+
+#![feature(stack_frame_limit)]
+#![stack_frame_limit = "1024"]
+#![deny(large_stack_frame)]
+
+fn small() {
+    let x = [0u8; 512];
+    let _y = &x;
+}
+
+fn large() { //~ ERROR the stack frame of `large` is estimated at
+    let x = [0u8; 4096];
+    let _y = &x;
+}
+
+// Reported once, rather than once per instance.
+fn generic<T>(x: T) -> T { //~ ERROR the stack frame of `generic
+    let big = [0u8; 4096];
+    let _y = &big;
+    x
+}
+
+#[allow(large_stack_frame)]
+fn allowed() {
+    let x = [0u8; 4096];
+    let _y = &x;
+}
+
+fn main() {
+    small();
+    large();
+    generic(0u8);
+    generic(0u32);
+    allowed();
+}
@@ -0,0 +1,55 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![deny(dead_assignments)]
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+fn field(mut p: Point) -> i32 {
+    let y = p.y;
+    p.x = y; //~ ERROR value assigned to a field of `p` is never read
+    y
+}
+
+fn through_ref() -> i32 {
+    let mut n = 0;
+    let r = &mut n;
+    *r = 5; //~ ERROR value assigned to `n` through a reference is never read
+    0
+}
+
+fn read_later(mut p: Point) -> i32 {
+    p.x = 3;
+    p.x
+}
+
+fn borrowed(mut p: Point) -> i32 {
+    p.y = 4;
+    show(&p)
+}
+
+fn show(p: &Point) -> i32 {
+    p.x + p.y
+}
+
+fn ignored(mut _p: Point) {
+    _p.x = 1;
+}
+
+fn main() {
+    field(Point { x: 0, y: 0 });
+    through_ref();
+    read_later(Point { x: 0, y: 0 });
+    borrowed(Point { x: 0, y: 0 });
+    ignored(Point { x: 0, y: 0 });
+}
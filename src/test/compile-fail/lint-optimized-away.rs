@@ -0,0 +1,48 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+#![deny(optimized_away)]
+#![allow(dead_code)]
+
+fn log(x: u32) { //~ ERROR the optimizations reduced this function to doing nothing
+    if false {
+        println!("{}", x);
+    }
+}
+
+fn narrow() -> i8 { //~ ERROR the optimizations reduced this function to returning a constant
+    456u16 as u8 as i8
+}
+
+fn answer() -> u32 {
+    42
+}
+
+fn nothing() {}
+
+#[inline]
+fn inlined() -> i8 {
+    456u16 as u8 as i8
+}
+
+#[allow(optimized_away)]
+fn allowed() -> i8 {
+    456u16 as u8 as i8
+}
+
+fn main() {
+    log(1);
+    narrow();
+    inlined();
+    allowed();
+    let _ = |x: u32| if false { x } else { 0 };
+}
@@ -0,0 +1,43 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the simplifiable_code lint reports what the MIR optimizations
+// found out about the source code.
+
+// compile-flags: -Z mir-opt-level=2
+
+#![deny(simplifiable_code)]
+
+fn mask(x: u32) -> u32 {
+    x & 3
+}
+
+fn dead_call(x: u32) -> u32 {
+    mask(x); //~ ERROR this call has no effect and its result is unused
+    x
+}
+
+fn dead_branch(x: u32) -> u32 {
+    if false { //~ ERROR this condition is always `false`
+        mask(x)
+    } else {
+        x
+    }
+}
+
+#[allow(simplifiable_code)]
+fn allowed(x: u32) -> u32 {
+    mask(x);
+    x
+}
+
+fn main() {
+    println!("{} {} {}", dead_call(0), dead_branch(0), allowed(0));
+}
@@ -0,0 +1,53 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![deny(unconditional_panic)]
+#![allow(dead_code)]
+
+fn todo(x: u32) -> u32 { //~ ERROR this function panics whenever it is called
+    let _y = x.wrapping_add(1);
+    unimplemented!()
+}
+
+fn message() { //~ ERROR this function panics whenever it is called
+    panic!("{} is not supported", "this")
+}
+
+fn inverted(x: u32) -> u32 { //~ ERROR this function panics whenever it is called
+    assert!(1 > 2, "inverted");
+    x
+}
+
+fn inverted_negation() { //~ ERROR this function panics whenever it is called
+    assert!(!true);
+}
+
+fn checked(x: u32) -> u32 {
+    assert!(x > 0);
+    x
+}
+
+fn spins(x: u32) -> u32 {
+    if x > 0 {
+        panic!("positive");
+    }
+    loop {}
+}
+
+fn diverges() -> ! {
+    panic!("done")
+}
+
+#[allow(unconditional_panic)]
+fn allowed() {
+    unimplemented!()
+}
+
+fn main() {}
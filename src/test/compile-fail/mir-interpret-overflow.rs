@@ -0,0 +1,22 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-interpret -Z force-overflow-checks=on
+
+fn next(x: u8) -> u8 {
+    x + 3 //~ ERROR MIR interpretation failed: attempt to add with overflow
+}
+
+fn main() {
+    let mut x = 1;
+    while x != 0 {
+        x = next(x); //~ NOTE inside call to `next`
+    }
+}
@@ -0,0 +1,26 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(optimize_attribute)]
+
+#[optimize(fast)] //~ ERROR invalid argument
+fn fast() {}
+
+#[optimize] //~ ERROR expected one argument
+fn bare() {}
+
+#[optimize(size, speed)] //~ ERROR expected one argument
+fn both() {}
+
+fn main() {
+    fast();
+    bare();
+    both();
+}
@@ -0,0 +1,49 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that #[rustc_mir_assert] checks the MIR left by the optimizations.
+
+// compile-flags: -Z mir-opt-level=2
+
+#![feature(rustc_attrs)]
+
+fn mask(x: u32) -> u32 {
+    x & 3
+}
+
+#[rustc_mir_assert(blocks = "1", max_statements = "4", no_calls, no_asserts)]
+fn removed(x: u32) -> u32 {
+    mask(x);
+    x
+}
+
+#[rustc_mir_assert(no_calls)] //~ ERROR MIR has 1 calls, expected none
+fn kept(x: u32) -> u32 {
+    mask(x)
+}
+
+#[rustc_mir_assert(no_asserts)] //~ ERROR MIR has 1 assertions, expected none
+fn checked(x: u32) -> u32 {
+    x + 1
+}
+
+#[rustc_mir_assert(blocks = "many")] //~ ERROR expected a number
+fn bad_number(x: u32) -> u32 {
+    x
+}
+
+#[rustc_mir_assert(inlined)] //~ ERROR unknown `#[rustc_mir_assert]` property `inlined`
+fn unknown(x: u32) -> u32 {
+    x
+}
+
+fn main() {
+    println!("{} {} {} {} {}", removed(0), kept(0), checked(0), bad_number(0), unknown(0));
+}
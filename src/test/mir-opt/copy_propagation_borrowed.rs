@@ -0,0 +1,31 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A copy isn't propagated past a write through a mutable borrow of its
+// source.
+
+fn test(mut x: u32) -> u32 {
+    let y = x;
+    *(&mut x) = 5;
+    y ^ x
+}
+
+fn main() {
+    test(0);
+}
+
+// END RUST SOURCE
+// START rustc.node4.CopyPropagation.after.mir
+//     bb0: {
+//         _2 = _1;
+//         _0 = BitXor(_2, _1);
+//         return;
+//     }
+// END rustc.node4.CopyPropagation.after.mir
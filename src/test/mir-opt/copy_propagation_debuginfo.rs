@@ -0,0 +1,36 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Copy propagation keeps the debuginfo of the variables it removes,
+// pointing it at the propagated local or constant.
+
+fn test(x: u32) -> u32 {
+    let y = x;
+    let z = 5;
+    y ^ z
+}
+
+fn main() {
+    test(0);
+}
+
+// END RUST SOURCE
+// START rustc.node4.CopyPropagation.after.mir
+//     scope 1 {
+//         debug y => _1;
+//         scope 2 {
+//             debug z => const 5u32;
+//         }
+//     }
+//     bb0: {
+//         _0 = BitXor(_1, const 5u32);
+//         return;
+//     }
+// END rustc.node4.CopyPropagation.after.mir
@@ -0,0 +1,28 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A variable copied out of a field is replaced by the field, and its
+// debuginfo points at the field.
+
+fn test(x: (u32, u32)) -> u32 {
+    let y = x.0;
+    y ^ x.1
+}
+
+fn main() {
+    test((0, 1));
+}
+
+// END RUST SOURCE
+// START rustc.node4.CopyPropagation.after.mir
+//     scope 1 {
+//         debug y => (_1.0: u32);
+//     }
+// END rustc.node4.CopyPropagation.after.mir
@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A temporary which is only moved into a field is computed in the field
+// directly.
+
+fn test(x: u32) -> Pair {
+    Pair { a: x & 1, b: x }
+}
+
+struct Pair {
+    a: u32,
+    b: u32,
+}
+
+fn main() {
+    test(0);
+}
+
+// END RUST SOURCE
+// START rustc.node4.CopyPropagation.after.mir
+//     bb0: {
+//         (_0.0: u32) = BitAnd(_1, const 1u32);
+//         (_0.1: u32) = _1;
+//         return;
+//     }
+// END rustc.node4.CopyPropagation.after.mir
@@ -0,0 +1,37 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A call to a function which only computes its result is removed when
+// the result is unused.
+
+fn test(x: u32) -> u32 {
+    mask(x);
+    x
+}
+
+fn mask(y: u32) -> u32 {
+    y & 3
+}
+
+fn main() {
+    test(0);
+}
+
+// END RUST SOURCE
+// START rustc.node4.DeadCalls.before.mir
+//     bb0: {
+//         _2 = mask(_1) -> bb1;
+//     }
+// END rustc.node4.DeadCalls.before.mir
+// START rustc.node4.DeadCalls.after.mir
+//     bb0: {
+//         goto -> bb1;
+//     }
+// END rustc.node4.DeadCalls.after.mir
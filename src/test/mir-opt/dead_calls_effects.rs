@@ -0,0 +1,51 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A call whose result is unused is removed when the callee only calls
+// functions without effects, but not when it writes through a pointer.
+
+fn test(x: u32, p: *mut u32) -> u32 {
+    masked(x);
+    set(p);
+    x
+}
+
+fn masked(x: u32) -> u32 {
+    mask(x) | 4
+}
+
+fn mask(y: u32) -> u32 {
+    y & 3
+}
+
+fn set(p: *mut u32) -> u32 {
+    unsafe { *p = 3; }
+    3
+}
+
+fn main() {
+    let mut x = 0;
+    test(0, &mut x);
+}
+
+// END RUST SOURCE
+// START rustc.node4.DeadCalls.before.mir
+//     bb0: {
+//         _3 = masked(_1) -> bb1;
+//     }
+// END rustc.node4.DeadCalls.before.mir
+// START rustc.node4.DeadCalls.after.mir
+//     bb0: {
+//         goto -> bb1;
+//     }
+//     bb1: {
+//         _5 = set(_2) -> bb2;
+//     }
+// END rustc.node4.DeadCalls.after.mir
@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn test() -> i8 {
+    (456u16 as u8) as i8
+}
+
+fn main() {
+    test();
+}
+
+// END RUST SOURCE
+// START rustc.node4.FoldCasts.before.mir
+// bb0: {
+//     _1 = const 456u16 as u8 (Misc);
+//     _0 = _1 as i8 (Misc);
+//     return;
+// }
+// END rustc.node4.FoldCasts.before.mir
+// START rustc.node4.FoldCasts.after.mir
+// bb0: {
+//     _1 = const 200u8;
+//     _0 = const -56i8;
+//     return;
+// }
+// END rustc.node4.FoldCasts.after.mir
@@ -0,0 +1,35 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn test() -> f32 {
+    (1.5f64 * 2.0 + 1.0) as f32
+}
+
+fn main() {
+    test();
+}
+
+// END RUST SOURCE
+// START rustc.node4.FoldFloats.before.mir
+// bb0: {
+//     _2 = Mul(const F64(1.5), const F64(2));
+//     _1 = Add(_2, const F64(1));
+//     _0 = _1 as f32 (Misc);
+//     return;
+// }
+// END rustc.node4.FoldFloats.before.mir
+// START rustc.node4.FoldFloats.after.mir
+// bb0: {
+//     _2 = const F64(3);
+//     _1 = const F64(4);
+//     _0 = const F32(4);
+//     return;
+// }
+// END rustc.node4.FoldFloats.after.mir
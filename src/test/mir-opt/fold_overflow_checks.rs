@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z force-overflow-checks=on
+
+fn widening_add(x: u8, y: u8) -> u16 {
+    (x as u16) + (y as u16)
+}
+
+fn main() {
+    widening_add(1, 2);
+}
+
+// END RUST SOURCE
+// START rustc.node4.FoldOverflowChecks.before.mir
+// bb0: {
+//     _7 = CheckedAdd(_3, _5);
+//     assert(!(_7.1: bool), "attempt to add with overflow") -> bb1;
+// }
+// END rustc.node4.FoldOverflowChecks.before.mir
+// START rustc.node4.FoldOverflowChecks.after.mir
+// bb0: {
+//     (_7.0: u16) = Add(_3, _5);
+//     (_7.1: bool) = const false;
+//     goto -> bb1;
+// }
+// END rustc.node4.FoldOverflowChecks.after.mir
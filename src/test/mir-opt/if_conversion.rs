@@ -0,0 +1,36 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A branch which only chooses the value assigned to a local is replaced
+// by arithmetic on the condition.
+
+fn test(c: bool) -> u32 {
+    if c { 5 } else { 7 }
+}
+
+fn main() {
+    test(false);
+}
+
+// END RUST SOURCE
+// START rustc.node4.IfConversion.before.mir
+//     bb0: {
+//         switchInt(_1) -> [false: bb2, otherwise: bb1];
+//     }
+// END rustc.node4.IfConversion.before.mir
+// START rustc.node4.IfConversion.after.mir
+//     bb0: {
+//         _2 = BitXor(const 5u32, const 7u32);
+//         _3 = _1 as u32 (Misc);
+//         _4 = Mul(_2, _3);
+//         _0 = BitXor(const 7u32, _4);
+//         goto -> bb3;
+//     }
+// END rustc.node4.IfConversion.after.mir
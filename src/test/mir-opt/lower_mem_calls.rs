@@ -0,0 +1,40 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// A call to `mem::swap` on small values becomes moves through its arguments.
+
+fn test(mut x: u32, mut y: u32) -> u32 {
+    std::mem::swap(&mut x, &mut y);
+    x
+}
+
+fn main() {
+    test(1, 2);
+}
+
+// END RUST SOURCE
+// START rustc.node4.LowerMemCalls.before.mir
+//     bb0: {
+//         _4 = &mut _1;
+//         _5 = &mut _2;
+//         _3 = std::mem::swap::<u32>(_4, _5) -> bb1;
+//     }
+// END rustc.node4.LowerMemCalls.before.mir
+// START rustc.node4.LowerMemCalls.after.mir
+//     bb0: {
+//         _4 = &mut _1;
+//         _5 = &mut _2;
+//         _6 = (*_4);
+//         (*_4) = (*_5);
+//         (*_5) = _6;
+//         _3 = ();
+//         goto -> bb1;
+//     }
+// END rustc.node4.LowerMemCalls.after.mir
@@ -0,0 +1,24 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn pick(flag: bool, x: u32) -> u32 {
+    if flag { x } else { 0 }
+}
+
+fn main() {
+    pick(true, 1);
+    pick(true, 2);
+}
+
+// END RUST SOURCE
+// START rustc.node4.PropagateConstArgs.after.mir
+// bb0: {
+//     _1 = const true;
+// END rustc.node4.PropagateConstArgs.after.mir
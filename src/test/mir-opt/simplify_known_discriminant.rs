@@ -0,0 +1,35 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let x = Some(0u8);
+    match x {
+        Some(_) => {}
+        None => {}
+    }
+}
+
+// END RUST SOURCE
+// START rustc.node4.SimplifyBranches.after-deaggregator-before.mir
+// bb0: {
+//     StorageLive(_1);
+//     ((_1 as Some).0: u8) = const 0u8;
+//     discriminant(_1) = 1;
+//     _2 = discriminant(_1);
+// }
+// END rustc.node4.SimplifyBranches.after-deaggregator-before.mir
+// START rustc.node4.SimplifyBranches.after-deaggregator-after.mir
+// bb0: {
+//     StorageLive(_1);
+//     ((_1 as Some).0: u8) = const 0u8;
+//     discriminant(_1) = 1;
+//     _2 = const 1isize;
+// }
+// END rustc.node4.SimplifyBranches.after-deaggregator-after.mir
@@ -0,0 +1,31 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The calls `?` on a `Result` goes through in a function returning the same
+// `Result` are built as moves and an `Err` aggregate, so the switch on the
+// discriminant is in the first block.
+
+fn test(x: Result<u32, i32>) -> Result<u32, i32> {
+    let y = x?;
+    Ok(y)
+}
+
+fn main() {
+    test(Ok(0)).unwrap();
+}
+
+// END RUST SOURCE
+// START rustc.node4.TypeckMir.before.mir
+//     bb0: {
+//         _4 = _1;
+//         _3 = _4;
+//         _5 = discriminant(_3);
+//     }
+// END rustc.node4.TypeckMir.before.mir
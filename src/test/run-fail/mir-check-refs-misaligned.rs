@@ -0,0 +1,19 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-check-refs
+// error-pattern:misaligned pointer dereference
+
+fn main() {
+    let x = [0u32; 2];
+    let p = (&x as *const [u32; 2] as *const u8 as usize + 1) as *const u32;
+    let y = unsafe { *p };
+    println!("{}", y);
+}
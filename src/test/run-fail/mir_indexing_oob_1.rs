@@ -10,6 +10,8 @@
 
 // error-pattern:index out of bounds: the len is 5 but the index is 10
 
+#![allow(index_out_of_bounds)]
+
 const C: [u32; 5] = [0; 5];
 
 fn test() -> u32 {
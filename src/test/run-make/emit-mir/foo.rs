@@ -0,0 +1,25 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_type = "lib"]
+
+pub fn zeta(x: u32) -> u32 {
+    x + 1
+}
+
+pub fn alpha(x: u32) -> u32 {
+    zeta(x) * 2
+}
+
+pub const LIMIT: u32 = 10;
+
+pub fn mid(x: u32) -> bool {
+    alpha(x) < LIMIT
+}
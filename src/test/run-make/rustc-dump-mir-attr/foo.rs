@@ -0,0 +1,24 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(rustc_attrs)]
+#![crate_type = "lib"]
+
+#[rustc_dump_mir(CopyPropagation)]
+pub fn dumped(x: u32) -> u32 {
+    x + 1
+}
+
+pub fn not_dumped(x: u32) -> u32 {
+    x + 2
+}
+
+#[rustc_dump_mir]
+pub fn all_passes() {}
@@ -32,8 +32,9 @@ impl transform::Pass for Pass {}
 
 impl<'tcx> MirPass<'tcx> for Pass {
     fn run_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>,
-                    _: MirSource, mir: &mut Mir<'tcx>) {
-        Visitor.visit_mir(mir)
+                    _: MirSource, mir: &mut Mir<'tcx>) -> bool {
+        Visitor.visit_mir(mir);
+        true
     }
 }
 
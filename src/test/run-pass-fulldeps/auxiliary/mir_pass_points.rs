@@ -0,0 +1,77 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// force-host
+
+#![feature(plugin_registrar, rustc_private)]
+#![feature(box_syntax)]
+
+#[macro_use] extern crate rustc;
+extern crate rustc_plugin;
+extern crate rustc_const_math;
+extern crate syntax;
+
+use rustc::mir::transform::{self, MirPass, MirPassHook, MirSource, PassPoint};
+use rustc::mir::{Mir, Literal, Location};
+use rustc::mir::visit::MutVisitor;
+use rustc::ty::TyCtxt;
+use rustc::middle::const_val::ConstVal;
+use rustc_const_math::ConstInt;
+use rustc_plugin::Registry;
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Counts the passes run, over all the pass managers.
+struct Hook(Rc<Cell<usize>>);
+
+impl transform::Pass for Hook {}
+
+impl<'tcx> MirPassHook<'tcx> for Hook {
+    fn on_mir_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>, _: MirSource, _: &Mir<'tcx>,
+                       _: &transform::Pass, is_after: bool) {
+        if is_after {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+}
+
+/// Replaces `13` by `42`, once the built-in passes have run.
+struct Pass(Rc<Cell<usize>>);
+
+impl transform::Pass for Pass {}
+
+impl<'tcx> MirPass<'tcx> for Pass {
+    fn run_pass<'a>(&mut self, _: TyCtxt<'a, 'tcx, 'tcx>,
+                    _: MirSource, mir: &mut Mir<'tcx>) -> bool {
+        assert!(self.0.get() > 0, "the pass hook wasn't called");
+        Visitor.visit_mir(mir);
+        true
+    }
+}
+
+struct Visitor;
+
+impl<'tcx> MutVisitor<'tcx> for Visitor {
+    fn visit_literal(&mut self, literal: &mut Literal<'tcx>, _: Location) {
+        if let Literal::Value { ref mut value } = *literal {
+            if let ConstVal::Integral(ConstInt::I32(ref mut i @ 13)) = *value {
+                *i = 42;
+            }
+        }
+    }
+}
+
+#[plugin_registrar]
+pub fn plugin_registrar(reg: &mut Registry) {
+    let passes = Rc::new(Cell::new(0));
+    let hook_passes = passes.clone();
+    reg.register_mir_pass_hook(box move || box Hook(hook_passes.clone()));
+    reg.register_mir_pass_at(PassPoint::Optimized, box Pass(passes));
+}
@@ -0,0 +1,38 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that the rare variants of MIR statements and terminators don't make
+// every node larger: a statement should be no larger than an assignment of
+// a binary operation, and the message of an assertion should be boxed.
+
+// ignore-cross-compile
+
+#![feature(rustc_private)]
+
+extern crate rustc;
+
+use rustc::mir::{AssertMessage, Lvalue, Operand, Rvalue, SourceInfo, Statement};
+use rustc::mir::{StatementKind, Terminator, TerminatorKind};
+use std::mem::size_of;
+
+fn main() {
+    // A discriminant and the operator, then the two operands, with room
+    // for padding.
+    let binary_op = 2 * size_of::<usize>() + 2 * size_of::<Operand>();
+    assert!(size_of::<Rvalue>() <= binary_op);
+    assert!(size_of::<StatementKind>() <=
+            size_of::<usize>() + size_of::<Lvalue>() + size_of::<Rvalue>());
+    assert!(size_of::<Statement>() <= size_of::<SourceInfo>() + size_of::<StatementKind>());
+
+    // An inline message would need at least as much room as the condition
+    // and the message together.
+    assert!(size_of::<TerminatorKind>() < size_of::<Operand>() + size_of::<AssertMessage>());
+    assert!(size_of::<Terminator>() <= size_of::<SourceInfo>() + size_of::<TerminatorKind>());
+}
@@ -0,0 +1,25 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_pass_points.rs
+// ignore-stage1
+
+#![feature(plugin)]
+#![plugin(mir_pass_points)]
+
+// The literal is only rewritten at the end of the pipeline, so the
+// built-in passes see `13`.
+fn math() -> i32 {
+    13
+}
+
+pub fn main() {
+    assert_eq!(math(), 42);
+}
@@ -0,0 +1,53 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z cross-crate-auto-inline
+
+#![crate_type = "rlib"]
+#![feature(unwind_attributes)]
+
+pub struct Pair(pub u32, pub u32);
+
+impl Pair {
+    pub fn sum(&self) -> u32 {
+        self.0.wrapping_add(self.1)
+    }
+}
+
+pub fn double(x: u32) -> u32 {
+    x.wrapping_mul(2)
+}
+
+// The bounds check panics out of line.
+pub fn second(xs: &[u32]) -> u32 {
+    xs[1]
+}
+
+pub fn greeting() -> &'static str {
+    "hello"
+}
+
+static COUNT: u32 = 7;
+
+// Refers to a static, which is left to the crate.
+pub fn count() -> u32 {
+    COUNT
+}
+
+#[inline(never)]
+pub fn never(x: u32) -> u32 {
+    x + 1
+}
+
+// Unwinds out of an `extern` function, which its copies wouldn't.
+#[unwind]
+pub extern fn unwinding(x: u32) -> u32 {
+    x + 2
+}
@@ -0,0 +1,29 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Private functions only reached through function pointer constants, from
+// MIR other crates instantiate.
+
+fn helper() -> u32 {
+    42
+}
+
+const HELPER: fn() -> u32 = helper;
+
+#[inline]
+pub fn call_inline() -> u32 {
+    HELPER()
+}
+
+pub fn call_generic<T>(_: T) -> u32 {
+    HELPER()
+}
@@ -0,0 +1,73 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that `const fn` bodies can use `let`, `if`, `match` and loops.
+
+#![feature(const_fn)]
+
+const fn fib(n: u32) -> u32 {
+    let mut a = 0;
+    let mut b = 1;
+    let mut i = 0;
+    while i < n {
+        let next = a + b;
+        a = b;
+        b = next;
+        i += 1;
+    }
+    a
+}
+
+const fn sum_to(n: u32) -> u32 {
+    let mut total = 0;
+    let mut i = 0;
+    loop {
+        if i > n {
+            break;
+        }
+        total += i;
+        i += 1;
+    }
+    total
+}
+
+const fn signum(x: i32) -> i32 {
+    if x < 0 {
+        -1
+    } else if x == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+const fn digit(c: char) -> u32 {
+    match c {
+        '0' => 0,
+        '1' => 1,
+        '2' => 2,
+        _ => 10,
+    }
+}
+
+const FIB: u32 = fib(10);
+const SUM: u32 = sum_to(100);
+const SIGNS: [i32; 3] = [signum(-7), signum(0), signum(7)];
+const DIGITS: [u32; 2] = [digit('2'), digit('x')];
+
+fn main() {
+    assert_eq!(FIB, 55);
+    assert_eq!(SUM, 5050);
+    assert_eq!(SIGNS, [-1, 0, 1]);
+    assert_eq!(DIGITS, [2, 10]);
+
+    assert_eq!(fib(20), 6765);
+    assert_eq!(sum_to(10), 55);
+}
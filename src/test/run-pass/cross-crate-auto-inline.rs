@@ -0,0 +1,30 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:cross_crate_auto_inline.rs
+// compile-flags: -O
+
+// Calls the functions `-Z cross-crate-auto-inline` made the MIR of
+// available, which this crate translates its own copies of, along with
+// those it left to their crate.
+
+extern crate cross_crate_auto_inline as auto;
+
+use auto::Pair;
+
+fn main() {
+    assert_eq!(auto::double(21), 42);
+    assert_eq!(Pair(1, 2).sum(), 3);
+    assert_eq!(auto::second(&[1, 2, 3]), 2);
+    assert_eq!(auto::greeting(), "hello");
+    assert_eq!(auto::count(), 7);
+    assert_eq!(auto::never(1), 2);
+    assert_eq!(auto::unwinding(1), 3);
+}
@@ -0,0 +1,52 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Test that drop glue still drops every field, variant field and array
+// element in order once the MIR optimizations ran on it.
+
+use std::cell::Cell;
+
+thread_local!(static LOG: Cell<usize> = Cell::new(0));
+
+struct D(u8);
+
+impl Drop for D {
+    fn drop(&mut self) {
+        LOG.with(|log| log.set(log.get() << 4 | self.0 as usize));
+    }
+}
+
+struct S {
+    _a: D,
+    _b: (D, D),
+}
+
+enum E {
+    A(D, D),
+    #[allow(dead_code)]
+    B(D),
+    #[allow(dead_code)]
+    C,
+}
+
+fn drop_log<T>(value: T) -> usize {
+    LOG.with(|log| log.set(0));
+    drop(value);
+    LOG.with(|log| log.get())
+}
+
+fn main() {
+    assert_eq!(drop_log(S { _a: D(1), _b: (D(2), D(3)) }), 0x123);
+    assert_eq!(drop_log(E::A(D(1), D(2))), 0x12);
+    assert_eq!(drop_log([D(1), D(2), D(3)]), 0x123);
+    assert_eq!(drop_log(Some((D(1), [D(2), D(3)]))), 0x123);
+}
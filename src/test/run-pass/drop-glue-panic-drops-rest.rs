@@ -0,0 +1,56 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that drop glue keeps dropping the remaining fields, variant
+// fields and array elements when the destructor of one of them panics.
+
+// ignore-emscripten no threads support
+
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::thread;
+
+static LOG: AtomicUsize = ATOMIC_USIZE_INIT;
+
+struct D(u8, bool);
+
+impl Drop for D {
+    fn drop(&mut self) {
+        let old = LOG.load(Ordering::SeqCst);
+        LOG.store(old << 4 | self.0 as usize, Ordering::SeqCst);
+        if self.1 {
+            panic!("D({})", self.0);
+        }
+    }
+}
+
+struct S {
+    _a: D,
+    _b: D,
+    _c: D,
+}
+
+enum E {
+    A(D, D),
+    #[allow(dead_code)]
+    B(D),
+}
+
+fn drop_log<T: Send + 'static>(value: T) -> usize {
+    LOG.store(0, Ordering::SeqCst);
+    assert!(thread::spawn(move || drop(value)).join().is_err());
+    LOG.load(Ordering::SeqCst)
+}
+
+fn main() {
+    assert_eq!(drop_log(S { _a: D(1, false), _b: D(2, true), _c: D(3, false) }), 0x123);
+    assert_eq!(drop_log(E::A(D(1, true), D(2, false))), 0x12);
+    assert_eq!(drop_log([D(1, false), D(2, true), D(3, false)]), 0x123);
+    assert_eq!(drop_log((D(1, true), [D(2, false), D(3, false)])), 0x123);
+}
@@ -0,0 +1,92 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test the `FnOnce` adapters of `Fn` and `FnMut` closures, the `Fn`
+// impls of fn pointers and fn items, and reified trait object methods,
+// which are all built as MIR shims.
+
+// ignore-emscripten no threads support
+
+use std::sync::atomic::{AtomicUsize, Ordering, ATOMIC_USIZE_INIT};
+use std::thread;
+
+static DROPS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+struct Flag;
+
+impl Drop for Flag {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn call_once<F: FnOnce(u32, u32) -> u32>(f: F) -> u32 {
+    f(3, 4)
+}
+
+fn call_mut<F: FnMut(u32) -> u32>(mut f: F) -> u32 {
+    f(1) + f(2)
+}
+
+fn call<F: Fn(u32) -> u32>(f: F) -> u32 {
+    f(5)
+}
+
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+trait Shape {
+    fn area(&self, scale: u32) -> u32;
+}
+
+struct Square(u32);
+
+impl Shape for Square {
+    fn area(&self, scale: u32) -> u32 {
+        self.0 * self.0 * scale
+    }
+}
+
+fn main() {
+    // `Fn` and `FnMut` closures called through `FnOnce` drop their
+    // environment afterwards.
+    let flag = Flag;
+    let offset = 10;
+    assert_eq!(call_once(move |a, b| { let _ = &flag; a + b + offset }), 17);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    let flag = Flag;
+    let mut calls = 0;
+    assert_eq!(call_once(move |a, b| { let _ = &flag; calls += 1; a * b + calls }), 13);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+    // Even if the closure panics.
+    let result = thread::spawn(|| {
+        let flag = Flag;
+        call_once(move |_, _| -> u32 { let _ = &flag; panic!() })
+    }).join();
+    assert!(result.is_err());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+
+    // Fn pointers and fn items, by reference and by value.
+    let ptr: fn(u32) -> u32 = double;
+    assert_eq!(call(ptr), 10);
+    assert_eq!(call(double), 10);
+    assert_eq!(call_mut(ptr), 6);
+    assert_eq!(call_mut(&double), 6);
+    assert_eq!(call_once(|a, b| ptr(a) + b), 10);
+
+    // Reified trait object methods.
+    let area = <Shape>::area;
+    let square: &Shape = &Square(3);
+    assert_eq!(area(square, 2), 18);
+    assert_eq!(call(|scale| area(square, scale)), 45);
+}
@@ -0,0 +1,38 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-check-refs
+
+// Checks that the pointer checks let valid dereferences through, nested
+// ones included, and leave those of generic pointees alone.
+
+struct Node {
+    value: u64,
+    next: *const Node,
+}
+
+unsafe fn read<T: Copy>(p: *const T) -> T {
+    *p
+}
+
+fn main() {
+    let last = Node { value: 2, next: 0 as *const Node };
+    let first = Node { value: 1, next: &last };
+    let p: *const Node = &first;
+    unsafe {
+        assert_eq!((*p).value, 1);
+        assert_eq!((*(*p).next).value, 2);
+        assert_eq!(read(&last.value), 2);
+    }
+    let mut x = 0u8;
+    let q: *mut u8 = &mut x;
+    unsafe { *q = 3; }
+    assert_eq!(x, 3);
+}
@@ -0,0 +1,58 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Checks that calls of closures through trait objects made in the same
+// function still call the right closure once devirtualized.
+
+#[inline(never)]
+fn id<T>(x: T) -> T { x }
+
+fn boxed(offset: u32) -> u32 {
+    let f: Box<Fn(u32) -> u32> = Box::new(move |x| x + offset);
+    f(2) + f(3)
+}
+
+fn by_ref() -> u32 {
+    let scale = id(10);
+    let f: &Fn(u32) -> u32 = &|x| x * scale;
+    f(4)
+}
+
+fn by_mut_ref() -> u32 {
+    let mut total = 0;
+    {
+        let f: &mut FnMut(u32) = &mut |x| total += x;
+        f(1);
+        f(2);
+        f(3);
+    }
+    total
+}
+
+fn either(flag: bool) -> u32 {
+    // Assigned twice, so left alone.
+    let f: Box<Fn() -> u32>;
+    if flag {
+        f = Box::new(|| 1);
+    } else {
+        f = Box::new(|| 2);
+    }
+    f()
+}
+
+fn main() {
+    assert_eq!(boxed(id(1)), 7);
+    assert_eq!(by_ref(), 40);
+    assert_eq!(by_mut_ref(), 6);
+    assert_eq!(either(id(true)), 1);
+    assert_eq!(either(id(false)), 2);
+}
@@ -0,0 +1,33 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=2 -Z mir-effects-round-limit=1
+
+// Giving up on the effects of `even` and `odd` leaves them assumed to do
+// anything, so the unused calls below must stay.
+
+static mut CALLS: u32 = 0;
+
+fn even(n: u32) -> bool {
+    unsafe { CALLS += 1; }
+    n == 0 || odd(n - 1)
+}
+
+fn odd(n: u32) -> bool {
+    unsafe { CALLS += 1; }
+    n != 0 && even(n - 1)
+}
+
+fn main() {
+    assert!(even(4));
+    assert!(odd(3));
+    even(2);
+    assert_eq!(unsafe { CALLS }, 5 + 4 + 3);
+}
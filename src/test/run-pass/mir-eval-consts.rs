@@ -0,0 +1,55 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The constants functions refer to get the values the MIR interpreter
+// computes for them, associated constants resolved for the types they're
+// used with, and array lengths and discriminants agree with it.
+
+#![feature(associated_consts, const_fn)]
+
+trait Size {
+    const SIZE: usize = 1;
+}
+
+struct Small;
+struct Large;
+
+impl Size for Small {}
+
+impl Size for Large {
+    const SIZE: usize = double(LEN);
+}
+
+const fn double(x: usize) -> usize {
+    x * 2
+}
+
+const LEN: usize = 3 + 4;
+
+#[repr(u8)]
+enum Level {
+    Low = double(2) as u8,
+    High = (LEN as u8) << 4,
+}
+
+fn size<T: Size>() -> usize {
+    T::SIZE
+}
+
+fn main() {
+    let array = [0u8; LEN * 2];
+    assert_eq!(array.len(), 14);
+    assert_eq!(<Small as Size>::SIZE, 1);
+    assert_eq!(<Large as Size>::SIZE, 14);
+    assert_eq!(size::<Large>(), 14);
+    assert_eq!(Level::Low as u8, 4);
+    assert_eq!(Level::High as u8, 112);
+    assert_eq!(LEN, 7);
+}
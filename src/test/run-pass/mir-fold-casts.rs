@@ -0,0 +1,46 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Checks that folding integer, `char` and enum casts gives the results
+// computed at runtime, truncation and extension included.
+
+#[derive(Copy, Clone)]
+#[repr(u8)]
+enum Level {
+    Low = 1,
+    High = 200,
+}
+
+#[inline(never)]
+fn id<T>(x: T) -> T { x }
+
+fn high() -> i8 {
+    match Level::High as i8 {
+        -56 => 1,
+        _ => 2,
+    }
+}
+
+fn main() {
+    assert_eq!(456u16 as u8, id(456u16) as u8);
+    assert_eq!((456u16 as u8) as i8, (id(456u16) as u8) as i8);
+    assert_eq!(200u8 as i8 as i64, id(200u8) as i8 as i64);
+    assert_eq!(200u8 as i8 as u64, id(200u8) as i8 as u64);
+    assert_eq!(4000000000u32 as i32 as isize, id(4000000000u32) as i32 as isize);
+    assert_eq!(u64::max_value() as usize, id(u64::max_value()) as usize);
+    assert_eq!(97u8 as char, id(97u8) as char);
+    assert_eq!('\u{10ffff}' as u8, id('\u{10ffff}') as u8);
+    assert_eq!(true as i32, id(true) as i32);
+    assert_eq!(Level::Low as u32, id(Level::Low) as u32);
+    assert_eq!(Level::High as i8, id(Level::High) as i8);
+    assert_eq!(high(), 1);
+}
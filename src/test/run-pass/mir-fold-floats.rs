@@ -0,0 +1,34 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Checks that folding float arithmetic gives the results computed at
+// runtime, rounding and special values included.
+
+#[inline(never)]
+fn id<T>(x: T) -> T { x }
+
+fn main() {
+    assert_eq!(0.1f64 + 0.2, id(0.1f64) + id(0.2));
+    assert_eq!(0.1f32 + 0.2, id(0.1f32) + id(0.2));
+    assert_eq!((0.1f64 + 0.2) as f32, (id(0.1f64) + id(0.2)) as f32);
+    assert_eq!(16777217i32 as f32, id(16777217i32) as f32);
+    assert_eq!(u64::max_value() as f32, id(u64::max_value()) as f32);
+    assert_eq!(-9007199254740993i64 as f64, id(-9007199254740993i64) as f64);
+    assert_eq!(1.0f64 / 0.0, id(1.0f64) / id(0.0));
+    assert_eq!(-(0.0f64), -id(0.0f64));
+    assert_eq!((-(0.0f64)).is_sign_negative(), true);
+    assert_eq!(5.5f64 % 2.0, id(5.5f64) % id(2.0));
+    assert!((0.0f64 / 0.0).is_nan());
+    assert!(!(0.0f64 / 0.0 == 0.0f64 / 0.0));
+    assert!(1.0f32 < 2.0);
+    assert!(!(1.0f64 >= 2.0));
+}
@@ -0,0 +1,54 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+// ignore-emscripten no threads support
+
+// A `#[rustc_pure]` function touches no memory but may still panic, so
+// the calls to its callers must keep unwinding through the drops.
+
+#![feature(rustc_attrs)]
+
+use std::cell::Cell;
+use std::panic;
+
+struct Counted<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Counted<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[rustc_pure]
+fn checked(x: u32) -> u32 {
+    if x > 10 {
+        panic!("too large");
+    }
+    x
+}
+
+fn forward(x: u32) -> u32 {
+    checked(x)
+}
+
+fn guarded(drops: &Cell<u32>, x: u32) -> u32 {
+    let _a = Counted(drops);
+    forward(x)
+}
+
+fn main() {
+    let drops = Cell::new(0);
+    assert_eq!(guarded(&drops, 3), 3);
+    assert_eq!(drops.get(), 1);
+
+    assert!(panic::catch_unwind(panic::AssertUnwindSafe(|| guarded(&drops, 11))).is_err());
+    assert_eq!(drops.get(), 2);
+}
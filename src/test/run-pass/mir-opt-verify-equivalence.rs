@@ -0,0 +1,49 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-verify-equivalence -Z mir-opt-level=3
+
+// Functions which `-Z mir-opt-verify-equivalence` runs around every MIR
+// optimisation, and whose results none of them may change.
+
+fn select(c: bool, a: u32, b: u32) -> u32 {
+    if c { a } else { b }
+}
+
+fn checked(a: i32, b: i32) -> i32 {
+    let x = a * 3;
+    x - b / 2
+}
+
+fn sum(n: u8) -> u64 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < n {
+        total += i as u64;
+        i += 1;
+    }
+    total
+}
+
+fn classify(x: usize) -> usize {
+    match x {
+        0 => 10,
+        1 | 2 => 20,
+        127 => 30,
+        _ => x % 7,
+    }
+}
+
+fn main() {
+    assert_eq!(select(true, 1, 2), 1);
+    assert_eq!(checked(4, 2), 11);
+    assert_eq!(sum(4), 6);
+    assert_eq!(classify(2), 20);
+}
@@ -0,0 +1,54 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=1
+// ignore-emscripten no threads support
+
+// The panicking path of a function optimized for size is outlined without
+// `-Z mir-outline-cold`, and still runs the cleanup of the function.
+
+#![feature(optimize_attribute)]
+
+use std::cell::Cell;
+use std::panic;
+
+struct Counted<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Counted<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[optimize(size)]
+fn check(drops: &Cell<u32>, limit: u32, actual: u32) -> u32 {
+    let _guard = Counted(drops);
+    if actual > limit {
+        panic!("{} > {}", actual, limit);
+    }
+    actual * 2
+}
+
+#[optimize(speed)]
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn main() {
+    let drops = Cell::new(0);
+    assert_eq!(check(&drops, 10, 4), 8);
+    assert_eq!(drops.get(), 1);
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| check(&drops, 10, 11)));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 2);
+
+    assert_eq!(double(21), 42);
+}
@@ -0,0 +1,47 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=1 -Z mir-outline-cold
+// ignore-emscripten no threads support
+
+// Panicking paths moved into functions of their own still see the
+// locals of the function, and still run its cleanup when unwinding.
+
+use std::cell::Cell;
+use std::panic;
+
+struct Counted<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Counted<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn check<T: std::fmt::Debug>(drops: &Cell<u32>, value: T, limit: u32, actual: u32) -> u32 {
+    let _guard = Counted(drops);
+    if actual > limit {
+        let message = format!("{:?} is over the limit", value);
+        panic!("{}: {} > {} ({} drops so far)", message, actual, limit, drops.get());
+    }
+    actual * 2
+}
+
+fn main() {
+    let drops = Cell::new(0);
+    assert_eq!(check(&drops, "a", 10, 4), 8);
+    assert_eq!(drops.get(), 1);
+
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        check(&drops, vec![1, 2], 10, 11)
+    }));
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 2);
+}
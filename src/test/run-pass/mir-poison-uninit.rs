@@ -0,0 +1,22 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-poison-uninit -C debug-assertions
+
+// Checks that the memory of a local is filled with the poison pattern
+// once it was moved out of.
+
+fn main() {
+    let v = vec![1u8, 2, 3];
+    let p = &v as *const Vec<u8> as *const usize;
+    let w = v;
+    assert_eq!(unsafe { *p }, !0 / 0xff * 0xa5);
+    assert_eq!(w, [1, 2, 3]);
+}
@@ -0,0 +1,67 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Checks that constant arguments are only propagated into functions which
+// are always called with them.
+
+#[inline(never)]
+fn id<T>(x: T) -> T { x }
+
+// Always called with `true`, through a few layers.
+fn leaf(verbose: bool, x: u32) -> u32 {
+    if verbose { x + 1 } else { x }
+}
+
+fn middle(verbose: bool, x: u32) -> u32 {
+    leaf(verbose, x) * 2
+}
+
+fn top(x: u32) -> u32 {
+    middle(true, x)
+}
+
+// Called with different constants.
+fn scale(x: u32, factor: u32) -> u32 {
+    x * factor
+}
+
+// Called with a constant, but also through a pointer.
+fn shift(x: u32, by: u32) -> u32 {
+    x << by
+}
+
+// Called with a constant, and with a variable.
+fn add(x: u32, y: u32) -> u32 {
+    x + y
+}
+
+// Public, so it may be called with anything.
+pub fn sub(x: u32, y: u32) -> u32 {
+    x - y
+}
+
+fn main() {
+    assert_eq!(top(1), 4);
+    assert_eq!(top(id(2)), 6);
+
+    assert_eq!(scale(3, 2), 6);
+    assert_eq!(scale(3, 5), 15);
+
+    let f: fn(u32, u32) -> u32 = shift;
+    assert_eq!(shift(1, 3), 8);
+    assert_eq!(f(1, id(4)), 16);
+
+    assert_eq!(add(1, 2), 3);
+    assert_eq!(add(1, id(5)), 6);
+
+    assert_eq!(sub(5, 2), 3);
+}
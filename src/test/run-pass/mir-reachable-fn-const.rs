@@ -0,0 +1,25 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:mir_reachable_fn_const.rs
+// compile-flags: -Z mir-opt-level=3
+
+// A private function reached through a function pointer constant in the
+// MIR of an inline or generic function is exported for the crates which
+// instantiate that MIR.
+
+extern crate mir_reachable_fn_const;
+
+use mir_reachable_fn_const::{call_generic, call_inline};
+
+fn main() {
+    assert_eq!(call_inline(), 42);
+    assert_eq!(call_generic(()), 42);
+}
@@ -0,0 +1,60 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z verify-cleanup-edges -Z mir-opt-level=3
+// ignore-emscripten no threads support
+
+// Unwinding through drops, calls and assertions, whose cleanup code
+// `-Z verify-cleanup-edges` must accept after every MIR optimisation.
+
+use std::cell::Cell;
+use std::panic;
+
+struct Counted<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Counted<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn maybe_panic(panic: bool) {
+    if panic {
+        panic!("unwinding");
+    }
+}
+
+fn nested(drops: &Cell<u32>, panic: bool) {
+    let _a = Counted(drops);
+    let b = Box::new(Counted(drops));
+    maybe_panic(panic);
+    drop(b);
+}
+
+fn index(drops: &Cell<u32>, i: usize) -> u32 {
+    let _a = Counted(drops);
+    let v = [Counted(drops), Counted(drops)];
+    v[i].0.get()
+}
+
+fn main() {
+    let drops = Cell::new(0);
+    nested(&drops, false);
+    assert_eq!(drops.get(), 2);
+
+    assert!(panic::catch_unwind(panic::AssertUnwindSafe(|| nested(&drops, true))).is_err());
+    assert_eq!(drops.get(), 4);
+
+    index(&drops, 1);
+    assert_eq!(drops.get(), 7);
+
+    assert!(panic::catch_unwind(panic::AssertUnwindSafe(|| index(&drops, 2))).is_err());
+    assert_eq!(drops.get(), 10);
+}
@@ -0,0 +1,59 @@
+// Copyright 2016 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z verify-mir-drops
+
+// Moves and drops which `-Z verify-mir-drops` must accept after every
+// MIR optimisation, including conditionally initialized values.
+
+use std::cell::Cell;
+
+struct Counted<'a>(&'a Cell<u32>);
+
+impl<'a> Drop for Counted<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+fn consume(_: Counted) {}
+
+fn maybe_move(drops: &Cell<u32>, move_it: bool) {
+    let x = Counted(drops);
+    if move_it {
+        consume(x);
+    }
+}
+
+fn partial_move(drops: &Cell<u32>) {
+    let pair = (Counted(drops), Counted(drops));
+    consume(pair.0);
+}
+
+fn reassign_in_loop(drops: &Cell<u32>) {
+    let mut x = Box::new(Counted(drops));
+    for _ in 0..3 {
+        consume(*x);
+        x = Box::new(Counted(drops));
+    }
+}
+
+fn main() {
+    let drops = Cell::new(0);
+    maybe_move(&drops, true);
+    maybe_move(&drops, false);
+    assert_eq!(drops.get(), 2);
+
+    partial_move(&drops);
+    assert_eq!(drops.get(), 4);
+
+    reassign_in_loop(&drops);
+    assert_eq!(drops.get(), 8);
+}